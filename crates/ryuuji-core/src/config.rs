@@ -1,6 +1,7 @@
 use std::path::PathBuf;
 
 use directories::ProjectDirs;
+use ryuuji_detect::{RemoteEndpoint, ServerEndpoint};
 use serde::{Deserialize, Serialize};
 
 use crate::error::RyuujiError;
@@ -22,6 +23,12 @@ pub struct AppConfig {
     pub logging: LoggingConfig,
     #[serde(default)]
     pub update: UpdateConfig,
+    #[serde(default)]
+    pub remote_players: RemotePlayersConfig,
+    #[serde(default)]
+    pub media_servers: MediaServersConfig,
+    #[serde(default)]
+    pub browser_bridge: BrowserBridgeConfig,
 }
 
 /// Appearance / theme settings.
@@ -70,6 +77,23 @@ impl std::fmt::Display for ThemeMode {
 pub struct GeneralConfig {
     pub detection_interval: u64,
     pub close_to_tray: bool,
+    /// Player names to prefer when several are detected, highest first.
+    #[serde(default)]
+    pub player_priority: Vec<String>,
+    /// mpv `--input-ipc-server` socket paths to query; `*` matches any characters.
+    #[serde(default = "default_mpv_ipc_sockets")]
+    pub mpv_ipc_sockets: Vec<String>,
+    /// Read Firefox's session file for the active tab (Linux).
+    #[serde(default = "default_true")]
+    pub firefox_sessions: bool,
+}
+
+fn default_mpv_ipc_sockets() -> Vec<String> {
+    vec!["/tmp/mpvsocket*".into()]
+}
+
+fn default_true() -> bool {
+    true
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -82,12 +106,44 @@ pub struct LibraryConfig {
     pub min_file_size_mb: u64,
     #[serde(default)]
     pub scan_on_startup: bool,
+    /// Percentage of an episode that must actually play before it counts as
+    /// watched (0 = as soon as it is detected).
+    #[serde(default = "default_watch_threshold_percent")]
+    pub watch_threshold_percent: u8,
+    /// Seconds of playback after which an episode counts as watched even if
+    /// the percentage has not been reached (0 = disabled).
+    #[serde(default)]
+    pub watch_threshold_secs: u64,
+    /// Filename template used when renaming library files.
+    /// See [`crate::rename`] for the syntax.
+    #[serde(default = "default_rename_template")]
+    pub rename_template: String,
+    /// Search the primary service for titles that aren't in the local
+    /// database, and add the best result.
+    #[serde(default)]
+    pub online_fallback: bool,
+    /// Match confidence (0.0–1.0) an online result needs to be added without
+    /// asking; weaker matches are queued for confirmation.
+    #[serde(default = "default_online_fallback_threshold")]
+    pub online_fallback_threshold: f64,
 }
 
 fn default_min_file_size_mb() -> u64 {
     10
 }
 
+fn default_watch_threshold_percent() -> u8 {
+    80
+}
+
+fn default_rename_template() -> String {
+    "{title} - S{season:02}E{episode:02} [{group}][{resolution}].{ext}".into()
+}
+
+fn default_online_fallback_threshold() -> f64 {
+    0.9
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServicesConfig {
     pub primary: String,
@@ -135,6 +191,128 @@ pub struct TorrentConfig {
     pub auto_download: bool,
 }
 
+/// Players polled over their network control interfaces.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RemotePlayersConfig {
+    #[serde(default)]
+    pub kodi: RemotePlayerConfig,
+    #[serde(default)]
+    pub vlc: RemotePlayerConfig,
+}
+
+/// Connection settings for one remote player interface.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemotePlayerConfig {
+    pub enabled: bool,
+    pub host: String,
+    pub port: u16,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+}
+
+impl RemotePlayerConfig {
+    /// Endpoint to poll, or `None` when disabled.
+    pub fn endpoint(&self) -> Option<RemoteEndpoint> {
+        self.enabled.then(|| RemoteEndpoint {
+            host: self.host.clone(),
+            port: self.port,
+            username: self.username.clone(),
+            password: self.password.clone(),
+        })
+    }
+}
+
+impl Default for RemotePlayerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: "localhost".into(),
+            port: 8080,
+            username: None,
+            password: None,
+        }
+    }
+}
+
+/// Loopback endpoint for the browser extension's now-playing reports.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrowserBridgeConfig {
+    pub enabled: bool,
+    pub port: u16,
+}
+
+impl Default for BrowserBridgeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: ryuuji_detect::bridge::DEFAULT_PORT,
+        }
+    }
+}
+
+/// Media servers whose playback sessions are polled.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MediaServersConfig {
+    #[serde(default = "MediaServerConfig::jellyfin_default")]
+    pub jellyfin: MediaServerConfig,
+    #[serde(default = "MediaServerConfig::plex_default")]
+    pub plex: MediaServerConfig,
+}
+
+impl Default for MediaServersConfig {
+    fn default() -> Self {
+        Self {
+            jellyfin: MediaServerConfig::jellyfin_default(),
+            plex: MediaServerConfig::plex_default(),
+        }
+    }
+}
+
+/// Connection settings for a Jellyfin or Plex server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MediaServerConfig {
+    pub enabled: bool,
+    pub host: String,
+    pub port: u16,
+    /// Jellyfin API key or Plex token.
+    #[serde(default)]
+    pub token: String,
+    /// Only track this user's sessions (empty = all users).
+    #[serde(default)]
+    pub user: String,
+}
+
+impl MediaServerConfig {
+    fn jellyfin_default() -> Self {
+        Self {
+            enabled: false,
+            host: "localhost".into(),
+            port: 8096,
+            token: String::new(),
+            user: String::new(),
+        }
+    }
+
+    fn plex_default() -> Self {
+        Self {
+            port: 32400,
+            ..Self::jellyfin_default()
+        }
+    }
+
+    /// Endpoint to poll, or `None` when disabled or no token is set.
+    pub fn endpoint(&self) -> Option<ServerEndpoint> {
+        (self.enabled && !self.token.is_empty()).then(|| ServerEndpoint {
+            host: self.host.clone(),
+            port: self.port,
+            token: self.token.clone(),
+            user: Some(self.user.clone()).filter(|u| !u.is_empty()),
+        })
+    }
+}
+
 /// Logging configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LoggingConfig {
@@ -203,9 +381,14 @@ impl AppConfig {
 
     /// Path to user config file (XDG on Linux, AppData on Windows).
     pub fn config_path() -> PathBuf {
+        Self::config_dir().join("config.toml")
+    }
+
+    /// Directory holding the config file and user overrides such as `players.toml`.
+    pub fn config_dir() -> PathBuf {
         Self::project_dirs()
-            .map(|d| d.config_dir().join("config.toml"))
-            .unwrap_or_else(|| PathBuf::from("config.toml"))
+            .map(|d| d.config_dir().to_path_buf())
+            .unwrap_or_default()
     }
 
     /// Path to the database file.
@@ -251,6 +434,8 @@ mod tests {
         let config = AppConfig::default();
         assert_eq!(config.general.detection_interval, 5);
         assert!(config.library.auto_update);
+        assert_eq!(config.library.watch_threshold_percent, 80);
+        assert_eq!(config.library.watch_threshold_secs, 0);
         assert!(config.services.anilist.enabled);
         assert!(!config.discord.enabled);
     }
@@ -319,5 +504,16 @@ enabled = false
         // Defaults from UpdateConfig::default()
         assert!(config.update.check_on_startup);
         assert!(!config.update.include_prerelease);
+        // Remote players are off unless configured.
+        assert!(config.remote_players.kodi.endpoint().is_none());
+        assert!(config.remote_players.vlc.endpoint().is_none());
+        assert!(config.media_servers.jellyfin.endpoint().is_none());
+        assert_eq!(config.media_servers.plex.port, 32400);
+        // The bridge opens a port, so it waits for the user to opt in.
+        assert!(!config.browser_bridge.enabled);
+        assert_eq!(
+            config.browser_bridge.port,
+            ryuuji_detect::bridge::DEFAULT_PORT
+        );
     }
 }
@@ -18,6 +18,12 @@ pub enum DebugEvent {
         is_browser: bool,
         media_title: Option<String>,
     },
+    PlayerRanked {
+        player_name: String,
+        /// 1-based position after arbitration; 1 is the tracked player.
+        rank: u32,
+        reason: String,
+    },
     StreamMatched {
         service_name: String,
         extracted_title: String,
@@ -58,12 +64,16 @@ pub enum DebugEvent {
 }
 
 /// How a title was matched in the recognition cache.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum MatchLevel {
+    /// A user title alias.
+    Alias,
     Exact,
     Normalized,
     Fuzzy(f64),
     LruHit,
+    /// Picked from the primary service's search results, with confidence.
+    Online(f64),
     NoMatch,
 }
 
@@ -124,6 +134,7 @@ pub fn shared_event_log() -> SharedEventLog {
 pub struct CacheStats {
     pub entries_indexed: usize,
     pub lru_size: usize,
+    pub hits_alias: u64,
     pub hits_exact: u64,
     pub hits_normalized: u64,
     pub hits_fuzzy: u64,
@@ -0,0 +1,293 @@
+//! Player and stream databases with user overrides.
+//!
+//! The embedded `players.toml` / `streams.toml` are merged with same-named
+//! files from the user config directory. The user files are re-read whenever
+//! their modification time changes, so edits apply without a restart.
+//!
+//! `keywords.toml` adds filename parser keywords and release group names
+//! (see [`KeywordSet`]). Pass [`DetectionDatabases::keywords`] to whatever
+//! parses filenames.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use ryuuji_detect::{PlayerDatabase, StreamDatabase};
+use ryuuji_parse::KeywordSet;
+
+use crate::config::AppConfig;
+
+const PLAYERS_FILE: &str = "players.toml";
+const STREAMS_FILE: &str = "streams.toml";
+const KEYWORDS_FILE: &str = "keywords.toml";
+
+/// A problem found in a user override file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// File name the problem was found in (e.g. `streams.toml`).
+    pub file: &'static str,
+    pub message: String,
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.file, self.message)
+    }
+}
+
+/// Embedded databases merged with the user's override files.
+#[derive(Debug, Clone)]
+pub struct DetectionDatabases {
+    pub players: Arc<PlayerDatabase>,
+    pub streams: Arc<StreamDatabase>,
+    /// Parser keywords from the user's `keywords.toml` (empty without one).
+    pub keywords: Arc<KeywordSet>,
+    dir: Option<PathBuf>,
+    stamps: [Option<SystemTime>; 3],
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl DetectionDatabases {
+    /// Embedded databases only, without user overrides.
+    pub fn embedded() -> Self {
+        Self {
+            players: Arc::new(PlayerDatabase::embedded()),
+            streams: Arc::new(StreamDatabase::embedded()),
+            keywords: Arc::new(KeywordSet::default()),
+            dir: None,
+            stamps: [None, None, None],
+            diagnostics: Vec::new(),
+        }
+    }
+
+    /// Load with overrides from the user config directory.
+    pub fn load() -> Self {
+        Self::load_from(AppConfig::config_dir())
+    }
+
+    /// Load with overrides from `dir`.
+    pub fn load_from(dir: impl Into<PathBuf>) -> Self {
+        let mut db = Self::embedded();
+        db.dir = Some(dir.into());
+        db.reload();
+        db
+    }
+
+    /// Problems found during the last (re)load. Empty when everything parsed.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// Re-read the user files if any was created, modified or removed since
+    /// the last load. Returns `true` when the databases were rebuilt.
+    pub fn reload_if_changed(&mut self) -> bool {
+        let Some(dir) = &self.dir else {
+            return false;
+        };
+        if file_stamps(dir) == self.stamps {
+            return false;
+        }
+        self.reload();
+        true
+    }
+
+    fn reload(&mut self) {
+        let Some(dir) = self.dir.clone() else {
+            return;
+        };
+        self.stamps = file_stamps(&dir);
+        self.diagnostics.clear();
+
+        let mut players = PlayerDatabase::embedded();
+        if let Some(content) = self.read_user_file(&dir, PLAYERS_FILE) {
+            match PlayerDatabase::from_toml(&content) {
+                Ok(user_db) => {
+                    self.push_all(
+                        PLAYERS_FILE,
+                        user_db.pattern_errors().iter().map(ToString::to_string),
+                    );
+                    players.merge_user(&user_db);
+                    tracing::info!(players = user_db.players.len(), "Loaded user players.toml");
+                }
+                Err(e) => self.push(PLAYERS_FILE, e.to_string()),
+            }
+        }
+
+        let mut streams = StreamDatabase::embedded();
+        if let Some(content) = self.read_user_file(&dir, STREAMS_FILE) {
+            match StreamDatabase::from_toml(&content) {
+                Ok(user_db) => {
+                    self.push_all(
+                        STREAMS_FILE,
+                        user_db.pattern_errors().iter().map(ToString::to_string),
+                    );
+                    streams.merge_user(&user_db);
+                    tracing::info!(streams = user_db.len(), "Loaded user streams.toml");
+                }
+                Err(e) => self.push(STREAMS_FILE, e.to_string()),
+            }
+        }
+
+        let mut keywords = KeywordSet::default();
+        if let Some(content) = self.read_user_file(&dir, KEYWORDS_FILE) {
+            match KeywordSet::from_toml(&content) {
+                Ok(user_set) => {
+                    tracing::info!(
+                        keywords = user_set.keyword_count(),
+                        release_groups = user_set.release_group_count(),
+                        "Loaded user keywords.toml"
+                    );
+                    keywords = user_set;
+                }
+                Err(e) => self.push(KEYWORDS_FILE, e.to_string()),
+            }
+        }
+
+        self.players = Arc::new(players);
+        self.streams = Arc::new(streams);
+        self.keywords = Arc::new(keywords);
+    }
+
+    fn read_user_file(&mut self, dir: &Path, file: &'static str) -> Option<String> {
+        let path = dir.join(file);
+        if !path.exists() {
+            return None;
+        }
+        match std::fs::read_to_string(&path) {
+            Ok(content) => Some(content),
+            Err(e) => {
+                self.push(file, e.to_string());
+                None
+            }
+        }
+    }
+
+    fn push(&mut self, file: &'static str, message: String) {
+        tracing::warn!(file, %message, "Invalid detection override");
+        self.diagnostics.push(Diagnostic { file, message });
+    }
+
+    fn push_all(&mut self, file: &'static str, messages: impl Iterator<Item = String>) {
+        for message in messages {
+            self.push(file, message);
+        }
+    }
+}
+
+impl Default for DetectionDatabases {
+    fn default() -> Self {
+        Self::embedded()
+    }
+}
+
+/// Modification times of the user files (`None` when absent).
+fn file_stamps(dir: &Path) -> [Option<SystemTime>; 3] {
+    [PLAYERS_FILE, STREAMS_FILE, KEYWORDS_FILE].map(|file| {
+        std::fs::metadata(dir.join(file))
+            .and_then(|m| m.modified())
+            .ok()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const USER_STREAMS: &str = r#"
+        [[stream]]
+        name = "Funimation"
+        url_patterns = ["funimation\\.com/v/", "funimation\\.com/(broken"]
+        title_pattern = "^(.+?)\\s*-\\s*Funimation$"
+    "#;
+
+    #[test]
+    fn test_no_user_files() {
+        let dir = TempDir::new().unwrap();
+        let mut db = DetectionDatabases::load_from(dir.path());
+        assert_eq!(db.streams.len(), StreamDatabase::embedded().len());
+        assert!(db.diagnostics().is_empty());
+        assert!(!db.reload_if_changed());
+    }
+
+    #[test]
+    fn test_user_streams_merged_with_diagnostics() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join(STREAMS_FILE), USER_STREAMS).unwrap();
+
+        let db = DetectionDatabases::load_from(dir.path());
+        assert!(db
+            .streams
+            .match_url("https://www.funimation.com/v/frieren")
+            .is_some());
+        assert_eq!(db.diagnostics().len(), 1);
+        assert_eq!(db.diagnostics()[0].file, STREAMS_FILE);
+        assert!(db.diagnostics()[0].message.contains("(broken"));
+    }
+
+    #[test]
+    fn test_invalid_toml_keeps_embedded() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join(PLAYERS_FILE), "[[player]\nname = ").unwrap();
+
+        let db = DetectionDatabases::load_from(dir.path());
+        assert_eq!(
+            db.players.players.len(),
+            PlayerDatabase::embedded().players.len()
+        );
+        assert_eq!(db.diagnostics().len(), 1);
+        assert_eq!(db.diagnostics()[0].file, PLAYERS_FILE);
+    }
+
+    #[test]
+    fn test_user_keywords() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join(KEYWORDS_FILE),
+            "release_groups = [\"Erai-raws\"]\n[keywords]\nADN = { kind = \"streaming_source\" }\n",
+        )
+        .unwrap();
+
+        let db = DetectionDatabases::load_from(dir.path());
+        assert!(db.diagnostics().is_empty());
+        let parsed =
+            ryuuji_parse::parse_with_keywords("Title - 05 [ADN] [Erai-raws].mkv", &db.keywords);
+        assert_eq!(parsed.streaming_source.as_deref(), Some("ADN"));
+        assert_eq!(parsed.release_group.as_deref(), Some("Erai-raws"));
+
+        std::fs::write(
+            dir.path().join(KEYWORDS_FILE),
+            "[keywords]\nADN = \"streaming_source\"\n",
+        )
+        .unwrap();
+        let db = DetectionDatabases::load_from(dir.path());
+        assert_eq!(db.keywords.keyword_count(), 0);
+        assert_eq!(db.diagnostics()[0].file, KEYWORDS_FILE);
+    }
+
+    #[test]
+    fn test_reload_on_change() {
+        let dir = TempDir::new().unwrap();
+        let mut db = DetectionDatabases::load_from(dir.path());
+        assert!(db
+            .streams
+            .match_url("https://www.funimation.com/v/x")
+            .is_none());
+
+        std::fs::write(dir.path().join(STREAMS_FILE), USER_STREAMS).unwrap();
+        assert!(db.reload_if_changed());
+        assert!(db
+            .streams
+            .match_url("https://www.funimation.com/v/x")
+            .is_some());
+        assert!(!db.reload_if_changed());
+
+        std::fs::remove_file(dir.path().join(STREAMS_FILE)).unwrap();
+        assert!(db.reload_if_changed());
+        assert!(db
+            .streams
+            .match_url("https://www.funimation.com/v/x")
+            .is_none());
+        assert!(db.diagnostics().is_empty());
+    }
+}
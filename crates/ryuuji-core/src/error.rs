@@ -28,4 +28,7 @@ pub enum RyuujiError {
 
     #[error("update error: {0}")]
     Update(String),
+
+    #[error("rename error: {0}")]
+    Rename(String),
 }
@@ -1,13 +1,18 @@
 pub mod config;
 pub mod debug_log;
+pub mod detect_db;
 pub mod error;
 pub mod matcher;
 pub mod models;
-pub mod normalize;
 pub mod orchestrator;
+pub mod pipeline;
 pub mod recognition;
 pub mod relations;
+pub mod rename;
 pub mod scanner;
 pub mod storage;
 pub mod torrent;
 pub mod updater;
+pub mod watch;
+
+pub use ryuuji_parse::normalize;
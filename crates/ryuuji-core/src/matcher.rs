@@ -1,5 +1,4 @@
-use fuzzy_matcher::skim::SkimMatcherV2;
-use fuzzy_matcher::FuzzyMatcher;
+use ryuuji_parse::matcher::MatchKind;
 
 use crate::models::Anime;
 
@@ -14,80 +13,18 @@ pub enum MatchResult {
     NoMatch,
 }
 
-/// Minimum fuzzy score (0.0–1.0) to consider a match valid.
-const FUZZY_THRESHOLD: f64 = 0.6;
-
 /// Attempt to match a parsed title against a list of known anime.
 ///
-/// Strategy: exact → normalized → fuzzy (Skim) → NoMatch.
+/// Strategy: exact → normalized → fuzzy (Skim) → NoMatch; see
+/// [`ryuuji_parse::matcher::match_title`].
 pub fn match_title(query: &str, candidates: &[Anime]) -> MatchResult {
-    if query.is_empty() || candidates.is_empty() {
-        return MatchResult::NoMatch;
-    }
-
-    let normalized_query = normalize(query);
-
-    // Pass 1: Exact match against any title variant or synonym.
-    for anime in candidates {
-        if exact_match(query, anime) {
-            return MatchResult::Matched(anime.clone());
-        }
-    }
-
-    // Pass 2: Normalized match (lowercase, no punctuation).
-    for anime in candidates {
-        if normalized_match(&normalized_query, anime) {
-            return MatchResult::Matched(anime.clone());
+    match ryuuji_parse::matcher::match_title(query, candidates, all_titles) {
+        Some(m) if m.kind == MatchKind::Fuzzy => {
+            MatchResult::Fuzzy(candidates[m.index].clone(), m.confidence)
         }
+        Some(m) => MatchResult::Matched(candidates[m.index].clone()),
+        None => MatchResult::NoMatch,
     }
-
-    // Pass 3: Fuzzy match using Skim algorithm.
-    let matcher = SkimMatcherV2::default();
-    let mut best_score: i64 = 0;
-    let mut best_anime: Option<&Anime> = None;
-    let mut max_possible: i64 = 1;
-
-    if let Some(self_score) = matcher.fuzzy_match(&normalized_query, &normalized_query) {
-        max_possible = self_score.max(1);
-    }
-
-    for anime in candidates {
-        let score = best_fuzzy_score(&matcher, &normalized_query, anime);
-        if score > best_score {
-            best_score = score;
-            best_anime = Some(anime);
-        }
-    }
-
-    if let Some(anime) = best_anime {
-        let confidence = best_score as f64 / max_possible as f64;
-        if confidence >= FUZZY_THRESHOLD {
-            return MatchResult::Fuzzy(anime.clone(), confidence);
-        }
-    }
-
-    MatchResult::NoMatch
-}
-
-/// Check for exact string match against all title variants.
-fn exact_match(query: &str, anime: &Anime) -> bool {
-    let titles = all_titles(anime);
-    titles.contains(&query)
-}
-
-/// Check for normalized match (case-insensitive, stripped punctuation).
-fn normalized_match(normalized_query: &str, anime: &Anime) -> bool {
-    let titles = all_titles(anime);
-    titles.iter().any(|t| normalize(t) == *normalized_query)
-}
-
-/// Get the best fuzzy score across all title variants.
-fn best_fuzzy_score(matcher: &SkimMatcherV2, query: &str, anime: &Anime) -> i64 {
-    all_titles(anime)
-        .iter()
-        .filter_map(|t| matcher.fuzzy_match(&normalize(t), query))
-        .max()
-        .unwrap_or(0)
 }
 
 /// Collect all title strings for an anime (romaji, english, native, synonyms).
@@ -113,7 +50,7 @@ pub fn all_titles(anime: &Anime) -> Vec<&str> {
 /// Levels: NFKC + case fold → transliteration → roman numerals → ordinals →
 /// season keywords → stop words → punctuation erasure → whitespace collapse.
 pub fn normalize(s: &str) -> String {
-    crate::normalize::normalize(s)
+    ryuuji_parse::normalize::normalize(s)
 }
 
 #[cfg(test)]
@@ -49,3 +49,53 @@ pub struct Anime {
     pub start_date: Option<String>,
     pub end_date: Option<String>,
 }
+
+impl Anime {
+    /// Whether the anime is currently airing, by any service's status name.
+    pub fn is_airing(&self) -> bool {
+        matches!(
+            self.airing_status.as_deref(),
+            Some("currently_airing" | "releasing" | "current")
+        )
+    }
+}
+
+/// A user correction: detections of `title` in `season` are `anime_id`.
+///
+/// `title` is stored normalized, and `season` is `None` for the first season.
+/// A release group or path prefix narrows the alias to files from that group
+/// or under that folder.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TitleAlias {
+    pub id: i64,
+    pub title: String,
+    pub season: Option<u32>,
+    pub anime_id: i64,
+    pub release_group: Option<String>,
+    pub path_prefix: Option<String>,
+}
+
+impl TitleAlias {
+    /// Whether the alias applies to a file from `release_group` at `file_path`.
+    pub fn applies_to(&self, release_group: Option<&str>, file_path: Option<&str>) -> bool {
+        let group_ok = match (&self.release_group, release_group) {
+            (None, _) => true,
+            (Some(want), Some(got)) => want.eq_ignore_ascii_case(got),
+            (Some(_), None) => false,
+        };
+        let path_ok = match (&self.path_prefix, file_path) {
+            (None, _) => true,
+            (Some(prefix), Some(path)) => path.starts_with(prefix.as_str()),
+            (Some(_), None) => false,
+        };
+        group_ok && path_ok
+    }
+
+    /// Higher for narrower aliases, so the most specific one wins.
+    pub fn specificity(&self) -> (usize, bool) {
+        (
+            self.path_prefix.as_ref().map_or(0, String::len),
+            self.release_group.is_some(),
+        )
+    }
+}
@@ -1,7 +1,10 @@
+use std::time::{Duration, Instant};
+
+use ryuuji_detect::PlaybackState;
 use serde::{Deserialize, Serialize};
 
 /// Result of detecting and parsing media playback.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct DetectedMedia {
     /// The media player that's playing.
     pub player_name: String,
@@ -9,12 +12,38 @@ pub struct DetectedMedia {
     pub anime_title: Option<String>,
     /// Parsed episode number.
     pub episode: Option<u32>,
+    /// Last episode of a multi-episode file (e.g., 2 for "01-02").
+    #[serde(default)]
+    pub episode_end: Option<u32>,
+    /// Season number, if the filename or media server reports one.
+    #[serde(default)]
+    pub season: Option<u32>,
+    /// Whether `anime_title` names the whole series rather than one season,
+    /// as media servers report it.
+    #[serde(default)]
+    pub series_title: bool,
     /// Release group (e.g., "SubsPlease").
     pub release_group: Option<String>,
     /// Video resolution (e.g., "1080p").
     pub resolution: Option<String>,
     /// Raw title string before parsing.
     pub raw_title: String,
+    /// Local file being played, if the player reports one.
+    #[serde(default)]
+    pub file_path: Option<String>,
     /// Streaming service name (e.g., "Crunchyroll"), if detected via browser.
     pub service_name: Option<String>,
+    /// Playback position reported by the player.
+    #[serde(default)]
+    pub position: Option<Duration>,
+    /// Total media length reported by the player.
+    #[serde(default)]
+    pub duration: Option<Duration>,
+    /// Playing/paused/stopped, if the player reports it.
+    #[serde(default)]
+    pub playback_state: Option<PlaybackState>,
+    /// When the player was seen in this state; `None` means just now.
+    /// Set for replayed recordings.
+    #[serde(skip)]
+    pub observed_at: Option<Instant>,
 }
@@ -94,3 +94,12 @@ pub struct LibraryEntry {
     pub rewatching: bool,
     pub rewatch_count: u32,
 }
+
+/// A detected progress update waiting for the user to approve or reject it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingUpdate {
+    pub id: i64,
+    pub anime_id: i64,
+    pub episode: u32,
+    pub created_at: DateTime<Utc>,
+}
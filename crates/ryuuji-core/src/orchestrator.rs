@@ -1,13 +1,17 @@
+use std::time::Duration;
+
 use chrono::Utc;
 use tracing::{debug, info, warn};
 
 use crate::config::AppConfig;
+use crate::debug_log::MatchLevel;
 use crate::error::RyuujiError;
-use crate::matcher::MatchResult;
-use crate::models::{DetectedMedia, LibraryEntry, WatchStatus};
-use crate::recognition::RecognitionCache;
+use crate::matcher::{self, MatchResult};
+use crate::models::{Anime, DetectedMedia, LibraryEntry, WatchStatus};
+use crate::recognition::{RecognitionCache, RecognitionContext};
 use crate::relations::RelationDatabase;
 use crate::storage::Storage;
+use crate::watch::{WatchPolicy, WatchProgress, WatchTracker};
 
 /// Outcome of processing a detection event.
 #[derive(Debug, Clone)]
@@ -24,6 +28,20 @@ pub enum UpdateOutcome {
         anime_title: String,
         episode: u32,
     },
+    /// Recognized, but not enough of the episode has played yet to count it.
+    InProgress {
+        anime_id: i64,
+        anime_title: String,
+        episode: u32,
+        watched: Duration,
+        required: Duration,
+    },
+    /// Update is waiting for the user to confirm it (`library.confirm_update`).
+    PendingConfirmation {
+        anime_id: i64,
+        anime_title: String,
+        episode: u32,
+    },
     /// Anime was recognized but no library entry exists yet — created one.
     AddedToLibrary {
         anime_id: i64,
@@ -40,9 +58,12 @@ pub enum UpdateOutcome {
 ///
 /// If a `relations` database is provided, episode numbers may be remapped
 /// to handle cross-season continuous numbering (e.g., episode 26 → S2E1).
+///
+/// Every call is also a tick for `tracker`: progress is only written once the
+/// configured watch threshold has been reached for the detected file.
 #[tracing::instrument(
     name = "process_detection",
-    skip(storage, config, cache, relations),
+    skip(storage, config, cache, tracker, relations),
     fields(
         raw_title = %detected.raw_title,
         player = %detected.player_name,
@@ -53,167 +74,519 @@ pub fn process_detection(
     storage: &Storage,
     config: &AppConfig,
     cache: &mut RecognitionCache,
+    tracker: &mut WatchTracker,
     relations: Option<&RelationDatabase>,
 ) -> Result<UpdateOutcome, RyuujiError> {
-    let title = match &detected.anime_title {
-        Some(t) => t,
-        None => {
-            return Ok(UpdateOutcome::Unrecognized {
+    let Some((title, episode)) = detected_episode(detected) else {
+        return Ok(UpdateOutcome::Unrecognized {
+            raw_title: detected.raw_title.clone(),
+        });
+    };
+
+    let progress = tracker.observe(detected, &WatchPolicy::from_config(&config.library));
+
+    // Try to match against all known anime using the recognition cache. A
+    // user correction beats the season-qualified title, so aliases go first.
+    let context = RecognitionContext::of(detected);
+    let result = match cache.recognize_alias(title, &context, storage) {
+        Some(anime) => MatchResult::Matched(anime),
+        None => match_season_first(title, series_season(detected), |q| {
+            cache.recognize_in(q, &context, storage)
+        }),
+    };
+    match result {
+        MatchResult::Matched(anime) | MatchResult::Fuzzy(anime, _) => update_for_match(
+            anime,
+            episode,
+            progress,
+            storage,
+            config,
+            relations,
+            config.library.confirm_update,
+        ),
+        MatchResult::NoMatch => {
+            warn!(title = %title, "No match found in local library");
+            Ok(UpdateOutcome::Unrecognized {
                 raw_title: detected.raw_title.clone(),
-            });
+            })
         }
+    }
+}
+
+/// Fallback for detections [`process_detection`] found no local match for:
+/// pick the best of `candidates`, the primary service's results for
+/// [`online_query`], add it to the local database and carry on as if it had
+/// been there all along.
+///
+/// `progress` is what [`process_detection`] already worked out for this
+/// tick. Updates for matches below `library.online_fallback_threshold` are
+/// queued for confirmation. An anime such a match adds is stored as
+/// unconfirmed, so later updates recognized locally are queued too until the
+/// user approves one, and [`reject_pending_update`] removes it again.
+#[tracing::instrument(
+    name = "recognize_online",
+    skip_all,
+    fields(raw_title = %detected.raw_title, candidates = candidates.len())
+)]
+pub fn recognize_online(
+    detected: &DetectedMedia,
+    progress: WatchProgress,
+    candidates: &[Anime],
+    storage: &Storage,
+    config: &AppConfig,
+    cache: &mut RecognitionCache,
+    relations: Option<&RelationDatabase>,
+) -> Result<UpdateOutcome, RyuujiError> {
+    let unrecognized = || UpdateOutcome::Unrecognized {
+        raw_title: detected.raw_title.clone(),
+    };
+    let Some((title, episode)) = detected_episode(detected) else {
+        return Ok(unrecognized());
+    };
+
+    let (candidate, confidence) = match match_season_first(title, series_season(detected), |q| {
+        matcher::match_title(q, candidates)
+    }) {
+        MatchResult::Matched(anime) => (anime, 1.0),
+        MatchResult::Fuzzy(anime, confidence) => (anime, confidence),
+        MatchResult::NoMatch => {
+            debug!(title = %title, "No online result matched");
+            return Ok(unrecognized());
+        }
+    };
+
+    let added = find_online_anime(storage, &candidate)?.is_none();
+    let anime_id = store_online_anime(storage, &candidate)?;
+    let weak = confidence < config.library.online_fallback_threshold;
+    if weak && added {
+        storage.set_unconfirmed(anime_id, true)?;
+    }
+    cache.invalidate();
+    cache.set_last_match(MatchLevel::Online(confidence));
+    let anime = storage.get_anime(anime_id)?.unwrap_or(Anime {
+        id: anime_id,
+        ..candidate
+    });
+    info!(
+        title = %title,
+        matched = %anime.title.preferred(),
+        confidence,
+        "Recognized online"
+    );
+
+    update_for_match(
+        anime,
+        episode,
+        progress,
+        storage,
+        config,
+        relations,
+        config.library.confirm_update || weak,
+    )
+}
+
+/// What [`recognize_online`] expects the primary service to be searched for.
+pub fn online_query(detected: &DetectedMedia) -> Option<String> {
+    let title = detected.anime_title.as_deref()?;
+    Some(match detected.season.filter(|s| *s > 1) {
+        Some(season) => format!("{title} Season {season}"),
+        None => title.to_string(),
+    })
+}
+
+/// A user's "this is actually…" answer to a wrong or missing recognition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Correction {
+    /// The anime the detection really is.
+    pub anime_id: i64,
+    /// Only apply to files from the detected release group.
+    pub same_release_group: bool,
+    /// Only apply to files in the detected file's folder.
+    pub same_folder: bool,
+}
+
+/// Remember that the detected title means `correction.anime_id`, then
+/// process the detection again with that in mind.
+///
+/// The alias is keyed on the parsed title and season, so later episodes of
+/// the same release are recognized too. Scope flags are ignored when the
+/// detection has no release group or local file.
+#[tracing::instrument(
+    name = "correct_recognition",
+    skip(storage, config, cache, tracker, relations),
+    fields(raw_title = %detected.raw_title)
+)]
+pub fn correct_recognition(
+    detected: &DetectedMedia,
+    correction: Correction,
+    storage: &Storage,
+    config: &AppConfig,
+    cache: &mut RecognitionCache,
+    tracker: &mut WatchTracker,
+    relations: Option<&RelationDatabase>,
+) -> Result<UpdateOutcome, RyuujiError> {
+    let Some(title) = detected.anime_title.as_deref() else {
+        return Ok(UpdateOutcome::Unrecognized {
+            raw_title: detected.raw_title.clone(),
+        });
     };
 
-    let episode = match detected.episode {
-        Some(ep) => ep,
+    let release_group = detected
+        .release_group
+        .as_deref()
+        .filter(|_| correction.same_release_group);
+    let folder = detected
+        .file_path
+        .as_deref()
+        .filter(|_| correction.same_folder)
+        .and_then(parent_folder);
+    storage.add_title_alias(
+        title,
+        detected.season,
+        correction.anime_id,
+        release_group,
+        folder,
+    )?;
+    // The user has said what this is; no need to ask about it again.
+    storage.set_unconfirmed(correction.anime_id, false)?;
+    info!(
+        title = %title,
+        anime_id = correction.anime_id,
+        release_group = ?release_group,
+        folder = ?folder,
+        "Title alias added"
+    );
+    cache.invalidate();
+
+    process_detection(detected, storage, config, cache, tracker, relations)
+}
+
+/// The folder part of a file path, keeping the trailing separator so it
+/// only prefixes paths inside that folder.
+fn parent_folder(path: &str) -> Option<&str> {
+    path.rfind(['/', '\\']).map(|i| &path[..=i])
+}
+
+/// The detected title and episode, if both are known.
+fn detected_episode(detected: &DetectedMedia) -> Option<(&str, u32)> {
+    let title = detected.anime_title.as_deref()?;
+    // A multi-episode file counts as every episode in it.
+    match detected.episode {
+        Some(ep) => Some((title, detected.episode_end.map_or(ep, |end| end.max(ep)))),
         None => {
             debug!(title = %title, "No episode number detected, skipping update");
-            return Ok(UpdateOutcome::Unrecognized {
-                raw_title: detected.raw_title.clone(),
-            });
+            None
         }
-    };
+    }
+}
 
-    // Try to match against all known anime using the recognition cache.
-    let match_result = cache.recognize(title, storage);
-
-    match match_result {
-        MatchResult::Matched(anime) | MatchResult::Fuzzy(anime, _) => {
-            let mut target_anime_id = anime.id;
-            let mut target_episode = episode;
-            let mut anime_title = anime.title.preferred().to_string();
-
-            // Check for episode relation redirects (cross-season mapping).
-            if let Some(relations) = relations {
-                if let Some(mal_id) = anime.ids.mal {
-                    if let Some(redirect) = relations.redirect_mal(mal_id, episode) {
-                        if let Some(dest_mal) = redirect.dest_mal {
-                            // Look up the destination anime in local DB.
-                            if let Ok(Some(dest_anime)) = storage.get_anime_by_mal_id(dest_mal) {
-                                debug!(
-                                    from_title = %anime_title,
-                                    from_ep = episode,
-                                    to_title = %dest_anime.title.preferred(),
-                                    to_ep = redirect.dest_episode,
-                                    "Episode relation redirect"
-                                );
-                                target_anime_id = dest_anime.id;
-                                target_episode = redirect.dest_episode;
-                                anime_title = dest_anime.title.preferred().to_string();
-                            }
-                        }
-                    }
-                }
-            }
+/// The season to try a series title with, for detections whose title names
+/// the whole series (media servers). Titles parsed from filenames are looked
+/// up as they are.
+fn series_season(detected: &DetectedMedia) -> Option<u32> {
+    detected.season.filter(|_| detected.series_title)
+}
+
+/// Media servers name the series, while services list later seasons as
+/// separate entries, so try the season title first.
+fn match_season_first(
+    title: &str,
+    season: Option<u32>,
+    mut recognize: impl FnMut(&str) -> MatchResult,
+) -> MatchResult {
+    match season.filter(|s| *s > 1) {
+        Some(season) => match recognize(&format!("{title} Season {season}")) {
+            exact @ MatchResult::Matched(_) => exact,
+            _ => recognize(title),
+        },
+        None => recognize(title),
+    }
+}
+
+/// Save an anime found online, merging with any row that has the same
+/// service ID. Returns its local ID.
+/// The local copy of an online result, found the way
+/// [`store_online_anime`] would update it.
+fn find_online_anime(storage: &Storage, anime: &Anime) -> Result<Option<Anime>, RyuujiError> {
+    if let Some(id) = anime.ids.anilist {
+        storage.get_anime_by_anilist_id(id)
+    } else if let Some(id) = anime.ids.kitsu {
+        storage.get_anime_by_kitsu_id(id)
+    } else if let Some(id) = anime.ids.mal {
+        storage.get_anime_by_mal_id(id)
+    } else {
+        Ok(None)
+    }
+}
+
+fn store_online_anime(storage: &Storage, anime: &Anime) -> Result<i64, RyuujiError> {
+    if anime.ids.anilist.is_some() {
+        storage.upsert_anime_by_anilist_id(anime)
+    } else if anime.ids.kitsu.is_some() {
+        storage.upsert_anime_by_kitsu_id(anime)
+    } else if anime.ids.mal.is_some() {
+        storage.upsert_anime_by_mal_id(anime)
+    } else {
+        storage.insert_anime(anime)
+    }
+}
+
+/// Write progress for a recognized anime: follow relation redirects, wait
+/// for the watch threshold, and queue the update instead when `confirm` is
+/// set or the anime is [unconfirmed](Storage::is_unconfirmed) (unless the
+/// user always confirms this anime).
+fn update_for_match(
+    anime: Anime,
+    episode: u32,
+    progress: WatchProgress,
+    storage: &Storage,
+    config: &AppConfig,
+    relations: Option<&RelationDatabase>,
+    confirm: bool,
+) -> Result<UpdateOutcome, RyuujiError> {
+    let confirm = confirm || storage.is_unconfirmed(anime.id)?;
+    let mut target_anime_id = anime.id;
+    let mut target_episode = episode;
+    let mut anime_title = anime.title.preferred().to_string();
 
-            match storage.get_library_entry_for_anime(target_anime_id)? {
-                Some(entry) => {
-                    if target_episode > entry.watched_episodes {
-                        if config.library.auto_update {
-                            storage.update_episode_count(target_anime_id, target_episode)?;
-                            storage.record_watch(target_anime_id, target_episode)?;
-                            info!(title = %anime_title, episode = target_episode, "Updated progress");
-                            Ok(UpdateOutcome::Updated {
-                                anime_id: target_anime_id,
-                                anime_title,
-                                episode: target_episode,
-                            })
-                        } else {
-                            debug!(title = %anime_title, episode = target_episode, "Auto-update disabled");
-                            Ok(UpdateOutcome::AlreadyCurrent {
-                                anime_id: target_anime_id,
-                                anime_title,
-                                episode: target_episode,
-                            })
-                        }
-                    } else {
+    // Check for episode relation redirects (cross-season mapping).
+    if let Some(relations) = relations {
+        if let Some(mal_id) = anime.ids.mal {
+            if let Some(redirect) = relations.redirect_mal(mal_id, episode) {
+                if let Some(dest_mal) = redirect.dest_mal {
+                    // Look up the destination anime in local DB.
+                    if let Ok(Some(dest_anime)) = storage.get_anime_by_mal_id(dest_mal) {
                         debug!(
-                            title = %anime_title,
-                            current = entry.watched_episodes,
-                            detected = target_episode,
-                            "Already at or past this episode"
+                            from_title = %anime_title,
+                            from_ep = episode,
+                            to_title = %dest_anime.title.preferred(),
+                            to_ep = redirect.dest_episode,
+                            "Episode relation redirect"
                         );
-                        Ok(UpdateOutcome::AlreadyCurrent {
-                            anime_id: target_anime_id,
-                            anime_title,
-                            episode: target_episode,
-                        })
+                        target_anime_id = dest_anime.id;
+                        target_episode = redirect.dest_episode;
+                        anime_title = dest_anime.title.preferred().to_string();
                     }
                 }
-                None => {
-                    // No library entry — auto-add as Watching.
-                    let entry = LibraryEntry {
-                        id: 0,
-                        anime_id: target_anime_id,
-                        status: WatchStatus::Watching,
-                        watched_episodes: target_episode,
-                        score: None,
-                        updated_at: Utc::now(),
-                        start_date: None,
-                        finish_date: None,
-                        notes: None,
-                        rewatching: false,
-                        rewatch_count: 0,
-                    };
-                    storage.upsert_library_entry(&entry)?;
-                    storage.record_watch(target_anime_id, target_episode)?;
-                    info!(title = %anime_title, episode = target_episode, "Added to library");
-                    Ok(UpdateOutcome::AddedToLibrary {
-                        anime_id: target_anime_id,
+            }
+        }
+    }
+
+    let entry = storage.get_library_entry_for_anime(target_anime_id)?;
+    let needs_write = entry
+        .as_ref()
+        .is_none_or(|e| target_episode > e.watched_episodes && config.library.auto_update);
+    if needs_write {
+        if let WatchProgress::Pending { watched, required } = progress {
+            debug!(
+                title = %anime_title,
+                episode = target_episode,
+                watched_secs = watched.as_secs(),
+                required_secs = required.as_secs(),
+                "Watch threshold not reached yet"
+            );
+            return Ok(UpdateOutcome::InProgress {
+                anime_id: target_anime_id,
+                anime_title,
+                episode: target_episode,
+                watched,
+                required,
+            });
+        }
+    }
+
+    if needs_write && confirm && !storage.is_auto_confirm(target_anime_id)? {
+        return if storage.queue_pending_update(target_anime_id, target_episode)? {
+            info!(title = %anime_title, episode = target_episode, "Queued update for confirmation");
+            Ok(UpdateOutcome::PendingConfirmation {
+                anime_id: target_anime_id,
+                anime_title,
+                episode: target_episode,
+            })
+        } else {
+            debug!(title = %anime_title, episode = target_episode, "Update was rejected earlier");
+            Ok(UpdateOutcome::AlreadyCurrent {
+                anime_id: target_anime_id,
+                anime_title,
+                episode: target_episode,
+            })
+        };
+    }
+
+    apply_progress(
+        storage,
+        config.library.auto_update,
+        entry,
+        target_anime_id,
+        anime_title,
+        target_episode,
+    )
+}
+
+/// Write detected progress to the library: bump the episode count of an
+/// existing entry, or add the anime as Watching if it has none.
+fn apply_progress(
+    storage: &Storage,
+    auto_update: bool,
+    entry: Option<LibraryEntry>,
+    anime_id: i64,
+    anime_title: String,
+    episode: u32,
+) -> Result<UpdateOutcome, RyuujiError> {
+    match entry {
+        Some(entry) => {
+            if episode > entry.watched_episodes {
+                if auto_update {
+                    storage.update_episode_count(anime_id, episode)?;
+                    storage.record_watch(anime_id, episode)?;
+                    info!(title = %anime_title, episode, "Updated progress");
+                    Ok(UpdateOutcome::Updated {
+                        anime_id,
                         anime_title,
-                        episode: target_episode,
+                        episode,
+                    })
+                } else {
+                    debug!(title = %anime_title, episode, "Auto-update disabled");
+                    Ok(UpdateOutcome::AlreadyCurrent {
+                        anime_id,
+                        anime_title,
+                        episode,
                     })
                 }
+            } else {
+                debug!(
+                    title = %anime_title,
+                    current = entry.watched_episodes,
+                    detected = episode,
+                    "Already at or past this episode"
+                );
+                Ok(UpdateOutcome::AlreadyCurrent {
+                    anime_id,
+                    anime_title,
+                    episode,
+                })
             }
         }
-        MatchResult::NoMatch => {
-            warn!(title = %title, "No match found in local library");
-            Ok(UpdateOutcome::Unrecognized {
-                raw_title: detected.raw_title.clone(),
+        None => {
+            // No library entry — auto-add as Watching.
+            let entry = LibraryEntry {
+                id: 0,
+                anime_id,
+                status: WatchStatus::Watching,
+                watched_episodes: episode,
+                score: None,
+                updated_at: Utc::now(),
+                start_date: None,
+                finish_date: None,
+                notes: None,
+                rewatching: false,
+                rewatch_count: 0,
+            };
+            storage.upsert_library_entry(&entry)?;
+            storage.record_watch(anime_id, episode)?;
+            info!(title = %anime_title, episode, "Added to library");
+            Ok(UpdateOutcome::AddedToLibrary {
+                anime_id,
+                anime_title,
+                episode,
             })
         }
     }
 }
 
+/// Apply the pending update the user approved for an anime.
+///
+/// Progress is written the same way a detection would have written it; with
+/// `always`, later updates for this anime are applied without asking.
+/// Returns `None` if nothing is pending for the anime.
+pub fn approve_pending_update(
+    storage: &Storage,
+    anime_id: i64,
+    always: bool,
+) -> Result<Option<UpdateOutcome>, RyuujiError> {
+    let Some(pending) = storage.get_pending_update(anime_id)? else {
+        return Ok(None);
+    };
+    let Some(anime) = storage.get_anime(anime_id)? else {
+        return Ok(None);
+    };
+
+    if always {
+        storage.set_auto_confirm(anime_id, true)?;
+    }
+    storage.set_unconfirmed(anime_id, false)?;
+    let entry = storage.get_library_entry_for_anime(anime_id)?;
+    let outcome = apply_progress(
+        storage,
+        true,
+        entry,
+        anime_id,
+        anime.title.preferred().to_string(),
+        pending.episode,
+    )?;
+    storage.clear_pending_updates(anime_id, pending.episode)?;
+    Ok(Some(outcome))
+}
+
+/// Dismiss the pending updates for an anime without touching the library.
+///
+/// An anime that only exists because of a weak online match is deleted, so
+/// its title is no longer recognized as it and the online fallback can run
+/// again. Returns whether it was deleted; the recognition cache is stale if
+/// so.
+pub fn reject_pending_update(storage: &Storage, anime_id: i64) -> Result<bool, RyuujiError> {
+    storage.reject_pending_updates(anime_id)?;
+    if !storage.is_unconfirmed(anime_id)?
+        || storage.get_library_entry_for_anime(anime_id)?.is_some()
+    {
+        return Ok(false);
+    }
+    info!(anime_id, "Removing rejected online match");
+    storage.delete_anime(anime_id)?;
+    Ok(true)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::models::{Anime, AnimeIds, AnimeTitle};
 
-    fn setup() -> (Storage, AppConfig, RecognitionCache) {
+    fn setup() -> (Storage, AppConfig, RecognitionCache, WatchTracker) {
         let storage = Storage::open_memory().unwrap();
         let config = AppConfig::default();
         let cache = RecognitionCache::new();
-        (storage, config, cache)
+        (storage, config, cache, WatchTracker::new())
+    }
+
+    fn frieren() -> Anime {
+        Anime {
+            id: 0,
+            ids: AnimeIds::default(),
+            title: AnimeTitle {
+                romaji: Some("Sousou no Frieren".into()),
+                english: Some("Frieren: Beyond Journey's End".into()),
+                native: None,
+            },
+            synonyms: vec!["Frieren".into()],
+            episodes: Some(28),
+            cover_url: None,
+            season: None,
+            year: None,
+            synopsis: None,
+            genres: vec![],
+            media_type: None,
+            airing_status: None,
+            mean_score: None,
+            studios: vec![],
+            source: None,
+            rating: None,
+            start_date: None,
+            end_date: None,
+        }
     }
 
     fn insert_frieren(storage: &Storage) -> i64 {
-        storage
-            .insert_anime(&Anime {
-                id: 0,
-                ids: AnimeIds::default(),
-                title: AnimeTitle {
-                    romaji: Some("Sousou no Frieren".into()),
-                    english: Some("Frieren: Beyond Journey's End".into()),
-                    native: None,
-                },
-                synonyms: vec!["Frieren".into()],
-                episodes: Some(28),
-                cover_url: None,
-                season: None,
-                year: None,
-                synopsis: None,
-                genres: vec![],
-                media_type: None,
-                airing_status: None,
-                mean_score: None,
-                studios: vec![],
-                source: None,
-                rating: None,
-                start_date: None,
-                end_date: None,
-            })
-            .unwrap()
+        storage.insert_anime(&frieren()).unwrap()
     }
 
     fn detected(title: &str, episode: u32) -> DetectedMedia {
@@ -224,13 +597,13 @@ mod tests {
             release_group: None,
             resolution: None,
             raw_title: format!("[Group] {title} - {episode:02} [1080p].mkv"),
-            service_name: None,
+            ..Default::default()
         }
     }
 
     #[test]
     fn test_adds_to_library_on_first_detection() {
-        let (storage, config, mut cache) = setup();
+        let (storage, config, mut cache, mut tracker) = setup();
         insert_frieren(&storage);
 
         let result = process_detection(
@@ -238,6 +611,7 @@ mod tests {
             &storage,
             &config,
             &mut cache,
+            &mut tracker,
             None,
         );
         match result.unwrap() {
@@ -250,9 +624,32 @@ mod tests {
         assert_eq!(entry.status, WatchStatus::Watching);
     }
 
+    #[test]
+    fn test_multi_episode_file_counts_every_episode() {
+        let (storage, config, mut cache, mut tracker) = setup();
+        let anime_id = insert_frieren(&storage);
+
+        let detected = DetectedMedia {
+            episode_end: Some(2),
+            raw_title: "[Group] Sousou no Frieren - 01-02 [1080p].mkv".into(),
+            ..detected("Sousou no Frieren", 1)
+        };
+        let result =
+            process_detection(&detected, &storage, &config, &mut cache, &mut tracker, None);
+        assert!(matches!(
+            result.unwrap(),
+            UpdateOutcome::AddedToLibrary { episode: 2, .. }
+        ));
+        let entry = storage
+            .get_library_entry_for_anime(anime_id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.watched_episodes, 2);
+    }
+
     #[test]
     fn test_updates_progress() {
-        let (storage, config, mut cache) = setup();
+        let (storage, config, mut cache, mut tracker) = setup();
         let anime_id = insert_frieren(&storage);
 
         // First detection creates entry.
@@ -261,6 +658,7 @@ mod tests {
             &storage,
             &config,
             &mut cache,
+            &mut tracker,
             None,
         )
         .unwrap();
@@ -271,6 +669,7 @@ mod tests {
             &storage,
             &config,
             &mut cache,
+            &mut tracker,
             None,
         );
         match result.unwrap() {
@@ -287,7 +686,7 @@ mod tests {
 
     #[test]
     fn test_already_current() {
-        let (storage, config, mut cache) = setup();
+        let (storage, config, mut cache, mut tracker) = setup();
         insert_frieren(&storage);
 
         process_detection(
@@ -295,6 +694,7 @@ mod tests {
             &storage,
             &config,
             &mut cache,
+            &mut tracker,
             None,
         )
         .unwrap();
@@ -305,6 +705,7 @@ mod tests {
             &storage,
             &config,
             &mut cache,
+            &mut tracker,
             None,
         );
         assert!(matches!(
@@ -315,13 +716,14 @@ mod tests {
 
     #[test]
     fn test_unrecognized() {
-        let (storage, config, mut cache) = setup();
+        let (storage, config, mut cache, mut tracker) = setup();
         // DB is empty, so nothing matches.
         let result = process_detection(
             &detected("Unknown Anime", 1),
             &storage,
             &config,
             &mut cache,
+            &mut tracker,
             None,
         );
         assert!(matches!(
@@ -329,4 +731,450 @@ mod tests {
             UpdateOutcome::Unrecognized { .. }
         ));
     }
+
+    #[test]
+    fn test_waits_for_watch_threshold() {
+        let (storage, config, mut cache, mut tracker) = setup();
+        let anime_id = insert_frieren(&storage);
+
+        let mut media = detected("Sousou no Frieren", 1);
+        media.position = Some(Duration::ZERO);
+        media.duration = Some(Duration::from_secs(1440));
+        media.playback_state = Some(ryuuji_detect::PlaybackState::Playing);
+
+        let result = process_detection(&media, &storage, &config, &mut cache, &mut tracker, None);
+        match result.unwrap() {
+            UpdateOutcome::InProgress {
+                episode, required, ..
+            } => {
+                assert_eq!(episode, 1);
+                assert_eq!(required, Duration::from_secs(1152));
+            }
+            other => panic!("Expected InProgress, got {other:?}"),
+        }
+        assert!(storage
+            .get_library_entry_for_anime(anime_id)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_confirm_update_queues_and_approves() {
+        let (storage, mut config, mut cache, mut tracker) = setup();
+        config.library.confirm_update = true;
+        let anime_id = insert_frieren(&storage);
+
+        let result = process_detection(
+            &detected("Sousou no Frieren", 2),
+            &storage,
+            &config,
+            &mut cache,
+            &mut tracker,
+            None,
+        );
+        assert!(matches!(
+            result.unwrap(),
+            UpdateOutcome::PendingConfirmation { episode: 2, .. }
+        ));
+        assert!(storage
+            .get_library_entry_for_anime(anime_id)
+            .unwrap()
+            .is_none());
+        assert_eq!(storage.get_pending_updates().unwrap().len(), 1);
+
+        let outcome = approve_pending_update(&storage, anime_id, false).unwrap();
+        assert!(matches!(
+            outcome,
+            Some(UpdateOutcome::AddedToLibrary { episode: 2, .. })
+        ));
+        assert!(storage.get_pending_updates().unwrap().is_empty());
+        let entry = storage
+            .get_library_entry_for_anime(anime_id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.watched_episodes, 2);
+    }
+
+    #[test]
+    fn test_confirm_update_rejected_is_not_asked_again() {
+        let (storage, mut config, mut cache, mut tracker) = setup();
+        config.library.confirm_update = true;
+        let anime_id = insert_frieren(&storage);
+
+        process_detection(
+            &detected("Sousou no Frieren", 2),
+            &storage,
+            &config,
+            &mut cache,
+            &mut tracker,
+            None,
+        )
+        .unwrap();
+        storage.reject_pending_updates(anime_id).unwrap();
+
+        let result = process_detection(
+            &detected("Sousou no Frieren", 2),
+            &storage,
+            &config,
+            &mut cache,
+            &mut tracker,
+            None,
+        );
+        assert!(matches!(
+            result.unwrap(),
+            UpdateOutcome::AlreadyCurrent { .. }
+        ));
+        assert!(approve_pending_update(&storage, anime_id, false)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_confirm_update_always_for_anime() {
+        let (storage, mut config, mut cache, mut tracker) = setup();
+        config.library.confirm_update = true;
+        let anime_id = insert_frieren(&storage);
+
+        process_detection(
+            &detected("Sousou no Frieren", 1),
+            &storage,
+            &config,
+            &mut cache,
+            &mut tracker,
+            None,
+        )
+        .unwrap();
+        approve_pending_update(&storage, anime_id, true).unwrap();
+
+        // Later episodes are applied without asking.
+        let result = process_detection(
+            &detected("Sousou no Frieren", 2),
+            &storage,
+            &config,
+            &mut cache,
+            &mut tracker,
+            None,
+        );
+        assert!(matches!(
+            result.unwrap(),
+            UpdateOutcome::Updated { episode: 2, .. }
+        ));
+    }
+
+    #[test]
+    fn test_season_title_preferred() {
+        let (storage, config, mut cache, mut tracker) = setup();
+        insert_frieren(&storage);
+        let mut season2 = frieren();
+        season2.title = AnimeTitle {
+            romaji: Some("Sousou no Frieren Season 2".into()),
+            english: None,
+            native: None,
+        };
+        season2.synonyms = vec![];
+        let season2_id = storage.insert_anime(&season2).unwrap();
+
+        let mut media = detected("Sousou no Frieren", 3);
+        media.season = Some(2);
+        media.series_title = true;
+        match process_detection(&media, &storage, &config, &mut cache, &mut tracker, None).unwrap()
+        {
+            UpdateOutcome::AddedToLibrary { anime_id, .. } => assert_eq!(anime_id, season2_id),
+            other => panic!("Expected AddedToLibrary, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_season_title_only_for_series_titles() {
+        let (storage, config, mut cache, mut tracker) = setup();
+        let frieren_id = insert_frieren(&storage);
+        let mut season2 = frieren();
+        season2.title = AnimeTitle {
+            romaji: Some("Sousou no Frieren Season 2".into()),
+            english: None,
+            native: None,
+        };
+        season2.synonyms = vec![];
+        storage.insert_anime(&season2).unwrap();
+
+        // A filename's title is looked up as parsed, season or not.
+        let mut media = detected("Sousou no Frieren", 3);
+        media.season = Some(2);
+        match process_detection(&media, &storage, &config, &mut cache, &mut tracker, None).unwrap()
+        {
+            UpdateOutcome::AddedToLibrary { anime_id, .. } => assert_eq!(anime_id, frieren_id),
+            other => panic!("Expected AddedToLibrary, got {other:?}"),
+        }
+        assert_eq!(cache.stats().hits_exact, 1);
+    }
+
+    #[test]
+    fn test_alias_beats_season_title() {
+        let (storage, config, mut cache, mut tracker) = setup();
+        let frieren_id = insert_frieren(&storage);
+        let mut season2 = frieren();
+        season2.title = AnimeTitle {
+            romaji: Some("Sousou no Frieren Season 2".into()),
+            english: None,
+            native: None,
+        };
+        season2.synonyms = vec![];
+        storage.insert_anime(&season2).unwrap();
+        storage
+            .add_title_alias("Sousou no Frieren", Some(2), frieren_id, None, None)
+            .unwrap();
+
+        let mut media = detected("Sousou no Frieren", 3);
+        media.season = Some(2);
+        media.series_title = true;
+        match process_detection(&media, &storage, &config, &mut cache, &mut tracker, None).unwrap()
+        {
+            UpdateOutcome::AddedToLibrary { anime_id, .. } => assert_eq!(anime_id, frieren_id),
+            other => panic!("Expected AddedToLibrary, got {other:?}"),
+        }
+        assert_eq!(cache.stats().hits_alias, 1);
+    }
+
+    fn online_frieren() -> Anime {
+        let mut anime = frieren();
+        anime.ids.anilist = Some(154587);
+        anime
+    }
+
+    #[test]
+    fn test_recognize_online_adds_best_result() {
+        let (storage, config, mut cache, mut tracker) = setup();
+        let media = detected("Sousou no Frieren", 1);
+        assert!(matches!(
+            process_detection(&media, &storage, &config, &mut cache, &mut tracker, None).unwrap(),
+            UpdateOutcome::Unrecognized { .. }
+        ));
+        assert_eq!(online_query(&media).as_deref(), Some("Sousou no Frieren"));
+
+        let mut other = frieren();
+        other.title.romaji = Some("Shingeki no Kyojin".into());
+        other.title.english = None;
+        other.synonyms = vec![];
+        other.ids.anilist = Some(16498);
+        let candidates = [other, online_frieren()];
+
+        let anime_id = match recognize_online(
+            &media,
+            WatchProgress::Reached,
+            &candidates,
+            &storage,
+            &config,
+            &mut cache,
+            None,
+        )
+        .unwrap()
+        {
+            UpdateOutcome::AddedToLibrary {
+                anime_id, episode, ..
+            } => {
+                assert_eq!(episode, 1);
+                anime_id
+            }
+            other => panic!("Expected AddedToLibrary, got {other:?}"),
+        };
+        let stored = storage.get_anime(anime_id).unwrap().unwrap();
+        assert_eq!(stored.ids.anilist, Some(154587));
+        assert_eq!(storage.all_anime().unwrap().len(), 1);
+
+        // Later episodes are recognized locally.
+        assert!(matches!(
+            process_detection(
+                &detected("Sousou no Frieren", 2),
+                &storage,
+                &config,
+                &mut cache,
+                &mut tracker,
+                None,
+            )
+            .unwrap(),
+            UpdateOutcome::Updated { episode: 2, .. }
+        ));
+    }
+
+    #[test]
+    fn test_recognize_online_queues_weak_match() {
+        let (storage, mut config, mut cache, mut tracker) = setup();
+        config.library.online_fallback_threshold = 1.0;
+        let media = detected("Sosou no Frieren", 1);
+
+        let result = recognize_online(
+            &media,
+            WatchProgress::Reached,
+            &[online_frieren()],
+            &storage,
+            &config,
+            &mut cache,
+            None,
+        );
+        let anime_id = match result.unwrap() {
+            UpdateOutcome::PendingConfirmation { anime_id, .. } => anime_id,
+            other => panic!("Expected PendingConfirmation, got {other:?}"),
+        };
+
+        // The next tick recognizes it locally, but still asks.
+        let result = process_detection(&media, &storage, &config, &mut cache, &mut tracker, None);
+        assert!(matches!(
+            result.unwrap(),
+            UpdateOutcome::PendingConfirmation { anime_id: id, .. } if id == anime_id
+        ));
+        assert!(storage
+            .get_library_entry_for_anime(anime_id)
+            .unwrap()
+            .is_none());
+
+        assert!(matches!(
+            approve_pending_update(&storage, anime_id, false).unwrap(),
+            Some(UpdateOutcome::AddedToLibrary { episode: 1, .. })
+        ));
+        // Approved once, it's recognized like any other anime.
+        let result = process_detection(
+            &detected("Sosou no Frieren", 2),
+            &storage,
+            &config,
+            &mut cache,
+            &mut tracker,
+            None,
+        );
+        assert!(matches!(
+            result.unwrap(),
+            UpdateOutcome::Updated { episode: 2, .. }
+        ));
+    }
+
+    #[test]
+    fn test_reject_weak_online_match() {
+        let (storage, mut config, mut cache, mut tracker) = setup();
+        config.library.online_fallback_threshold = 1.0;
+        let media = detected("Sosou no Frieren", 1);
+
+        let anime_id = match recognize_online(
+            &media,
+            WatchProgress::Reached,
+            &[online_frieren()],
+            &storage,
+            &config,
+            &mut cache,
+            None,
+        )
+        .unwrap()
+        {
+            UpdateOutcome::PendingConfirmation { anime_id, .. } => anime_id,
+            other => panic!("Expected PendingConfirmation, got {other:?}"),
+        };
+
+        assert!(reject_pending_update(&storage, anime_id).unwrap());
+        cache.invalidate();
+        assert!(storage.get_anime(anime_id).unwrap().is_none());
+        assert!(storage.get_pending_updates().unwrap().is_empty());
+        // Not recognized locally any more, so the fallback runs again.
+        assert!(matches!(
+            process_detection(&media, &storage, &config, &mut cache, &mut tracker, None).unwrap(),
+            UpdateOutcome::Unrecognized { .. }
+        ));
+    }
+
+    #[test]
+    fn test_weak_match_to_known_anime() {
+        let (storage, mut config, mut cache, _) = setup();
+        config.library.online_fallback_threshold = 1.0;
+        let anime_id = storage.insert_anime(&online_frieren()).unwrap();
+
+        // The update is still confirmed first...
+        let result = recognize_online(
+            &detected("Sosou no Frieren", 1),
+            WatchProgress::Reached,
+            &[online_frieren()],
+            &storage,
+            &config,
+            &mut cache,
+            None,
+        );
+        assert!(matches!(
+            result.unwrap(),
+            UpdateOutcome::PendingConfirmation { anime_id: id, .. } if id == anime_id
+        ));
+        // ...but rejecting it leaves an anime the match didn't add.
+        assert!(!reject_pending_update(&storage, anime_id).unwrap());
+        assert!(storage.get_anime(anime_id).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_recognize_online_without_match() {
+        let (storage, config, mut cache, _) = setup();
+        let result = recognize_online(
+            &detected("Completely Different Anime", 1),
+            WatchProgress::Reached,
+            &[online_frieren()],
+            &storage,
+            &config,
+            &mut cache,
+            None,
+        );
+        assert!(matches!(
+            result.unwrap(),
+            UpdateOutcome::Unrecognized { .. }
+        ));
+        assert!(storage.all_anime().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_correct_recognition() {
+        let (storage, config, mut cache, mut tracker) = setup();
+        insert_frieren(&storage);
+        let other_id = storage
+            .insert_anime(&Anime {
+                title: AnimeTitle {
+                    romaji: Some("Frieren no Mahou".into()),
+                    english: None,
+                    native: None,
+                },
+                synonyms: vec![],
+                ..frieren()
+            })
+            .unwrap();
+
+        let mut detected = detected("Frieren", 1);
+        detected.release_group = Some("Group".into());
+        detected.file_path = Some("/anime/Mahou/Frieren - 01.mkv".into());
+        let correction = Correction {
+            anime_id: other_id,
+            same_release_group: true,
+            same_folder: true,
+        };
+        let result = correct_recognition(
+            &detected,
+            correction,
+            &storage,
+            &config,
+            &mut cache,
+            &mut tracker,
+            None,
+        );
+        match result.unwrap() {
+            UpdateOutcome::AddedToLibrary { anime_id, .. } => assert_eq!(anime_id, other_id),
+            other => panic!("Expected AddedToLibrary, got {other:?}"),
+        }
+
+        let aliases = storage.all_title_aliases().unwrap();
+        assert_eq!(aliases.len(), 1);
+        assert_eq!(aliases[0].title, "frieren");
+        assert_eq!(aliases[0].season, None);
+        assert_eq!(aliases[0].release_group.as_deref(), Some("Group"));
+        assert_eq!(aliases[0].path_prefix.as_deref(), Some("/anime/Mahou/"));
+    }
+
+    #[test]
+    fn test_parent_folder() {
+        assert_eq!(parent_folder("/anime/Show/01.mkv"), Some("/anime/Show/"));
+        assert_eq!(
+            parent_folder(r"C:\Anime\Show\01.mkv"),
+            Some(r"C:\Anime\Show\")
+        );
+        assert_eq!(parent_folder("01.mkv"), None);
+    }
 }
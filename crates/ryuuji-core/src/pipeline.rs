@@ -0,0 +1,503 @@
+//! Front half of the detection pipeline: poll a [`Detector`], pick the
+//! player to track, and turn what it's playing into [`DetectedMedia`].
+//!
+//! The result is handed to [`process_detection`](crate::orchestrator::process_detection)
+//! for recognition and library updates. Every stage is logged to the debug
+//! event log.
+
+use std::path::Path;
+use std::sync::Mutex;
+
+use ryuuji_detect::{Detector, PlayerArbiter, PlayerInfo, StreamDatabase};
+use ryuuji_parse::KeywordSet;
+use tracing::debug;
+
+use crate::debug_log::{DebugEvent, SharedEventLog};
+use crate::models::DetectedMedia;
+
+/// Poll `detector` once and describe what the highest-ranked player is
+/// showing. Returns `None` when nothing usable is playing. Titles are parsed
+/// with `keywords` on top of the built-in ones.
+///
+/// Local files inside one of `watch_folders` are parsed below it, as the
+/// scanner does, so the library folder's own name is never taken for a title.
+pub fn detect_media(
+    detector: &mut dyn Detector,
+    streams: &StreamDatabase,
+    keywords: &KeywordSet,
+    watch_folders: &[String],
+    arbiter: &Mutex<PlayerArbiter>,
+    event_log: &SharedEventLog,
+) -> Option<DetectedMedia> {
+    let push = |event: DebugEvent| {
+        event_log
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(event);
+    };
+
+    let players = detector.detect();
+    let observed_at = detector.observed_at();
+    debug!(player_count = players.len(), "Detection tick");
+    push(DebugEvent::DetectionTick {
+        players_found: players.len() as u32,
+    });
+
+    let (ranked, ranking_changed) = {
+        let mut arbiter = arbiter.lock().unwrap_or_else(|e| e.into_inner());
+        let ranked = arbiter.rank(players, |p| has_episode(p, streams, keywords));
+        (ranked, arbiter.ranking_changed())
+    };
+
+    // A ranking that stays the same tick after tick would crowd everything
+    // else out of the event log.
+    if ranking_changed {
+        for (i, candidate) in ranked.iter().enumerate() {
+            push(DebugEvent::PlayerRanked {
+                player_name: candidate.player.player_name.clone(),
+                rank: i as u32 + 1,
+                reason: candidate.reason(),
+            });
+        }
+    }
+
+    let player = ranked.into_iter().next()?.player;
+
+    push(DebugEvent::PlayerDetected {
+        player_name: player.player_name.clone(),
+        file_path: player.file_path.clone(),
+        is_browser: player.is_browser,
+        media_title: player.media_title.clone(),
+    });
+
+    // Media server session — series and episode come from its library.
+    if let Some(info) = player.episode_info {
+        let raw_title = match (info.season, info.episode) {
+            (Some(s), Some(e)) => format!("{} S{s:02}E{e:02}", info.series),
+            (None, Some(e)) => format!("{} - {e:02}", info.series),
+            _ => info.series.clone(),
+        };
+        push(DebugEvent::Parsed {
+            raw_title: raw_title.clone(),
+            title: Some(info.series.clone()),
+            episode: info.episode,
+            group: None,
+            resolution: None,
+        });
+        return Some(DetectedMedia {
+            service_name: Some(player.player_name.clone()),
+            player_name: player.player_name,
+            anime_title: Some(info.series),
+            episode: info.episode,
+            season: info.season,
+            series_title: true,
+            raw_title,
+            position: player.position,
+            duration: player.duration,
+            playback_state: player.playback_state,
+            observed_at,
+            ..Default::default()
+        });
+    }
+
+    let (raw_title, stream) = if player.is_browser {
+        // Browser detected — try stream service matching.
+        match ryuuji_detect::stream::detect_stream(&player, streams) {
+            Some(m) => {
+                debug!(
+                    service = %m.service_name,
+                    title = %m.extracted_title,
+                    episode = ?m.episode,
+                    "Stream service matched"
+                );
+                push(DebugEvent::StreamMatched {
+                    service_name: m.service_name.clone(),
+                    extracted_title: m.extracted_title.clone(),
+                });
+                (m.extracted_title.clone(), Some(m))
+            }
+            None => {
+                debug!(player = %player.player_name, "Browser detected but no stream service matched");
+                push(DebugEvent::StreamNotMatched {
+                    player_name: player.player_name.clone(),
+                });
+                return None;
+            }
+        }
+    } else {
+        // Regular media player — extract basename from file path or use media title.
+        let raw_title = player_title(&player)?;
+        debug!(player = %player.player_name, raw_title = %raw_title, "Parsing filename");
+        (raw_title, None)
+    };
+
+    let local_path = player
+        .file_path
+        .clone()
+        .filter(|path| stream.is_none() && !path.contains("://"));
+    let parsed = match local_path.as_deref() {
+        // Local files: directory names can supply a title the filename lacks.
+        Some(path) => {
+            let root = watch_folder_of(path, watch_folders).unwrap_or_default();
+            let parsed = ryuuji_parse::parse_path_under(path, root, keywords);
+            if let Some(source) = &parsed.sources.title {
+                debug!(source = ?source, "Title taken from path");
+            }
+            parsed.elements
+        }
+        _ => ryuuji_parse::parse_with_keywords(&raw_title, keywords),
+    };
+    // The service's own episode/season patterns beat the generic parser.
+    let episode = stream
+        .as_ref()
+        .and_then(|m| m.episode)
+        .or(parsed.episode_number);
+    let season = stream
+        .as_ref()
+        .and_then(|m| m.season)
+        .or(parsed.season_number);
+    let episode_end = parsed
+        .episode_number_end
+        .filter(|end| episode.is_some_and(|start| *end > start));
+    push(DebugEvent::Parsed {
+        raw_title: raw_title.clone(),
+        title: parsed.title.clone(),
+        episode,
+        group: parsed.release_group.clone(),
+        resolution: parsed.resolution.clone(),
+    });
+
+    Some(DetectedMedia {
+        player_name: player.player_name,
+        anime_title: parsed.title,
+        episode,
+        episode_end,
+        season,
+        series_title: false,
+        release_group: parsed.release_group,
+        resolution: parsed.resolution,
+        raw_title,
+        file_path: local_path,
+        service_name: stream.map(|m| m.service_name),
+        position: player.position,
+        duration: player.duration,
+        playback_state: player.playback_state,
+        observed_at,
+    })
+}
+
+/// Title a regular media player is showing: the file's basename, or the
+/// reported media title when there is no path.
+fn player_title(player: &PlayerInfo) -> Option<String> {
+    player
+        .file_path
+        .as_deref()
+        .and_then(|p| {
+            Path::new(p)
+                .file_name()
+                .and_then(|f| f.to_str())
+                .map(|s| s.to_string())
+        })
+        .or_else(|| player.media_title.clone())
+}
+
+/// The watch folder `path` is in. The deepest one wins when folders nest.
+fn watch_folder_of<'a>(path: &str, watch_folders: &'a [String]) -> Option<&'a str> {
+    watch_folders
+        .iter()
+        .filter(|folder| !folder.is_empty() && Path::new(path).starts_with(folder))
+        .max_by_key(|folder| folder.len())
+        .map(String::as_str)
+}
+
+/// Whether an episode number can be worked out for this player, used to
+/// rank candidates.
+fn has_episode(player: &PlayerInfo, streams: &StreamDatabase, keywords: &KeywordSet) -> bool {
+    if let Some(info) = &player.episode_info {
+        return info.episode.is_some();
+    }
+    if player.is_browser {
+        ryuuji_detect::stream::detect_stream(player, streams).is_some_and(|m| {
+            m.episode.is_some()
+                || ryuuji_parse::parse_with_keywords(&m.extracted_title, keywords)
+                    .episode_number
+                    .is_some()
+        })
+    } else {
+        player_title(player).is_some_and(|t| {
+            ryuuji_parse::parse_with_keywords(&t, keywords)
+                .episode_number
+                .is_some()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppConfig;
+    use crate::debug_log::shared_event_log;
+    use crate::models::{Anime, AnimeIds, AnimeTitle};
+    use crate::orchestrator::{process_detection, UpdateOutcome};
+    use crate::recognition::RecognitionCache;
+    use crate::storage::Storage;
+    use crate::watch::WatchTracker;
+    use ryuuji_detect::ReplayDetector;
+
+    /// A short mpv session: episode 5 plays to the end at one snapshot every
+    /// 30 seconds, then the player closes.
+    fn recording() -> String {
+        let mut lines = Vec::new();
+        for i in 0..=47 {
+            lines.push(format!(
+                r#"{{"at":{},"players":[{{"player_name":"mpv","media_title":null,"file_path":"/anime/[SubsPlease] Sousou no Frieren - 05 (1080p).mkv","is_browser":false,"position":{{"secs":{},"nanos":0}},"duration":{{"secs":1420,"nanos":0}},"playback_state":"Playing"}}]}}"#,
+                1_700_000_000_000u64 + i * 30_000,
+                (i * 30).min(1420),
+            ));
+        }
+        lines.push(r#"{"at":1700001450000,"players":[]}"#.into());
+        lines.join("\n")
+    }
+
+    fn frieren() -> Anime {
+        Anime {
+            id: 0,
+            ids: AnimeIds::default(),
+            title: AnimeTitle {
+                romaji: Some("Sousou no Frieren".into()),
+                english: Some("Frieren: Beyond Journey's End".into()),
+                native: None,
+            },
+            synonyms: vec![],
+            episodes: Some(28),
+            cover_url: None,
+            season: None,
+            year: None,
+            synopsis: None,
+            genres: vec![],
+            media_type: None,
+            airing_status: None,
+            mean_score: None,
+            studios: vec![],
+            source: None,
+            rating: None,
+            start_date: None,
+            end_date: None,
+        }
+    }
+
+    #[test]
+    fn test_replay_through_pipeline() {
+        let storage = Storage::open_memory().unwrap();
+        let config = AppConfig::default();
+        let mut cache = RecognitionCache::new();
+        let mut tracker = WatchTracker::new();
+        let anime_id = storage.insert_anime(&frieren()).unwrap();
+
+        let mut detector = ReplayDetector::from_reader(recording().as_bytes()).unwrap();
+        let streams = StreamDatabase::embedded();
+        let arbiter = Mutex::new(PlayerArbiter::new(Vec::new()));
+        let keywords = KeywordSet::default();
+        let log = shared_event_log();
+
+        let mut outcomes = Vec::new();
+        while detector.remaining() > 0 {
+            let Some(media) = detect_media(&mut detector, &streams, &keywords, &[], &arbiter, &log)
+            else {
+                continue;
+            };
+            assert_eq!(media.anime_title.as_deref(), Some("Sousou no Frieren"));
+            assert_eq!(media.episode, Some(5));
+            outcomes.push(
+                process_detection(&media, &storage, &config, &mut cache, &mut tracker, None)
+                    .unwrap(),
+            );
+        }
+
+        // Replayed in an instant, but watched at the recorded pace: 80% of
+        // 1420s is reached on the snapshot at 1140s.
+        assert_eq!(outcomes.len(), 48);
+        assert!(outcomes[..38]
+            .iter()
+            .all(|o| matches!(o, UpdateOutcome::InProgress { episode: 5, .. })));
+        assert!(matches!(
+            outcomes[38],
+            UpdateOutcome::AddedToLibrary { episode: 5, .. }
+        ));
+        assert!(outcomes[39..]
+            .iter()
+            .all(|o| matches!(o, UpdateOutcome::AlreadyCurrent { .. })));
+        assert_eq!(
+            storage
+                .get_library_entry_for_anime(anime_id)
+                .unwrap()
+                .unwrap()
+                .watched_episodes,
+            5
+        );
+
+        let events = log.lock().unwrap().snapshot();
+        assert!(events
+            .iter()
+            .any(|(_, e)| matches!(e, DebugEvent::DetectionTick { players_found: 0 })));
+        // Ranked once when mpv appears and once when it becomes the last
+        // active player, not on every tick.
+        let ranked = events
+            .iter()
+            .filter(|(_, e)| matches!(e, DebugEvent::PlayerRanked { .. }))
+            .count();
+        assert_eq!(ranked, 2);
+    }
+
+    #[test]
+    fn test_title_from_parent_directory() {
+        let mut detector = ReplayDetector::new([ryuuji_detect::Snapshot {
+            at: 0,
+            players: vec![PlayerInfo {
+                player_name: "mpv".into(),
+                file_path: Some("/anime/Mushoku Tensei/Season 2/Episode 05.mkv".into()),
+                ..Default::default()
+            }],
+        }]);
+        let media = detect_media(
+            &mut detector,
+            &StreamDatabase::embedded(),
+            &KeywordSet::default(),
+            &[],
+            &Mutex::new(PlayerArbiter::new(Vec::new())),
+            &shared_event_log(),
+        )
+        .unwrap();
+        assert_eq!(media.raw_title, "Episode 05.mkv");
+        assert_eq!(media.anime_title.as_deref(), Some("Mushoku Tensei"));
+        assert_eq!(media.season, Some(2));
+        assert_eq!(media.episode, Some(5));
+    }
+
+    #[test]
+    fn test_watch_folder_name_is_not_a_title() {
+        // A library folder named like a show, holding a file without a title.
+        let dir = tempfile::tempdir().unwrap();
+        let folder = dir.path().join("Anime Club");
+        std::fs::create_dir(&folder).unwrap();
+        let file = folder.join("05.mkv");
+        std::fs::write(&file, b"").unwrap();
+        let folder = folder.to_string_lossy().into_owned();
+        let file = file.to_string_lossy().into_owned();
+
+        let storage = Storage::open_memory().unwrap();
+        storage
+            .insert_anime(&Anime {
+                title: AnimeTitle {
+                    romaji: Some("Anime Club".into()),
+                    english: None,
+                    native: None,
+                },
+                ..frieren()
+            })
+            .unwrap();
+        let mut library = AppConfig::default().library;
+        library.watch_folders = vec![folder.clone()];
+        library.min_file_size_mb = 0;
+        let keywords = KeywordSet::default();
+
+        let scan = crate::scanner::scan_watch_folders(
+            &storage,
+            &mut RecognitionCache::new(),
+            &library,
+            &keywords,
+        )
+        .unwrap();
+        assert_eq!(scan.files_scanned, 1);
+        assert_eq!(scan.files_matched, 0);
+
+        let detect = |watch_folders: &[String]| {
+            let mut detector = ReplayDetector::new([ryuuji_detect::Snapshot {
+                at: 0,
+                players: vec![PlayerInfo {
+                    player_name: "mpv".into(),
+                    file_path: Some(file.clone()),
+                    ..Default::default()
+                }],
+            }]);
+            detect_media(
+                &mut detector,
+                &StreamDatabase::embedded(),
+                &keywords,
+                watch_folders,
+                &Mutex::new(PlayerArbiter::new(Vec::new())),
+                &shared_event_log(),
+            )
+            .unwrap()
+        };
+        let media = detect(&library.watch_folders);
+        assert_eq!(media.anime_title, None);
+        assert_eq!(media.episode, Some(5));
+        // Outside every watch folder, the folder name is all there is.
+        let media = detect(&[]);
+        assert_eq!(media.anime_title.as_deref(), Some("Anime Club"));
+    }
+
+    #[test]
+    fn test_kodi_library_episode() {
+        // What the Kodi backend reports for a library episode on a share.
+        let mut detector = ReplayDetector::new([ryuuji_detect::Snapshot {
+            at: 0,
+            players: vec![PlayerInfo {
+                player_name: "Kodi".into(),
+                media_title: Some("The Hero's Party".into()),
+                file_path: Some("smb://nas/anime/frieren/S01E05.mkv".into()),
+                episode_info: Some(ryuuji_detect::EpisodeInfo {
+                    series: "Frieren: Beyond Journey's End".into(),
+                    season: Some(1),
+                    episode: Some(5),
+                }),
+                ..Default::default()
+            }],
+        }]);
+        let media = detect_media(
+            &mut detector,
+            &StreamDatabase::embedded(),
+            &KeywordSet::default(),
+            &[],
+            &Mutex::new(PlayerArbiter::new(Vec::new())),
+            &shared_event_log(),
+        )
+        .unwrap();
+        assert_eq!(
+            media.anime_title.as_deref(),
+            Some("Frieren: Beyond Journey's End")
+        );
+        assert_eq!(media.season, Some(1));
+        assert_eq!(media.episode, Some(5));
+    }
+
+    #[test]
+    fn test_stream_episode_overrides_parser() {
+        let mut detector = ReplayDetector::new([ryuuji_detect::Snapshot {
+            at: 0,
+            players: vec![PlayerInfo {
+                player_name: "Firefox".into(),
+                media_title: Some("Frieren: Beyond Journey's End - Watch on Crunchyroll".into()),
+                file_path: Some("https://www.crunchyroll.com/watch/GG1U2J1M7/episode-7".into()),
+                is_browser: true,
+                ..Default::default()
+            }],
+        }]);
+        let media = detect_media(
+            &mut detector,
+            &StreamDatabase::embedded(),
+            &KeywordSet::default(),
+            &[],
+            &Mutex::new(PlayerArbiter::new(Vec::new())),
+            &shared_event_log(),
+        )
+        .unwrap();
+        assert_eq!(media.service_name.as_deref(), Some("Crunchyroll"));
+        assert_eq!(
+            media.anime_title.as_deref(),
+            Some("Frieren: Beyond Journey's End")
+        );
+        assert_eq!(media.episode, Some(7));
+    }
+}
@@ -3,10 +3,10 @@ use std::collections::{HashMap, VecDeque};
 use fuzzy_matcher::skim::SkimMatcherV2;
 use fuzzy_matcher::FuzzyMatcher;
 
-use crate::debug_log::CacheStats;
+use crate::debug_log::{CacheStats, MatchLevel};
 use crate::error::RyuujiError;
 use crate::matcher::{self, MatchResult};
-use crate::models::Anime;
+use crate::models::{Anime, DetectedMedia, TitleAlias};
 use crate::storage::Storage;
 
 /// Maximum number of recent query results to cache.
@@ -22,11 +22,34 @@ const FUZZY_THRESHOLD: f64 = 0.6;
 /// rebuilds lazily after `invalidate()`.
 pub struct RecognitionCache {
     entries: Vec<Anime>,
+    /// User title aliases by normalized title, checked before anything else.
+    alias_index: HashMap<String, Vec<TitleAlias>>,
     exact_index: HashMap<String, i64>,
     normalized_index: HashMap<String, i64>,
     query_cache: VecDeque<(String, CachedMatch)>,
     populated: bool,
     stats: CacheStats,
+    last_match: MatchLevel,
+}
+
+/// Where a detected title came from, for title aliases keyed on a season or
+/// scoped to a release group or folder.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RecognitionContext<'a> {
+    /// The detected season; `None` for the first season.
+    pub season: Option<u32>,
+    pub release_group: Option<&'a str>,
+    pub file_path: Option<&'a str>,
+}
+
+impl<'a> RecognitionContext<'a> {
+    pub fn of(detected: &'a DetectedMedia) -> Self {
+        Self {
+            season: detected.season.filter(|s| *s > 1),
+            release_group: detected.release_group.as_deref(),
+            file_path: detected.file_path.as_deref(),
+        }
+    }
 }
 
 /// A cached recognition result (avoids cloning full Anime on every cache hit).
@@ -47,21 +70,32 @@ impl RecognitionCache {
     pub fn new() -> Self {
         Self {
             entries: Vec::new(),
+            alias_index: HashMap::new(),
             exact_index: HashMap::new(),
             normalized_index: HashMap::new(),
             query_cache: VecDeque::with_capacity(QUERY_CACHE_CAPACITY),
             populated: false,
             stats: CacheStats::default(),
+            last_match: MatchLevel::NoMatch,
         }
     }
 
-    /// Load all anime from storage and build the title indices.
+    /// Load all anime and title aliases from storage and build the title
+    /// indices.
     pub fn populate(&mut self, storage: &Storage) -> Result<(), RyuujiError> {
         self.entries = storage.all_anime()?;
+        self.alias_index.clear();
         self.exact_index.clear();
         self.normalized_index.clear();
         self.query_cache.clear();
 
+        for alias in storage.all_title_aliases()? {
+            self.alias_index
+                .entry(alias.title.clone())
+                .or_default()
+                .push(alias);
+        }
+
         for anime in &self.entries {
             let titles = matcher::all_titles(anime);
             for title in titles {
@@ -83,6 +117,7 @@ impl RecognitionCache {
     pub fn invalidate(&mut self) {
         self.populated = false;
         self.entries.clear();
+        self.alias_index.clear();
         self.exact_index.clear();
         self.normalized_index.clear();
         self.query_cache.clear();
@@ -94,61 +129,102 @@ impl RecognitionCache {
         self.stats.clone()
     }
 
+    /// How the most recent recognition was decided.
+    pub fn last_match(&self) -> MatchLevel {
+        self.last_match.clone()
+    }
+
+    /// Record a match made outside the cache (e.g. from online results).
+    pub(crate) fn set_last_match(&mut self, level: MatchLevel) {
+        self.last_match = level;
+    }
+
     /// Recognize an anime title, using cached indices for fast lookup.
     ///
-    /// Flow: query cache → exact index → normalized index → fuzzy scan.
-    /// Results are stored in the query cache for subsequent calls.
-    #[tracing::instrument(name = "recognize", skip(self, storage), fields(query = %query))]
+    /// Only title aliases that apply everywhere are considered; see
+    /// [`recognize_in`](Self::recognize_in).
     pub fn recognize(&mut self, query: &str, storage: &Storage) -> MatchResult {
-        if query.is_empty() {
+        self.recognize_in(query, &RecognitionContext::default(), storage)
+    }
+
+    /// Check only the title aliases for a title detected in `context`.
+    ///
+    /// Callers that try several queries for one detection run this first, so
+    /// a user correction beats a title match on any of them.
+    pub fn recognize_alias(
+        &mut self,
+        query: &str,
+        context: &RecognitionContext<'_>,
+        storage: &Storage,
+    ) -> Option<Anime> {
+        self.last_match = MatchLevel::NoMatch;
+        if query.is_empty() || !self.ensure_populated(storage) {
+            return None;
+        }
+        self.alias_hit(&matcher::normalize(query), context)
+    }
+
+    /// Recognize an anime title detected in `context`.
+    ///
+    /// Flow: title aliases → query cache → exact index → normalized index →
+    /// fuzzy scan. Results other than alias hits are stored in the query
+    /// cache for subsequent calls.
+    #[tracing::instrument(name = "recognize", skip(self, context, storage), fields(query = %query))]
+    pub fn recognize_in(
+        &mut self,
+        query: &str,
+        context: &RecognitionContext<'_>,
+        storage: &Storage,
+    ) -> MatchResult {
+        self.last_match = MatchLevel::NoMatch;
+        if query.is_empty() || !self.ensure_populated(storage) {
             return MatchResult::NoMatch;
         }
 
-        if !self.populated {
-            if let Err(e) = self.populate(storage) {
-                tracing::error!(error = %e, "Failed to populate recognition cache");
-                return MatchResult::NoMatch;
-            }
-            tracing::debug!(
-                anime_count = self.entries.len(),
-                exact_keys = self.exact_index.len(),
-                "Recognition cache populated"
-            );
+        // 1. Check title aliases. Their scope depends on the context, which the
+        // query cache doesn't key on.
+        let normalized = matcher::normalize(query);
+        if let Some(anime) = self.alias_hit(&normalized, context) {
+            return MatchResult::Matched(anime);
         }
 
-        // 1. Check query cache.
+        // 2. Check query cache.
         if let Some(cached) = self.query_cache_lookup(query) {
             tracing::debug!(method = "query_cache", "Recognition hit");
             self.stats.hits_lru += 1;
+            if !matches!(cached, MatchResult::NoMatch) {
+                self.last_match = MatchLevel::LruHit;
+            }
             return cached;
         }
 
-        // 2. Check exact index.
+        // 3. Check exact index.
         if let Some(&anime_id) = self.exact_index.get(query) {
             if let Some(anime) = self.find_entry(anime_id) {
                 tracing::debug!(method = "exact", matched = %anime.title.preferred(), "Recognition hit");
                 let result = MatchResult::Matched(anime);
                 self.query_cache_insert(query, &result);
                 self.stats.hits_exact += 1;
+                self.last_match = MatchLevel::Exact;
                 self.stats.lru_size = self.query_cache.len();
                 return result;
             }
         }
 
-        // 3. Check normalized index.
-        let normalized = matcher::normalize(query);
+        // 4. Check normalized index.
         if let Some(&anime_id) = self.normalized_index.get(&normalized) {
             if let Some(anime) = self.find_entry(anime_id) {
                 tracing::debug!(method = "normalized", matched = %anime.title.preferred(), "Recognition hit");
                 let result = MatchResult::Matched(anime);
                 self.query_cache_insert(query, &result);
                 self.stats.hits_normalized += 1;
+                self.last_match = MatchLevel::Normalized;
                 self.stats.lru_size = self.query_cache.len();
                 return result;
             }
         }
 
-        // 4. Fuzzy fallback over all entries.
+        // 5. Fuzzy fallback over all entries.
         let result = self.fuzzy_scan(&normalized);
         match &result {
             MatchResult::Fuzzy(anime, confidence) => {
@@ -159,6 +235,7 @@ impl RecognitionCache {
                     "Recognition hit"
                 );
                 self.stats.hits_fuzzy += 1;
+                self.last_match = MatchLevel::Fuzzy(*confidence);
             }
             MatchResult::NoMatch => {
                 tracing::debug!("No recognition match");
@@ -171,6 +248,51 @@ impl RecognitionCache {
         result
     }
 
+    /// Populate the cache if it is stale. Returns false if that failed.
+    fn ensure_populated(&mut self, storage: &Storage) -> bool {
+        if !self.populated {
+            if let Err(e) = self.populate(storage) {
+                tracing::error!(error = %e, "Failed to populate recognition cache");
+                return false;
+            }
+            tracing::debug!(
+                anime_count = self.entries.len(),
+                exact_keys = self.exact_index.len(),
+                "Recognition cache populated"
+            );
+        }
+        true
+    }
+
+    /// Look up an alias and record the hit.
+    fn alias_hit(
+        &mut self,
+        normalized_query: &str,
+        context: &RecognitionContext<'_>,
+    ) -> Option<Anime> {
+        let anime = self.alias_lookup(normalized_query, context)?;
+        tracing::debug!(method = "alias", matched = %anime.title.preferred(), "Recognition hit");
+        self.stats.hits_alias += 1;
+        self.last_match = MatchLevel::Alias;
+        Some(anime)
+    }
+
+    /// The most specific alias for `normalized_query` that applies in `context`.
+    fn alias_lookup(
+        &self,
+        normalized_query: &str,
+        context: &RecognitionContext<'_>,
+    ) -> Option<Anime> {
+        let alias = self
+            .alias_index
+            .get(normalized_query)?
+            .iter()
+            .filter(|a| a.season == context.season)
+            .filter(|a| a.applies_to(context.release_group, context.file_path))
+            .max_by_key(|a| a.specificity())?;
+        self.find_entry(alias.anime_id)
+    }
+
     /// Run fuzzy matching over all cached entries.
     fn fuzzy_scan(&self, normalized_query: &str) -> MatchResult {
         if self.entries.is_empty() {
@@ -492,4 +614,122 @@ mod tests {
         let stats = cache.stats();
         assert_eq!(stats.misses, 1);
     }
+
+    #[test]
+    fn test_alias_beats_title_indices() {
+        let storage = Storage::open_memory().unwrap();
+        let frieren = insert_frieren(&storage);
+        let aot = insert_aot(&storage);
+        storage
+            .add_title_alias("Frieren", None, aot, None, None)
+            .unwrap();
+
+        let mut cache = RecognitionCache::new();
+        match cache.recognize("Frieren", &storage) {
+            MatchResult::Matched(a) => assert_eq!(a.id, aot),
+            other => panic!("Expected Matched, got {other:?}"),
+        }
+        assert_eq!(cache.stats().hits_alias, 1);
+        // Other titles of the anime are unaffected.
+        match cache.recognize("Sousou no Frieren", &storage) {
+            MatchResult::Matched(a) => assert_eq!(a.id, frieren),
+            other => panic!("Expected Matched, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_alias_keyed_on_season() {
+        let storage = Storage::open_memory().unwrap();
+        let frieren = insert_frieren(&storage);
+        let aot = insert_aot(&storage);
+        storage
+            .add_title_alias("Frieren", Some(2), aot, None, None)
+            .unwrap();
+
+        let mut cache = RecognitionCache::new();
+        let season = |season| RecognitionContext {
+            season,
+            ..Default::default()
+        };
+        assert_eq!(
+            cache
+                .recognize_alias("Frieren", &season(Some(2)), &storage)
+                .map(|a| a.id),
+            Some(aot)
+        );
+        // The first season still matches by title.
+        assert!(cache
+            .recognize_alias("Frieren", &season(None), &storage)
+            .is_none());
+        match cache.recognize_in("Frieren", &season(None), &storage) {
+            MatchResult::Matched(a) => assert_eq!(a.id, frieren),
+            other => panic!("Expected Matched, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_scoped_alias() {
+        let storage = Storage::open_memory().unwrap();
+        let frieren = insert_frieren(&storage);
+        let aot = insert_aot(&storage);
+        storage
+            .add_title_alias("frieren", None, aot, Some("Erai-raws"), None)
+            .unwrap();
+        storage
+            .add_title_alias(
+                "frieren",
+                None,
+                frieren,
+                Some("Erai-raws"),
+                Some("/anime/Frieren/"),
+            )
+            .unwrap();
+
+        let mut cache = RecognitionCache::new();
+        let mut recognize = |release_group, file_path| {
+            let context = RecognitionContext {
+                season: None,
+                release_group,
+                file_path,
+            };
+            match cache.recognize_in("Frieren", &context, &storage) {
+                MatchResult::Matched(a) => a.id,
+                other => panic!("Expected Matched, got {other:?}"),
+            }
+        };
+
+        // No alias applies, so the synonym matches.
+        assert_eq!(recognize(Some("SubsPlease"), None), frieren);
+        // Group names compare case-insensitively.
+        assert_eq!(
+            recognize(Some("erai-raws"), Some("/downloads/ep01.mkv")),
+            aot
+        );
+        // The folder alias is more specific than the group-only one.
+        assert_eq!(
+            recognize(Some("Erai-raws"), Some("/anime/Frieren/ep01.mkv")),
+            frieren
+        );
+    }
+
+    #[test]
+    fn test_last_match() {
+        let storage = Storage::open_memory().unwrap();
+        insert_frieren(&storage);
+
+        let mut cache = RecognitionCache::new();
+        cache.recognize("Sousou no Frieren", &storage);
+        assert_eq!(cache.last_match(), MatchLevel::Exact);
+        cache.recognize("sousou no frieren", &storage);
+        assert_eq!(cache.last_match(), MatchLevel::Normalized);
+        cache.recognize("Sousou no Frieren", &storage);
+        assert_eq!(cache.last_match(), MatchLevel::LruHit);
+        cache.recognize("Totally Unknown Anime Title", &storage);
+        assert_eq!(cache.last_match(), MatchLevel::NoMatch);
+        assert!(matches!(
+            cache.recognize("Frieren Beyond Journey", &storage),
+            MatchResult::Fuzzy(..)
+        ));
+        assert!(matches!(cache.last_match(), MatchLevel::Fuzzy(_)));
+    }
 }
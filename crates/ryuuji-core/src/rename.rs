@@ -0,0 +1,616 @@
+//! Library renamer: render canonical filenames from a template and rename
+//! indexed episode files to match.
+//!
+//! A [`FilenameTemplate`] is the inverse of parsing. Placeholders in braces
+//! are filled from the matched [`Anime`] and the file's parsed
+//! [`Elements`](ryuuji_parse::Elements):
+//!
+//! ```text
+//! {title} - S{season:02}E{episode:02} [{group}][{resolution}].{ext}
+//! ```
+//!
+//! `{name:02}` zero-pads a number to two digits; `{{` and `}}` are literal
+//! braces. Brackets left empty by a missing value are dropped.
+//!
+//! [`plan_renames`] previews what would change for every file in
+//! `available_episode`, [`apply_renames`] renames them on disk and in the
+//! database, and [`undo_last_rename`] puts the last batch back.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use ryuuji_parse::{Elements, KeywordSet};
+use tracing::{info, warn};
+
+use crate::error::RyuujiError;
+use crate::models::Anime;
+use crate::storage::Storage;
+
+/// A value a template can refer to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Title,
+    TitleRomaji,
+    TitleEnglish,
+    TitleNative,
+    Season,
+    Episode,
+    EpisodeTitle,
+    Year,
+    Group,
+    Resolution,
+    Source,
+    VideoCodec,
+    AudioCodec,
+    Version,
+    Ext,
+}
+
+impl Field {
+    const ALL: &[(&str, Field)] = &[
+        ("title", Field::Title),
+        ("title_romaji", Field::TitleRomaji),
+        ("title_english", Field::TitleEnglish),
+        ("title_native", Field::TitleNative),
+        ("season", Field::Season),
+        ("episode", Field::Episode),
+        ("episode_title", Field::EpisodeTitle),
+        ("year", Field::Year),
+        ("group", Field::Group),
+        ("resolution", Field::Resolution),
+        ("source", Field::Source),
+        ("video_codec", Field::VideoCodec),
+        ("audio_codec", Field::AudioCodec),
+        ("version", Field::Version),
+        ("ext", Field::Ext),
+    ];
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, field)| *field)
+    }
+
+    fn is_numeric(self) -> bool {
+        matches!(self, Self::Season | Self::Episode | Self::Year)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Literal(String),
+    Field { field: Field, width: Option<usize> },
+}
+
+/// A parsed filename template. See the [module docs](self) for the syntax.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilenameTemplate {
+    parts: Vec<Part>,
+}
+
+impl FilenameTemplate {
+    /// Parse a template, rejecting unknown placeholders, bad format specs
+    /// and path separators (files are only renamed within their folder).
+    pub fn parse(template: &str) -> Result<Self, RyuujiError> {
+        let err = |msg: String| RyuujiError::Rename(format!("{msg} in template {template:?}"));
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '}' => return Err(err("unmatched '}'".into())),
+                '/' | '\\' => return Err(err("path separator".into())),
+                '{' => {
+                    let mut inner = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => inner.push(c),
+                            None => return Err(err("unclosed '{'".into())),
+                        }
+                    }
+                    let (name, spec) = match inner.split_once(':') {
+                        Some((name, spec)) => (name.trim(), Some(spec.trim())),
+                        None => (inner.trim(), None),
+                    };
+                    let field = Field::from_name(name)
+                        .ok_or_else(|| err(format!("unknown placeholder {{{name}}}")))?;
+                    let width = match spec {
+                        None => None,
+                        Some(spec) => {
+                            let width = spec
+                                .strip_prefix('0')
+                                .and_then(|w| w.parse::<usize>().ok())
+                                .ok_or_else(|| err(format!("bad format spec {spec:?}")))?;
+                            if !field.is_numeric() {
+                                return Err(err(format!("{{{name}}} is not a number")));
+                            }
+                            Some(width)
+                        }
+                    };
+                    if !literal.is_empty() {
+                        parts.push(Part::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(Part::Field { field, width });
+                }
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+        Ok(Self { parts })
+    }
+
+    /// Render a filename for an episode of `anime`, with everything else
+    /// taken from `elements`.
+    pub fn render(&self, anime: Option<&Anime>, elements: &Elements) -> String {
+        let mut out = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Literal(text) => out.push_str(text),
+                Part::Field { field, width } => {
+                    out.push_str(&sanitize(&field_value(*field, *width, anime, elements)))
+                }
+            }
+        }
+        tidy(&out)
+    }
+}
+
+fn field_value(
+    field: Field,
+    width: Option<usize>,
+    anime: Option<&Anime>,
+    elements: &Elements,
+) -> String {
+    let number = |n: Option<u32>| match n {
+        Some(n) => format!("{n:0width$}", width = width.unwrap_or(0)),
+        None => String::new(),
+    };
+    let text = |s: Option<&str>| s.unwrap_or_default().to_string();
+    let title = anime.map(|a| &a.title);
+
+    match field {
+        Field::Title => match title {
+            Some(t) => t.preferred().to_string(),
+            None => text(elements.title.as_deref()),
+        },
+        Field::TitleRomaji => text(title.and_then(|t| t.romaji.as_deref())),
+        Field::TitleEnglish => text(title.and_then(|t| t.english.as_deref())),
+        Field::TitleNative => text(title.and_then(|t| t.native.as_deref())),
+        // Most anime are listed one season per entry.
+        Field::Season => number(elements.season_number.or(Some(1))),
+        Field::Episode => {
+            let start = number(elements.episode_number);
+            match elements.episode_number_end {
+                Some(end) if !start.is_empty() => format!("{start}-{}", number(Some(end))),
+                _ => start,
+            }
+        }
+        Field::EpisodeTitle => text(elements.episode_title.as_deref()),
+        Field::Year => number(anime.and_then(|a| a.year).or(elements.year)),
+        Field::Group => text(elements.release_group.as_deref()),
+        Field::Resolution => text(elements.resolution.as_deref()),
+        Field::Source => text(elements.source.as_deref()),
+        Field::VideoCodec => text(elements.video_codec.as_deref()),
+        Field::AudioCodec => text(elements.audio_codec.as_deref()),
+        Field::Version => text(elements.release_version.as_deref()),
+        Field::Ext => text(elements.file_extension.as_deref()),
+    }
+}
+
+/// Make a value safe to put in a filename on any platform.
+fn sanitize(value: &str) -> String {
+    value
+        .chars()
+        .filter_map(|c| match c {
+            ':' | '/' | '\\' | '|' => Some('-'),
+            '*' | '?' | '"' | '<' | '>' => None,
+            c if c.is_control() => None,
+            c => Some(c),
+        })
+        .collect()
+}
+
+/// Drop brackets left empty by missing values and the spacing around them.
+fn tidy(name: &str) -> String {
+    let mut name = name.to_string();
+    loop {
+        let before = name.len();
+        for empty in ["[]", "()", "{}"] {
+            name = name.replace(empty, "");
+        }
+        name = name.replace("  ", " ").replace(" .", ".");
+        if name.len() == before {
+            break;
+        }
+    }
+    name.trim().trim_end_matches(['-', ' ']).to_string()
+}
+
+/// One file the renamer would rename.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenamePlan {
+    pub anime_id: i64,
+    pub episode: u32,
+    pub from: String,
+    pub to: String,
+    /// Why the file can't be renamed, if it can't; such entries are
+    /// skipped by [`apply_renames`].
+    pub conflict: Option<String>,
+}
+
+/// Result of [`apply_renames`] or [`undo_last_rename`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RenameOutcome {
+    pub renamed: u32,
+    pub skipped: u32,
+    /// Files that failed to rename, with the error.
+    pub failed: Vec<(String, String)>,
+}
+
+/// Work out the new name of every indexed episode file. Files that already
+/// have the right name are left out. Filenames are parsed with `keywords`.
+pub fn plan_renames(
+    storage: &Storage,
+    template: &FilenameTemplate,
+    keywords: &KeywordSet,
+) -> Result<Vec<RenamePlan>, RyuujiError> {
+    let mut plans = Vec::new();
+    let mut targets = HashSet::new();
+
+    for ep in storage.get_available_episodes()? {
+        let path = Path::new(&ep.file_path);
+        let (Some(dir), Some(file_name)) = (path.parent(), path.file_name()) else {
+            continue;
+        };
+        let anime = storage.get_anime(ep.anime_id)?;
+
+        let mut elements =
+            ryuuji_parse::parse_with_keywords(&file_name.to_string_lossy(), keywords);
+        elements.episode_number = Some(ep.episode);
+        elements.release_group = ep.release_group.or(elements.release_group);
+        elements.resolution = ep.resolution.or(elements.resolution);
+        if elements.file_extension.is_none() {
+            elements.file_extension = path.extension().map(|e| e.to_string_lossy().to_lowercase());
+        }
+
+        let new_name = template.render(anime.as_ref(), &elements);
+        let to = dir.join(&new_name).to_string_lossy().to_string();
+        if to == ep.file_path {
+            continue;
+        }
+
+        let conflict = if new_name.is_empty() {
+            Some("template rendered an empty name".to_string())
+        } else if !path.exists() {
+            Some("file no longer exists".to_string())
+        } else if Path::new(&to).exists() || !targets.insert(to.clone()) {
+            Some(format!("{new_name} already exists"))
+        } else {
+            None
+        };
+        plans.push(RenamePlan {
+            anime_id: ep.anime_id,
+            episode: ep.episode,
+            from: ep.file_path,
+            to,
+            conflict,
+        });
+    }
+    Ok(plans)
+}
+
+/// Rename the files in `plans` (skipping conflicts) and update their stored
+/// paths. The renames are recorded as one batch for [`undo_last_rename`].
+pub fn apply_renames(
+    storage: &Storage,
+    plans: &[RenamePlan],
+) -> Result<RenameOutcome, RyuujiError> {
+    let batch_id = storage.next_rename_batch()?;
+    let mut outcome = RenameOutcome::default();
+
+    for plan in plans {
+        if plan.conflict.is_some() {
+            outcome.skipped += 1;
+            continue;
+        }
+        match move_file(storage, &plan.from, &plan.to) {
+            Ok(()) => {
+                storage.record_rename(batch_id, &plan.from, &plan.to)?;
+                outcome.renamed += 1;
+            }
+            Err(e) => {
+                warn!(from = %plan.from, error = %e, "Rename failed");
+                outcome.failed.push((plan.from.clone(), e.to_string()));
+            }
+        }
+    }
+
+    info!(batch_id, renamed = outcome.renamed, "Renamed library files");
+    Ok(outcome)
+}
+
+/// Undo the most recent [`apply_renames`] batch. Returns `None` when there
+/// is nothing to undo.
+///
+/// Files that were skipped or failed stay in the batch, so undoing again
+/// retries them once the conflict is resolved.
+pub fn undo_last_rename(storage: &Storage) -> Result<Option<RenameOutcome>, RyuujiError> {
+    let Some((batch_id, records)) = storage.last_rename_batch()? else {
+        return Ok(None);
+    };
+    let mut outcome = RenameOutcome::default();
+
+    for record in records.iter().rev() {
+        if Path::new(&record.old_path).exists() {
+            outcome.skipped += 1;
+            continue;
+        }
+        match move_file(storage, &record.new_path, &record.old_path) {
+            Ok(()) => {
+                storage.delete_rename_record(record.id)?;
+                outcome.renamed += 1;
+            }
+            Err(e) => {
+                warn!(path = %record.new_path, error = %e, "Undoing rename failed");
+                outcome
+                    .failed
+                    .push((record.new_path.clone(), e.to_string()));
+            }
+        }
+    }
+
+    info!(batch_id, restored = outcome.renamed, "Undid library rename");
+    Ok(Some(outcome))
+}
+
+/// Rename a file on disk and in `available_episode`, putting the file back
+/// if the database can't be updated.
+fn move_file(storage: &Storage, from: &str, to: &str) -> Result<(), RyuujiError> {
+    if Path::new(to).exists() {
+        return Err(RyuujiError::Rename(format!("{to} already exists")));
+    }
+    std::fs::rename(from, to)?;
+    if let Err(e) = storage.move_available_episode(from, to) {
+        let _ = std::fs::rename(to, from);
+        return Err(e);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{AnimeIds, AnimeTitle, AvailableEpisode};
+    use tempfile::TempDir;
+
+    const TEMPLATE: &str = "{title} - S{season:02}E{episode:02} [{group}][{resolution}].{ext}";
+
+    fn frieren() -> Anime {
+        Anime {
+            id: 0,
+            ids: AnimeIds::default(),
+            title: AnimeTitle {
+                romaji: Some("Sousou no Frieren".into()),
+                english: Some("Frieren: Beyond Journey's End".into()),
+                native: None,
+            },
+            synonyms: vec![],
+            episodes: Some(28),
+            cover_url: None,
+            season: None,
+            year: Some(2023),
+            synopsis: None,
+            genres: vec![],
+            media_type: None,
+            airing_status: None,
+            mean_score: None,
+            studios: vec![],
+            source: None,
+            rating: None,
+            start_date: None,
+            end_date: None,
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let template = FilenameTemplate::parse(TEMPLATE).unwrap();
+        let elements = ryuuji_parse::parse("[SubsPlease] Frieren - 05 (1080p) [ABCD1234].mkv");
+        assert_eq!(
+            template.render(Some(&frieren()), &elements),
+            "Sousou no Frieren - S01E05 [SubsPlease][1080p].mkv"
+        );
+
+        // Values are made filename-safe; empty brackets disappear.
+        let template =
+            FilenameTemplate::parse("{title_english} ({year}) - {episode:03} [{group}].{ext}")
+                .unwrap();
+        let elements = ryuuji_parse::parse("Frieren S2 - 05.mkv");
+        assert_eq!(
+            template.render(Some(&frieren()), &elements),
+            "Frieren- Beyond Journey's End (2023) - 005.mkv"
+        );
+    }
+
+    #[test]
+    fn test_render_without_anime() {
+        let template = FilenameTemplate::parse("{title} - {episode:02}.{ext}").unwrap();
+        let elements = ryuuji_parse::parse("[Group] Some Show - 01-02 [720p].mp4");
+        assert_eq!(template.render(None, &elements), "Some Show - 01-02.mp4");
+    }
+
+    #[test]
+    fn test_template_errors() {
+        for bad in [
+            "{title",
+            "title}",
+            "{nope}",
+            "{title:02}",
+            "{episode:x}",
+            "{title}/{episode}",
+        ] {
+            assert!(FilenameTemplate::parse(bad).is_err(), "{bad}");
+        }
+        let template = FilenameTemplate::parse("{{literal}} {episode}").unwrap();
+        let elements = ryuuji_parse::parse("Show - 03.mkv");
+        assert_eq!(template.render(None, &elements), "{literal} 3");
+    }
+
+    #[test]
+    fn test_rename_and_undo() {
+        let dir = TempDir::new().unwrap();
+        let storage = Storage::open_memory().unwrap();
+        let anime_id = storage.insert_anime(&frieren()).unwrap();
+
+        let index = |name: &str, episode: u32| {
+            let path = dir.path().join(name);
+            std::fs::write(&path, b"video").unwrap();
+            let file_path = path.to_string_lossy().to_string();
+            storage
+                .upsert_available_episode(&AvailableEpisode {
+                    id: 0,
+                    anime_id,
+                    episode,
+                    file_path: file_path.clone(),
+                    file_size: 5,
+                    file_modified: String::new(),
+                    release_group: Some("SubsPlease".into()),
+                    resolution: Some("1080p".into()),
+                })
+                .unwrap();
+            file_path
+        };
+        let ep5 = index("[SubsPlease] Frieren - 05 (1080p).mkv", 5);
+        let ep6 = index("[SubsPlease] Frieren - 06 (1080p).mkv", 6);
+        // Already named correctly: not part of the plan.
+        index("Sousou no Frieren - S01E07 [SubsPlease][1080p].mkv", 7);
+
+        let template = FilenameTemplate::parse(TEMPLATE).unwrap();
+        let plans = plan_renames(&storage, &template, &KeywordSet::default()).unwrap();
+        assert_eq!(plans.len(), 2);
+        assert!(plans.iter().all(|p| p.conflict.is_none()));
+        // A dry run touches nothing.
+        assert!(Path::new(&ep5).exists());
+
+        let outcome = apply_renames(&storage, &plans).unwrap();
+        assert_eq!(outcome.renamed, 2);
+        let renamed = dir
+            .path()
+            .join("Sousou no Frieren - S01E05 [SubsPlease][1080p].mkv");
+        assert!(renamed.exists());
+        assert!(!Path::new(&ep5).exists());
+        let paths: Vec<String> = storage
+            .get_available_episodes()
+            .unwrap()
+            .into_iter()
+            .map(|e| e.file_path)
+            .collect();
+        assert!(paths.contains(&renamed.to_string_lossy().to_string()));
+        assert!(plan_renames(&storage, &template, &KeywordSet::default())
+            .unwrap()
+            .is_empty());
+
+        let undone = undo_last_rename(&storage).unwrap().unwrap();
+        assert_eq!(undone.renamed, 2);
+        assert!(Path::new(&ep5).exists() && Path::new(&ep6).exists());
+        assert!(!renamed.exists());
+        assert!(storage
+            .get_available_episodes()
+            .unwrap()
+            .iter()
+            .any(|e| e.file_path == ep5));
+        assert!(undo_last_rename(&storage).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_conflicts_are_skipped() {
+        let dir = TempDir::new().unwrap();
+        let storage = Storage::open_memory().unwrap();
+        let anime_id = storage.insert_anime(&frieren()).unwrap();
+
+        // Two releases of the same episode would get the same name.
+        for name in ["[A] Frieren - 05.mkv", "[A] Frieren - 05v2.mkv"] {
+            let path = dir.path().join(name);
+            std::fs::write(&path, b"video").unwrap();
+            storage
+                .upsert_available_episode(&AvailableEpisode {
+                    id: 0,
+                    anime_id,
+                    episode: 5,
+                    file_path: path.to_string_lossy().to_string(),
+                    file_size: 5,
+                    file_modified: String::new(),
+                    release_group: None,
+                    resolution: None,
+                })
+                .unwrap();
+        }
+
+        let template = FilenameTemplate::parse("{title} - {episode:02}.{ext}").unwrap();
+        let plans = plan_renames(&storage, &template, &KeywordSet::default()).unwrap();
+        assert_eq!(plans.len(), 2);
+        assert_eq!(plans.iter().filter(|p| p.conflict.is_some()).count(), 1);
+
+        let outcome = apply_renames(&storage, &plans).unwrap();
+        assert_eq!((outcome.renamed, outcome.skipped), (1, 1));
+    }
+
+    #[test]
+    fn test_partial_undo_keeps_batch() {
+        let dir = TempDir::new().unwrap();
+        let storage = Storage::open_memory().unwrap();
+        let anime_id = storage.insert_anime(&frieren()).unwrap();
+
+        let mut originals = Vec::new();
+        for episode in [5, 6] {
+            let path = dir.path().join(format!("[A] Frieren - {episode:02}.mkv"));
+            std::fs::write(&path, b"video").unwrap();
+            let file_path = path.to_string_lossy().to_string();
+            storage
+                .upsert_available_episode(&AvailableEpisode {
+                    id: 0,
+                    anime_id,
+                    episode,
+                    file_path: file_path.clone(),
+                    file_size: 5,
+                    file_modified: String::new(),
+                    release_group: None,
+                    resolution: None,
+                })
+                .unwrap();
+            originals.push(file_path);
+        }
+
+        let template = FilenameTemplate::parse("{title} - {episode:02}.{ext}").unwrap();
+        let plans = plan_renames(&storage, &template, &KeywordSet::default()).unwrap();
+        assert_eq!(apply_renames(&storage, &plans).unwrap().renamed, 2);
+
+        // Something new took episode 6's old name.
+        std::fs::write(&originals[1], b"other").unwrap();
+        let undone = undo_last_rename(&storage).unwrap().unwrap();
+        assert_eq!((undone.renamed, undone.skipped), (1, 1));
+        assert!(Path::new(&originals[0]).exists());
+
+        // The skipped file is still in the batch and can be undone later.
+        let (_, records) = storage.last_rename_batch().unwrap().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].old_path, originals[1]);
+        std::fs::remove_file(&originals[1]).unwrap();
+        let undone = undo_last_rename(&storage).unwrap().unwrap();
+        assert_eq!((undone.renamed, undone.skipped), (1, 0));
+        assert!(undo_last_rename(&storage).unwrap().is_none());
+    }
+}
@@ -6,6 +6,7 @@
 
 use std::path::Path;
 
+use ryuuji_parse::KeywordSet;
 use tracing::warn;
 use walkdir::WalkDir;
 
@@ -32,13 +33,15 @@ pub struct ScanResult {
 /// For each video file found:
 /// 1. Check size threshold (skip tiny files / samples)
 /// 2. Check if already indexed with same size + mtime (incremental skip)
-/// 3. Parse filename via `ryuuji_parse::parse()`
+/// 3. Parse the path via `ryuuji_parse::parse_path_under()`, below the watch folder
+///    and with `keywords`
 /// 4. Match title via `RecognitionCache::recognize()`
 /// 5. Upsert `available_episode` record
 pub fn scan_watch_folders(
     storage: &Storage,
     cache: &mut RecognitionCache,
     config: &LibraryConfig,
+    keywords: &KeywordSet,
 ) -> Result<ScanResult, RyuujiError> {
     let mut result = ScanResult::default();
     let min_bytes = config.min_file_size_mb * 1024 * 1024;
@@ -110,13 +113,9 @@ pub fn scan_watch_folders(
                 continue;
             }
 
-            // Parse filename
-            let file_stem = path
-                .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or_default();
-
-            let parsed = ryuuji_parse::parse(file_stem);
+            // Parse the path; folders below the watch folder fill in a
+            // title the filename lacks.
+            let parsed = ryuuji_parse::parse_path_under(&file_path_str, folder, keywords).elements;
             let title = parsed.title.as_deref().unwrap_or_default();
             if title.is_empty() {
                 result.files_skipped += 1;
@@ -230,10 +229,16 @@ mod tests {
             watch_folders: vec![dir.path().to_string_lossy().to_string()],
             min_file_size_mb: 10,
             scan_on_startup: false,
+            watch_threshold_percent: 80,
+            watch_threshold_secs: 0,
+            rename_template: String::new(),
+            online_fallback: false,
+            online_fallback_threshold: 0.9,
         };
 
         let mut cache = RecognitionCache::new();
-        let result = scan_watch_folders(&storage, &mut cache, &config).unwrap();
+        let result =
+            scan_watch_folders(&storage, &mut cache, &config, &KeywordSet::default()).unwrap();
 
         assert_eq!(result.files_scanned, 1);
         assert_eq!(result.files_matched, 1);
@@ -258,10 +263,16 @@ mod tests {
             watch_folders: vec![dir.path().to_string_lossy().to_string()],
             min_file_size_mb: 10,
             scan_on_startup: false,
+            watch_threshold_percent: 80,
+            watch_threshold_secs: 0,
+            rename_template: String::new(),
+            online_fallback: false,
+            online_fallback_threshold: 0.9,
         };
 
         let mut cache = RecognitionCache::new();
-        let result = scan_watch_folders(&storage, &mut cache, &config).unwrap();
+        let result =
+            scan_watch_folders(&storage, &mut cache, &config, &KeywordSet::default()).unwrap();
 
         assert_eq!(result.files_scanned, 1);
         assert_eq!(result.files_skipped, 1);
@@ -280,10 +291,16 @@ mod tests {
             watch_folders: vec![dir.path().to_string_lossy().to_string()],
             min_file_size_mb: 10,
             scan_on_startup: false,
+            watch_threshold_percent: 80,
+            watch_threshold_secs: 0,
+            rename_template: String::new(),
+            online_fallback: false,
+            online_fallback_threshold: 0.9,
         };
 
         let mut cache = RecognitionCache::new();
-        let result = scan_watch_folders(&storage, &mut cache, &config).unwrap();
+        let result =
+            scan_watch_folders(&storage, &mut cache, &config, &KeywordSet::default()).unwrap();
 
         // Non-video files are filtered before counting
         assert_eq!(result.files_scanned, 0);
@@ -304,16 +321,23 @@ mod tests {
             watch_folders: vec![dir.path().to_string_lossy().to_string()],
             min_file_size_mb: 10,
             scan_on_startup: false,
+            watch_threshold_percent: 80,
+            watch_threshold_secs: 0,
+            rename_template: String::new(),
+            online_fallback: false,
+            online_fallback_threshold: 0.9,
         };
 
         let mut cache = RecognitionCache::new();
 
         // First scan
-        let result1 = scan_watch_folders(&storage, &mut cache, &config).unwrap();
+        let result1 =
+            scan_watch_folders(&storage, &mut cache, &config, &KeywordSet::default()).unwrap();
         assert_eq!(result1.files_matched, 1);
 
         // Second scan — file unchanged, should skip
-        let result2 = scan_watch_folders(&storage, &mut cache, &config).unwrap();
+        let result2 =
+            scan_watch_folders(&storage, &mut cache, &config, &KeywordSet::default()).unwrap();
         assert_eq!(result2.files_skipped, 1);
         assert_eq!(result2.files_matched, 0);
     }
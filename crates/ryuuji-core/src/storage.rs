@@ -5,9 +5,10 @@ use rusqlite::{params, Connection, OptionalExtension};
 use tracing::debug;
 
 use crate::error::RyuujiError;
+use crate::matcher;
 use crate::models::{
     Anime, AnimeIds, AnimeTitle, AvailableEpisode, AvailableEpisodeSummary, LibraryEntry,
-    WatchStatus,
+    PendingUpdate, TitleAlias, WatchStatus,
 };
 use crate::torrent::filter::{FilterAction, MatchMode, TorrentFilter};
 use crate::torrent::models::TorrentFeed;
@@ -17,6 +18,10 @@ const SCHEMA_V2: &str = include_str!("../../../migrations/002_add_anime_metadata
 const SCHEMA_V3: &str = include_str!("../../../migrations/003_torrent_tables.sql");
 const SCHEMA_V4: &str = include_str!("../../../migrations/004_add_library_fields.sql");
 const SCHEMA_V5: &str = include_str!("../../../migrations/005_add_available_episodes.sql");
+const SCHEMA_V6: &str = include_str!("../../../migrations/006_pending_updates.sql");
+const SCHEMA_V7: &str = include_str!("../../../migrations/007_rename_history.sql");
+const SCHEMA_V8: &str = include_str!("../../../migrations/008_title_alias.sql");
+const SCHEMA_V9: &str = include_str!("../../../migrations/009_unconfirmed_anime.sql");
 
 /// Token record: (access_token, refresh_token, expires_at).
 pub type TokenRecord = (String, Option<String>, Option<String>);
@@ -54,6 +59,21 @@ pub struct WatchHistoryRow {
     pub watched_at: DateTime<Utc>,
 }
 
+/// A pending progress update joined with anime data for display.
+#[derive(Debug, Clone)]
+pub struct PendingUpdateRow {
+    pub pending: PendingUpdate,
+    pub anime: Anime,
+}
+
+/// One file moved by a rename batch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenameRecord {
+    pub id: i64,
+    pub old_path: String,
+    pub new_path: String,
+}
+
 /// A watch history record joined with anime data for display.
 #[derive(Debug, Clone)]
 pub struct HistoryRow {
@@ -137,6 +157,15 @@ impl Storage {
             .map_err(Into::into)
     }
 
+    /// Delete an anime along with its pending updates, aliases and flags.
+    /// Fails while a library entry or watch history still refers to it.
+    pub fn delete_anime(&self, id: i64) -> Result<(), RyuujiError> {
+        debug!(id, "Deleting anime");
+        self.conn
+            .execute("DELETE FROM anime WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
     /// Search anime by title substring (case-insensitive).
     pub fn search_anime(&self, query: &str) -> Result<Vec<Anime>, RyuujiError> {
         let pattern = format!("%{query}%");
@@ -629,6 +658,144 @@ impl Storage {
         Ok(rows)
     }
 
+    // ── Pending Updates ─────────────────────────────────────────
+
+    /// Queue an update for confirmation.
+    ///
+    /// Supersedes any still-pending lower episode for the same anime. Returns
+    /// `false` if the user already rejected this exact update, in which case
+    /// it should not be asked about again.
+    pub fn queue_pending_update(&self, anime_id: i64, episode: u32) -> Result<bool, RyuujiError> {
+        self.conn.execute(
+            "DELETE FROM pending_update
+             WHERE anime_id = ?1 AND episode < ?2 AND status = 'pending'",
+            params![anime_id, episode],
+        )?;
+        self.conn.execute(
+            "INSERT OR IGNORE INTO pending_update (anime_id, episode) VALUES (?1, ?2)",
+            params![anime_id, episode],
+        )?;
+        let status: String = self.conn.query_row(
+            "SELECT status FROM pending_update WHERE anime_id = ?1 AND episode = ?2",
+            params![anime_id, episode],
+            |row| row.get(0),
+        )?;
+        Ok(status == "pending")
+    }
+
+    /// Get the highest pending update for an anime, if any.
+    pub fn get_pending_update(&self, anime_id: i64) -> Result<Option<PendingUpdate>, RyuujiError> {
+        self.conn
+            .query_row(
+                "SELECT id, anime_id, episode, created_at FROM pending_update
+                 WHERE anime_id = ?1 AND status = 'pending'
+                 ORDER BY episode DESC LIMIT 1",
+                params![anime_id],
+                |row| Ok(row_to_pending_update(row, 0)),
+            )
+            .optional()
+            .map_err(Into::into)
+    }
+
+    /// Get all pending updates joined with anime data, oldest first.
+    pub fn get_pending_updates(&self) -> Result<Vec<PendingUpdateRow>, RyuujiError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT a.id, a.anilist_id, a.kitsu_id, a.mal_id, a.title_romaji, a.title_english,
+                    a.title_native, a.synonyms, a.episodes, a.cover_url, a.season, a.year,
+                    a.synopsis, a.genres, a.media_type, a.airing_status, a.mean_score,
+                    a.studios, a.source, a.rating, a.start_date, a.end_date,
+                    pu.id, pu.anime_id, pu.episode, pu.created_at
+             FROM pending_update pu
+             JOIN anime a ON pu.anime_id = a.id
+             WHERE pu.status = 'pending'
+             ORDER BY pu.created_at, pu.id",
+        )?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok(PendingUpdateRow {
+                    anime: row_to_anime_at(row, 0),
+                    pending: row_to_pending_update(row, 22),
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(rows)
+    }
+
+    /// Remove pending updates for an anime up to and including `episode`.
+    pub fn clear_pending_updates(&self, anime_id: i64, episode: u32) -> Result<(), RyuujiError> {
+        self.conn.execute(
+            "DELETE FROM pending_update WHERE anime_id = ?1 AND episode <= ?2",
+            params![anime_id, episode],
+        )?;
+        Ok(())
+    }
+
+    /// Mark all pending updates for an anime as rejected.
+    pub fn reject_pending_updates(&self, anime_id: i64) -> Result<(), RyuujiError> {
+        debug!(anime_id, "Rejecting pending updates");
+        self.conn.execute(
+            "UPDATE pending_update SET status = 'rejected'
+             WHERE anime_id = ?1 AND status = 'pending'",
+            params![anime_id],
+        )?;
+        Ok(())
+    }
+
+    /// Apply future updates for this anime without asking.
+    pub fn set_auto_confirm(&self, anime_id: i64, enabled: bool) -> Result<(), RyuujiError> {
+        if enabled {
+            self.conn.execute(
+                "INSERT OR IGNORE INTO auto_confirm_anime (anime_id) VALUES (?1)",
+                params![anime_id],
+            )?;
+        } else {
+            self.conn.execute(
+                "DELETE FROM auto_confirm_anime WHERE anime_id = ?1",
+                params![anime_id],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Whether updates for this anime skip confirmation.
+    pub fn is_auto_confirm(&self, anime_id: i64) -> Result<bool, RyuujiError> {
+        let count: i32 = self.conn.query_row(
+            "SELECT COUNT(*) FROM auto_confirm_anime WHERE anime_id = ?1",
+            params![anime_id],
+            |row| row.get(0),
+        )?;
+        Ok(count > 0)
+    }
+
+    /// Mark an anime as coming from a weak online match, so its updates are
+    /// confirmed first, or clear the mark.
+    pub fn set_unconfirmed(&self, anime_id: i64, unconfirmed: bool) -> Result<(), RyuujiError> {
+        if unconfirmed {
+            self.conn.execute(
+                "INSERT OR IGNORE INTO unconfirmed_anime (anime_id) VALUES (?1)",
+                params![anime_id],
+            )?;
+        } else {
+            self.conn.execute(
+                "DELETE FROM unconfirmed_anime WHERE anime_id = ?1",
+                params![anime_id],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Whether this anime came from a weak online match the user hasn't
+    /// confirmed yet.
+    pub fn is_unconfirmed(&self, anime_id: i64) -> Result<bool, RyuujiError> {
+        let count: i32 = self.conn.query_row(
+            "SELECT COUNT(*) FROM unconfirmed_anime WHERE anime_id = ?1",
+            params![anime_id],
+            |row| row.get(0),
+        )?;
+        Ok(count > 0)
+    }
+
     // ── Auth Tokens ─────────────────────────────────────────────
 
     /// Store an auth token for a service.
@@ -922,12 +1089,175 @@ impl Storage {
         Ok(count > 0)
     }
 
+    /// Get every indexed episode file.
+    pub fn get_available_episodes(&self) -> Result<Vec<AvailableEpisode>, RyuujiError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, anime_id, episode, file_path, file_size, file_modified,
+                    release_group, resolution
+             FROM available_episode
+             ORDER BY anime_id, episode, file_path",
+        )?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok(AvailableEpisode {
+                    id: row.get(0)?,
+                    anime_id: row.get(1)?,
+                    episode: row.get(2)?,
+                    file_path: row.get(3)?,
+                    file_size: row.get::<_, i64>(4)? as u64,
+                    file_modified: row.get(5)?,
+                    release_group: row.get(6)?,
+                    resolution: row.get(7)?,
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(rows)
+    }
+
+    /// Point indexed episodes at `old_path` to `new_path` after the file moved.
+    pub fn move_available_episode(
+        &self,
+        old_path: &str,
+        new_path: &str,
+    ) -> Result<(), RyuujiError> {
+        self.conn.execute(
+            "UPDATE available_episode SET file_path = ?2 WHERE file_path = ?1",
+            params![old_path, new_path],
+        )?;
+        Ok(())
+    }
+
     /// Remove all available episodes (before a full re-scan).
     pub fn clear_available_episodes(&self) -> Result<(), RyuujiError> {
         self.conn.execute("DELETE FROM available_episode", [])?;
         Ok(())
     }
 
+    // ── Rename history ──────────────────────────────────────────
+
+    /// ID for a new rename batch.
+    pub fn next_rename_batch(&self) -> Result<i64, RyuujiError> {
+        let id: i64 = self.conn.query_row(
+            "SELECT COALESCE(MAX(batch_id), 0) + 1 FROM rename_history",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(id)
+    }
+
+    /// Record that a file in `batch_id` was renamed.
+    pub fn record_rename(
+        &self,
+        batch_id: i64,
+        old_path: &str,
+        new_path: &str,
+    ) -> Result<(), RyuujiError> {
+        self.conn.execute(
+            "INSERT INTO rename_history (batch_id, old_path, new_path) VALUES (?1, ?2, ?3)",
+            params![batch_id, old_path, new_path],
+        )?;
+        Ok(())
+    }
+
+    /// The most recent rename batch and its files, in the order they were renamed.
+    pub fn last_rename_batch(&self) -> Result<Option<(i64, Vec<RenameRecord>)>, RyuujiError> {
+        let batch_id: Option<i64> =
+            self.conn
+                .query_row("SELECT MAX(batch_id) FROM rename_history", [], |row| {
+                    row.get(0)
+                })?;
+        let Some(batch_id) = batch_id else {
+            return Ok(None);
+        };
+        let mut stmt = self.conn.prepare(
+            "SELECT id, old_path, new_path FROM rename_history WHERE batch_id = ?1 ORDER BY id",
+        )?;
+        let records = stmt
+            .query_map(params![batch_id], |row| {
+                Ok(RenameRecord {
+                    id: row.get(0)?,
+                    old_path: row.get(1)?,
+                    new_path: row.get(2)?,
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(Some((batch_id, records)))
+    }
+
+    /// Forget a rename once it has been undone. A batch is gone when all of
+    /// its records are.
+    pub fn delete_rename_record(&self, id: i64) -> Result<(), RyuujiError> {
+        self.conn
+            .execute("DELETE FROM rename_history WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    // ── Title Aliases ───────────────────────────────────────────
+
+    /// Save a recognition correction, replacing any alias with the same
+    /// title, season and scope. Returns the alias ID.
+    ///
+    /// `title` is normalized before it is stored; season 1 is stored as no
+    /// season.
+    pub fn add_title_alias(
+        &self,
+        title: &str,
+        season: Option<u32>,
+        anime_id: i64,
+        release_group: Option<&str>,
+        path_prefix: Option<&str>,
+    ) -> Result<i64, RyuujiError> {
+        let id = self.conn.query_row(
+            "INSERT INTO title_alias (title, season, anime_id, release_group, path_prefix)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(title, season, release_group, path_prefix)
+             DO UPDATE SET anime_id = excluded.anime_id, created_at = datetime('now')
+             RETURNING id",
+            params![
+                matcher::normalize(title),
+                season.filter(|s| *s > 1).unwrap_or(0),
+                anime_id,
+                release_group.unwrap_or_default(),
+                path_prefix.unwrap_or_default()
+            ],
+            |row| row.get(0),
+        )?;
+        Ok(id)
+    }
+
+    /// All title aliases, oldest first.
+    pub fn all_title_aliases(&self) -> Result<Vec<TitleAlias>, RyuujiError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, title, season, anime_id, release_group, path_prefix
+             FROM title_alias ORDER BY id",
+        )?;
+        let non_empty = |s: String| (!s.is_empty()).then_some(s);
+        let aliases = stmt
+            .query_map([], |row| {
+                let season: u32 = row.get(2)?;
+                Ok(TitleAlias {
+                    id: row.get(0)?,
+                    title: row.get(1)?,
+                    season: (season > 0).then_some(season),
+                    anime_id: row.get(3)?,
+                    release_group: non_empty(row.get(4)?),
+                    path_prefix: non_empty(row.get(5)?),
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(aliases)
+    }
+
+    /// Remove a title alias.
+    pub fn delete_title_alias(&self, id: i64) -> Result<(), RyuujiError> {
+        self.conn
+            .execute("DELETE FROM title_alias WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
     // ── Statistics ──────────────────────────────────────────────
 
     /// Get aggregate library statistics in a single query.
@@ -1021,7 +1351,7 @@ impl Storage {
             }
         }
         let mut top_genres: Vec<(String, usize)> = genre_counts.into_iter().collect();
-        top_genres.sort_by(|a, b| b.1.cmp(&a.1));
+        top_genres.sort_by_key(|b| std::cmp::Reverse(b.1));
         top_genres.truncate(10);
 
         Ok(LibraryStatistics {
@@ -1076,6 +1406,22 @@ fn run_migrations(conn: &Connection) -> Result<(), RyuujiError> {
         conn.execute_batch(SCHEMA_V5)?;
         conn.pragma_update(None, "user_version", 5)?;
     }
+    if version < 6 {
+        conn.execute_batch(SCHEMA_V6)?;
+        conn.pragma_update(None, "user_version", 6)?;
+    }
+    if version < 7 {
+        conn.execute_batch(SCHEMA_V7)?;
+        conn.pragma_update(None, "user_version", 7)?;
+    }
+    if version < 8 {
+        conn.execute_batch(SCHEMA_V8)?;
+        conn.pragma_update(None, "user_version", 8)?;
+    }
+    if version < 9 {
+        conn.execute_batch(SCHEMA_V9)?;
+        conn.pragma_update(None, "user_version", 9)?;
+    }
     Ok(())
 }
 
@@ -1168,6 +1514,16 @@ fn row_to_library_entry(row: &rusqlite::Row<'_>, off: usize) -> LibraryEntry {
     }
 }
 
+fn row_to_pending_update(row: &rusqlite::Row<'_>, off: usize) -> PendingUpdate {
+    let created_str: String = row.get(off + 3).unwrap_or_default();
+    PendingUpdate {
+        id: row.get(off).unwrap_or(0),
+        anime_id: row.get(off + 1).unwrap_or(0),
+        episode: row.get(off + 2).unwrap_or(0),
+        created_at: parse_datetime(&created_str),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1286,4 +1642,71 @@ mod tests {
         let token = db.get_token("anilist").unwrap();
         assert_eq!(token.as_deref(), Some("xyz789"));
     }
+
+    #[test]
+    fn test_pending_updates() {
+        let db = Storage::open_memory().unwrap();
+        let anime_id = db.insert_anime(&test_anime()).unwrap();
+
+        assert!(db.queue_pending_update(anime_id, 3).unwrap());
+        // A later episode supersedes the earlier one.
+        assert!(db.queue_pending_update(anime_id, 4).unwrap());
+        let rows = db.get_pending_updates().unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].pending.episode, 4);
+        assert_eq!(rows[0].anime.id, anime_id);
+
+        // Rejected updates are not asked about again.
+        db.reject_pending_updates(anime_id).unwrap();
+        assert!(!db.queue_pending_update(anime_id, 4).unwrap());
+        assert!(db.get_pending_update(anime_id).unwrap().is_none());
+
+        db.set_auto_confirm(anime_id, true).unwrap();
+        assert!(db.is_auto_confirm(anime_id).unwrap());
+        db.set_auto_confirm(anime_id, false).unwrap();
+        assert!(!db.is_auto_confirm(anime_id).unwrap());
+    }
+
+    #[test]
+    fn test_title_aliases() {
+        let db = Storage::open_memory().unwrap();
+        let anime_id = db.insert_anime(&test_anime()).unwrap();
+
+        let any = db
+            .add_title_alias("Frieren", None, anime_id, None, None)
+            .unwrap();
+        let scoped = db
+            .add_title_alias(
+                "frieren",
+                None,
+                anime_id,
+                Some("SubsPlease"),
+                Some("/anime/"),
+            )
+            .unwrap();
+        assert_ne!(any, scoped);
+        // Same normalized title, season and scope replaces rather than
+        // duplicates; season 1 is the same as no season.
+        assert_eq!(
+            db.add_title_alias("FRIEREN!", Some(1), anime_id, None, None)
+                .unwrap(),
+            any
+        );
+        let season_2 = db
+            .add_title_alias("Frieren", Some(2), anime_id, None, None)
+            .unwrap();
+        assert_ne!(season_2, any);
+
+        let aliases = db.all_title_aliases().unwrap();
+        assert_eq!(aliases.len(), 3);
+        assert_eq!(aliases[0].title, "frieren");
+        assert_eq!(aliases[0].season, None);
+        assert_eq!(aliases[0].release_group, None);
+        assert_eq!(aliases[1].release_group.as_deref(), Some("SubsPlease"));
+        assert_eq!(aliases[1].path_prefix.as_deref(), Some("/anime/"));
+        assert_eq!(aliases[2].season, Some(2));
+
+        db.delete_title_alias(any).unwrap();
+        assert_eq!(db.all_title_aliases().unwrap().len(), 2);
+    }
 }
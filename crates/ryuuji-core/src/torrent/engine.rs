@@ -82,6 +82,8 @@ fn evaluate_condition(item: &TorrentItem, cond: &crate::torrent::filter::FilterC
                 return false;
             }
         }
+        FilterElement::Batch => return evaluate_flag(item.is_batch, cond),
+        FilterElement::Airing => return evaluate_flag(item.anime_airing, cond),
     };
 
     let value_lower = cond.value.to_lowercase();
@@ -98,7 +100,113 @@ fn evaluate_condition(item: &TorrentItem, cond: &crate::torrent::filter::FilterC
     }
 }
 
+/// Evaluate a yes/no condition; the value is "true"/"false" (or "yes"/"no").
+fn evaluate_flag(flag: bool, cond: &crate::torrent::filter::FilterCondition) -> bool {
+    use crate::torrent::filter::FilterOperator;
+
+    let wanted = match cond.value.trim().to_lowercase().as_str() {
+        "true" | "yes" | "1" => true,
+        "false" | "no" | "0" => false,
+        _ => return false,
+    };
+    match cond.operator {
+        FilterOperator::Equals => flag == wanted,
+        FilterOperator::NotEquals => flag != wanted,
+        _ => false,
+    }
+}
+
 /// Remove items whose GUID is already in the archive.
 pub fn filter_archived(items: &mut Vec<TorrentItem>, storage: &Storage) {
     items.retain(|item| !storage.is_torrent_archived(&item.guid).unwrap_or(false));
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::torrent::filter::{
+        FilterAction, FilterCondition, FilterElement, FilterOperator, MatchMode,
+    };
+
+    fn item(title: &str) -> TorrentItem {
+        let parsed = ryuuji_parse::parse(title);
+        TorrentItem {
+            guid: title.into(),
+            title: title.into(),
+            link: None,
+            magnet_link: None,
+            description: None,
+            size: None,
+            seeders: None,
+            leechers: None,
+            downloads: None,
+            pub_date: None,
+            info_link: None,
+            anime_id: None,
+            anime_title: None,
+            episode: parsed.episode_number,
+            episode_end: parsed.episode_number_end,
+            is_batch: parsed.is_batch,
+            anime_airing: false,
+            release_group: parsed.release_group,
+            resolution: parsed.resolution,
+            filter_state: FilterState::None,
+        }
+    }
+
+    fn condition(element: FilterElement, value: &str) -> FilterCondition {
+        FilterCondition {
+            element,
+            operator: FilterOperator::Equals,
+            value: value.into(),
+        }
+    }
+
+    #[test]
+    fn test_discard_batches_for_airing_shows() {
+        let filter = TorrentFilter {
+            id: 1,
+            name: "No batches while airing".into(),
+            enabled: true,
+            priority: 0,
+            match_mode: MatchMode::All,
+            action: FilterAction::Discard,
+            conditions: vec![
+                condition(FilterElement::Batch, "true"),
+                condition(FilterElement::Airing, "true"),
+            ],
+        };
+
+        let mut airing_batch = item("[Group] Title (01-12) [1080p] [Batch]");
+        airing_batch.anime_airing = true;
+        let mut airing_episode = item("[Group] Title - 05 [1080p]");
+        airing_episode.anime_airing = true;
+        let finished_batch = item("[Group] Title (01-12) [1080p] [Batch]");
+
+        let mut items = vec![airing_batch, airing_episode, finished_batch];
+        apply_filters(&mut items, &[filter]);
+        let states: Vec<_> = items.iter().map(|i| i.filter_state).collect();
+        assert_eq!(
+            states,
+            [FilterState::Discarded, FilterState::None, FilterState::None]
+        );
+    }
+
+    #[test]
+    fn test_flag_values() {
+        let batch = item("Title S01 Complete");
+        assert!(evaluate_condition(
+            &batch,
+            &condition(FilterElement::Batch, "yes")
+        ));
+        assert!(!evaluate_condition(
+            &batch,
+            &condition(FilterElement::Batch, "false")
+        ));
+        // Unrecognised values never match.
+        assert!(!evaluate_condition(
+            &batch,
+            &condition(FilterElement::Batch, "maybe")
+        ));
+    }
+}
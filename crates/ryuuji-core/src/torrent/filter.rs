@@ -65,6 +65,10 @@ pub enum FilterElement {
     ReleaseGroup,
     Resolution,
     Size,
+    /// "true" for batch releases.
+    Batch,
+    /// "true" when the matched anime is currently airing.
+    Airing,
 }
 
 impl std::fmt::Display for FilterElement {
@@ -75,6 +79,8 @@ impl std::fmt::Display for FilterElement {
             Self::ReleaseGroup => write!(f, "Group"),
             Self::Resolution => write!(f, "Resolution"),
             Self::Size => write!(f, "Size"),
+            Self::Batch => write!(f, "Batch"),
+            Self::Airing => write!(f, "Airing"),
         }
     }
 }
@@ -1,3 +1,5 @@
+use ryuuji_parse::KeywordSet;
+
 use crate::matcher::MatchResult;
 use crate::recognition::RecognitionCache;
 use crate::storage::Storage;
@@ -7,16 +9,21 @@ use crate::torrent::models::TorrentItem;
 ///
 /// For each item, uses `ryuuji_parse` to extract episode/group/resolution
 /// from the raw title, then runs the recognition cache to match against
-/// known anime in the database.
+/// known anime in the database. Titles are parsed with `keywords`.
 pub fn match_torrent_items(
     items: &mut [TorrentItem],
     storage: &Storage,
     cache: &mut RecognitionCache,
+    keywords: &KeywordSet,
 ) {
     for item in items.iter_mut() {
-        let parsed = ryuuji_parse::parse(&item.title);
+        let parsed = ryuuji_parse::parse_with_keywords(&item.title, keywords);
 
         item.episode = parsed.episode_number;
+        item.episode_end = parsed
+            .episode_number_end
+            .filter(|end| parsed.episode_number.is_some_and(|start| *end > start));
+        item.is_batch = parsed.is_batch;
         item.release_group = parsed.release_group;
         item.resolution = parsed.resolution;
 
@@ -25,6 +32,7 @@ pub fn match_torrent_items(
                 MatchResult::Matched(anime) | MatchResult::Fuzzy(anime, _) => {
                     item.anime_id = Some(anime.id);
                     item.anime_title = Some(anime.title.preferred().to_string());
+                    item.anime_airing = anime.is_airing();
                 }
                 MatchResult::NoMatch => {}
             }
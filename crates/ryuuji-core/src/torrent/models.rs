@@ -33,12 +33,31 @@ pub struct TorrentItem {
     pub anime_id: Option<i64>,
     pub anime_title: Option<String>,
     pub episode: Option<u32>,
+    /// Last episode covered, for batches and multi-episode releases.
+    pub episode_end: Option<u32>,
+    /// A batch of episodes or volumes rather than a single release.
+    pub is_batch: bool,
+    /// Whether the matched anime is currently airing.
+    pub anime_airing: bool,
     pub release_group: Option<String>,
     pub resolution: Option<String>,
     /// Filter evaluation result.
     pub filter_state: FilterState,
 }
 
+impl TorrentItem {
+    /// Episode or episode range for display: "5", "1-12", or "Batch" for a
+    /// batch with no episode numbers.
+    pub fn episode_label(&self) -> Option<String> {
+        match (self.episode, self.episode_end) {
+            (Some(start), Some(end)) => Some(format!("{start}-{end}")),
+            (Some(ep), None) => Some(ep.to_string()),
+            (None, _) if self.is_batch => Some("Batch".into()),
+            (None, _) => None,
+        }
+    }
+}
+
 /// Result of filter evaluation on a torrent item.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum FilterState {
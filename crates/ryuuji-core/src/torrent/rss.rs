@@ -79,6 +79,9 @@ pub async fn fetch_feed(
                 anime_id: None,
                 anime_title: None,
                 episode: None,
+                episode_end: None,
+                is_batch: false,
+                anime_airing: false,
                 release_group: None,
                 resolution: None,
                 filter_state: FilterState::None,
@@ -0,0 +1,337 @@
+//! Watch-time tracking.
+//!
+//! Accumulates actual playback time per detected file across detection ticks,
+//! so an episode is only marked watched once enough of it has really played —
+//! not the moment the file shows up in a player.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use ryuuji_detect::PlaybackState;
+
+use crate::config::LibraryConfig;
+use crate::models::DetectedMedia;
+
+/// Maximum number of files tracked at once; the stalest session is evicted.
+const MAX_SESSIONS: usize = 16;
+
+/// Longest gap between two ticks that still counts as continuous playback.
+///
+/// Protects against crediting a suspend/resume or a long detection stall.
+const MAX_TICK_GAP: Duration = Duration::from_secs(60);
+
+/// Slack allowed between position delta and wall-clock delta before a jump is
+/// treated as a seek rather than playback.
+const SEEK_TOLERANCE: Duration = Duration::from_secs(2);
+
+/// When an episode counts as watched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WatchPolicy {
+    /// Percentage of the media length that must have played (0 = immediately).
+    pub min_percent: u8,
+    /// Seconds of playback after which the episode counts regardless of
+    /// length (0 = disabled).
+    pub min_seconds: u64,
+}
+
+impl WatchPolicy {
+    pub fn from_config(config: &LibraryConfig) -> Self {
+        Self {
+            min_percent: config.watch_threshold_percent.min(100),
+            min_seconds: config.watch_threshold_secs,
+        }
+    }
+
+    /// Playback time required for media of the given length.
+    ///
+    /// Returns `None` when there is nothing to wait for: the percentage rule
+    /// is off, or neither rule can be evaluated (unknown length and no
+    /// seconds rule), in which case detection alone counts as watched.
+    pub fn required(&self, duration: Option<Duration>) -> Option<Duration> {
+        if self.min_percent == 0 {
+            return None;
+        }
+        let by_percent = duration
+            .filter(|d| !d.is_zero())
+            .map(|d| d.mul_f64(f64::from(self.min_percent) / 100.0));
+        let by_seconds = (self.min_seconds > 0).then(|| Duration::from_secs(self.min_seconds));
+        match (by_percent, by_seconds) {
+            (Some(p), Some(s)) => Some(p.min(s)),
+            (p, s) => p.or(s),
+        }
+    }
+}
+
+/// Whether the current file has played long enough to count.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchProgress {
+    Reached,
+    Pending {
+        watched: Duration,
+        required: Duration,
+    },
+}
+
+/// Per-file playback accounting.
+#[derive(Debug)]
+struct Session {
+    watched: Duration,
+    last_seen: Instant,
+    last_position: Option<Duration>,
+}
+
+/// Tracks accumulated playback time for recently detected files.
+#[derive(Debug, Default)]
+pub struct WatchTracker {
+    sessions: HashMap<String, Session>,
+}
+
+impl WatchTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a detection tick at the time it was observed and report
+    /// progress against `policy`.
+    pub fn observe(&mut self, detected: &DetectedMedia, policy: &WatchPolicy) -> WatchProgress {
+        let now = detected.observed_at.unwrap_or_else(Instant::now);
+        self.observe_at(detected, policy, now)
+    }
+
+    /// Like [`observe`](Self::observe), with an explicit clock for tests and replay.
+    pub fn observe_at(
+        &mut self,
+        detected: &DetectedMedia,
+        policy: &WatchPolicy,
+        now: Instant,
+    ) -> WatchProgress {
+        let key = session_key(detected);
+        if !self.sessions.contains_key(&key) && self.sessions.len() >= MAX_SESSIONS {
+            self.evict_stalest();
+        }
+
+        let session = self.sessions.entry(key).or_insert(Session {
+            watched: Duration::ZERO,
+            last_seen: now,
+            last_position: detected.position,
+        });
+
+        let wall = now.saturating_duration_since(session.last_seen);
+        let played = match detected.playback_state {
+            Some(PlaybackState::Paused | PlaybackState::Stopped) => Duration::ZERO,
+            _ if wall > MAX_TICK_GAP => Duration::ZERO,
+            _ => match (session.last_position, detected.position) {
+                (Some(prev), Some(cur)) if cur > prev => {
+                    let delta = cur - prev;
+                    // A forward jump larger than the elapsed time is a seek.
+                    if delta <= wall + SEEK_TOLERANCE {
+                        delta
+                    } else {
+                        Duration::ZERO
+                    }
+                }
+                (Some(_), Some(_)) => Duration::ZERO,
+                _ => wall,
+            },
+        };
+
+        session.watched += played;
+        session.last_seen = now;
+        session.last_position = detected.position;
+
+        progress(session.watched, detected, policy)
+    }
+
+    /// Progress as of the last [`observe`](Self::observe) of `detected`,
+    /// without counting another tick.
+    pub fn progress(&self, detected: &DetectedMedia, policy: &WatchPolicy) -> WatchProgress {
+        let watched = self
+            .sessions
+            .get(&session_key(detected))
+            .map_or(Duration::ZERO, |s| s.watched);
+        progress(watched, detected, policy)
+    }
+
+    fn evict_stalest(&mut self) {
+        if let Some(key) = self
+            .sessions
+            .iter()
+            .min_by_key(|(_, s)| s.last_seen)
+            .map(|(k, _)| k.clone())
+        {
+            self.sessions.remove(&key);
+        }
+    }
+}
+
+fn progress(watched: Duration, detected: &DetectedMedia, policy: &WatchPolicy) -> WatchProgress {
+    match policy.required(detected.duration) {
+        Some(required) if watched < required => WatchProgress::Pending { watched, required },
+        _ => WatchProgress::Reached,
+    }
+}
+
+/// Identify a detected file across ticks.
+fn session_key(detected: &DetectedMedia) -> String {
+    format!("{}\u{0}{}", detected.player_name, detected.raw_title)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(min_percent: u8, min_seconds: u64) -> WatchPolicy {
+        WatchPolicy {
+            min_percent,
+            min_seconds,
+        }
+    }
+
+    fn media(position: u64, state: PlaybackState) -> DetectedMedia {
+        DetectedMedia {
+            player_name: "mpv".into(),
+            anime_title: Some("Sousou no Frieren".into()),
+            episode: Some(5),
+            raw_title: "Sousou no Frieren - 05.mkv".into(),
+            position: Some(Duration::from_secs(position)),
+            duration: Some(Duration::from_secs(100)),
+            playback_state: Some(state),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_required_percent() {
+        let p = policy(80, 0);
+        assert_eq!(
+            p.required(Some(Duration::from_secs(100))),
+            Some(Duration::from_secs(80))
+        );
+        // Unknown length and no seconds rule: nothing to wait for.
+        assert_eq!(p.required(None), None);
+    }
+
+    #[test]
+    fn test_required_takes_earlier_rule() {
+        let p = policy(80, 60);
+        assert_eq!(
+            p.required(Some(Duration::from_secs(1440))),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(p.required(None), Some(Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn test_zero_percent_disables_gating() {
+        assert_eq!(policy(0, 60).required(Some(Duration::from_secs(100))), None);
+    }
+
+    #[test]
+    fn test_accumulates_until_threshold() {
+        let mut tracker = WatchTracker::new();
+        let p = policy(50, 0);
+        let t0 = Instant::now();
+
+        let progress = tracker.observe_at(&media(0, PlaybackState::Playing), &p, t0);
+        assert!(matches!(progress, WatchProgress::Pending { .. }));
+
+        let progress = tracker.observe_at(
+            &media(30, PlaybackState::Playing),
+            &p,
+            t0 + Duration::from_secs(30),
+        );
+        assert_eq!(
+            progress,
+            WatchProgress::Pending {
+                watched: Duration::from_secs(30),
+                required: Duration::from_secs(50),
+            }
+        );
+
+        let progress = tracker.observe_at(
+            &media(55, PlaybackState::Playing),
+            &p,
+            t0 + Duration::from_secs(55),
+        );
+        assert_eq!(progress, WatchProgress::Reached);
+    }
+
+    #[test]
+    fn test_paused_time_not_counted() {
+        let mut tracker = WatchTracker::new();
+        let p = policy(50, 0);
+        let t0 = Instant::now();
+
+        tracker.observe_at(&media(10, PlaybackState::Playing), &p, t0);
+        let progress = tracker.observe_at(
+            &media(10, PlaybackState::Paused),
+            &p,
+            t0 + Duration::from_secs(45),
+        );
+        assert!(matches!(
+            progress,
+            WatchProgress::Pending { watched, .. } if watched.is_zero()
+        ));
+    }
+
+    #[test]
+    fn test_seek_not_counted() {
+        let mut tracker = WatchTracker::new();
+        let p = policy(50, 0);
+        let t0 = Instant::now();
+
+        tracker.observe_at(&media(0, PlaybackState::Playing), &p, t0);
+        // Jumped 90s forward in 5s of wall time.
+        let progress = tracker.observe_at(
+            &media(90, PlaybackState::Playing),
+            &p,
+            t0 + Duration::from_secs(5),
+        );
+        assert!(matches!(
+            progress,
+            WatchProgress::Pending { watched, .. } if watched.is_zero()
+        ));
+    }
+
+    #[test]
+    fn test_unknown_position_uses_wall_clock() {
+        let mut tracker = WatchTracker::new();
+        let p = policy(80, 20);
+        let t0 = Instant::now();
+        let detected = DetectedMedia {
+            player_name: "VLC".into(),
+            raw_title: "Sousou no Frieren - 05.mkv".into(),
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            tracker.observe_at(&detected, &p, t0),
+            WatchProgress::Pending { .. }
+        ));
+        assert_eq!(
+            tracker.observe_at(&detected, &p, t0 + Duration::from_secs(20)),
+            WatchProgress::Reached
+        );
+    }
+
+    #[test]
+    fn test_sessions_tracked_per_file() {
+        let mut tracker = WatchTracker::new();
+        let p = policy(50, 0);
+        let t0 = Instant::now();
+        let mut other = media(0, PlaybackState::Playing);
+        other.raw_title = "Sousou no Frieren - 06.mkv".into();
+
+        tracker.observe_at(&media(0, PlaybackState::Playing), &p, t0);
+        tracker.observe_at(&other, &p, t0 + Duration::from_secs(1));
+        let progress = tracker.observe_at(
+            &media(20, PlaybackState::Playing),
+            &p,
+            t0 + Duration::from_secs(20),
+        );
+        assert!(matches!(
+            progress,
+            WatchProgress::Pending { watched, .. } if watched == Duration::from_secs(20)
+        ));
+    }
+}
@@ -16,6 +16,19 @@ fn main() {
             if let Some(path) = &player.file_path {
                 println!("  File:  {path}");
             }
+            if let Some(state) = &player.playback_state {
+                println!("  State: {state:?}");
+            }
+            if let Some(pos) = player.position {
+                match player.duration {
+                    Some(len) => println!(
+                        "  Time:  {:.0}s / {:.0}s",
+                        pos.as_secs_f64(),
+                        len.as_secs_f64()
+                    ),
+                    None => println!("  Time:  {:.0}s", pos.as_secs_f64()),
+                }
+            }
             println!();
         }
     }
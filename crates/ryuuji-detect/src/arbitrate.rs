@@ -0,0 +1,266 @@
+//! Choosing which of several detected players to track.
+//!
+//! Platforms report every player they can see, in no particular order. A
+//! paused VLC window or a browser playing music should not hide the mpv
+//! instance that is actually playing an episode, so candidates are ranked by
+//! playback state, whether their title looks like an episode, the user's
+//! priority list and, as a tie-breaker, which player won last time.
+
+use std::cmp::Reverse;
+
+use crate::{PlaybackState, PlayerInfo};
+
+/// One factor that contributed to a candidate's rank.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RankReason {
+    Playing,
+    Paused,
+    Stopped,
+    StateUnknown,
+    Episode,
+    NoEpisode,
+    /// Position in the user's priority list (0 = highest).
+    Priority(usize),
+    /// Was the selected player on the previous tick.
+    LastActive,
+}
+
+impl std::fmt::Display for RankReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Playing => f.write_str("playing"),
+            Self::Paused => f.write_str("paused"),
+            Self::Stopped => f.write_str("stopped"),
+            Self::StateUnknown => f.write_str("state unknown"),
+            Self::Episode => f.write_str("episode title"),
+            Self::NoEpisode => f.write_str("no episode"),
+            Self::Priority(i) => write!(f, "priority #{}", i + 1),
+            Self::LastActive => f.write_str("last active"),
+        }
+    }
+}
+
+/// A detected player together with why it ranked where it did.
+#[derive(Debug, Clone)]
+pub struct RankedPlayer {
+    pub player: PlayerInfo,
+    pub reasons: Vec<RankReason>,
+}
+
+impl RankedPlayer {
+    /// Human-readable summary of [`reasons`](Self::reasons).
+    pub fn reason(&self) -> String {
+        self.reasons
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Ranks detected players and remembers the last winner.
+#[derive(Debug, Clone, Default)]
+pub struct PlayerArbiter {
+    priority: Vec<String>,
+    last_active: Option<String>,
+    /// Names and reasons from the previous call to [`rank`](Self::rank).
+    last_ranking: Vec<(String, Vec<RankReason>)>,
+    ranking_changed: bool,
+}
+
+impl PlayerArbiter {
+    /// Create an arbiter with a user priority list of player names
+    /// (case-insensitive, highest first).
+    pub fn new(priority: Vec<String>) -> Self {
+        Self {
+            priority,
+            ..Default::default()
+        }
+    }
+
+    /// Replace the user priority list.
+    pub fn set_priority(&mut self, priority: Vec<String>) {
+        self.priority = priority;
+    }
+
+    /// Name of the player selected on the previous call to [`rank`](Self::rank).
+    pub fn last_active(&self) -> Option<&str> {
+        self.last_active.as_deref()
+    }
+
+    /// Whether the last call to [`rank`](Self::rank) ordered the players
+    /// differently, or for different reasons, than the call before it.
+    pub fn ranking_changed(&self) -> bool {
+        self.ranking_changed
+    }
+
+    /// Order `players` best-first. `has_episode` reports whether a player's
+    /// title parses to an episode. The winner becomes the sticky player.
+    ///
+    /// Ties keep the platform's original order.
+    pub fn rank(
+        &mut self,
+        players: Vec<PlayerInfo>,
+        has_episode: impl Fn(&PlayerInfo) -> bool,
+    ) -> Vec<RankedPlayer> {
+        let mut ranked: Vec<_> = players
+            .into_iter()
+            .map(|player| {
+                let (state_score, state_reason) = match player.playback_state {
+                    Some(PlaybackState::Playing) => (3, RankReason::Playing),
+                    None => (2, RankReason::StateUnknown),
+                    Some(PlaybackState::Paused) => (1, RankReason::Paused),
+                    Some(PlaybackState::Stopped) => (0, RankReason::Stopped),
+                };
+                let episode = has_episode(&player);
+                let priority = self
+                    .priority
+                    .iter()
+                    .position(|name| name.eq_ignore_ascii_case(&player.player_name));
+                let sticky = self.last_active.as_deref() == Some(player.player_name.as_str());
+
+                let mut reasons = vec![
+                    state_reason,
+                    if episode {
+                        RankReason::Episode
+                    } else {
+                        RankReason::NoEpisode
+                    },
+                ];
+                reasons.extend(priority.map(RankReason::Priority));
+                if sticky {
+                    reasons.push(RankReason::LastActive);
+                }
+
+                let key = (
+                    Reverse(state_score),
+                    Reverse(episode),
+                    priority.unwrap_or(usize::MAX),
+                    Reverse(sticky),
+                );
+                (key, RankedPlayer { player, reasons })
+            })
+            .collect();
+
+        ranked.sort_by_key(|(key, _)| *key);
+        let ranked: Vec<_> = ranked.into_iter().map(|(_, r)| r).collect();
+
+        if let Some(winner) = ranked.first() {
+            self.last_active = Some(winner.player.player_name.clone());
+        }
+        let ranking: Vec<_> = ranked
+            .iter()
+            .map(|r| (r.player.player_name.clone(), r.reasons.clone()))
+            .collect();
+        self.ranking_changed = ranking != self.last_ranking;
+        self.last_ranking = ranking;
+        ranked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn player(name: &str, title: &str, state: Option<PlaybackState>) -> PlayerInfo {
+        PlayerInfo {
+            player_name: name.into(),
+            media_title: Some(title.into()),
+            playback_state: state,
+            ..Default::default()
+        }
+    }
+
+    fn looks_like_episode(p: &PlayerInfo) -> bool {
+        p.media_title.as_deref().is_some_and(|t| t.contains(" - 0"))
+    }
+
+    fn names(ranked: &[RankedPlayer]) -> Vec<&str> {
+        ranked
+            .iter()
+            .map(|r| r.player.player_name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_playing_beats_paused() {
+        let mut arbiter = PlayerArbiter::default();
+        let ranked = arbiter.rank(
+            vec![
+                player("VLC", "Frieren - 04.mkv", Some(PlaybackState::Paused)),
+                player("mpv", "Frieren - 05.mkv", Some(PlaybackState::Playing)),
+            ],
+            looks_like_episode,
+        );
+        assert_eq!(names(&ranked), ["mpv", "VLC"]);
+        assert_eq!(ranked[0].reasons[0], RankReason::Playing);
+        assert_eq!(arbiter.last_active(), Some("mpv"));
+    }
+
+    #[test]
+    fn test_episode_beats_music() {
+        let mut arbiter = PlayerArbiter::default();
+        let ranked = arbiter.rank(
+            vec![
+                player("Firefox", "Lofi beats", Some(PlaybackState::Playing)),
+                player("mpv", "Frieren - 05.mkv", Some(PlaybackState::Playing)),
+            ],
+            looks_like_episode,
+        );
+        assert_eq!(names(&ranked), ["mpv", "Firefox"]);
+        assert_eq!(ranked[1].reason(), "playing, no episode");
+    }
+
+    #[test]
+    fn test_user_priority() {
+        let mut arbiter = PlayerArbiter::new(vec!["vlc".into()]);
+        let ranked = arbiter.rank(
+            vec![
+                player("mpv", "Frieren - 05.mkv", Some(PlaybackState::Playing)),
+                player("VLC", "Frieren - 05.mkv", Some(PlaybackState::Playing)),
+            ],
+            looks_like_episode,
+        );
+        assert_eq!(names(&ranked), ["VLC", "mpv"]);
+        assert!(ranked[0].reasons.contains(&RankReason::Priority(0)));
+    }
+
+    #[test]
+    fn test_last_active_is_sticky() {
+        let mut arbiter = PlayerArbiter::default();
+        let both = |first: &str, second: &str| {
+            vec![
+                player(first, "Frieren - 05.mkv", Some(PlaybackState::Playing)),
+                player(second, "Frieren - 05.mkv", Some(PlaybackState::Playing)),
+            ]
+        };
+
+        arbiter.rank(both("mpv", "VLC"), looks_like_episode);
+        // Platform order flipped, but mpv stays selected.
+        let ranked = arbiter.rank(both("VLC", "mpv"), looks_like_episode);
+        assert_eq!(names(&ranked), ["mpv", "VLC"]);
+        assert!(ranked[0].reasons.contains(&RankReason::LastActive));
+    }
+
+    #[test]
+    fn test_ranking_changed() {
+        let mut arbiter = PlayerArbiter::default();
+        let players = |vlc_state| {
+            vec![
+                player("mpv", "Frieren - 05.mkv", Some(PlaybackState::Playing)),
+                player("VLC", "Frieren - 04.mkv", Some(vlc_state)),
+            ]
+        };
+
+        arbiter.rank(players(PlaybackState::Paused), looks_like_episode);
+        assert!(arbiter.ranking_changed());
+        // mpv is now also the last active player.
+        arbiter.rank(players(PlaybackState::Paused), looks_like_episode);
+        assert!(arbiter.ranking_changed());
+        arbiter.rank(players(PlaybackState::Paused), looks_like_episode);
+        assert!(!arbiter.ranking_changed());
+        // Same order, different reason.
+        arbiter.rank(players(PlaybackState::Stopped), looks_like_episode);
+        assert!(arbiter.ranking_changed());
+    }
+}
@@ -0,0 +1,27 @@
+//! Native-messaging host for the ryuuji browser extension.
+//!
+//! The browser starts this binary and exchanges length-prefixed JSON
+//! messages with it over stdin/stdout. Each message is a now-playing report
+//! (see `ryuuji_detect::bridge`), forwarded to the running app's loopback
+//! endpoint. Set `RYUUJI_BRIDGE_PORT` if the app listens on a non-default
+//! port.
+
+use std::io;
+
+use ryuuji_detect::bridge::{self, DEFAULT_PORT};
+
+fn main() -> io::Result<()> {
+    let port = std::env::var("RYUUJI_BRIDGE_PORT")
+        .ok()
+        .and_then(|p| p.parse().ok())
+        .unwrap_or(DEFAULT_PORT);
+
+    let mut stdin = io::stdin().lock();
+    let mut stdout = io::stdout().lock();
+    // The browser closes stdin when the extension disconnects.
+    while let Some(message) = bridge::read_native_message(&mut stdin)? {
+        let reply = bridge::forward_native_message(port, &message);
+        bridge::write_native_message(&mut stdout, &reply)?;
+    }
+    Ok(())
+}
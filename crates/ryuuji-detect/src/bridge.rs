@@ -0,0 +1,827 @@
+//! Browser bridge: now-playing reports pushed by a browser extension.
+//!
+//! Window titles are a poor source for streaming sites: the tab title often
+//! lacks the episode, and browsers may not expose it at all. A companion
+//! extension knows the page URL and the `<video>` element's state, and pushes
+//! them here over loopback HTTP or a WebSocket, or through the
+//! `ryuuji-native-host` native-messaging binary.
+//!
+//! # Protocol
+//!
+//! The server listens on `127.0.0.1` only ([`DEFAULT_PORT`] by default).
+//!
+//! `POST /v1/now-playing` with `Content-Type: application/json` and a
+//! [`BrowserReport`] body:
+//!
+//! ```json
+//! {
+//!   "browser": "Firefox",
+//!   "tabId": 12,
+//!   "url": "https://www.crunchyroll.com/watch/GG1U2J1M7/episode-7",
+//!   "title": "Frieren: Beyond Journey's End - Watch on Crunchyroll",
+//!   "currentTime": 312.5,
+//!   "duration": 1420.0,
+//!   "paused": false
+//! }
+//! ```
+//!
+//! Only `browser` and `url` are required. Send a report when playback
+//! starts, pauses, seeks or navigates, and at least every few seconds while a
+//! video is open: reports not refreshed within [`REPORT_TTL`] are dropped.
+//! Send `"closed": true` to withdraw a tab's report immediately.
+//!
+//! Replies are `204 No Content` on success, `400` for a malformed report,
+//! `403` for requests carrying a web page `Origin` (only extension origins
+//! are accepted), `404`/`405` for anything else. `GET /v1/status` answers
+//! `200 {"app":"ryuuji","protocol":1}` so an extension can check that the
+//! app is running.
+//!
+//! An extension that keeps a connection open can instead upgrade
+//! `GET /v1/ws` to a WebSocket and send each report as a text message. Every
+//! message is answered with `{"ok":true}` or `{"ok":false,"error":"..."}`.
+//! The same `Origin` rule applies to the upgrade request, and a socket idle
+//! for longer than a minute is closed.
+//!
+//! The native-messaging host uses the browser's framing: each message is a
+//! 32-bit native-endian length followed by that many bytes of JSON. It
+//! forwards each report to the HTTP endpoint and answers `{"ok":true}` or
+//! `{"ok":false,"error":"..."}`.
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::player_db::PlayerDatabase;
+use crate::{PlaybackState, PlayerInfo};
+
+/// Port the bridge listens on unless configured otherwise.
+pub const DEFAULT_PORT: u16 = 38469;
+
+/// How long a report stays valid without being refreshed.
+pub const REPORT_TTL: Duration = Duration::from_secs(15);
+
+const REPORT_PATH: &str = "/v1/now-playing";
+const STATUS_PATH: &str = "/v1/status";
+const WS_PATH: &str = "/v1/ws";
+const IO_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How long a WebSocket may stay silent. Extensions report far more often.
+const WS_IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Appended to the client's key to answer a WebSocket handshake (RFC 6455).
+const WS_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+const WS_TEXT: u8 = 0x1;
+const WS_CLOSE: u8 = 0x8;
+const WS_PING: u8 = 0x9;
+const WS_PONG: u8 = 0xa;
+
+/// Connections served at once; further ones are closed straight away.
+const MAX_CONNECTIONS: usize = 16;
+
+/// Reports are a URL and a title; anything larger is not one.
+const MAX_BODY_BYTES: usize = 64 * 1024;
+
+/// One now-playing report from a browser tab.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrowserReport {
+    /// Browser name, resolved against the player database (e.g. "Firefox").
+    pub browser: String,
+    /// Browser tab ID; reports from different tabs are tracked separately.
+    #[serde(default)]
+    pub tab_id: Option<u64>,
+    pub url: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    /// `<video>.currentTime`, in seconds.
+    #[serde(default)]
+    pub current_time: Option<f64>,
+    /// `<video>.duration`, in seconds.
+    #[serde(default)]
+    pub duration: Option<f64>,
+    #[serde(default)]
+    pub paused: bool,
+    /// The tab closed or left the video; forget its report.
+    #[serde(default)]
+    pub closed: bool,
+}
+
+impl BrowserReport {
+    /// Convert to a browser [`PlayerInfo`] for stream detection.
+    pub fn to_player_info(&self, db: &PlayerDatabase) -> PlayerInfo {
+        let seconds = |s: Option<f64>| s.and_then(|s| Duration::try_from_secs_f64(s).ok());
+        let player_name = db
+            .find_by_executable(&self.browser)
+            .map(|p| p.name.clone())
+            .unwrap_or_else(|| self.browser.clone());
+        PlayerInfo {
+            player_name,
+            media_title: self.title.clone().filter(|t| !t.is_empty()),
+            file_path: Some(self.url.clone()),
+            is_browser: true,
+            position: seconds(self.current_time),
+            duration: seconds(self.duration),
+            playback_state: Some(if self.paused {
+                PlaybackState::Paused
+            } else {
+                PlaybackState::Playing
+            }),
+            episode_info: None,
+        }
+    }
+}
+
+type ReportKey = (String, Option<u64>);
+
+/// Latest report per browser tab, shared between the server and detection.
+#[derive(Debug, Clone, Default)]
+pub struct BridgeReports {
+    inner: Arc<Mutex<HashMap<ReportKey, (Instant, BrowserReport)>>>,
+}
+
+impl BridgeReports {
+    /// Record a report, replacing the tab's previous one.
+    pub fn submit(&self, report: BrowserReport) {
+        self.submit_at(report, Instant::now());
+    }
+
+    fn submit_at(&self, report: BrowserReport, now: Instant) {
+        let key = (report.browser.to_lowercase(), report.tab_id);
+        let mut reports = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        if report.closed {
+            reports.remove(&key);
+        } else {
+            reports.insert(key, (now, report));
+        }
+    }
+
+    /// Players for every report still within [`REPORT_TTL`].
+    pub fn players(&self, db: &PlayerDatabase) -> Vec<PlayerInfo> {
+        self.players_at(db, Instant::now())
+    }
+
+    fn players_at(&self, db: &PlayerDatabase, now: Instant) -> Vec<PlayerInfo> {
+        let mut reports = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        reports.retain(|_, (at, _)| now.saturating_duration_since(*at) <= REPORT_TTL);
+        reports
+            .values()
+            .map(|(_, report)| report.to_player_info(db))
+            .collect()
+    }
+}
+
+/// The loopback HTTP server. Each connection is served on its own thread.
+/// Dropping it stops accepting connections.
+pub struct BrowserBridge {
+    reports: BridgeReports,
+    port: u16,
+    stop: Arc<AtomicBool>,
+}
+
+impl BrowserBridge {
+    /// Listen on `127.0.0.1:port` (0 picks a free port).
+    pub fn start(port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", port))?;
+        let port = listener.local_addr()?.port();
+        let reports = BridgeReports::default();
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let thread_reports = reports.clone();
+        let thread_stop = stop.clone();
+        thread::Builder::new()
+            .name("browser-bridge".into())
+            .spawn(move || {
+                let active = Arc::new(AtomicUsize::new(0));
+                for stream in listener.incoming() {
+                    if thread_stop.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    match stream {
+                        Ok(stream) => spawn_connection(stream, &thread_reports, &active),
+                        Err(e) => warn!("Browser bridge accept failed: {e}"),
+                    }
+                }
+                debug!("Browser bridge stopped");
+            })?;
+
+        Ok(Self {
+            reports,
+            port,
+            stop,
+        })
+    }
+
+    /// Port actually bound.
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Handle for reading the reports received so far.
+    pub fn reports(&self) -> BridgeReports {
+        self.reports.clone()
+    }
+}
+
+impl Drop for BrowserBridge {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        // Wake the blocking accept so the thread sees the flag.
+        let _ = TcpStream::connect_timeout(
+            &([127, 0, 0, 1], self.port).into(),
+            Duration::from_millis(100),
+        );
+    }
+}
+
+/// Serve `stream` on a thread of its own, unless [`MAX_CONNECTIONS`] are
+/// already being served.
+fn spawn_connection(stream: TcpStream, reports: &BridgeReports, active: &Arc<AtomicUsize>) {
+    if active.fetch_add(1, Ordering::Relaxed) >= MAX_CONNECTIONS {
+        active.fetch_sub(1, Ordering::Relaxed);
+        debug!("Browser bridge busy, dropping connection");
+        return;
+    }
+    let reports = reports.clone();
+    let thread_active = active.clone();
+    let spawned = thread::Builder::new()
+        .name("browser-bridge-conn".into())
+        .spawn(move || {
+            if let Err(e) = handle_connection(stream, &reports) {
+                debug!("Browser bridge connection failed: {e}");
+            }
+            thread_active.fetch_sub(1, Ordering::Relaxed);
+        });
+    if let Err(e) = spawned {
+        active.fetch_sub(1, Ordering::Relaxed);
+        warn!("Browser bridge can't serve connection: {e}");
+    }
+}
+
+/// A parsed request: just the parts the bridge looks at.
+struct Request {
+    method: String,
+    path: String,
+    content_type: Option<String>,
+    origin: Option<String>,
+    upgrade: Option<String>,
+    websocket_key: Option<String>,
+    body: Vec<u8>,
+}
+
+impl Request {
+    /// Web pages can reach loopback too; only extensions (and the native
+    /// host, which sends no Origin) may report.
+    fn is_from_web_page(&self) -> bool {
+        self.origin
+            .as_deref()
+            .is_some_and(|o| o.starts_with("http://") || o.starts_with("https://") || o == "null")
+    }
+
+    /// The client's key, if this is a WebSocket upgrade.
+    fn websocket_key(&self) -> Option<&str> {
+        let upgrade = self.upgrade.as_deref()?;
+        (self.method == "GET" && upgrade.eq_ignore_ascii_case("websocket"))
+            .then_some(self.websocket_key.as_deref()?)
+    }
+}
+
+fn handle_connection(mut stream: TcpStream, reports: &BridgeReports) -> io::Result<()> {
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+    let (status, body) = match read_request(&mut stream)? {
+        Some(request) if request.path == WS_PATH && !request.is_from_web_page() => {
+            match request.websocket_key() {
+                Some(key) => return serve_websocket(stream, key, reports),
+                None => (400, error_body("expected a WebSocket upgrade")),
+            }
+        }
+        Some(request) => respond(&request, reports),
+        None => (413, String::new()),
+    };
+    let reason = match status {
+        200 => "OK",
+        204 => "No Content",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        _ => "Error",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {status} {reason}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+/// Read one request. Returns `None` if the body exceeds [`MAX_BODY_BYTES`].
+fn read_request(stream: &mut TcpStream) -> io::Result<Option<Request>> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+
+    let mut content_length = 0;
+    let mut content_type = None;
+    let mut origin = None;
+    let mut upgrade = None;
+    let mut websocket_key = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            break;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            let value = value.trim();
+            match name.to_ascii_lowercase().as_str() {
+                "content-length" => content_length = value.parse().unwrap_or(usize::MAX),
+                "content-type" => content_type = Some(value.to_ascii_lowercase()),
+                "origin" => origin = Some(value.to_string()),
+                "upgrade" => upgrade = Some(value.to_string()),
+                "sec-websocket-key" => websocket_key = Some(value.to_string()),
+                _ => {}
+            }
+        }
+    }
+    if content_length > MAX_BODY_BYTES {
+        return Ok(None);
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    Ok(Some(Request {
+        method,
+        path,
+        content_type,
+        origin,
+        upgrade,
+        websocket_key,
+        body,
+    }))
+}
+
+fn respond(request: &Request, reports: &BridgeReports) -> (u16, String) {
+    if request.is_from_web_page() {
+        return (403, String::new());
+    }
+    match (request.method.as_str(), request.path.as_str()) {
+        ("POST", REPORT_PATH) => {
+            let is_json = request
+                .content_type
+                .as_deref()
+                .is_some_and(|t| t.starts_with("application/json"));
+            if !is_json {
+                return (400, error_body("expected application/json"));
+            }
+            match submit_report(&request.body, reports) {
+                Ok(()) => (204, String::new()),
+                Err(e) => (400, error_body(&e)),
+            }
+        }
+        ("GET", STATUS_PATH) => (200, r#"{"app":"ryuuji","protocol":1}"#.into()),
+        (_, REPORT_PATH | STATUS_PATH) => (405, String::new()),
+        _ => (404, String::new()),
+    }
+}
+
+/// Parse a [`BrowserReport`] and record it.
+fn submit_report(body: &[u8], reports: &BridgeReports) -> Result<(), String> {
+    let report = serde_json::from_slice::<BrowserReport>(body).map_err(|e| e.to_string())?;
+    debug!(browser = %report.browser, url = %report.url, "Browser report");
+    reports.submit(report);
+    Ok(())
+}
+
+/// Complete the WebSocket handshake, then take one report per text message
+/// until the client closes the socket or goes quiet.
+fn serve_websocket(mut stream: TcpStream, key: &str, reports: &BridgeReports) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        websocket_accept(key)
+    )?;
+    stream.set_read_timeout(Some(WS_IDLE_TIMEOUT))?;
+    while let Some((opcode, payload)) = read_frame(&mut stream)? {
+        match opcode {
+            WS_TEXT => {
+                let reply = match submit_report(&payload, reports) {
+                    Ok(()) => serde_json::json!({ "ok": true }),
+                    Err(error) => serde_json::json!({ "ok": false, "error": error }),
+                };
+                write_frame(&mut stream, WS_TEXT, reply.to_string().as_bytes())?;
+            }
+            WS_PING => write_frame(&mut stream, WS_PONG, &payload)?,
+            WS_CLOSE => {
+                // Echo the status code, if any, and hang up.
+                return write_frame(&mut stream, WS_CLOSE, &payload[..payload.len().min(2)]);
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// `Sec-WebSocket-Accept` for a client's `Sec-WebSocket-Key`.
+fn websocket_accept(key: &str) -> String {
+    let mut sha = sha1_smol::Sha1::new();
+    sha.update(key.as_bytes());
+    sha.update(WS_GUID.as_bytes());
+    base64::engine::general_purpose::STANDARD.encode(sha.digest().bytes())
+}
+
+/// Read one client frame: its opcode and unmasked payload. Returns `None`
+/// once the client has gone.
+///
+/// Reports are small, so fragmented messages and frames over
+/// [`MAX_BODY_BYTES`] are refused rather than reassembled.
+fn read_frame(input: &mut impl Read) -> io::Result<Option<(u8, Vec<u8>)>> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+    let mut head = [0; 2];
+    match input.read_exact(&mut head) {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    if head[0] & 0x80 == 0 {
+        return Err(invalid("fragmented WebSocket message"));
+    }
+    // Clients must mask every frame.
+    if head[1] & 0x80 == 0 {
+        return Err(invalid("unmasked WebSocket frame"));
+    }
+    let len = match head[1] & 0x7f {
+        126 => {
+            let mut len = [0; 2];
+            input.read_exact(&mut len)?;
+            u64::from(u16::from_be_bytes(len))
+        }
+        127 => {
+            let mut len = [0; 8];
+            input.read_exact(&mut len)?;
+            u64::from_be_bytes(len)
+        }
+        len => u64::from(len),
+    };
+    if len > MAX_BODY_BYTES as u64 {
+        return Err(invalid("WebSocket message too large"));
+    }
+    let mut mask = [0; 4];
+    input.read_exact(&mut mask)?;
+    let mut payload = vec![0; len as usize];
+    input.read_exact(&mut payload)?;
+    for (i, byte) in payload.iter_mut().enumerate() {
+        *byte ^= mask[i % 4];
+    }
+    Ok(Some((head[0] & 0x0f, payload)))
+}
+
+/// Write one unmasked, unfragmented server frame.
+fn write_frame(output: &mut impl Write, opcode: u8, payload: &[u8]) -> io::Result<()> {
+    let mut frame = vec![0x80 | opcode];
+    match payload.len() {
+        len if len < 126 => frame.push(len as u8),
+        len if len <= usize::from(u16::MAX) => {
+            frame.push(126);
+            frame.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            frame.push(127);
+            frame.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(payload);
+    output.write_all(&frame)
+}
+
+fn error_body(message: &str) -> String {
+    serde_json::json!({ "error": message }).to_string()
+}
+
+/// Read one native-messaging message. Returns `None` at end of input.
+pub fn read_native_message(input: &mut impl Read) -> io::Result<Option<Vec<u8>>> {
+    let mut len = [0; 4];
+    match input.read_exact(&mut len) {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    let len = u32::from_ne_bytes(len) as usize;
+    if len > MAX_BODY_BYTES {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "native message too large",
+        ));
+    }
+    let mut message = vec![0; len];
+    input.read_exact(&mut message)?;
+    Ok(Some(message))
+}
+
+/// Write one native-messaging message.
+pub fn write_native_message(output: &mut impl Write, message: &[u8]) -> io::Result<()> {
+    output.write_all(&(message.len() as u32).to_ne_bytes())?;
+    output.write_all(message)?;
+    output.flush()
+}
+
+/// Forward a report received over native messaging to the app's HTTP
+/// endpoint on `port`. Returns the reply to send back to the browser.
+pub fn forward_native_message(port: u16, message: &[u8]) -> Vec<u8> {
+    let result = serde_json::from_slice::<BrowserReport>(message)
+        .map_err(|e| e.to_string())
+        .and_then(|report| {
+            let body = serde_json::to_string(&report).map_err(|e| e.to_string())?;
+            match crate::remote::http::request(
+                "127.0.0.1",
+                port,
+                "POST",
+                REPORT_PATH,
+                &[],
+                Some(&body),
+            ) {
+                Ok((204, _)) => Ok(()),
+                Ok((status, body)) => Err(format!("ryuuji answered {status}: {body}")),
+                Err(e) => Err(format!("ryuuji is not running: {e}")),
+            }
+        });
+    let reply = match result {
+        Ok(()) => serde_json::json!({ "ok": true }),
+        Err(error) => serde_json::json!({ "ok": false, "error": error }),
+    };
+    reply.to_string().into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::remote::http::request;
+    use crate::stream::{self, StreamDatabase};
+
+    const REPORT: &str = r#"{
+        "browser": "firefox",
+        "tabId": 12,
+        "url": "https://www.crunchyroll.com/watch/GG1U2J1M7/episode-7",
+        "title": "Frieren: Beyond Journey's End - Watch on Crunchyroll",
+        "currentTime": 312.5,
+        "duration": 1420.0,
+        "paused": true
+    }"#;
+
+    #[test]
+    fn test_report_to_player_info() {
+        let report: BrowserReport = serde_json::from_str(REPORT).unwrap();
+        let db = PlayerDatabase::embedded();
+        let player = report.to_player_info(&db);
+        assert_eq!(player.player_name, "Firefox");
+        assert!(player.is_browser);
+        assert_eq!(player.position, Some(Duration::from_secs_f64(312.5)));
+        assert_eq!(player.duration, Some(Duration::from_secs(1420)));
+        assert_eq!(player.playback_state, Some(PlaybackState::Paused));
+
+        let m = stream::detect_stream(&player, &StreamDatabase::embedded()).unwrap();
+        assert_eq!(m.service_name, "Crunchyroll");
+        assert_eq!(m.episode, Some(7));
+
+        // Live streams report an infinite duration.
+        let report = BrowserReport {
+            duration: Some(f64::INFINITY),
+            current_time: Some(-1.0),
+            ..report
+        };
+        let player = report.to_player_info(&db);
+        assert_eq!(player.duration, None);
+        assert_eq!(player.position, None);
+    }
+
+    #[test]
+    fn test_reports_expire_and_close() {
+        let db = PlayerDatabase::embedded();
+        let reports = BridgeReports::default();
+        let report: BrowserReport = serde_json::from_str(REPORT).unwrap();
+        let t0 = Instant::now();
+        reports.submit_at(report.clone(), t0);
+        reports.submit_at(
+            BrowserReport {
+                tab_id: Some(13),
+                ..report.clone()
+            },
+            t0,
+        );
+        assert_eq!(reports.players_at(&db, t0).len(), 2);
+
+        reports.submit_at(
+            BrowserReport {
+                closed: true,
+                ..report.clone()
+            },
+            t0,
+        );
+        assert_eq!(reports.players_at(&db, t0).len(), 1);
+        assert!(reports
+            .players_at(&db, t0 + REPORT_TTL + Duration::from_secs(1))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_server() {
+        let bridge = BrowserBridge::start(0).unwrap();
+        let port = bridge.port();
+        let db = PlayerDatabase::embedded();
+
+        let (status, body) = request("127.0.0.1", port, "GET", STATUS_PATH, &[], None).unwrap();
+        assert_eq!(status, 200);
+        assert!(body.contains("\"protocol\":1"));
+
+        let (status, _) = request("127.0.0.1", port, "POST", REPORT_PATH, &[], Some("{}")).unwrap();
+        assert_eq!(status, 400);
+
+        // A web page must not be able to inject reports.
+        let origin = [("Origin", "https://evil.example".to_string())];
+        let (status, _) = request(
+            "127.0.0.1",
+            port,
+            "POST",
+            REPORT_PATH,
+            &origin,
+            Some(REPORT),
+        )
+        .unwrap();
+        assert_eq!(status, 403);
+        assert!(bridge.reports().players(&db).is_empty());
+
+        let origin = [("Origin", "moz-extension://1234".to_string())];
+        let (status, _) = request(
+            "127.0.0.1",
+            port,
+            "POST",
+            REPORT_PATH,
+            &origin,
+            Some(REPORT),
+        )
+        .unwrap();
+        assert_eq!(status, 204);
+        let players = bridge.reports().players(&db);
+        assert_eq!(players.len(), 1);
+        assert_eq!(players[0].player_name, "Firefox");
+
+        let (status, _) = request("127.0.0.1", port, "GET", "/", &[], None).unwrap();
+        assert_eq!(status, 404);
+    }
+
+    /// A masked client frame.
+    fn client_frame(opcode: u8, payload: &[u8]) -> Vec<u8> {
+        let mask = [0x12, 0x34, 0x56, 0x78];
+        let mut frame = vec![0x80 | opcode];
+        match payload.len() {
+            len if len < 126 => frame.push(0x80 | len as u8),
+            len => {
+                frame.push(0x80 | 126);
+                frame.extend_from_slice(&(len as u16).to_be_bytes());
+            }
+        }
+        frame.extend_from_slice(&mask);
+        frame.extend(payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+        frame
+    }
+
+    /// Read one unmasked server frame.
+    fn server_frame(input: &mut impl Read) -> (u8, Vec<u8>) {
+        let mut head = [0; 2];
+        input.read_exact(&mut head).unwrap();
+        assert_eq!(head[1] & 0x80, 0);
+        let mut payload = vec![0; usize::from(head[1] & 0x7f)];
+        input.read_exact(&mut payload).unwrap();
+        (head[0] & 0x0f, payload)
+    }
+
+    #[test]
+    fn test_websocket_accept() {
+        // The example handshake from RFC 6455, section 1.3.
+        assert_eq!(
+            websocket_accept("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+    }
+
+    #[test]
+    fn test_frames_round_trip() {
+        let payload = vec![b'x'; 300];
+        let frame = client_frame(WS_TEXT, &payload);
+        assert_eq!(
+            read_frame(&mut frame.as_slice()).unwrap(),
+            Some((WS_TEXT, payload))
+        );
+        assert_eq!(read_frame(&mut &b""[..]).unwrap(), None);
+
+        let mut unmasked = Vec::new();
+        write_frame(&mut unmasked, WS_TEXT, b"hi").unwrap();
+        assert_eq!(unmasked, b"\x81\x02hi");
+        assert!(read_frame(&mut unmasked.as_slice()).is_err());
+    }
+
+    #[test]
+    fn test_websocket() {
+        let bridge = BrowserBridge::start(0).unwrap();
+        let db = PlayerDatabase::embedded();
+        let connect = |origin: &str| {
+            let mut stream = TcpStream::connect(("127.0.0.1", bridge.port())).unwrap();
+            stream.set_read_timeout(Some(IO_TIMEOUT)).unwrap();
+            write!(
+                stream,
+                "GET /v1/ws HTTP/1.1\r\nHost: 127.0.0.1\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\nOrigin: {origin}\r\n\r\n"
+            )
+            .unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut status = String::new();
+            reader.read_line(&mut status).unwrap();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim_end().is_empty() {
+                    break;
+                }
+            }
+            (status, reader)
+        };
+
+        let (status, _) = connect("https://evil.example");
+        assert!(status.starts_with("HTTP/1.1 403"));
+
+        let (status, mut reader) = connect("moz-extension://1234");
+        assert!(status.starts_with("HTTP/1.1 101"));
+        let mut send = |frame: Vec<u8>| reader.get_mut().write_all(&frame).unwrap();
+        send(client_frame(WS_TEXT, REPORT.as_bytes()));
+        send(client_frame(WS_TEXT, b"{}"));
+        send(client_frame(WS_PING, b"?"));
+        send(client_frame(WS_CLOSE, &1000u16.to_be_bytes()));
+
+        assert_eq!(
+            server_frame(&mut reader),
+            (WS_TEXT, br#"{"ok":true}"#.to_vec())
+        );
+        let (opcode, reply) = server_frame(&mut reader);
+        assert_eq!(opcode, WS_TEXT);
+        let reply: serde_json::Value = serde_json::from_slice(&reply).unwrap();
+        assert_eq!(reply["ok"], false);
+        assert_eq!(server_frame(&mut reader), (WS_PONG, b"?".to_vec()));
+        assert_eq!(
+            server_frame(&mut reader),
+            (WS_CLOSE, 1000u16.to_be_bytes().to_vec())
+        );
+        assert_eq!(bridge.reports().players(&db).len(), 1);
+    }
+
+    #[test]
+    fn test_stalled_connection_does_not_block() {
+        let bridge = BrowserBridge::start(0).unwrap();
+        // Connects and never sends a request.
+        let _stalled = TcpStream::connect(("127.0.0.1", bridge.port())).unwrap();
+        let started = Instant::now();
+        let (status, _) =
+            request("127.0.0.1", bridge.port(), "GET", STATUS_PATH, &[], None).unwrap();
+        assert_eq!(status, 200);
+        assert!(started.elapsed() < IO_TIMEOUT);
+    }
+
+    #[test]
+    fn test_native_messaging() {
+        let mut framed = Vec::new();
+        write_native_message(&mut framed, REPORT.as_bytes()).unwrap();
+        write_native_message(&mut framed, b"{}").unwrap();
+
+        let mut input = framed.as_slice();
+        assert_eq!(
+            read_native_message(&mut input).unwrap().unwrap(),
+            REPORT.as_bytes()
+        );
+        assert_eq!(read_native_message(&mut input).unwrap().unwrap(), b"{}");
+        assert!(read_native_message(&mut input).unwrap().is_none());
+
+        let bridge = BrowserBridge::start(0).unwrap();
+        let reply = forward_native_message(bridge.port(), REPORT.as_bytes());
+        assert_eq!(reply, br#"{"ok":true}"#);
+        assert_eq!(
+            bridge.reports().players(&PlayerDatabase::embedded()).len(),
+            1
+        );
+
+        let reply: serde_json::Value =
+            serde_json::from_slice(&forward_native_message(bridge.port(), b"{}")).unwrap();
+        assert_eq!(reply["ok"], false);
+    }
+}
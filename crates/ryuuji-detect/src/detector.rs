@@ -0,0 +1,561 @@
+//! Pluggable detection sources.
+//!
+//! Everything that can report what's playing implements [`Detector`]: each
+//! backend on its own, and [`SystemDetector`] combining the ones enabled in
+//! [`DetectOptions`]. Besides the live backends, [`RecordingDetector`] tees
+//! snapshots to a JSON-lines file and [`ReplayDetector`] plays such a file
+//! back, so a user's bug report can be fed through the rest of the pipeline
+//! unchanged.
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::bridge::BridgeReports;
+use crate::platform::{self, NativeDetector};
+use crate::player_db::PlayerDatabase;
+use crate::remote::{jellyfin, kodi, plex, vlc, RemoteEndpoint, ServerEndpoint};
+#[cfg(target_os = "linux")]
+use crate::stream::StreamDatabase;
+use crate::{DetectOptions, PlayerInfo};
+
+/// A source of media player snapshots.
+pub trait Detector: Send {
+    /// Report every player that is currently open.
+    fn detect(&mut self) -> Vec<PlayerInfo>;
+
+    /// When the last [`detect`](Self::detect) result was observed, if not
+    /// just now. A replay reports the recorded time, so watch-time tracking
+    /// sees the session at its original pace.
+    fn observed_at(&self) -> Option<Instant> {
+        None
+    }
+}
+
+impl<D: Detector + ?Sized> Detector for Box<D> {
+    fn detect(&mut self) -> Vec<PlayerInfo> {
+        (**self).detect()
+    }
+
+    fn observed_at(&self) -> Option<Instant> {
+        (**self).observed_at()
+    }
+}
+
+/// Shared detectors (e.g. a replay that outlives a single detection tick).
+impl<D: Detector> Detector for Arc<Mutex<D>> {
+    fn detect(&mut self) -> Vec<PlayerInfo> {
+        self.lock().unwrap_or_else(|e| e.into_inner()).detect()
+    }
+
+    fn observed_at(&self) -> Option<Instant> {
+        self.lock().unwrap_or_else(|e| e.into_inner()).observed_at()
+    }
+}
+
+/// The platform's native backend plus the optional ones in [`DetectOptions`].
+pub struct SystemDetector {
+    native: NativeDetector,
+    backends: Vec<Box<dyn Detector>>,
+    #[cfg(target_os = "linux")]
+    firefox: Option<FirefoxDetector>,
+}
+
+impl SystemDetector {
+    pub fn new(players: Arc<PlayerDatabase>, options: DetectOptions) -> Self {
+        let native = NativeDetector::new(players.clone(), &options);
+        let mut backends: Vec<Box<dyn Detector>> = Vec::new();
+        #[cfg(unix)]
+        if !options.mpv_ipc_sockets.is_empty() {
+            backends.push(Box::new(MpvIpcDetector::new(
+                players.clone(),
+                options.mpv_ipc_sockets,
+            )));
+        }
+        if let Some(endpoint) = options.kodi {
+            backends.push(Box::new(KodiDetector::new(players.clone(), endpoint)));
+        }
+        if let Some(endpoint) = options.vlc_http {
+            backends.push(Box::new(VlcHttpDetector::new(players.clone(), endpoint)));
+        }
+        if let Some(endpoint) = options.jellyfin {
+            backends.push(Box::new(JellyfinDetector::new(endpoint)));
+        }
+        if let Some(endpoint) = options.plex {
+            backends.push(Box::new(PlexDetector::new(endpoint)));
+        }
+        if let Some(reports) = options.browser_bridge {
+            backends.push(Box::new(BrowserBridgeDetector::new(
+                players.clone(),
+                reports,
+            )));
+        }
+        Self {
+            native,
+            backends,
+            #[cfg(target_os = "linux")]
+            firefox: options
+                .firefox_sessions
+                .map(|streams| FirefoxDetector::new(players, streams)),
+        }
+    }
+}
+
+impl Detector for SystemDetector {
+    fn detect(&mut self) -> Vec<PlayerInfo> {
+        let extra = self.backends.iter_mut().flat_map(|b| b.detect()).collect();
+        let players = platform::merge(self.native.detect(), extra);
+        #[cfg(target_os = "linux")]
+        if let Some(firefox) = &mut self.firefox {
+            let mut players = players;
+            let tabs = firefox.detect();
+            platform::firefox::apply_session_tabs(&mut players, tabs, &firefox.streams);
+            return players;
+        }
+        players
+    }
+}
+
+/// MPRIS players on the session bus.
+#[cfg(target_os = "linux")]
+pub struct MprisDetector {
+    players: Arc<PlayerDatabase>,
+    cache: Option<crate::MprisCache>,
+}
+
+#[cfg(target_os = "linux")]
+impl MprisDetector {
+    pub fn new(players: Arc<PlayerDatabase>) -> Self {
+        Self {
+            players,
+            cache: None,
+        }
+    }
+
+    /// Answer from `cache` while its watcher is running.
+    pub fn with_cache(mut self, cache: crate::MprisCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Detector for MprisDetector {
+    fn detect(&mut self) -> Vec<PlayerInfo> {
+        match &self.cache {
+            Some(cache) => cache.players(&self.players),
+            None => platform::linux::detect_mpris(&self.players),
+        }
+    }
+}
+
+/// Players found by scanning `/proc`, for those without MPRIS support.
+#[cfg(target_os = "linux")]
+pub struct ProcfsDetector {
+    players: Arc<PlayerDatabase>,
+}
+
+#[cfg(target_os = "linux")]
+impl ProcfsDetector {
+    pub fn new(players: Arc<PlayerDatabase>) -> Self {
+        Self { players }
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Detector for ProcfsDetector {
+    fn detect(&mut self) -> Vec<PlayerInfo> {
+        platform::procfs::detect_procfs(&self.players)
+    }
+}
+
+/// Firefox tabs on known streaming services, read from its session file.
+#[cfg(target_os = "linux")]
+pub struct FirefoxDetector {
+    players: Arc<PlayerDatabase>,
+    streams: Arc<StreamDatabase>,
+}
+
+#[cfg(target_os = "linux")]
+impl FirefoxDetector {
+    pub fn new(players: Arc<PlayerDatabase>, streams: Arc<StreamDatabase>) -> Self {
+        Self { players, streams }
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Detector for FirefoxDetector {
+    fn detect(&mut self) -> Vec<PlayerInfo> {
+        platform::firefox::detect_firefox(&self.players, &self.streams)
+    }
+}
+
+/// Media sessions reported by Windows' System Media Transport Controls.
+#[cfg(target_os = "windows")]
+pub struct SmtcDetector {
+    players: Arc<PlayerDatabase>,
+}
+
+#[cfg(target_os = "windows")]
+impl SmtcDetector {
+    pub fn new(players: Arc<PlayerDatabase>) -> Self {
+        Self { players }
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl Detector for SmtcDetector {
+    fn detect(&mut self) -> Vec<PlayerInfo> {
+        platform::windows_smtc::detect_smtc(&self.players)
+    }
+}
+
+/// Players recognised from their window titles.
+#[cfg(target_os = "windows")]
+pub struct WindowTitleDetector {
+    players: Arc<PlayerDatabase>,
+}
+
+#[cfg(target_os = "windows")]
+impl WindowTitleDetector {
+    pub fn new(players: Arc<PlayerDatabase>) -> Self {
+        Self { players }
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl Detector for WindowTitleDetector {
+    fn detect(&mut self) -> Vec<PlayerInfo> {
+        platform::windows::detect_windows(&self.players)
+    }
+}
+
+/// mpv instances listening on JSON IPC sockets.
+#[cfg(unix)]
+pub struct MpvIpcDetector {
+    players: Arc<PlayerDatabase>,
+    sockets: Vec<String>,
+}
+
+#[cfg(unix)]
+impl MpvIpcDetector {
+    /// `sockets` are paths whose file names may contain `*`.
+    pub fn new(players: Arc<PlayerDatabase>, sockets: Vec<String>) -> Self {
+        Self { players, sockets }
+    }
+}
+
+#[cfg(unix)]
+impl Detector for MpvIpcDetector {
+    fn detect(&mut self) -> Vec<PlayerInfo> {
+        crate::mpv_ipc::detect_mpv_ipc(&self.players, &self.sockets)
+    }
+}
+
+/// Kodi's JSON-RPC interface.
+pub struct KodiDetector {
+    players: Arc<PlayerDatabase>,
+    endpoint: RemoteEndpoint,
+}
+
+impl KodiDetector {
+    pub fn new(players: Arc<PlayerDatabase>, endpoint: RemoteEndpoint) -> Self {
+        Self { players, endpoint }
+    }
+}
+
+impl Detector for KodiDetector {
+    fn detect(&mut self) -> Vec<PlayerInfo> {
+        kodi::detect_kodi(&self.players, &self.endpoint)
+    }
+}
+
+/// VLC's HTTP interface.
+pub struct VlcHttpDetector {
+    players: Arc<PlayerDatabase>,
+    endpoint: RemoteEndpoint,
+}
+
+impl VlcHttpDetector {
+    pub fn new(players: Arc<PlayerDatabase>, endpoint: RemoteEndpoint) -> Self {
+        Self { players, endpoint }
+    }
+}
+
+impl Detector for VlcHttpDetector {
+    fn detect(&mut self) -> Vec<PlayerInfo> {
+        vlc::detect_vlc(&self.players, &self.endpoint)
+    }
+}
+
+/// Sessions on a Jellyfin server.
+pub struct JellyfinDetector {
+    endpoint: ServerEndpoint,
+}
+
+impl JellyfinDetector {
+    pub fn new(endpoint: ServerEndpoint) -> Self {
+        Self { endpoint }
+    }
+}
+
+impl Detector for JellyfinDetector {
+    fn detect(&mut self) -> Vec<PlayerInfo> {
+        jellyfin::detect_jellyfin(&self.endpoint)
+    }
+}
+
+/// Sessions on a Plex server.
+pub struct PlexDetector {
+    endpoint: ServerEndpoint,
+}
+
+impl PlexDetector {
+    pub fn new(endpoint: ServerEndpoint) -> Self {
+        Self { endpoint }
+    }
+}
+
+impl Detector for PlexDetector {
+    fn detect(&mut self) -> Vec<PlayerInfo> {
+        plex::detect_plex(&self.endpoint)
+    }
+}
+
+/// Tabs reported by the browser extension through the bridge.
+pub struct BrowserBridgeDetector {
+    players: Arc<PlayerDatabase>,
+    reports: BridgeReports,
+}
+
+impl BrowserBridgeDetector {
+    pub fn new(players: Arc<PlayerDatabase>, reports: BridgeReports) -> Self {
+        Self { players, reports }
+    }
+}
+
+impl Detector for BrowserBridgeDetector {
+    fn detect(&mut self) -> Vec<PlayerInfo> {
+        self.reports.players(&self.players)
+    }
+}
+
+/// One line of a recording: what a detector reported at a point in time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Milliseconds since the Unix epoch.
+    pub at: u64,
+    pub players: Vec<PlayerInfo>,
+}
+
+/// Wraps another detector and appends each result to a JSON-lines log.
+pub struct RecordingDetector<D, W> {
+    inner: D,
+    out: W,
+}
+
+/// Open `path` for recording, appending if it already exists.
+pub fn open_recording(path: &Path) -> io::Result<File> {
+    File::options().create(true).append(true).open(path)
+}
+
+impl<D: Detector> RecordingDetector<D, File> {
+    /// Record to `path`, appending if it already exists.
+    pub fn create(inner: D, path: &Path) -> io::Result<Self> {
+        Ok(Self::new(inner, open_recording(path)?))
+    }
+}
+
+impl<D: Detector, W: Write + Send> RecordingDetector<D, W> {
+    pub fn new(inner: D, out: W) -> Self {
+        Self { inner, out }
+    }
+
+    pub fn into_inner(self) -> (D, W) {
+        (self.inner, self.out)
+    }
+}
+
+impl<D: Detector, W: Write + Send> Detector for RecordingDetector<D, W> {
+    fn detect(&mut self) -> Vec<PlayerInfo> {
+        let players = self.inner.detect();
+        let snapshot = Snapshot {
+            at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+            players,
+        };
+        // Serialise first so each snapshot lands in a single write.
+        match serde_json::to_vec(&snapshot) {
+            Ok(mut line) => {
+                line.push(b'\n');
+                if let Err(e) = self.out.write_all(&line).and_then(|_| self.out.flush()) {
+                    tracing::warn!("Failed to record detection snapshot: {e}");
+                }
+            }
+            Err(e) => tracing::warn!("Failed to serialise detection snapshot: {e}"),
+        }
+        snapshot.players
+    }
+
+    fn observed_at(&self) -> Option<Instant> {
+        self.inner.observed_at()
+    }
+}
+
+/// Plays back a recording one snapshot per call. Once the recording is
+/// exhausted it reports nothing playing.
+///
+/// Snapshots are reported as observed at their recorded times, relative to
+/// when the first one was played back.
+#[derive(Debug, Clone, Default)]
+pub struct ReplayDetector {
+    snapshots: VecDeque<Snapshot>,
+    /// The first snapshot's `at`, and when it was played back.
+    epoch: Option<(u64, Instant)>,
+    observed_at: Option<Instant>,
+}
+
+impl ReplayDetector {
+    pub fn new(snapshots: impl IntoIterator<Item = Snapshot>) -> Self {
+        Self {
+            snapshots: snapshots.into_iter().collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Load a recording written by [`RecordingDetector`].
+    pub fn open(path: &Path) -> io::Result<Self> {
+        Self::from_reader(BufReader::new(File::open(path)?))
+    }
+
+    /// Parse JSON-lines snapshots. Blank lines are skipped; a malformed line
+    /// is an error carrying its line number.
+    pub fn from_reader(reader: impl BufRead) -> io::Result<Self> {
+        let mut snapshots = VecDeque::new();
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let snapshot = serde_json::from_str(&line).map_err(|e| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {e}", i + 1))
+            })?;
+            snapshots.push_back(snapshot);
+        }
+        Ok(Self::new(snapshots))
+    }
+
+    /// Snapshots not yet played back.
+    pub fn remaining(&self) -> usize {
+        self.snapshots.len()
+    }
+}
+
+impl Detector for ReplayDetector {
+    fn detect(&mut self) -> Vec<PlayerInfo> {
+        let Some(snapshot) = self.snapshots.pop_front() else {
+            self.observed_at = None;
+            return Vec::new();
+        };
+        let (first, start) = *self.epoch.get_or_insert((snapshot.at, Instant::now()));
+        self.observed_at = Some(start + Duration::from_millis(snapshot.at.saturating_sub(first)));
+        snapshot.players
+    }
+
+    fn observed_at(&self) -> Option<Instant> {
+        self.observed_at
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PlaybackState;
+
+    fn mpv(file: &str) -> PlayerInfo {
+        PlayerInfo {
+            player_name: "mpv".into(),
+            file_path: Some(format!("/anime/{file}")),
+            position: Some(Duration::from_secs(300)),
+            duration: Some(Duration::from_secs(1420)),
+            playback_state: Some(PlaybackState::Playing),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_record_then_replay() {
+        let live = ReplayDetector::new([
+            Snapshot {
+                at: 0,
+                players: vec![mpv("[SubsPlease] Frieren - 05 (1080p).mkv")],
+            },
+            Snapshot {
+                at: 0,
+                players: vec![],
+            },
+        ]);
+        let mut recorder = RecordingDetector::new(live, Vec::new());
+        assert_eq!(recorder.detect().len(), 1);
+        assert!(recorder.detect().is_empty());
+
+        let (_, log) = recorder.into_inner();
+        let text = String::from_utf8(log).unwrap();
+        assert_eq!(text.lines().count(), 2);
+
+        let mut replay = ReplayDetector::from_reader(text.as_bytes()).unwrap();
+        assert_eq!(replay.remaining(), 2);
+        let players = replay.detect();
+        assert_eq!(players[0].player_name, "mpv");
+        assert_eq!(players[0].position, Some(Duration::from_secs(300)));
+        assert_eq!(players[0].playback_state, Some(PlaybackState::Playing));
+        assert!(replay.detect().is_empty());
+        // Exhausted: nothing playing.
+        assert!(replay.detect().is_empty());
+    }
+
+    #[test]
+    fn test_replay_keeps_recorded_pace() {
+        let snapshot = |at| Snapshot {
+            at,
+            players: vec![mpv("a.mkv")],
+        };
+        let mut replay = ReplayDetector::new([snapshot(5_000), snapshot(35_000)]);
+        assert_eq!(replay.observed_at(), None);
+        replay.detect();
+        let first = replay.observed_at().unwrap();
+        replay.detect();
+        assert_eq!(
+            replay.observed_at().unwrap() - first,
+            Duration::from_secs(30)
+        );
+        replay.detect();
+        assert_eq!(replay.observed_at(), None);
+    }
+
+    #[test]
+    fn test_replay_reports_bad_line() {
+        let input = "{\"at\":1,\"players\":[]}\n\nnot json\n";
+        let err = ReplayDetector::from_reader(input.as_bytes()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().starts_with("line 3:"));
+    }
+
+    #[test]
+    fn test_shared_detector() {
+        let shared = Arc::new(Mutex::new(ReplayDetector::new([Snapshot {
+            at: 0,
+            players: vec![mpv("a.mkv")],
+        }])));
+        let mut tick: Box<dyn Detector> = Box::new(shared.clone());
+        assert_eq!(tick.detect().len(), 1);
+        assert_eq!(shared.lock().unwrap().remaining(), 0);
+    }
+}
@@ -1,14 +1,86 @@
+pub mod arbitrate;
+pub mod bridge;
+pub mod detector;
+#[cfg(unix)]
+pub mod mpv_ipc;
 pub mod platform;
 pub mod player_db;
+pub mod remote;
 pub mod stream;
 
+use std::sync::Arc;
+use std::time::Duration;
+
 use serde::{Deserialize, Serialize};
 
+pub use arbitrate::{PlayerArbiter, RankReason, RankedPlayer};
+pub use bridge::{BridgeReports, BrowserBridge, BrowserReport};
+#[cfg(unix)]
+pub use detector::MpvIpcDetector;
+pub use detector::{
+    open_recording, BrowserBridgeDetector, Detector, JellyfinDetector, KodiDetector, PlexDetector,
+    RecordingDetector, ReplayDetector, Snapshot, SystemDetector, VlcHttpDetector,
+};
+#[cfg(target_os = "linux")]
+pub use detector::{FirefoxDetector, MprisDetector, ProcfsDetector};
+#[cfg(target_os = "windows")]
+pub use detector::{SmtcDetector, WindowTitleDetector};
+#[cfg(target_os = "linux")]
+pub use platform::mpris_watch::{MprisCache, MprisEvent, MprisWatcher};
 pub use player_db::{PlayerDatabase, PlayerDef};
+pub use remote::{RemoteEndpoint, ServerEndpoint};
 pub use stream::{StreamDatabase, StreamDef, StreamMatch};
 
+/// A regex in a player or stream definition that failed to compile.
+///
+/// Invalid patterns are skipped during matching; these are surfaced so the
+/// user can fix their `players.toml` / `streams.toml`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternError {
+    /// Name of the player or stream the pattern belongs to.
+    pub owner: String,
+    /// Field the pattern came from (e.g. `title_patterns`).
+    pub field: &'static str,
+    pub pattern: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for PatternError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: invalid {} regex \"{}\": {}",
+            self.owner, self.field, self.pattern, self.message
+        )
+    }
+}
+
+impl PatternError {
+    /// Compile `pattern`, describing the failure if it is invalid.
+    pub(crate) fn compile(
+        owner: &str,
+        field: &'static str,
+        pattern: &str,
+    ) -> Result<regex::Regex, Self> {
+        regex::Regex::new(pattern).map_err(|e| Self {
+            owner: owner.to_string(),
+            field,
+            pattern: pattern.to_string(),
+            message: e.to_string(),
+        })
+    }
+}
+
+/// Playback state reported by a media player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PlaybackState {
+    Playing,
+    Paused,
+    Stopped,
+}
+
 /// Information about a detected media player and what it's playing.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PlayerInfo {
     /// Name of the media player (e.g., "mpv", "VLC").
     pub player_name: String,
@@ -18,6 +90,52 @@ pub struct PlayerInfo {
     pub file_path: Option<String>,
     /// Whether this player is a web browser.
     pub is_browser: bool,
+    /// Current playback position, if the player reports one.
+    #[serde(default)]
+    pub position: Option<Duration>,
+    /// Total length of the current media, if known.
+    #[serde(default)]
+    pub duration: Option<Duration>,
+    /// Playing/paused/stopped, if the player reports it.
+    #[serde(default)]
+    pub playback_state: Option<PlaybackState>,
+    /// Series and episode as reported by a media server. When present,
+    /// filename parsing is unnecessary.
+    #[serde(default)]
+    pub episode_info: Option<EpisodeInfo>,
+}
+
+/// Structured episode metadata from a media server's library.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EpisodeInfo {
+    pub series: String,
+    pub season: Option<u32>,
+    pub episode: Option<u32>,
+}
+
+/// Optional detection backends beyond the platform's native one.
+#[derive(Debug, Clone, Default)]
+pub struct DetectOptions {
+    /// mpv `--input-ipc-server` socket paths; file names may contain `*`.
+    /// Ignored on Windows.
+    pub mpv_ipc_sockets: Vec<String>,
+    /// Kodi JSON-RPC interface, if enabled.
+    pub kodi: Option<RemoteEndpoint>,
+    /// VLC HTTP interface, if enabled.
+    pub vlc_http: Option<RemoteEndpoint>,
+    /// Jellyfin server whose sessions to poll, if enabled.
+    pub jellyfin: Option<ServerEndpoint>,
+    /// Plex server whose sessions to poll, if enabled.
+    pub plex: Option<ServerEndpoint>,
+    /// Read Firefox's session file for tabs on these streaming services,
+    /// to name what a playing Firefox shows. Linux only; `None` disables it.
+    pub firefox_sessions: Option<Arc<StreamDatabase>>,
+    /// Reports pushed by the browser extension, if the bridge is running.
+    pub browser_bridge: Option<BridgeReports>,
+    /// Answers MPRIS queries while an [`MprisWatcher`] reports changes.
+    /// `None` queries every player on each call.
+    #[cfg(target_os = "linux")]
+    pub mpris_cache: Option<MprisCache>,
 }
 
 /// Detect what's currently playing across all supported media players.
@@ -30,5 +148,35 @@ pub fn detect_players() -> Vec<PlayerInfo> {
 
 /// Detect what's currently playing, using a custom player database.
 pub fn detect_players_with_db(db: &PlayerDatabase) -> Vec<PlayerInfo> {
-    platform::detect(db)
+    detect_players_with(db, &DetectOptions::default())
+}
+
+/// Detect what's currently playing, using a custom player database and
+/// additional backends from `options`.
+///
+/// Builds a [`SystemDetector`] for the one call; keep one around instead to
+/// detect repeatedly.
+pub fn detect_players_with(db: &PlayerDatabase, options: &DetectOptions) -> Vec<PlayerInfo> {
+    SystemDetector::new(Arc::new(db.clone()), options.clone()).detect()
+}
+
+/// Simple percent-decoding for file paths.
+pub(crate) fn percent_decode(s: &str) -> Option<String> {
+    let mut result = Vec::new();
+    let bytes = s.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            if let Ok(byte) =
+                u8::from_str_radix(std::str::from_utf8(&bytes[i + 1..i + 3]).ok()?, 16)
+            {
+                result.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        result.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8(result).ok()
 }
@@ -0,0 +1,303 @@
+//! mpv JSON IPC detection.
+//!
+//! mpv started with `--input-ipc-server=<path>` listens on a Unix socket that
+//! accepts newline-delimited JSON commands. This works without the mpv-mpris
+//! plugin and reports exact position and pause state.
+//!
+//! See <https://mpv.io/manual/stable/#json-ipc>.
+
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde_json::Value;
+use tracing::debug;
+
+use crate::player_db::PlayerDatabase;
+use crate::{PlaybackState, PlayerInfo};
+
+/// How long to wait for mpv to answer before giving up on a socket.
+const IO_TIMEOUT: Duration = Duration::from_millis(250);
+
+/// Lines to skip while waiting for a reply (mpv interleaves events).
+const MAX_LINES_PER_REPLY: usize = 64;
+
+/// Query every mpv IPC socket matching `patterns`.
+///
+/// Each pattern is a socket path; its file name may contain `*` wildcards
+/// (e.g. `/tmp/mpvsocket*`). Sockets that refuse the connection, time out or
+/// have nothing loaded are skipped.
+pub fn detect_mpv_ipc(db: &PlayerDatabase, patterns: &[String]) -> Vec<PlayerInfo> {
+    let player_name = db
+        .find_by_executable("mpv")
+        .map(|p| p.name.clone())
+        .unwrap_or_else(|| "mpv".into());
+
+    patterns
+        .iter()
+        .flat_map(|p| expand_pattern(p))
+        .filter_map(|socket| match query_socket(&socket, &player_name) {
+            Ok(info) => info,
+            Err(e) => {
+                debug!(socket = %socket.display(), error = %e, "mpv IPC socket not usable");
+                None
+            }
+        })
+        .collect()
+}
+
+/// Connect to one socket and read the current playback properties.
+///
+/// Returns `Ok(None)` when mpv is idle (no file loaded).
+fn query_socket(socket: &Path, player_name: &str) -> std::io::Result<Option<PlayerInfo>> {
+    let stream = UnixStream::connect(socket)?;
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+    let mut client = IpcClient {
+        reader: BufReader::new(stream.try_clone()?),
+        writer: stream,
+        next_id: 1,
+    };
+
+    let Some(path) = client
+        .get_property("path")?
+        .and_then(|v| v.as_str().map(String::from))
+    else {
+        return Ok(None);
+    };
+    let media_title = client
+        .get_property("media-title")?
+        .and_then(|v| v.as_str().map(String::from));
+    let position = client.get_property("time-pos")?.and_then(seconds);
+    let duration = client.get_property("duration")?.and_then(seconds);
+    let paused = client.get_property("pause")?.and_then(|v| v.as_bool());
+    let working_dir = client
+        .get_property("working-directory")?
+        .and_then(|v| v.as_str().map(PathBuf::from));
+
+    let file_path = resolve_path(&path, working_dir.as_deref());
+    let playback_state = paused.map(|p| {
+        if p {
+            PlaybackState::Paused
+        } else {
+            PlaybackState::Playing
+        }
+    });
+
+    debug!(
+        socket = %socket.display(),
+        path = %file_path,
+        state = ?playback_state,
+        position = ?position,
+        "Detected mpv via IPC"
+    );
+
+    Ok(Some(PlayerInfo {
+        player_name: player_name.to_string(),
+        media_title,
+        file_path: Some(file_path),
+        is_browser: false,
+        position,
+        duration,
+        playback_state,
+        episode_info: None,
+    }))
+}
+
+struct IpcClient {
+    reader: BufReader<UnixStream>,
+    writer: UnixStream,
+    next_id: u64,
+}
+
+impl IpcClient {
+    /// Send `get_property` and wait for the matching reply.
+    ///
+    /// mpv answers unavailable properties with an error, mapped to `None`.
+    fn get_property(&mut self, name: &str) -> std::io::Result<Option<Value>> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let request = serde_json::json!({
+            "command": ["get_property", name],
+            "request_id": id,
+        });
+        writeln!(self.writer, "{request}")?;
+
+        let mut line = String::new();
+        for _ in 0..MAX_LINES_PER_REPLY {
+            line.clear();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Err(std::io::ErrorKind::UnexpectedEof.into());
+            }
+            let Ok(reply) = serde_json::from_str::<Value>(&line) else {
+                continue;
+            };
+            if reply.get("request_id").and_then(Value::as_u64) != Some(id) {
+                continue;
+            }
+            if reply.get("error").and_then(Value::as_str) != Some("success") {
+                return Ok(None);
+            }
+            return Ok(reply.get("data").cloned());
+        }
+        Err(std::io::ErrorKind::InvalidData.into())
+    }
+}
+
+fn seconds(value: Value) -> Option<Duration> {
+    value
+        .as_f64()
+        .filter(|s| s.is_finite() && *s >= 0.0)
+        .map(Duration::from_secs_f64)
+}
+
+/// mpv reports `path` as given on its command line; make relative paths
+/// absolute using mpv's working directory. URLs are returned unchanged.
+fn resolve_path(path: &str, working_dir: Option<&Path>) -> String {
+    if path.contains("://") || Path::new(path).is_absolute() {
+        return path.to_string();
+    }
+    match working_dir {
+        Some(dir) => dir.join(path).to_string_lossy().into_owned(),
+        None => path.to_string(),
+    }
+}
+
+/// Expand `*` wildcards in the file name of `pattern`.
+fn expand_pattern(pattern: &str) -> Vec<PathBuf> {
+    let path = Path::new(pattern);
+    let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+        return vec![];
+    };
+    if !name.contains('*') {
+        return vec![path.to_path_buf()];
+    }
+    let dir = path.parent().filter(|p| !p.as_os_str().is_empty());
+    let dir = dir.unwrap_or(Path::new("."));
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return vec![];
+    };
+    let mut matches: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .filter(|e| {
+            e.file_name()
+                .to_str()
+                .is_some_and(|n| wildcard_match(name, n))
+        })
+        .map(|e| e.path())
+        .collect();
+    matches.sort();
+    matches
+}
+
+/// Match `text` against a pattern where `*` matches any run of characters.
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No `*` at all: exact match.
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixListener;
+    use tempfile::TempDir;
+
+    /// Fake mpv: answers `get_property` from `props` (missing = error) and
+    /// emits an unrelated event before every reply.
+    fn spawn_fake_mpv(socket: &Path, props: Value) {
+        let listener = UnixListener::bind(socket).unwrap();
+        std::thread::spawn(move || {
+            let Ok((stream, _)) = listener.accept() else {
+                return;
+            };
+            let mut writer = stream.try_clone().unwrap();
+            for line in BufReader::new(stream).lines() {
+                let Ok(line) = line else { break };
+                let req: Value = serde_json::from_str(&line).unwrap();
+                let name = req["command"][1].as_str().unwrap();
+                let id = &req["request_id"];
+                let reply = match props.get(name) {
+                    Some(data) => {
+                        serde_json::json!({"data": data, "error": "success", "request_id": id})
+                    }
+                    None => serde_json::json!({"error": "property unavailable", "request_id": id}),
+                };
+                writeln!(writer, "{{\"event\":\"audio-reconfig\"}}").unwrap();
+                writeln!(writer, "{reply}").unwrap();
+            }
+        });
+    }
+
+    #[test]
+    fn test_query_fake_socket() {
+        let dir = TempDir::new().unwrap();
+        let socket = dir.path().join("mpvsocket-1");
+        spawn_fake_mpv(
+            &socket,
+            serde_json::json!({
+                "path": "Frieren/[SubsPlease] Sousou no Frieren - 05 (1080p).mkv",
+                "media-title": "[SubsPlease] Sousou no Frieren - 05 (1080p).mkv",
+                "time-pos": 312.5,
+                "duration": 1420.0,
+                "pause": true,
+                "working-directory": "/home/user/Anime",
+            }),
+        );
+
+        let pattern = dir.path().join("mpvsocket*").to_string_lossy().into_owned();
+        let players = detect_mpv_ipc(&PlayerDatabase::embedded(), &[pattern]);
+        assert_eq!(players.len(), 1);
+        let p = &players[0];
+        assert_eq!(p.player_name, "mpv");
+        assert_eq!(
+            p.file_path.as_deref(),
+            Some("/home/user/Anime/Frieren/[SubsPlease] Sousou no Frieren - 05 (1080p).mkv")
+        );
+        assert_eq!(p.position, Some(Duration::from_secs_f64(312.5)));
+        assert_eq!(p.duration, Some(Duration::from_secs(1420)));
+        assert_eq!(p.playback_state, Some(PlaybackState::Paused));
+    }
+
+    #[test]
+    fn test_idle_mpv_skipped() {
+        let dir = TempDir::new().unwrap();
+        let socket = dir.path().join("mpvsocket");
+        spawn_fake_mpv(&socket, serde_json::json!({ "pause": false }));
+
+        let pattern = socket.to_string_lossy().into_owned();
+        assert!(detect_mpv_ipc(&PlayerDatabase::embedded(), &[pattern]).is_empty());
+    }
+
+    #[test]
+    fn test_missing_socket_skipped() {
+        let dir = TempDir::new().unwrap();
+        let pattern = dir.path().join("nope").to_string_lossy().into_owned();
+        assert!(detect_mpv_ipc(&PlayerDatabase::embedded(), &[pattern]).is_empty());
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("mpvsocket*", "mpvsocket"));
+        assert!(wildcard_match("mpvsocket*", "mpvsocket-1234"));
+        assert!(wildcard_match("mpv*.sock", "mpv-1.sock"));
+        assert!(wildcard_match("*", "anything"));
+        assert!(!wildcard_match("mpv*.sock", "mpv-1.socket"));
+        assert!(!wildcard_match("mpvsocket", "mpvsocket2"));
+    }
+}
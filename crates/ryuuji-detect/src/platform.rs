@@ -1,46 +1,150 @@
+#[cfg(target_os = "linux")]
+pub mod firefox;
+
 #[cfg(target_os = "linux")]
 pub mod linux;
 
+#[cfg(target_os = "linux")]
+pub mod mpris_watch;
+
+#[cfg(target_os = "linux")]
+pub mod procfs;
+
 #[cfg(target_os = "windows")]
 pub mod windows;
 
 #[cfg(target_os = "windows")]
 pub mod windows_smtc;
 
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::detector::Detector;
+#[cfg(target_os = "linux")]
+use crate::detector::{MprisDetector, ProcfsDetector};
+#[cfg(target_os = "windows")]
+use crate::detector::{SmtcDetector, WindowTitleDetector};
 use crate::player_db::PlayerDatabase;
-use crate::PlayerInfo;
+use crate::{DetectOptions, PlayerInfo};
 
-/// Platform-specific detection dispatcher.
-pub fn detect(db: &PlayerDatabase) -> Vec<PlayerInfo> {
+/// The platform's own backends: MPRIS with a /proc fallback on Linux, SMTC
+/// with a window-title fallback on Windows, nothing elsewhere.
+pub(crate) struct NativeDetector {
     #[cfg(target_os = "linux")]
-    {
-        linux::detect_mpris(db)
-    }
+    mpris: MprisDetector,
+    #[cfg(target_os = "linux")]
+    procfs: ProcfsDetector,
+    #[cfg(target_os = "windows")]
+    smtc: SmtcDetector,
     #[cfg(target_os = "windows")]
-    {
-        detect_windows_combined(db)
+    windows: WindowTitleDetector,
+}
+
+impl NativeDetector {
+    pub(crate) fn new(players: Arc<PlayerDatabase>, options: &DetectOptions) -> Self {
+        #[cfg(target_os = "linux")]
+        {
+            let mut mpris = MprisDetector::new(players.clone());
+            if let Some(cache) = &options.mpris_cache {
+                mpris = mpris.with_cache(cache.clone());
+            }
+            Self {
+                mpris,
+                procfs: ProcfsDetector::new(players),
+            }
+        }
+        #[cfg(target_os = "windows")]
+        {
+            let _ = options;
+            Self {
+                smtc: SmtcDetector::new(players.clone()),
+                windows: WindowTitleDetector::new(players),
+            }
+        }
+        #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+        {
+            let _ = (players, options);
+            Self {}
+        }
     }
-    #[cfg(not(any(target_os = "linux", target_os = "windows")))]
-    {
-        let _ = db;
-        vec![]
+}
+
+impl Detector for NativeDetector {
+    fn detect(&mut self) -> Vec<PlayerInfo> {
+        #[cfg(target_os = "linux")]
+        {
+            fill_from_procfs(self.mpris.detect(), self.procfs.detect())
+        }
+        #[cfg(target_os = "windows")]
+        {
+            fill_from_windows(self.smtc.detect(), self.windows.detect())
+        }
+        #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+        {
+            vec![]
+        }
     }
 }
 
+/// Merge results from the optional backends into the native ones.
+///
+/// A player reachable both ways (mpv with the mpris plugin and an IPC
+/// socket, a local VLC with its HTTP interface on) shows up twice; the
+/// backend entry is kept because it reports position and state reliably.
+pub(crate) fn merge(native: Vec<PlayerInfo>, extra: Vec<PlayerInfo>) -> Vec<PlayerInfo> {
+    let mut merged: Vec<PlayerInfo> = native
+        .into_iter()
+        .filter(|n| !extra.iter().any(|e| same_media(e, n)))
+        .collect();
+    merged.extend(extra);
+    merged
+}
+
+fn same_media(a: &PlayerInfo, b: &PlayerInfo) -> bool {
+    a.player_name == b.player_name && file_name(a).is_some() && file_name(a) == file_name(b)
+}
+
+/// Add the players only /proc can see to the MPRIS ones.
+///
+/// A /proc entry is the same player as an MPRIS entry with its name that
+/// plays the same file, or that reports no file to tell them apart by. A
+/// second instance playing something else is kept.
+#[cfg(any(target_os = "linux", test))]
+fn fill_from_procfs(mut mpris: Vec<PlayerInfo>, procfs: Vec<PlayerInfo>) -> Vec<PlayerInfo> {
+    let fallback: Vec<_> = procfs
+        .into_iter()
+        .filter(|p| {
+            !mpris.iter().any(|m| {
+                m.player_name == p.player_name
+                    && (file_name(m).is_none() || file_name(m) == file_name(p))
+            })
+        })
+        .collect();
+    mpris.extend(fallback);
+    mpris
+}
+
+fn file_name(p: &PlayerInfo) -> Option<std::ffi::OsString> {
+    p.file_path
+        .as_deref()
+        .and_then(|f| Path::new(f).file_name())
+        .map(|n| n.to_os_string())
+}
+
 /// SMTC-first detection with EnumWindows fallback.
 ///
-/// Runs SMTC detection first (structured metadata), then fills in any players
-/// that only EnumWindows can see. Deduplicates by player name so each player
-/// appears at most once, with the SMTC result winning ties.
+/// SMTC results (structured metadata) come first, then any players that only
+/// EnumWindows can see. Deduplicates by player name so each player appears
+/// at most once, with the SMTC result winning ties.
 #[cfg(target_os = "windows")]
-fn detect_windows_combined(db: &PlayerDatabase) -> Vec<PlayerInfo> {
+fn fill_from_windows(
+    mut results: Vec<PlayerInfo>,
+    win32_results: Vec<PlayerInfo>,
+) -> Vec<PlayerInfo> {
     use std::collections::HashSet;
 
-    let mut results = windows_smtc::detect_smtc(db);
-
     let smtc_names: HashSet<String> = results.iter().map(|p| p.player_name.clone()).collect();
 
-    let win32_results = windows::detect_windows(db);
     results.extend(
         win32_results
             .into_iter()
@@ -49,3 +153,72 @@ fn detect_windows_combined(db: &PlayerDatabase) -> Vec<PlayerInfo> {
 
     results
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PlaybackState;
+
+    #[test]
+    fn test_merge_prefers_backend_entry() {
+        let native = vec![
+            PlayerInfo {
+                player_name: "mpv".into(),
+                file_path: Some("/anime/Frieren - 05.mkv".into()),
+                ..Default::default()
+            },
+            PlayerInfo {
+                player_name: "VLC".into(),
+                file_path: Some("/anime/Frieren - 05.mkv".into()),
+                ..Default::default()
+            },
+        ];
+        let extra = vec![PlayerInfo {
+            player_name: "mpv".into(),
+            file_path: Some("Frieren - 05.mkv".into()),
+            playback_state: Some(PlaybackState::Playing),
+            ..Default::default()
+        }];
+
+        let merged = merge(native, extra);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].player_name, "VLC");
+        assert_eq!(merged[1].playback_state, Some(PlaybackState::Playing));
+    }
+
+    #[test]
+    fn test_procfs_keeps_other_instances() {
+        let player = |name: &str, file: Option<&str>| PlayerInfo {
+            player_name: name.into(),
+            file_path: file.map(Into::into),
+            ..Default::default()
+        };
+        let mpris = vec![player("mpv", Some("/anime/Frieren - 05.mkv"))];
+        let procfs = vec![
+            player("mpv", Some("/anime/Frieren - 05.mkv")),
+            player("mpv", Some("/anime/Dandadan - 02.mkv")),
+            player("VLC", Some("/anime/Frieren - 05.mkv")),
+        ];
+
+        let players = fill_from_procfs(mpris, procfs);
+        let files: Vec<_> = players
+            .iter()
+            .map(|p| (p.player_name.as_str(), p.file_path.as_deref().unwrap()))
+            .collect();
+        assert_eq!(
+            files,
+            [
+                ("mpv", "/anime/Frieren - 05.mkv"),
+                ("mpv", "/anime/Dandadan - 02.mkv"),
+                ("VLC", "/anime/Frieren - 05.mkv"),
+            ]
+        );
+
+        // Without a file from MPRIS there is no telling instances apart.
+        let players = fill_from_procfs(
+            vec![player("mpv", None)],
+            vec![player("mpv", Some("/anime/Frieren - 05.mkv"))],
+        );
+        assert_eq!(players.len(), 1);
+    }
+}
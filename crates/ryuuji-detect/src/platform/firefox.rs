@@ -0,0 +1,481 @@
+//! Firefox session-file detection (Linux).
+//!
+//! Firefox often registers with MPRIS without a usable title or URL, which
+//! leaves stream detection nothing to match. Its session store, however,
+//! records every window's selected tab. This backend reads
+//! `sessionstore-backups/recovery.jsonlz4` from the running profile and
+//! uses the tabs that are on a known streaming service to name what a
+//! playing Firefox shows.
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use serde::Deserialize;
+use tracing::debug;
+
+use crate::player_db::PlayerDatabase;
+use crate::stream::{self, StreamDatabase};
+use crate::{PlaybackState, PlayerInfo};
+
+/// Header of Mozilla's LZ4 container: magic, then the decompressed size.
+const MOZLZ4_MAGIC: &[u8] = b"mozLz40\0";
+
+/// Largest decompressed session accepted. Real ones are a few MiB; the
+/// header's size is untrusted and could otherwise ask for 4 GiB.
+const MAX_SESSION_SIZE: usize = 64 * 1024 * 1024;
+
+/// Profile directories of the native, snap and flatpak builds, relative to `$HOME`.
+const PROFILE_ROOTS: &[&str] = &[
+    ".mozilla/firefox",
+    "snap/firefox/common/.mozilla/firefox",
+    ".var/app/org.mozilla.firefox/.mozilla/firefox",
+];
+
+/// Decoded tabs of the last session file read, keyed by path and mtime.
+/// Firefox rewrites the file at most every 15 seconds; decoding it on every
+/// detection tick would be wasted work.
+static CACHE: Mutex<Option<(PathBuf, SystemTime, Vec<SessionTab>)>> = Mutex::new(None);
+
+/// The selected tab of one browser window.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionTab {
+    pub url: String,
+    pub title: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Session {
+    #[serde(default)]
+    windows: Vec<Window>,
+}
+
+#[derive(Deserialize)]
+struct Window {
+    #[serde(default)]
+    tabs: Vec<Tab>,
+    /// 1-based index into `tabs`.
+    #[serde(default)]
+    selected: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct Tab {
+    /// Back/forward history; `index` (1-based) is the page being shown.
+    #[serde(default)]
+    entries: Vec<Entry>,
+    #[serde(default)]
+    index: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct Entry {
+    url: String,
+    #[serde(default)]
+    title: Option<String>,
+}
+
+/// Report the selected tab of every Firefox window that is on a streaming
+/// service known to `streams`.
+pub fn detect_firefox(db: &PlayerDatabase, streams: &StreamDatabase) -> Vec<PlayerInfo> {
+    let Some(home) = std::env::var_os("HOME").map(PathBuf::from) else {
+        return vec![];
+    };
+    let roots: Vec<PathBuf> = PROFILE_ROOTS.iter().map(|r| home.join(r)).collect();
+    let Some(path) = find_session_file(&roots) else {
+        return vec![];
+    };
+    let tabs = match cached_session(&path) {
+        Ok(tabs) => tabs,
+        Err(e) => {
+            debug!(path = %path.display(), "Failed to read Firefox session: {e}");
+            return vec![];
+        }
+    };
+    session_players(db, streams, tabs)
+}
+
+/// Turn session tabs into browser players, keeping only streaming tabs.
+fn session_players(
+    db: &PlayerDatabase,
+    streams: &StreamDatabase,
+    tabs: Vec<SessionTab>,
+) -> Vec<PlayerInfo> {
+    let player_name = db
+        .find_by_executable("firefox")
+        .map(|p| p.name.clone())
+        .unwrap_or_else(|| "Firefox".into());
+    tabs.into_iter()
+        .filter(|tab| {
+            streams.match_url(&tab.url).is_some()
+                || tab
+                    .title
+                    .as_deref()
+                    .is_some_and(|t| streams.match_title(t).is_some())
+        })
+        .map(|tab| PlayerInfo {
+            player_name: player_name.clone(),
+            media_title: tab.title,
+            file_path: Some(tab.url),
+            is_browser: true,
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// Combine session tabs with what the native backend saw.
+///
+/// A selected tab only says a page is open, not that anything is playing,
+/// so tabs never become players of their own. If the browser already
+/// reported a stream MPRIS could identify, the tabs add nothing. Otherwise
+/// they fill in the title and URL of the entries MPRIS reports as playing.
+pub(crate) fn apply_session_tabs(
+    players: &mut [PlayerInfo],
+    tabs: Vec<PlayerInfo>,
+    streams: &StreamDatabase,
+) {
+    let Some(name) = tabs.first().map(|t| t.player_name.clone()) else {
+        return;
+    };
+    let native: Vec<usize> = players
+        .iter()
+        .enumerate()
+        .filter(|(_, p)| p.is_browser && p.player_name == name)
+        .map(|(i, _)| i)
+        .collect();
+    if native
+        .iter()
+        .any(|&i| stream::detect_stream(&players[i], streams).is_some())
+    {
+        return;
+    }
+    let playing: Vec<usize> = native
+        .into_iter()
+        .filter(|&i| players[i].playback_state == Some(PlaybackState::Playing))
+        .collect();
+    for (i, tab) in playing.into_iter().zip(tabs) {
+        players[i].media_title = tab.media_title;
+        players[i].file_path = tab.file_path;
+    }
+}
+
+/// The newest `recovery.jsonlz4` among profiles that are currently in use.
+///
+/// A running Firefox holds a `lock` symlink in its profile directory; the
+/// session file of a closed profile is stale and ignored.
+fn find_session_file(roots: &[PathBuf]) -> Option<PathBuf> {
+    roots
+        .iter()
+        .filter_map(|root| std::fs::read_dir(root).ok())
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|profile| std::fs::symlink_metadata(profile.join("lock")).is_ok())
+        .map(|profile| profile.join("sessionstore-backups/recovery.jsonlz4"))
+        .filter_map(|file| {
+            let modified = std::fs::metadata(&file).and_then(|m| m.modified()).ok()?;
+            Some((modified, file))
+        })
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, file)| file)
+}
+
+/// Read `path`, reusing the previous result if the file hasn't changed.
+fn cached_session(path: &Path) -> io::Result<Vec<SessionTab>> {
+    let modified = std::fs::metadata(path)?.modified()?;
+    let mut cache = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((cached_path, cached_time, tabs)) = cache.as_ref() {
+        if cached_path == path && *cached_time == modified {
+            return Ok(tabs.clone());
+        }
+    }
+    let tabs = read_session(path)?;
+    *cache = Some((path.to_path_buf(), modified, tabs.clone()));
+    Ok(tabs)
+}
+
+/// Read a `.jsonlz4` session file and return each window's selected tab.
+pub fn read_session(path: &Path) -> io::Result<Vec<SessionTab>> {
+    let json = decode_mozlz4(&std::fs::read(path)?)?;
+    let session: Session =
+        serde_json::from_slice(&json).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    Ok(session
+        .windows
+        .into_iter()
+        .filter_map(|window| {
+            let tab_index = window.selected.unwrap_or(1).checked_sub(1)?;
+            let mut tab = window.tabs.into_iter().nth(tab_index)?;
+            let entry_index = tab.index.unwrap_or(tab.entries.len()).checked_sub(1)?;
+            if entry_index >= tab.entries.len() {
+                return None;
+            }
+            let entry = tab.entries.swap_remove(entry_index);
+            Some(SessionTab {
+                url: entry.url,
+                title: entry.title.filter(|t| !t.is_empty()),
+            })
+        })
+        .collect())
+}
+
+/// Decode Mozilla's `mozLz40` container: an 8-byte magic, the decompressed
+/// size as a little-endian `u32`, then one raw LZ4 block.
+pub fn decode_mozlz4(data: &[u8]) -> io::Result<Vec<u8>> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+    let rest = data
+        .strip_prefix(MOZLZ4_MAGIC)
+        .ok_or_else(|| invalid("not a mozLz4 file"))?;
+    if rest.len() < 4 {
+        return Err(invalid("truncated mozLz4 header"));
+    }
+    let size = u32::from_le_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+    if size > MAX_SESSION_SIZE {
+        return Err(invalid("mozLz4 file is too large"));
+    }
+    let out = lz4_block_decompress(&rest[4..], size).ok_or_else(|| invalid("corrupt LZ4 block"))?;
+    if out.len() != size {
+        return Err(invalid("LZ4 block has the wrong decompressed size"));
+    }
+    Ok(out)
+}
+
+/// Decompress a raw LZ4 block (no frame header). Returns `None` on malformed
+/// input or if the output would exceed `max_size`.
+fn lz4_block_decompress(src: &[u8], max_size: usize) -> Option<Vec<u8>> {
+    // Lengths of 15 in a token continue in following bytes, each adding up
+    // to 255 until one is smaller.
+    fn read_length(src: &[u8], pos: &mut usize, mut len: usize) -> Option<usize> {
+        if len == 15 {
+            loop {
+                let byte = *src.get(*pos)?;
+                *pos += 1;
+                len += byte as usize;
+                if byte != 255 {
+                    break;
+                }
+            }
+        }
+        Some(len)
+    }
+
+    let mut out = Vec::with_capacity(max_size);
+    let mut pos = 0;
+    loop {
+        let token = *src.get(pos)?;
+        pos += 1;
+
+        let literals = read_length(src, &mut pos, (token >> 4) as usize)?;
+        let end = pos.checked_add(literals)?;
+        if out.len() + literals > max_size {
+            return None;
+        }
+        out.extend_from_slice(src.get(pos..end)?);
+        pos = end;
+        if pos == src.len() {
+            // The last sequence is literals only.
+            break;
+        }
+
+        let offset = u16::from_le_bytes([*src.get(pos)?, *src.get(pos + 1)?]) as usize;
+        pos += 2;
+        if offset == 0 || offset > out.len() {
+            return None;
+        }
+        let match_len = read_length(src, &mut pos, (token & 0x0f) as usize)? + 4;
+        if out.len() + match_len > max_size {
+            return None;
+        }
+        // Matches may overlap their own output, so copy byte by byte.
+        let start = out.len() - offset;
+        for i in 0..match_len {
+            out.push(out[start + i]);
+        }
+    }
+    (out.len() <= max_size).then_some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two windows: Crunchyroll selected in the first (after navigating from
+    /// the series page), GitHub in the second.
+    const SESSION: &[u8] = include_bytes!("../../tests/fixtures/firefox/recovery.jsonlz4");
+    /// A single window whose only tab is on a Netflix watch page.
+    const SESSION_NETFLIX: &[u8] =
+        include_bytes!("../../tests/fixtures/firefox/recovery-netflix.jsonlz4");
+
+    fn write_profile(root: &Path, name: &str, session: &[u8], locked: bool) -> PathBuf {
+        let profile = root.join(name);
+        std::fs::create_dir_all(profile.join("sessionstore-backups")).unwrap();
+        let file = profile.join("sessionstore-backups/recovery.jsonlz4");
+        std::fs::write(&file, session).unwrap();
+        if locked {
+            std::os::unix::fs::symlink("127.0.1.1:+4242", profile.join("lock")).unwrap();
+        }
+        file
+    }
+
+    #[test]
+    fn test_decode_mozlz4() {
+        let json = decode_mozlz4(SESSION).unwrap();
+        assert!(json.starts_with(b"{\"version\""));
+        assert!(decode_mozlz4(b"not mozlz4").is_err());
+
+        // Literal-only block: token 0x50 = five literals, no match.
+        let mut data = MOZLZ4_MAGIC.to_vec();
+        data.extend_from_slice(&5u32.to_le_bytes());
+        data.extend_from_slice(b"\x50hello");
+        assert_eq!(decode_mozlz4(&data).unwrap(), b"hello");
+
+        // Overlapping match: "ab" then copy 6 bytes from offset 2.
+        let mut data = MOZLZ4_MAGIC.to_vec();
+        data.extend_from_slice(&9u32.to_le_bytes());
+        data.extend_from_slice(b"\x22ab\x02\x00\x10!");
+        assert_eq!(decode_mozlz4(&data).unwrap(), b"abababab!");
+
+        // Offset pointing before the start of the output.
+        let mut data = MOZLZ4_MAGIC.to_vec();
+        data.extend_from_slice(&9u32.to_le_bytes());
+        data.extend_from_slice(b"\x22ab\x09\x00\x10!");
+        assert!(decode_mozlz4(&data).is_err());
+
+        // More literals than the header announced.
+        let mut data = MOZLZ4_MAGIC.to_vec();
+        data.extend_from_slice(&3u32.to_le_bytes());
+        data.extend_from_slice(b"\x50hello");
+        assert!(decode_mozlz4(&data).is_err());
+    }
+
+    #[test]
+    fn test_decode_mozlz4_rejects_huge_size() {
+        let mut data = MOZLZ4_MAGIC.to_vec();
+        data.extend_from_slice(&u32::MAX.to_le_bytes());
+        data.extend_from_slice(b"\x50hello");
+        let err = decode_mozlz4(&data).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), "mozLz4 file is too large");
+    }
+
+    #[test]
+    fn test_read_session_selected_tabs() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = write_profile(dir.path(), "abcd.default-release", SESSION, true);
+        let tabs = read_session(&file).unwrap();
+        assert_eq!(
+            tabs,
+            vec![
+                SessionTab {
+                    url: "https://www.crunchyroll.com/watch/GG1U2J1M7/episode-7".into(),
+                    title: Some("Frieren: Beyond Journey's End - Watch on Crunchyroll".into()),
+                },
+                SessionTab {
+                    url: "https://github.com/".into(),
+                    title: Some("GitHub".into()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_session_file_needs_running_profile() {
+        let dir = tempfile::tempdir().unwrap();
+        let roots = vec![dir.path().to_path_buf()];
+        write_profile(dir.path(), "old.default", SESSION_NETFLIX, false);
+        assert_eq!(find_session_file(&roots), None);
+
+        let running = write_profile(dir.path(), "abcd.default-release", SESSION, true);
+        assert_eq!(find_session_file(&roots), Some(running));
+    }
+
+    #[test]
+    fn test_session_players_filters_streams() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = write_profile(dir.path(), "p", SESSION, true);
+        let db = PlayerDatabase::embedded();
+        let streams = StreamDatabase::embedded();
+        let players = session_players(&db, &streams, read_session(&file).unwrap());
+        assert_eq!(players.len(), 1);
+        assert_eq!(players[0].player_name, "Firefox");
+        assert!(players[0].is_browser);
+
+        let m = stream::detect_stream(&players[0], &streams).unwrap();
+        assert_eq!(m.service_name, "Crunchyroll");
+        assert_eq!(m.extracted_title, "Frieren: Beyond Journey's End");
+        assert_eq!(m.episode, Some(7));
+    }
+
+    #[test]
+    fn test_apply_session_tabs() {
+        let streams = StreamDatabase::embedded();
+        let tab = PlayerInfo {
+            player_name: "Firefox".into(),
+            media_title: Some("Frieren: Beyond Journey's End - Watch on Crunchyroll".into()),
+            file_path: Some("https://www.crunchyroll.com/watch/GG1U2J1M7/episode-7".into()),
+            is_browser: true,
+            ..Default::default()
+        };
+
+        // Seen without a usable title: filled in, playback state kept.
+        let mut players = vec![PlayerInfo {
+            player_name: "Firefox".into(),
+            media_title: Some("Firefox".into()),
+            is_browser: true,
+            playback_state: Some(PlaybackState::Playing),
+            ..Default::default()
+        }];
+        apply_session_tabs(&mut players, vec![tab.clone()], &streams);
+        assert_eq!(players.len(), 1);
+        assert_eq!(players[0].file_path, tab.file_path);
+        assert_eq!(players[0].playback_state, Some(PlaybackState::Playing));
+
+        // Already identified through MPRIS: left alone.
+        let mut players = vec![PlayerInfo {
+            media_title: Some("One Piece | Netflix".into()),
+            file_path: None,
+            ..players[0].clone()
+        }];
+        apply_session_tabs(&mut players, vec![tab], &streams);
+        assert_eq!(
+            players[0].media_title.as_deref(),
+            Some("One Piece | Netflix")
+        );
+    }
+
+    #[test]
+    fn test_idle_tab_is_never_watched() {
+        let streams = StreamDatabase::embedded();
+        let tab = PlayerInfo {
+            player_name: "Firefox".into(),
+            media_title: Some("Frieren: Beyond Journey's End - Watch on Crunchyroll".into()),
+            file_path: Some("https://www.crunchyroll.com/watch/GG1U2J1M7/episode-7".into()),
+            is_browser: true,
+            ..Default::default()
+        };
+
+        // An open tab in a browser MPRIS doesn't see is not a player, so it
+        // is never detected, let alone counted as watched.
+        let mut players = vec![];
+        apply_session_tabs(&mut players, vec![tab.clone()], &streams);
+        assert!(players.is_empty());
+
+        // Nor does it give a paused or stopped browser something to track.
+        for state in [
+            None,
+            Some(PlaybackState::Paused),
+            Some(PlaybackState::Stopped),
+        ] {
+            let mut players = vec![PlayerInfo {
+                player_name: "Firefox".into(),
+                media_title: Some("Firefox".into()),
+                is_browser: true,
+                playback_state: state,
+                ..Default::default()
+            }];
+            apply_session_tabs(&mut players, vec![tab.clone()], &streams);
+            assert_eq!(players[0].file_path, None);
+            assert!(stream::detect_stream(&players[0], &streams).is_none());
+        }
+    }
+}
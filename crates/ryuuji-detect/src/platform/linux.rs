@@ -1,6 +1,6 @@
 use crate::player_db::PlayerDatabase;
-use crate::PlayerInfo;
-use mpris::PlayerFinder;
+use crate::{PlaybackState, PlayerInfo};
+use mpris::{PlaybackStatus, PlayerFinder};
 use tracing::{debug, warn};
 
 /// Detect media players via MPRIS D-Bus on Linux.
@@ -32,12 +32,20 @@ pub fn detect_mpris(db: &PlayerDatabase) -> Vec<PlayerInfo> {
             let media_title = metadata.title().map(|s| s.to_string());
             let file_path = metadata.url().and_then(|url| {
                 if url.starts_with("file://") {
-                    urlencoding_decode(url.strip_prefix("file://").unwrap_or(url))
+                    crate::percent_decode(url.strip_prefix("file://").unwrap_or(url))
                 } else {
                     Some(url.to_string())
                 }
             });
 
+            let duration = metadata.length();
+            let position = player.get_position().ok();
+            let playback_state = player.get_playback_status().ok().map(|s| match s {
+                PlaybackStatus::Playing => PlaybackState::Playing,
+                PlaybackStatus::Paused => PlaybackState::Paused,
+                PlaybackStatus::Stopped => PlaybackState::Stopped,
+            });
+
             // Resolve the player name using the database.
             let player_def = db.find_by_mpris(&identity);
             let player_name = player_def
@@ -45,35 +53,25 @@ pub fn detect_mpris(db: &PlayerDatabase) -> Vec<PlayerInfo> {
                 .unwrap_or_else(|| identity.clone());
             let is_browser = player_def.map(|p| p.is_browser).unwrap_or(false);
 
-            debug!(player = %identity, resolved = %player_name, title = ?media_title, "Detected MPRIS player");
+            debug!(
+                player = %identity,
+                resolved = %player_name,
+                title = ?media_title,
+                state = ?playback_state,
+                position = ?position,
+                "Detected MPRIS player"
+            );
 
             Some(PlayerInfo {
                 player_name,
                 media_title,
                 file_path,
                 is_browser,
+                position,
+                duration,
+                playback_state,
+                episode_info: None,
             })
         })
         .collect()
 }
-
-/// Simple percent-decoding for file paths.
-fn urlencoding_decode(s: &str) -> Option<String> {
-    let mut result = Vec::new();
-    let bytes = s.as_bytes();
-    let mut i = 0;
-    while i < bytes.len() {
-        if bytes[i] == b'%' && i + 2 < bytes.len() {
-            if let Ok(byte) =
-                u8::from_str_radix(std::str::from_utf8(&bytes[i + 1..i + 3]).ok()?, 16)
-            {
-                result.push(byte);
-                i += 3;
-                continue;
-            }
-        }
-        result.push(bytes[i]);
-        i += 1;
-    }
-    String::from_utf8(result).ok()
-}
@@ -0,0 +1,454 @@
+//! Long-lived MPRIS watcher.
+//!
+//! Instead of re-querying every player on a timer, a dedicated thread keeps a
+//! session-bus connection open and listens for `NameOwnerChanged` (players
+//! appearing or quitting) and `PropertiesChanged` on the player interface
+//! (track or playback-state changes). Each change is forwarded as an
+//! [`MprisEvent`]; callers re-run detection when one arrives.
+//!
+//! While a watcher runs, [`MprisCache`] answers detection ticks from the
+//! last query instead of asking every player again.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use dbus::blocking::stdintf::org_freedesktop_dbus::PropertiesPropertiesChanged;
+use dbus::blocking::Connection;
+use dbus::message::MatchRule;
+use tracing::{debug, warn};
+
+use crate::player_db::PlayerDatabase;
+use crate::{PlaybackState, PlayerInfo};
+
+const MPRIS_PREFIX: &str = "org.mpris.MediaPlayer2.";
+const MPRIS_PATH: &str = "/org/mpris/MediaPlayer2";
+const PLAYER_INTERFACE: &str = "org.mpris.MediaPlayer2.Player";
+
+/// Properties whose change means "something else is playing now".
+/// `Position` is deliberately absent: players don't signal it, and
+/// `Volume`/`Rate` changes don't affect what's detected.
+const WATCHED_PROPERTIES: &[&str] = &["Metadata", "PlaybackStatus"];
+
+/// How long the bus thread blocks per iteration before checking for shutdown.
+const PROCESS_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// How long a cached query is trusted even without events, in case a
+/// player misses a signal.
+const RESYNC_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A change reported by an MPRIS player. Each carries the player's bus name
+/// (e.g. `org.mpris.MediaPlayer2.vlc`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MprisEvent {
+    /// A player connected to the session bus.
+    PlayerAppeared(String),
+    /// A player disconnected from the session bus.
+    PlayerVanished(String),
+    /// A player's metadata or playback status changed, or it seeked.
+    Changed(String),
+}
+
+impl MprisEvent {
+    /// Bus name of the player the event is about.
+    pub fn bus_name(&self) -> &str {
+        match self {
+            Self::PlayerAppeared(name) | Self::PlayerVanished(name) | Self::Changed(name) => name,
+        }
+    }
+}
+
+/// Handle to the watcher thread. Events are read with [`recv`](Self::recv)
+/// or [`recv_timeout`](Self::recv_timeout); dropping the handle stops the
+/// thread.
+pub struct MprisWatcher {
+    events: Receiver<MprisEvent>,
+    stop: Arc<AtomicBool>,
+}
+
+impl MprisWatcher {
+    /// Connect to the session bus and start watching in a background thread.
+    ///
+    /// Fails if the session bus is unreachable or the match rules are refused.
+    pub fn spawn() -> Result<Self, dbus::Error> {
+        let (tx, events) = mpsc::channel();
+        let (ready_tx, ready_rx) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+
+        thread::Builder::new()
+            .name("mpris-watch".into())
+            .spawn(move || {
+                let conn = match subscribe(tx, thread_stop.clone()) {
+                    Ok(conn) => {
+                        let _ = ready_tx.send(Ok(()));
+                        conn
+                    }
+                    Err(e) => {
+                        let _ = ready_tx.send(Err(e));
+                        return;
+                    }
+                };
+                while !thread_stop.load(Ordering::Relaxed) {
+                    if let Err(e) = conn.process(PROCESS_TIMEOUT) {
+                        warn!("MPRIS watcher lost the session bus: {e}");
+                        break;
+                    }
+                }
+                debug!("MPRIS watcher stopped");
+            })
+            .map_err(|e| dbus::Error::new_failed(&e.to_string()))?;
+
+        ready_rx
+            .recv()
+            .unwrap_or_else(|_| Err(dbus::Error::new_failed("MPRIS watcher thread exited")))?;
+        Ok(Self { events, stop })
+    }
+
+    /// Block until the next event. Returns `None` once the watcher has stopped.
+    pub fn recv(&self) -> Option<MprisEvent> {
+        self.events.recv().ok()
+    }
+
+    /// Wait up to `timeout` for the next event.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<MprisEvent, RecvTimeoutError> {
+        self.events.recv_timeout(timeout)
+    }
+}
+
+impl Drop for MprisWatcher {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Open the bus connection and register both signal matches.
+fn subscribe(tx: Sender<MprisEvent>, stop: Arc<AtomicBool>) -> Result<Connection, dbus::Error> {
+    let conn = Connection::new_session()?;
+
+    // PropertiesChanged and Seeked arrive from the player's unique name
+    // (":1.42"), so keep a map back to its well-known MPRIS name.
+    let owners = Arc::new(Mutex::new(current_owners(&conn)));
+
+    let owner_tx = tx.clone();
+    let owner_stop = stop.clone();
+    let owner_map = owners.clone();
+    conn.add_match(
+        MatchRule::new_signal("org.freedesktop.DBus", "NameOwnerChanged"),
+        move |(name, old_owner, new_owner): (String, String, String), _, _| {
+            let Some(event) = owner_change_event(&name, &new_owner) else {
+                return true;
+            };
+            let mut owners = owner_map.lock().unwrap_or_else(|e| e.into_inner());
+            owners.remove(&old_owner);
+            if !new_owner.is_empty() {
+                owners.insert(new_owner, name);
+            }
+            forward(&owner_tx, &owner_stop, event)
+        },
+    )?;
+
+    let props_tx = tx.clone();
+    let props_stop = stop.clone();
+    let props_owners = owners.clone();
+    conn.add_match(
+        MatchRule::new_signal("org.freedesktop.DBus.Properties", "PropertiesChanged")
+            .with_path(MPRIS_PATH),
+        move |signal: PropertiesPropertiesChanged, _, msg| {
+            let changed = signal.changed_properties.keys().map(String::as_str);
+            let invalidated = signal.invalidated_properties.iter().map(String::as_str);
+            if !is_relevant_change(&signal.interface_name, changed.chain(invalidated)) {
+                return true;
+            }
+            let name = player_name(&props_owners, msg);
+            forward(&props_tx, &props_stop, MprisEvent::Changed(name))
+        },
+    )?;
+
+    // Position is never signalled as a property change, only jumps are.
+    conn.add_match(
+        MatchRule::new_signal(PLAYER_INTERFACE, "Seeked").with_path(MPRIS_PATH),
+        move |(_position,): (i64,), _, msg| {
+            let name = player_name(&owners, msg);
+            forward(&tx, &stop, MprisEvent::Changed(name))
+        },
+    )?;
+
+    Ok(conn)
+}
+
+/// Well-known MPRIS name of a signal's sender, or its unique name if unknown.
+fn player_name(owners: &Mutex<HashMap<String, String>>, msg: &dbus::Message) -> String {
+    let sender = msg.sender().map(|s| s.to_string()).unwrap_or_default();
+    owners
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&sender)
+        .cloned()
+        .unwrap_or(sender)
+}
+
+/// Send an event, stopping the watcher once nobody is listening.
+fn forward(tx: &Sender<MprisEvent>, stop: &AtomicBool, event: MprisEvent) -> bool {
+    debug!(?event, "MPRIS event");
+    if tx.send(event).is_err() {
+        stop.store(true, Ordering::Relaxed);
+        return false;
+    }
+    true
+}
+
+/// Map the unique names of already-running players to their MPRIS names.
+fn current_owners(conn: &Connection) -> HashMap<String, String> {
+    let proxy = conn.with_proxy(
+        "org.freedesktop.DBus",
+        "/org/freedesktop/DBus",
+        Duration::from_secs(2),
+    );
+    let names: Vec<String> = match proxy.method_call("org.freedesktop.DBus", "ListNames", ()) {
+        Ok((names,)) => names,
+        Err(e) => {
+            debug!("ListNames failed: {e}");
+            return HashMap::new();
+        }
+    };
+    names
+        .into_iter()
+        .filter(|name| name.starts_with(MPRIS_PREFIX))
+        .filter_map(|name| {
+            let (owner,): (String,) = proxy
+                .method_call("org.freedesktop.DBus", "GetNameOwner", (name.as_str(),))
+                .ok()?;
+            Some((owner, name))
+        })
+        .collect()
+}
+
+/// Classify a `NameOwnerChanged` signal. Non-MPRIS names are ignored.
+///
+/// An owner handing the name to another process counts as a new player.
+fn owner_change_event(name: &str, new_owner: &str) -> Option<MprisEvent> {
+    if !name.starts_with(MPRIS_PREFIX) {
+        None
+    } else if new_owner.is_empty() {
+        Some(MprisEvent::PlayerVanished(name.to_string()))
+    } else {
+        Some(MprisEvent::PlayerAppeared(name.to_string()))
+    }
+}
+
+/// Whether a `PropertiesChanged` signal touches anything detection cares about.
+fn is_relevant_change<'a>(interface: &str, mut properties: impl Iterator<Item = &'a str>) -> bool {
+    interface == PLAYER_INTERFACE && properties.any(|p| WATCHED_PROPERTIES.contains(&p))
+}
+
+/// MPRIS players as of the last query, kept while a watcher reports changes.
+///
+/// Without a watcher every call queries the bus. With one, the last result is
+/// reused until [`invalidate`](Self::invalidate) is called for an event, with
+/// the position of playing players advanced by the time since the query.
+/// Clones share the same cache.
+#[derive(Debug, Clone, Default)]
+pub struct MprisCache {
+    inner: Arc<Mutex<CacheState>>,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    watching: bool,
+    /// Bumped by every invalidation, so a query that raced one isn't stored.
+    generation: u64,
+    snapshot: Option<(Instant, Vec<PlayerInfo>)>,
+}
+
+impl MprisCache {
+    /// Whether a watcher is running. Turning it off drops the cached query.
+    pub fn set_watching(&self, watching: bool) {
+        let mut state = self.lock();
+        state.watching = watching;
+        state.generation += 1;
+        state.snapshot = None;
+    }
+
+    /// Forget the cached query; the next call asks the players again.
+    pub fn invalidate(&self) {
+        let mut state = self.lock();
+        state.generation += 1;
+        state.snapshot = None;
+    }
+
+    /// Current MPRIS players.
+    pub fn players(&self, db: &PlayerDatabase) -> Vec<PlayerInfo> {
+        self.players_with(Instant::now(), || super::linux::detect_mpris(db))
+    }
+
+    fn players_with(
+        &self,
+        now: Instant,
+        query: impl FnOnce() -> Vec<PlayerInfo>,
+    ) -> Vec<PlayerInfo> {
+        let generation = {
+            let state = self.lock();
+            if !state.watching {
+                drop(state);
+                return query();
+            }
+            if let Some((at, players)) = &state.snapshot {
+                let elapsed = now.saturating_duration_since(*at);
+                if elapsed < RESYNC_INTERVAL {
+                    return players.iter().map(|p| advance(p, elapsed)).collect();
+                }
+            }
+            state.generation
+        };
+
+        // The bus is queried without the lock held so events aren't blocked.
+        let players = query();
+        let mut state = self.lock();
+        if state.watching && state.generation == generation {
+            state.snapshot = Some((now, players.clone()));
+        }
+        players
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CacheState> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// A cached player as it should be `elapsed` after the query.
+fn advance(player: &PlayerInfo, elapsed: Duration) -> PlayerInfo {
+    let mut player = player.clone();
+    if player.playback_state == Some(PlaybackState::Playing) {
+        player.position = player.position.map(|pos| {
+            let pos = pos + elapsed;
+            player.duration.map_or(pos, |d| pos.min(d))
+        });
+    }
+    player
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn mpv(state: PlaybackState) -> PlayerInfo {
+        PlayerInfo {
+            player_name: "mpv".into(),
+            position: Some(Duration::from_secs(100)),
+            duration: Some(Duration::from_secs(1420)),
+            playback_state: Some(state),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_cache_queries_every_time_without_watcher() {
+        let cache = MprisCache::default();
+        let queries = Cell::new(0);
+        let now = Instant::now();
+        for _ in 0..3 {
+            cache.players_with(now, || {
+                queries.set(queries.get() + 1);
+                vec![mpv(PlaybackState::Playing)]
+            });
+        }
+        assert_eq!(queries.get(), 3);
+    }
+
+    #[test]
+    fn test_cache_reuses_query_while_watching() {
+        let cache = MprisCache::default();
+        cache.set_watching(true);
+        let queries = Cell::new(0);
+        let query = || {
+            queries.set(queries.get() + 1);
+            vec![mpv(PlaybackState::Playing), mpv(PlaybackState::Paused)]
+        };
+        let start = Instant::now();
+
+        cache.players_with(start, query);
+        let later = cache.players_with(start + Duration::from_secs(5), query);
+        assert_eq!(queries.get(), 1);
+        // Playing advances, paused doesn't.
+        assert_eq!(later[0].position, Some(Duration::from_secs(105)));
+        assert_eq!(later[1].position, Some(Duration::from_secs(100)));
+
+        cache.invalidate();
+        cache.players_with(start + Duration::from_secs(6), query);
+        assert_eq!(queries.get(), 2);
+
+        // Stale after the resync interval even without events.
+        cache.players_with(start + Duration::from_secs(6) + RESYNC_INTERVAL, query);
+        assert_eq!(queries.get(), 3);
+
+        cache.set_watching(false);
+        cache.players_with(start + Duration::from_secs(7) + RESYNC_INTERVAL, query);
+        cache.players_with(start + Duration::from_secs(7) + RESYNC_INTERVAL, query);
+        assert_eq!(queries.get(), 5);
+    }
+
+    #[test]
+    fn test_advance_clamps_to_duration() {
+        let player = mpv(PlaybackState::Playing);
+        let advanced = advance(&player, Duration::from_secs(2000));
+        assert_eq!(advanced.position, Some(Duration::from_secs(1420)));
+    }
+
+    #[test]
+    fn test_owner_change_event() {
+        assert_eq!(
+            owner_change_event("org.mpris.MediaPlayer2.mpv", ":1.42"),
+            Some(MprisEvent::PlayerAppeared(
+                "org.mpris.MediaPlayer2.mpv".into()
+            ))
+        );
+        assert_eq!(
+            owner_change_event("org.mpris.MediaPlayer2.mpv", ""),
+            Some(MprisEvent::PlayerVanished(
+                "org.mpris.MediaPlayer2.mpv".into()
+            ))
+        );
+        assert_eq!(
+            owner_change_event("org.mpris.MediaPlayer2.vlc", ":1.11"),
+            Some(MprisEvent::PlayerAppeared(
+                "org.mpris.MediaPlayer2.vlc".into()
+            ))
+        );
+        assert_eq!(
+            owner_change_event("org.freedesktop.Notifications", ":1.5"),
+            None
+        );
+        assert_eq!(owner_change_event(":1.42", ":1.42"), None);
+    }
+
+    #[test]
+    fn test_is_relevant_change() {
+        assert!(is_relevant_change(
+            PLAYER_INTERFACE,
+            ["Metadata"].into_iter()
+        ));
+        assert!(is_relevant_change(
+            PLAYER_INTERFACE,
+            ["Volume", "PlaybackStatus"].into_iter()
+        ));
+        assert!(!is_relevant_change(
+            PLAYER_INTERFACE,
+            ["Volume", "Rate"].into_iter()
+        ));
+        assert!(!is_relevant_change(
+            "org.mpris.MediaPlayer2",
+            ["Metadata"].into_iter()
+        ));
+    }
+
+    #[test]
+    fn test_event_bus_name() {
+        let event = MprisEvent::Changed("org.mpris.MediaPlayer2.mpv".into());
+        assert_eq!(event.bus_name(), "org.mpris.MediaPlayer2.mpv");
+    }
+}
@@ -0,0 +1,208 @@
+//! `/proc` scan for players without an MPRIS interface.
+//!
+//! mplayer, ffplay and mpv builds without the mpris plugin are invisible to
+//! D-Bus. Any running process whose executable is in the player database is
+//! checked for open file descriptors pointing at video files instead.
+
+use std::path::{Path, PathBuf};
+
+use tracing::debug;
+
+use crate::player_db::{PlayerDatabase, PlayerDef};
+use crate::PlayerInfo;
+
+/// Extensions treated as the media being played. Anything else a player
+/// has open (fonts, subtitles, shaders, caches) is ignored.
+const VIDEO_EXTENSIONS: &[&str] = &[
+    "mkv", "mp4", "avi", "ogm", "wmv", "webm", "flv", "m4v", "mov", "ts", "m2ts",
+];
+
+/// Scan the real `/proc`.
+pub fn detect_procfs(db: &PlayerDatabase) -> Vec<PlayerInfo> {
+    scan_procfs(Path::new("/proc"), db)
+}
+
+/// Scan a procfs-shaped directory rooted at `root`.
+///
+/// Browsers are skipped: their open media files are cache entries, and
+/// they are handled by stream detection.
+pub fn scan_procfs(root: &Path, db: &PlayerDatabase) -> Vec<PlayerInfo> {
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return vec![];
+    };
+
+    let mut pids: Vec<u32> = entries
+        .filter_map(Result::ok)
+        .filter_map(|e| e.file_name().to_str()?.parse().ok())
+        .collect();
+    pids.sort_unstable();
+
+    pids.into_iter()
+        .filter_map(|pid| {
+            let proc_dir = root.join(pid.to_string());
+            let player = find_player(&proc_dir, db)?;
+            if player.is_browser {
+                return None;
+            }
+            let file = open_video_file(&proc_dir)?;
+            debug!(pid, player = %player.name, file = %file.display(), "Found player via /proc");
+            Some(PlayerInfo {
+                player_name: player.name.clone(),
+                file_path: Some(file.to_string_lossy().into_owned()),
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+/// Resolve the process's executable name against the player database.
+///
+/// Tries the `exe` link first, then `argv[0]` (for wrappers and
+/// interpreters), then `comm` (truncated to 15 bytes by the kernel).
+fn find_player<'a>(proc_dir: &Path, db: &'a PlayerDatabase) -> Option<&'a PlayerDef> {
+    let exe = std::fs::read_link(proc_dir.join("exe"))
+        .ok()
+        .and_then(|p| file_name(&p));
+    let argv0 = std::fs::read(proc_dir.join("cmdline"))
+        .ok()
+        .and_then(|raw| {
+            let first = raw.split(|b| *b == 0).next()?;
+            file_name(Path::new(std::str::from_utf8(first).ok()?))
+        });
+    let comm = std::fs::read_to_string(proc_dir.join("comm"))
+        .ok()
+        .map(|s| s.trim_end().to_string());
+
+    [exe, argv0, comm]
+        .into_iter()
+        .flatten()
+        .find_map(|name| db.find_by_executable(&name))
+}
+
+/// The most recently opened video file among the process's descriptors.
+fn open_video_file(proc_dir: &Path) -> Option<PathBuf> {
+    let fds = std::fs::read_dir(proc_dir.join("fd")).ok()?;
+    fds.filter_map(Result::ok)
+        .filter_map(|e| {
+            let fd: u32 = e.file_name().to_str()?.parse().ok()?;
+            let target = std::fs::read_link(e.path()).ok()?;
+            is_video(&target).then_some((fd, target))
+        })
+        .max_by_key(|(fd, _)| *fd)
+        .map(|(_, target)| target)
+}
+
+fn is_video(path: &Path) -> bool {
+    // Deleted files show up as "<path> (deleted)" and have no usable extension.
+    path.is_absolute()
+        && path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| VIDEO_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+}
+
+fn file_name(path: &Path) -> Option<String> {
+    path.file_name()?.to_str().map(String::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+    use tempfile::TempDir;
+
+    /// Build `<root>/<pid>` with an `exe` link and `fd/<n>` links.
+    fn fake_process(root: &Path, pid: u32, exe: &str, fds: &[(u32, &str)]) {
+        let dir = root.join(pid.to_string());
+        std::fs::create_dir_all(dir.join("fd")).unwrap();
+        symlink(exe, dir.join("exe")).unwrap();
+        for (fd, target) in fds {
+            symlink(target, dir.join("fd").join(fd.to_string())).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_finds_player_video() {
+        let root = TempDir::new().unwrap();
+        fake_process(
+            root.path(),
+            1200,
+            "/usr/bin/mplayer",
+            &[
+                (0, "/dev/pts/1"),
+                (3, "/usr/share/fonts/noto/NotoSans-Regular.ttf"),
+                (
+                    4,
+                    "/home/u/Anime/[SubsPlease] Sousou no Frieren - 05 (1080p).mkv",
+                ),
+                (
+                    5,
+                    "/home/u/Anime/[SubsPlease] Sousou no Frieren - 05 (1080p).ass",
+                ),
+                (6, "socket:[12345]"),
+            ],
+        );
+
+        let players = scan_procfs(root.path(), &PlayerDatabase::embedded());
+        assert_eq!(players.len(), 1);
+        assert_eq!(players[0].player_name, "MPlayer");
+        assert_eq!(
+            players[0].file_path.as_deref(),
+            Some("/home/u/Anime/[SubsPlease] Sousou no Frieren - 05 (1080p).mkv")
+        );
+    }
+
+    #[test]
+    fn test_latest_video_fd_wins() {
+        let root = TempDir::new().unwrap();
+        fake_process(
+            root.path(),
+            42,
+            "/usr/bin/ffplay",
+            &[
+                (3, "/anime/Frieren - 04.mkv"),
+                (9, "/anime/Frieren - 05.MKV"),
+            ],
+        );
+
+        let players = scan_procfs(root.path(), &PlayerDatabase::embedded());
+        assert_eq!(
+            players[0].file_path.as_deref(),
+            Some("/anime/Frieren - 05.MKV")
+        );
+    }
+
+    #[test]
+    fn test_ignores_non_players_and_browsers() {
+        let root = TempDir::new().unwrap();
+        fake_process(root.path(), 10, "/usr/bin/bash", &[(3, "/anime/a.mkv")]);
+        fake_process(
+            root.path(),
+            11,
+            "/usr/lib/firefox/firefox",
+            &[(40, "/home/u/.cache/mozilla/media.mp4")],
+        );
+        fake_process(root.path(), 12, "/usr/bin/mpv", &[(3, "/home/u/notes.txt")]);
+        std::fs::create_dir(root.path().join("self")).unwrap();
+
+        assert!(scan_procfs(root.path(), &PlayerDatabase::embedded()).is_empty());
+    }
+
+    #[test]
+    fn test_falls_back_to_cmdline() {
+        let root = TempDir::new().unwrap();
+        // Unreadable exe link (e.g. another user's process), but cmdline is readable.
+        let dir = root.path().join("77");
+        std::fs::create_dir_all(dir.join("fd")).unwrap();
+        std::fs::write(
+            dir.join("cmdline"),
+            b"/opt/mpv/mpv\0--no-config\0/anime/x.mkv\0",
+        )
+        .unwrap();
+        symlink("/anime/x.mkv", dir.join("fd").join("5")).unwrap();
+
+        let players = scan_procfs(root.path(), &PlayerDatabase::embedded());
+        assert_eq!(players.len(), 1);
+        assert_eq!(players[0].player_name, "mpv");
+    }
+}
@@ -64,6 +64,7 @@ unsafe extern "system" fn enum_window_callback(hwnd: HWND, lparam: LPARAM) -> BO
             media_title,
             file_path: None,
             is_browser: player.is_browser,
+            ..Default::default()
         });
     }
 
@@ -72,6 +72,7 @@ fn extract_session(
         media_title: Some(title),
         file_path: None,
         is_browser,
+        ..Default::default()
     })
 }
 
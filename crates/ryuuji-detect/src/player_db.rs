@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::PatternError;
+
 /// Embedded player database.
 const EMBEDDED_DB: &str = include_str!("../data/players.toml");
 
@@ -141,6 +143,18 @@ impl PlayerDatabase {
         None
     }
 
+    /// Validate every player's `title_patterns`, returning the ones that fail to compile.
+    pub fn pattern_errors(&self) -> Vec<PatternError> {
+        self.players
+            .iter()
+            .flat_map(|p| {
+                p.title_patterns
+                    .iter()
+                    .filter_map(|pat| PatternError::compile(&p.name, "title_patterns", pat).err())
+            })
+            .collect()
+    }
+
     /// Get all enabled players.
     pub fn enabled_players(&self) -> impl Iterator<Item = &PlayerDef> {
         self.players.iter().filter(|p| p.enabled)
@@ -298,4 +312,22 @@ mod tests {
         let db = PlayerDatabase::embedded();
         assert!(db.find_by_smtc("unknown_app.exe").is_none());
     }
+
+    #[test]
+    fn test_pattern_errors() {
+        assert!(PlayerDatabase::embedded().pattern_errors().is_empty());
+
+        let db = PlayerDatabase::from_toml(
+            r#"
+            [[player]]
+            name = "Custom Player"
+            title_patterns = ["^(.+?) - Custom$", "^(unclosed"]
+        "#,
+        )
+        .unwrap();
+        let errors = db.pattern_errors();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].owner, "Custom Player");
+        assert_eq!(errors[0].pattern, "^(unclosed");
+    }
 }
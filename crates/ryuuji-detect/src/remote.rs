@@ -0,0 +1,47 @@
+//! Players reached over their network control interfaces.
+//!
+//! HTPCs and headless boxes often run Kodi or VLC where neither MPRIS nor
+//! window enumeration can see them; their HTTP interfaces report what is
+//! playing instead. Jellyfin and Plex servers additionally know about TV and
+//! mobile clients that never appear on this machine at all.
+
+pub(crate) mod http;
+pub mod jellyfin;
+pub mod kodi;
+pub mod plex;
+pub mod vlc;
+
+use serde::{Deserialize, Serialize};
+
+/// Address and credentials of a player's HTTP interface.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemoteEndpoint {
+    pub host: String,
+    pub port: u16,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+}
+
+/// A media server whose active sessions are polled.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerEndpoint {
+    pub host: String,
+    pub port: u16,
+    /// API key (Jellyfin) or `X-Plex-Token` (Plex).
+    pub token: String,
+    /// Only report sessions of this user (case-insensitive). `None` reports all.
+    #[serde(default)]
+    pub user: Option<String>,
+}
+
+impl ServerEndpoint {
+    fn matches_user(&self, name: Option<&str>) -> bool {
+        match (&self.user, name) {
+            (None, _) => true,
+            (Some(want), Some(name)) => want.eq_ignore_ascii_case(name),
+            (Some(_), None) => false,
+        }
+    }
+}
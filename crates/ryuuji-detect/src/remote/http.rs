@@ -0,0 +1,236 @@
+//! Minimal blocking HTTP/1.1 client for LAN player interfaces.
+//!
+//! Detection runs synchronously on a worker thread, and the control
+//! interfaces we talk to are plain HTTP on the local network, so a small
+//! `TcpStream` client avoids pulling an async HTTP stack into this crate.
+
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+use super::RemoteEndpoint;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(500);
+const IO_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Upper bound on a response body; status documents are a few KB.
+const MAX_RESPONSE_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Send a request and return the status code and body.
+pub(crate) fn request(
+    host: &str,
+    port: u16,
+    method: &str,
+    path: &str,
+    headers: &[(&str, String)],
+    body: Option<&str>,
+) -> std::io::Result<(u16, String)> {
+    let addr = (host, port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "host not found"))?;
+    let mut stream = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT)?;
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+
+    let mut head = format!(
+        "{method} {path} HTTP/1.1\r\nHost: {host}:{port}\r\nConnection: close\r\nAccept: application/json\r\n"
+    );
+    for (name, value) in headers {
+        head.push_str(&format!("{name}: {value}\r\n"));
+    }
+    if let Some(body) = body {
+        head.push_str(&format!(
+            "Content-Type: application/json\r\nContent-Length: {}\r\n",
+            body.len()
+        ));
+    }
+    head.push_str("\r\n");
+    stream.write_all(head.as_bytes())?;
+    if let Some(body) = body {
+        stream.write_all(body.as_bytes())?;
+    }
+
+    let mut raw = Vec::new();
+    stream.take(MAX_RESPONSE_BYTES).read_to_end(&mut raw)?;
+    parse_response(&raw)
+}
+
+/// `Authorization` header for an endpoint with credentials, if it has any.
+pub(crate) fn basic_auth(endpoint: &RemoteEndpoint) -> Option<(&'static str, String)> {
+    if endpoint.username.is_none() && endpoint.password.is_none() {
+        return None;
+    }
+    let credentials = format!(
+        "{}:{}",
+        endpoint.username.as_deref().unwrap_or_default(),
+        endpoint.password.as_deref().unwrap_or_default()
+    );
+    Some((
+        "Authorization",
+        format!("Basic {}", STANDARD.encode(credentials)),
+    ))
+}
+
+/// Split a raw response into status code and (de-chunked) body.
+fn parse_response(raw: &[u8]) -> std::io::Result<(u16, String)> {
+    let invalid = |msg: &str| std::io::Error::new(std::io::ErrorKind::InvalidData, msg.to_string());
+
+    let split = raw
+        .windows(4)
+        .position(|w| w == b"\r\n\r\n")
+        .ok_or_else(|| invalid("incomplete response headers"))?;
+    let head = String::from_utf8_lossy(&raw[..split]);
+    let body = &raw[split + 4..];
+
+    let mut lines = head.lines();
+    let status = lines
+        .next()
+        .and_then(|l| l.split_whitespace().nth(1))
+        .and_then(|code| code.parse::<u16>().ok())
+        .ok_or_else(|| invalid("malformed status line"))?;
+    let chunked = lines.any(|l| {
+        l.split_once(':').is_some_and(|(k, v)| {
+            k.trim().eq_ignore_ascii_case("transfer-encoding")
+                && v.trim().eq_ignore_ascii_case("chunked")
+        })
+    });
+
+    let body = if chunked {
+        decode_chunked(body).ok_or_else(|| invalid("malformed chunked body"))?
+    } else {
+        body.to_vec()
+    };
+    Ok((status, String::from_utf8_lossy(&body).into_owned()))
+}
+
+fn decode_chunked(mut data: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+    loop {
+        let line_end = data.windows(2).position(|w| w == b"\r\n")?;
+        let size_str = std::str::from_utf8(&data[..line_end]).ok()?;
+        let size_str = size_str.split(';').next()?.trim();
+        let size = usize::from_str_radix(size_str, 16).ok()?;
+        data = &data[line_end + 2..];
+        if size == 0 {
+            return Some(out);
+        }
+        out.extend_from_slice(data.get(..size)?);
+        data = data.get(size + 2..)?;
+    }
+}
+
+/// Local stub server for backend tests.
+#[cfg(test)]
+pub(crate) mod stub {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+
+    /// A request as seen by the stub server.
+    #[derive(Debug, Clone)]
+    pub struct StubRequest {
+        pub method: String,
+        pub path: String,
+        pub authorization: Option<String>,
+        pub body: String,
+    }
+
+    /// Serve `count` connections on an ephemeral port, answering each with
+    /// `handler(request) -> (status, body)`. Returns the bound port.
+    pub fn serve(
+        count: usize,
+        handler: impl Fn(&StubRequest) -> (u16, String) + Send + 'static,
+    ) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().take(count) {
+                let Ok(mut stream) = stream else { continue };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut parts = request_line.split_whitespace();
+                let method = parts.next().unwrap_or_default().to_string();
+                let path = parts.next().unwrap_or_default().to_string();
+
+                let mut content_length = 0;
+                let mut authorization = None;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    let line = line.trim_end();
+                    if line.is_empty() {
+                        break;
+                    }
+                    if let Some((k, v)) = line.split_once(':') {
+                        match k.to_ascii_lowercase().as_str() {
+                            "content-length" => content_length = v.trim().parse().unwrap(),
+                            "authorization" => authorization = Some(v.trim().to_string()),
+                            _ => {}
+                        }
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+
+                let (status, body) = handler(&StubRequest {
+                    method,
+                    path,
+                    authorization,
+                    body: String::from_utf8(body).unwrap(),
+                });
+                write!(
+                    stream,
+                    "HTTP/1.1 {status} OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                )
+                .unwrap();
+            }
+        });
+        port
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_chunked_response() {
+        let raw = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n4\r\n{\"a\"\r\n3\r\n:1}\r\n0\r\n\r\n";
+        let (status, body) = parse_response(raw).unwrap();
+        assert_eq!(status, 200);
+        assert_eq!(body, "{\"a\":1}");
+    }
+
+    #[test]
+    fn test_request_against_stub() {
+        let port = stub::serve(1, |req| {
+            assert_eq!(req.method, "POST");
+            assert_eq!(req.authorization.as_deref(), Some("Basic a29kaTpwdw=="));
+            (200, req.body.clone())
+        });
+        let endpoint = RemoteEndpoint {
+            host: "127.0.0.1".into(),
+            port,
+            username: Some("kodi".into()),
+            password: Some("pw".into()),
+        };
+        let headers: Vec<_> = basic_auth(&endpoint).into_iter().collect();
+        let (status, body) = request(
+            &endpoint.host,
+            endpoint.port,
+            "POST",
+            "/jsonrpc",
+            &headers,
+            Some("{}"),
+        )
+        .unwrap();
+        assert_eq!(status, 200);
+        assert_eq!(body, "{}");
+    }
+}
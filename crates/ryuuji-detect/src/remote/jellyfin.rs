@@ -0,0 +1,202 @@
+//! Jellyfin session polling.
+//!
+//! `GET /Sessions` lists every client currently connected to the server,
+//! including TVs and phones, with the library item each one is playing.
+//! Series, season and episode come straight from the library, so no
+//! filename parsing is needed.
+
+use std::time::Duration;
+
+use serde_json::Value;
+use tracing::debug;
+
+use super::http;
+use super::ServerEndpoint;
+use crate::{EpisodeInfo, PlaybackState, PlayerInfo};
+
+/// Only sessions active within this window are reported.
+const SESSIONS_PATH: &str = "/Sessions?ActiveWithinSeconds=960";
+
+/// Jellyfin timestamps are in 100 ns ticks.
+const TICKS_PER_SECOND: f64 = 10_000_000.0;
+
+/// Poll the server for sessions of the configured user that are playing something.
+pub fn detect_jellyfin(endpoint: &ServerEndpoint) -> Vec<PlayerInfo> {
+    match query(endpoint) {
+        Ok(players) => players,
+        Err(e) => {
+            debug!(host = %endpoint.host, port = endpoint.port, error = %e, "Jellyfin not reachable");
+            vec![]
+        }
+    }
+}
+
+fn query(endpoint: &ServerEndpoint) -> std::io::Result<Vec<PlayerInfo>> {
+    let headers = [(
+        "Authorization",
+        format!("MediaBrowser Token=\"{}\"", endpoint.token),
+    )];
+    let (status, body) = http::request(
+        &endpoint.host,
+        endpoint.port,
+        "GET",
+        SESSIONS_PATH,
+        &headers,
+        None,
+    )?;
+    if status != 200 {
+        return Err(std::io::Error::other(format!("/Sessions: HTTP {status}")));
+    }
+    let sessions: Value = serde_json::from_str(&body)?;
+
+    Ok(sessions
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|s| endpoint.matches_user(s["UserName"].as_str()))
+        .filter_map(session_to_player)
+        .collect())
+}
+
+fn session_to_player(session: &Value) -> Option<PlayerInfo> {
+    let item = session.get("NowPlayingItem")?;
+    let play_state = &session["PlayState"];
+
+    let episode_info = (item["Type"] == "Episode")
+        .then(|| {
+            Some(EpisodeInfo {
+                series: item["SeriesName"].as_str()?.to_string(),
+                season: as_u32(&item["ParentIndexNumber"]),
+                episode: as_u32(&item["IndexNumber"]),
+            })
+        })
+        .flatten();
+
+    let playback_state = match play_state["IsPaused"].as_bool() {
+        Some(true) => PlaybackState::Paused,
+        _ => PlaybackState::Playing,
+    };
+
+    debug!(
+        client = ?session["Client"].as_str(),
+        item = ?item["Name"].as_str(),
+        episode = ?episode_info,
+        "Jellyfin session"
+    );
+
+    Some(PlayerInfo {
+        player_name: "Jellyfin".into(),
+        media_title: item["Name"].as_str().map(String::from),
+        file_path: item["Path"].as_str().map(String::from),
+        is_browser: false,
+        position: ticks(&play_state["PositionTicks"]),
+        duration: ticks(&item["RunTimeTicks"]),
+        playback_state: Some(playback_state),
+        episode_info,
+    })
+}
+
+fn as_u32(value: &Value) -> Option<u32> {
+    value.as_u64().and_then(|n| u32::try_from(n).ok())
+}
+
+fn ticks(value: &Value) -> Option<Duration> {
+    value
+        .as_f64()
+        .filter(|t| *t >= 0.0)
+        .map(|t| Duration::from_secs_f64(t / TICKS_PER_SECOND))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::remote::http::stub;
+    use serde_json::json;
+
+    fn endpoint(port: u16, user: Option<&str>) -> ServerEndpoint {
+        ServerEndpoint {
+            host: "127.0.0.1".into(),
+            port,
+            token: "abc123".into(),
+            user: user.map(String::from),
+        }
+    }
+
+    fn sessions() -> String {
+        json!([
+            {
+                "UserName": "alice",
+                "Client": "Android TV",
+                "NowPlayingItem": {
+                    "Name": "The Journey's End",
+                    "Type": "Episode",
+                    "SeriesName": "Frieren: Beyond Journey's End",
+                    "ParentIndexNumber": 1,
+                    "IndexNumber": 5,
+                    "RunTimeTicks": 14_200_000_000u64,
+                    "Path": "/media/anime/Frieren/S01E05.mkv",
+                },
+                "PlayState": {"PositionTicks": 3_120_000_000u64, "IsPaused": false},
+            },
+            {
+                "UserName": "bob",
+                "Client": "Jellyfin Web",
+                "NowPlayingItem": {
+                    "Name": "Some Movie",
+                    "Type": "Movie",
+                },
+                "PlayState": {"IsPaused": true},
+            },
+            {
+                "UserName": "alice",
+                "Client": "Jellyfin Media Player",
+            },
+        ])
+        .to_string()
+    }
+
+    #[test]
+    fn test_maps_episode_session() {
+        let port = stub::serve(1, |req| {
+            assert!(req.path.starts_with("/Sessions"));
+            assert_eq!(
+                req.authorization.as_deref(),
+                Some("MediaBrowser Token=\"abc123\"")
+            );
+            (200, sessions())
+        });
+
+        let players = detect_jellyfin(&endpoint(port, Some("Alice")));
+        assert_eq!(players.len(), 1);
+        let p = &players[0];
+        assert_eq!(p.player_name, "Jellyfin");
+        assert_eq!(
+            p.episode_info,
+            Some(EpisodeInfo {
+                series: "Frieren: Beyond Journey's End".into(),
+                season: Some(1),
+                episode: Some(5),
+            })
+        );
+        assert_eq!(p.position, Some(Duration::from_secs(312)));
+        assert_eq!(p.duration, Some(Duration::from_secs(1420)));
+        assert_eq!(p.playback_state, Some(PlaybackState::Playing));
+    }
+
+    #[test]
+    fn test_all_users_without_filter() {
+        let port = stub::serve(1, |_| (200, sessions()));
+
+        let players = detect_jellyfin(&endpoint(port, None));
+        // Idle sessions without a NowPlayingItem are skipped.
+        assert_eq!(players.len(), 2);
+        assert!(players[1].episode_info.is_none());
+        assert_eq!(players[1].playback_state, Some(PlaybackState::Paused));
+    }
+
+    #[test]
+    fn test_bad_token_is_ignored() {
+        let port = stub::serve(1, |_| (401, String::new()));
+        assert!(detect_jellyfin(&endpoint(port, None)).is_empty());
+    }
+}
@@ -0,0 +1,259 @@
+//! Kodi JSON-RPC detection.
+//!
+//! Uses `Player.GetActivePlayers`, `Player.GetItem` and
+//! `Player.GetProperties` over `POST /jsonrpc`.
+//! See <https://kodi.wiki/view/JSON-RPC_API>.
+
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tracing::debug;
+
+use super::http;
+use super::RemoteEndpoint;
+use crate::player_db::PlayerDatabase;
+use crate::{EpisodeInfo, PlaybackState, PlayerInfo};
+
+/// Query Kodi for its active video player.
+///
+/// Returns an empty list if Kodi is unreachable or not playing video.
+pub fn detect_kodi(db: &PlayerDatabase, endpoint: &RemoteEndpoint) -> Vec<PlayerInfo> {
+    match query(db, endpoint) {
+        Ok(info) => info.into_iter().collect(),
+        Err(e) => {
+            debug!(host = %endpoint.host, port = endpoint.port, error = %e, "Kodi not reachable");
+            vec![]
+        }
+    }
+}
+
+fn query(db: &PlayerDatabase, endpoint: &RemoteEndpoint) -> std::io::Result<Option<PlayerInfo>> {
+    let players = call(endpoint, "Player.GetActivePlayers", json!({}))?;
+    let Some(player_id) = players.as_array().and_then(|list| {
+        list.iter()
+            .find(|p| p["type"] == "video")
+            .and_then(|p| p["playerid"].as_i64())
+    }) else {
+        return Ok(None);
+    };
+
+    let item = call(
+        endpoint,
+        "Player.GetItem",
+        json!({
+            "playerid": player_id,
+            "properties": ["file", "title", "showtitle", "season", "episode"],
+        }),
+    )?;
+    let props = call(
+        endpoint,
+        "Player.GetProperties",
+        json!({
+            "playerid": player_id,
+            "properties": ["time", "totaltime", "speed"],
+        }),
+    )?;
+
+    let item = &item["item"];
+    let file_path = non_empty(&item["file"]);
+    let media_title = non_empty(&item["title"]).or_else(|| non_empty(&item["label"]));
+    // Library episodes: Kodi already knows the series, so the file name
+    // (often just "S01E05.mkv") doesn't need parsing. It reports -1 for
+    // anything it doesn't know.
+    let episode_info = non_empty(&item["showtitle"])
+        .zip(positive(&item["episode"]))
+        .map(|(series, episode)| EpisodeInfo {
+            series,
+            season: positive(&item["season"]),
+            episode: Some(episode),
+        });
+    let playback_state = props["speed"].as_f64().map(|speed| {
+        if speed == 0.0 {
+            PlaybackState::Paused
+        } else {
+            PlaybackState::Playing
+        }
+    });
+
+    let player_name = db
+        .find_by_executable("kodi")
+        .map(|p| p.name.clone())
+        .unwrap_or_else(|| "Kodi".into());
+
+    debug!(
+        title = ?media_title,
+        file = ?file_path,
+        episode = ?episode_info,
+        state = ?playback_state,
+        "Detected Kodi via JSON-RPC"
+    );
+
+    Ok(Some(PlayerInfo {
+        player_name,
+        media_title,
+        file_path,
+        is_browser: false,
+        position: kodi_time(&props["time"]),
+        duration: kodi_time(&props["totaltime"]),
+        playback_state,
+        episode_info,
+    }))
+}
+
+/// Perform a JSON-RPC call and return its `result`.
+fn call(endpoint: &RemoteEndpoint, method: &str, params: Value) -> std::io::Result<Value> {
+    let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+    let headers: Vec<_> = http::basic_auth(endpoint).into_iter().collect();
+    let (status, body) = http::request(
+        &endpoint.host,
+        endpoint.port,
+        "POST",
+        "/jsonrpc",
+        &headers,
+        Some(&request.to_string()),
+    )?;
+    if status != 200 {
+        return Err(std::io::Error::other(format!("{method}: HTTP {status}")));
+    }
+    let mut reply: Value = serde_json::from_str(&body)?;
+    if let Some(err) = reply.get("error") {
+        return Err(std::io::Error::other(format!("{method}: {err}")));
+    }
+    Ok(reply["result"].take())
+}
+
+fn non_empty(value: &Value) -> Option<String> {
+    value
+        .as_str()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+}
+
+fn positive(value: &Value) -> Option<u32> {
+    value
+        .as_i64()
+        .filter(|n| *n > 0)
+        .and_then(|n| u32::try_from(n).ok())
+}
+
+/// Kodi reports times as `{hours, minutes, seconds, milliseconds}`.
+fn kodi_time(value: &Value) -> Option<Duration> {
+    let part = |key: &str| value.get(key).and_then(Value::as_u64);
+    let secs = part("hours")? * 3600 + part("minutes")? * 60 + part("seconds")?;
+    Some(Duration::from_secs(secs) + Duration::from_millis(part("milliseconds").unwrap_or(0)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::remote::http::stub;
+
+    fn endpoint(port: u16) -> RemoteEndpoint {
+        RemoteEndpoint {
+            host: "127.0.0.1".into(),
+            port,
+            username: Some("kodi".into()),
+            password: Some("kodi".into()),
+        }
+    }
+
+    fn stub_kodi(active: Value, item: Value, speed: i64) -> u16 {
+        stub::serve(3, move |req| {
+            assert_eq!(req.path, "/jsonrpc");
+            let call: Value = serde_json::from_str(&req.body).unwrap();
+            let result = match call["method"].as_str().unwrap() {
+                "Player.GetActivePlayers" => active.clone(),
+                "Player.GetItem" => json!({ "item": item.clone() }),
+                "Player.GetProperties" => json!({
+                    "speed": speed,
+                    "time": {"hours": 0, "minutes": 5, "seconds": 12, "milliseconds": 500},
+                    "totaltime": {"hours": 0, "minutes": 23, "seconds": 40, "milliseconds": 0},
+                }),
+                other => panic!("unexpected method {other}"),
+            };
+            (
+                200,
+                json!({"id": 1, "jsonrpc": "2.0", "result": result}).to_string(),
+            )
+        })
+    }
+
+    #[test]
+    fn test_detect_playing_file() {
+        let port = stub_kodi(
+            json!([{"playerid": 1, "playertype": "internal", "type": "video"}]),
+            json!({
+                "file": "/storage/anime/[SubsPlease] Sousou no Frieren - 05 (1080p).mkv",
+                "label": "[SubsPlease] Sousou no Frieren - 05 (1080p).mkv",
+                "title": "",
+                "showtitle": "",
+                "episode": -1,
+                "type": "unknown",
+            }),
+            1,
+        );
+
+        let players = detect_kodi(&PlayerDatabase::embedded(), &endpoint(port));
+        assert_eq!(players.len(), 1);
+        let p = &players[0];
+        assert_eq!(p.player_name, "Kodi");
+        assert_eq!(
+            p.file_path.as_deref(),
+            Some("/storage/anime/[SubsPlease] Sousou no Frieren - 05 (1080p).mkv")
+        );
+        assert_eq!(
+            p.media_title.as_deref(),
+            Some("[SubsPlease] Sousou no Frieren - 05 (1080p).mkv")
+        );
+        assert_eq!(p.position, Some(Duration::from_millis(312_500)));
+        assert_eq!(p.duration, Some(Duration::from_secs(1420)));
+        assert_eq!(p.playback_state, Some(PlaybackState::Playing));
+        assert_eq!(p.episode_info, None);
+    }
+
+    #[test]
+    fn test_library_episode_title() {
+        let port = stub_kodi(
+            json!([{"playerid": 1, "type": "video"}]),
+            json!({
+                "file": "smb://nas/anime/frieren/S01E05.mkv",
+                "title": "The Hero's Party",
+                "showtitle": "Frieren: Beyond Journey's End",
+                "season": 1,
+                "episode": 5,
+            }),
+            0,
+        );
+
+        let players = detect_kodi(&PlayerDatabase::embedded(), &endpoint(port));
+        assert_eq!(players[0].media_title.as_deref(), Some("The Hero's Party"));
+        assert_eq!(
+            players[0].episode_info,
+            Some(EpisodeInfo {
+                series: "Frieren: Beyond Journey's End".into(),
+                season: Some(1),
+                episode: Some(5),
+            })
+        );
+        assert_eq!(players[0].playback_state, Some(PlaybackState::Paused));
+    }
+
+    #[test]
+    fn test_music_only_is_ignored() {
+        let port = stub::serve(1, |_| {
+            (
+                200,
+                json!({"id": 1, "jsonrpc": "2.0", "result": [{"playerid": 0, "type": "audio"}]})
+                    .to_string(),
+            )
+        });
+        assert!(detect_kodi(&PlayerDatabase::embedded(), &endpoint(port)).is_empty());
+    }
+
+    #[test]
+    fn test_unauthorized_is_ignored() {
+        let port = stub::serve(1, |_| (401, String::new()));
+        assert!(detect_kodi(&PlayerDatabase::embedded(), &endpoint(port)).is_empty());
+    }
+}
@@ -0,0 +1,189 @@
+//! Plex session polling.
+//!
+//! `GET /status/sessions` (with `Accept: application/json`) lists what every
+//! client of the server is playing. Episodes carry the show title
+//! (`grandparentTitle`), season (`parentIndex`) and episode (`index`).
+
+use std::time::Duration;
+
+use serde_json::Value;
+use tracing::debug;
+
+use super::http;
+use super::ServerEndpoint;
+use crate::{EpisodeInfo, PlaybackState, PlayerInfo};
+
+/// Poll the server for sessions of the configured user.
+pub fn detect_plex(endpoint: &ServerEndpoint) -> Vec<PlayerInfo> {
+    match query(endpoint) {
+        Ok(players) => players,
+        Err(e) => {
+            debug!(host = %endpoint.host, port = endpoint.port, error = %e, "Plex not reachable");
+            vec![]
+        }
+    }
+}
+
+fn query(endpoint: &ServerEndpoint) -> std::io::Result<Vec<PlayerInfo>> {
+    let headers = [("X-Plex-Token", endpoint.token.clone())];
+    let (status, body) = http::request(
+        &endpoint.host,
+        endpoint.port,
+        "GET",
+        "/status/sessions",
+        &headers,
+        None,
+    )?;
+    if status != 200 {
+        return Err(std::io::Error::other(format!(
+            "/status/sessions: HTTP {status}"
+        )));
+    }
+    let reply: Value = serde_json::from_str(&body)?;
+
+    Ok(reply["MediaContainer"]["Metadata"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|m| endpoint.matches_user(m["User"]["title"].as_str()))
+        .map(metadata_to_player)
+        .collect())
+}
+
+fn metadata_to_player(item: &Value) -> PlayerInfo {
+    let episode_info = (item["type"] == "episode")
+        .then(|| {
+            Some(EpisodeInfo {
+                series: item["grandparentTitle"].as_str()?.to_string(),
+                season: as_u32(&item["parentIndex"]),
+                episode: as_u32(&item["index"]),
+            })
+        })
+        .flatten();
+
+    let playback_state = match item["Player"]["state"].as_str() {
+        Some("paused") => Some(PlaybackState::Paused),
+        Some("stopped") => Some(PlaybackState::Stopped),
+        Some(_) => Some(PlaybackState::Playing),
+        None => None,
+    };
+
+    let file_path = item["Media"][0]["Part"][0]["file"]
+        .as_str()
+        .map(String::from);
+
+    debug!(
+        client = ?item["Player"]["title"].as_str(),
+        item = ?item["title"].as_str(),
+        episode = ?episode_info,
+        "Plex session"
+    );
+
+    PlayerInfo {
+        player_name: "Plex".into(),
+        media_title: item["title"].as_str().map(String::from),
+        file_path,
+        is_browser: false,
+        position: millis(&item["viewOffset"]),
+        duration: millis(&item["duration"]),
+        playback_state,
+        episode_info,
+    }
+}
+
+/// Plex sends numbers either as JSON numbers or as strings.
+fn as_u64(value: &Value) -> Option<u64> {
+    value
+        .as_u64()
+        .or_else(|| value.as_str().and_then(|s| s.parse().ok()))
+}
+
+fn as_u32(value: &Value) -> Option<u32> {
+    as_u64(value).and_then(|n| u32::try_from(n).ok())
+}
+
+fn millis(value: &Value) -> Option<Duration> {
+    as_u64(value).map(Duration::from_millis)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::remote::http::stub;
+    use serde_json::json;
+
+    fn endpoint(port: u16, user: Option<&str>) -> ServerEndpoint {
+        ServerEndpoint {
+            host: "127.0.0.1".into(),
+            port,
+            token: "plex-token".into(),
+            user: user.map(String::from),
+        }
+    }
+
+    fn sessions() -> String {
+        json!({"MediaContainer": {"size": 2, "Metadata": [
+            {
+                "type": "episode",
+                "title": "The Journey's End",
+                "grandparentTitle": "Frieren: Beyond Journey's End",
+                "parentIndex": 1,
+                "index": "5",
+                "duration": 1_420_000,
+                "viewOffset": 312_000,
+                "User": {"title": "alice"},
+                "Player": {"title": "Living Room TV", "state": "paused"},
+                "Media": [{"Part": [{"file": "/data/anime/Frieren/S01E05.mkv"}]}],
+            },
+            {
+                "type": "track",
+                "title": "Some Song",
+                "User": {"title": "bob"},
+                "Player": {"state": "playing"},
+            },
+        ]}})
+        .to_string()
+    }
+
+    #[test]
+    fn test_maps_episode_session() {
+        let port = stub::serve(1, |req| {
+            assert_eq!(req.path, "/status/sessions");
+            (200, sessions())
+        });
+
+        let players = detect_plex(&endpoint(port, Some("alice")));
+        assert_eq!(players.len(), 1);
+        let p = &players[0];
+        assert_eq!(p.player_name, "Plex");
+        assert_eq!(
+            p.episode_info,
+            Some(EpisodeInfo {
+                series: "Frieren: Beyond Journey's End".into(),
+                season: Some(1),
+                episode: Some(5),
+            })
+        );
+        assert_eq!(
+            p.file_path.as_deref(),
+            Some("/data/anime/Frieren/S01E05.mkv")
+        );
+        assert_eq!(p.position, Some(Duration::from_secs(312)));
+        assert_eq!(p.duration, Some(Duration::from_secs(1420)));
+        assert_eq!(p.playback_state, Some(PlaybackState::Paused));
+    }
+
+    #[test]
+    fn test_empty_container() {
+        let port = stub::serve(1, |_| {
+            (200, json!({"MediaContainer": {"size": 0}}).to_string())
+        });
+        assert!(detect_plex(&endpoint(port, None)).is_empty());
+    }
+
+    #[test]
+    fn test_bad_token_is_ignored() {
+        let port = stub::serve(1, |_| (401, String::new()));
+        assert!(detect_plex(&endpoint(port, None)).is_empty());
+    }
+}
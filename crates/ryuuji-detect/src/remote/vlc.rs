@@ -0,0 +1,189 @@
+//! VLC HTTP interface detection.
+//!
+//! Polls `/requests/status.json` for state and position, and
+//! `/requests/playlist.json` for the URI of the current item. VLC's web
+//! interface uses HTTP Basic auth with an empty user name and the password
+//! set in its preferences (`--http-password`).
+
+use std::time::Duration;
+
+use serde_json::Value;
+use tracing::debug;
+
+use super::http;
+use super::RemoteEndpoint;
+use crate::player_db::PlayerDatabase;
+use crate::{PlaybackState, PlayerInfo};
+
+/// Query VLC's HTTP interface.
+///
+/// Returns an empty list if VLC is unreachable or stopped.
+pub fn detect_vlc(db: &PlayerDatabase, endpoint: &RemoteEndpoint) -> Vec<PlayerInfo> {
+    match query(db, endpoint) {
+        Ok(info) => info.into_iter().collect(),
+        Err(e) => {
+            debug!(host = %endpoint.host, port = endpoint.port, error = %e, "VLC HTTP interface not reachable");
+            vec![]
+        }
+    }
+}
+
+fn query(db: &PlayerDatabase, endpoint: &RemoteEndpoint) -> std::io::Result<Option<PlayerInfo>> {
+    let status = get_json(endpoint, "/requests/status.json")?;
+    let playback_state = match status["state"].as_str() {
+        Some("playing") => PlaybackState::Playing,
+        Some("paused") => PlaybackState::Paused,
+        _ => return Ok(None),
+    };
+
+    let meta = &status["information"]["category"]["meta"];
+    let filename = meta["filename"].as_str().map(String::from);
+    let media_title = meta["title"]
+        .as_str()
+        .map(String::from)
+        .or(filename.clone());
+
+    // The playlist is only needed for the full path; fall back to the name.
+    let file_path = get_json(endpoint, "/requests/playlist.json")
+        .ok()
+        .and_then(|playlist| current_uri(&playlist))
+        .map(|uri| match uri.strip_prefix("file://") {
+            Some(path) => crate::percent_decode(path).unwrap_or(uri),
+            None => uri,
+        })
+        .or(filename);
+
+    let player_name = db
+        .find_by_executable("vlc")
+        .map(|p| p.name.clone())
+        .unwrap_or_else(|| "VLC".into());
+
+    debug!(title = ?media_title, file = ?file_path, state = ?playback_state, "Detected VLC via HTTP");
+
+    Ok(Some(PlayerInfo {
+        player_name,
+        media_title,
+        file_path,
+        is_browser: false,
+        position: seconds(&status["time"]),
+        duration: seconds(&status["length"]).filter(|d| !d.is_zero()),
+        playback_state: Some(playback_state),
+        episode_info: None,
+    }))
+}
+
+fn get_json(endpoint: &RemoteEndpoint, path: &str) -> std::io::Result<Value> {
+    let headers: Vec<_> = http::basic_auth(endpoint).into_iter().collect();
+    let (status, body) = http::request(&endpoint.host, endpoint.port, "GET", path, &headers, None)?;
+    if status != 200 {
+        return Err(std::io::Error::other(format!("{path}: HTTP {status}")));
+    }
+    Ok(serde_json::from_str(&body)?)
+}
+
+/// Find the URI of the playlist node marked `"current": "current"`.
+fn current_uri(node: &Value) -> Option<String> {
+    if node["current"] == "current" {
+        if let Some(uri) = node["uri"].as_str() {
+            return Some(uri.to_string());
+        }
+    }
+    node["children"].as_array()?.iter().find_map(current_uri)
+}
+
+fn seconds(value: &Value) -> Option<Duration> {
+    value
+        .as_f64()
+        .filter(|s| s.is_finite() && *s >= 0.0)
+        .map(Duration::from_secs_f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::remote::http::stub;
+    use serde_json::json;
+
+    fn endpoint(port: u16) -> RemoteEndpoint {
+        RemoteEndpoint {
+            host: "127.0.0.1".into(),
+            port,
+            username: None,
+            password: Some("secret".into()),
+        }
+    }
+
+    fn status(state: &str) -> String {
+        json!({
+            "state": state,
+            "time": 312,
+            "length": 1420,
+            "information": {"category": {"meta": {
+                "filename": "[SubsPlease] Sousou no Frieren - 05 (1080p).mkv",
+            }}},
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_detect_playing() {
+        let port = stub::serve(2, |req| {
+            assert_eq!(req.authorization.as_deref(), Some("Basic OnNlY3JldA=="));
+            match req.path.as_str() {
+                "/requests/status.json" => (200, status("playing")),
+                "/requests/playlist.json" => (
+                    200,
+                    json!({"name": "", "children": [{"name": "Playlist", "children": [
+                        {"name": "other", "uri": "file:///anime/other.mkv"},
+                        {
+                            "name": "[SubsPlease] Sousou no Frieren - 05 (1080p).mkv",
+                            "uri": "file:///anime/%5BSubsPlease%5D%20Sousou%20no%20Frieren%20-%2005%20(1080p).mkv",
+                            "current": "current",
+                        },
+                    ]}]})
+                    .to_string(),
+                ),
+                other => panic!("unexpected path {other}"),
+            }
+        });
+
+        let players = detect_vlc(&PlayerDatabase::embedded(), &endpoint(port));
+        assert_eq!(players.len(), 1);
+        let p = &players[0];
+        assert_eq!(p.player_name, "VLC");
+        assert_eq!(
+            p.file_path.as_deref(),
+            Some("/anime/[SubsPlease] Sousou no Frieren - 05 (1080p).mkv")
+        );
+        assert_eq!(p.position, Some(Duration::from_secs(312)));
+        assert_eq!(p.duration, Some(Duration::from_secs(1420)));
+        assert_eq!(p.playback_state, Some(PlaybackState::Playing));
+    }
+
+    #[test]
+    fn test_playlist_failure_falls_back_to_filename() {
+        let port = stub::serve(2, |req| match req.path.as_str() {
+            "/requests/status.json" => (200, status("paused")),
+            _ => (404, String::new()),
+        });
+
+        let players = detect_vlc(&PlayerDatabase::embedded(), &endpoint(port));
+        assert_eq!(
+            players[0].file_path.as_deref(),
+            Some("[SubsPlease] Sousou no Frieren - 05 (1080p).mkv")
+        );
+        assert_eq!(players[0].playback_state, Some(PlaybackState::Paused));
+    }
+
+    #[test]
+    fn test_stopped_is_ignored() {
+        let port = stub::serve(1, |_| (200, json!({"state": "stopped"}).to_string()));
+        assert!(detect_vlc(&PlayerDatabase::embedded(), &endpoint(port)).is_empty());
+    }
+
+    #[test]
+    fn test_wrong_password_is_ignored() {
+        let port = stub::serve(1, |_| (401, String::new()));
+        assert!(detect_vlc(&PlayerDatabase::embedded(), &endpoint(port)).is_empty());
+    }
+}
@@ -1,5 +1,10 @@
+use std::collections::HashMap;
+
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 
+use crate::PatternError;
+
 /// Embedded stream provider database.
 const EMBEDDED_DB: &str = include_str!("../data/streams.toml");
 
@@ -13,11 +18,36 @@ pub struct StreamDef {
     pub url_patterns: Vec<String>,
     /// Regex with capture group 1 to extract the anime title from the window/tab title.
     pub title_pattern: String,
+    /// Regex capturing the episode number, tried on the tab title, then the URL.
+    /// The first capture group that matched is used.
+    #[serde(default)]
+    pub episode_pattern: Option<String>,
+    /// Regex capturing the season number, applied like `episode_pattern`.
+    #[serde(default)]
+    pub season_pattern: Option<String>,
+    /// Regex with capture group 1 extracting the service's episode ID from the URL.
+    #[serde(default)]
+    pub episode_id_pattern: Option<String>,
+    /// Known episode IDs, for services whose URLs and titles carry no
+    /// episode number (e.g. Netflix's `/watch/81564905`).
+    #[serde(default)]
+    pub episode_ids: HashMap<String, EpisodeRef>,
     /// Whether this stream provider is enabled for detection.
     #[serde(default = "default_true")]
     pub enabled: bool,
 }
 
+/// What a service episode ID refers to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EpisodeRef {
+    /// Series title, used when the tab title doesn't give one.
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub season: Option<u32>,
+    pub episode: u32,
+}
+
 fn default_true() -> bool {
     true
 }
@@ -36,14 +66,59 @@ pub struct StreamMatch {
     pub service_name: String,
     /// The anime title extracted from the browser title.
     pub extracted_title: String,
+    /// Episode number from the service's own patterns or ID table. Takes
+    /// precedence over whatever the filename parser finds in the title.
+    pub episode: Option<u32>,
+    /// Season number, from the same sources as `episode`.
+    pub season: Option<u32>,
+}
+
+/// Compiled regexes for one [`StreamDef`]. Invalid patterns are left out.
+#[derive(Debug, Clone, Default)]
+struct CompiledStream {
+    url: Vec<Regex>,
+    title: Option<Regex>,
+    episode: Option<Regex>,
+    season: Option<Regex>,
+    episode_id: Option<Regex>,
+}
+
+impl CompiledStream {
+    fn new(def: &StreamDef, errors: &mut Vec<PatternError>) -> Self {
+        let mut compile = |field, pattern: &str| {
+            PatternError::compile(&def.name, field, pattern)
+                .map_err(|e| errors.push(e))
+                .ok()
+        };
+        Self {
+            url: def
+                .url_patterns
+                .iter()
+                .filter_map(|p| compile("url_patterns", p))
+                .collect(),
+            title: compile("title_pattern", &def.title_pattern),
+            episode: def
+                .episode_pattern
+                .as_deref()
+                .and_then(|p| compile("episode_pattern", p)),
+            season: def
+                .season_pattern
+                .as_deref()
+                .and_then(|p| compile("season_pattern", p)),
+            episode_id: def
+                .episode_id_pattern
+                .as_deref()
+                .and_then(|p| compile("episode_id_pattern", p)),
+        }
+    }
 }
 
 /// Database of known streaming services.
 #[derive(Debug, Clone)]
 pub struct StreamDatabase {
     streams: Vec<StreamDef>,
-    compiled_url: Vec<Vec<regex::Regex>>,
-    compiled_title: Vec<Option<regex::Regex>>,
+    compiled: Vec<CompiledStream>,
+    errors: Vec<PatternError>,
 }
 
 impl StreamDatabase {
@@ -55,25 +130,16 @@ impl StreamDatabase {
     /// Load a stream database from a TOML string.
     pub fn from_toml(toml_str: &str) -> Result<Self, toml::de::Error> {
         let db: StreamDbFile = toml::from_str(toml_str)?;
-        let compiled_url = db
+        let mut errors = Vec::new();
+        let compiled = db
             .streams
             .iter()
-            .map(|s| {
-                s.url_patterns
-                    .iter()
-                    .filter_map(|p| regex::Regex::new(p).ok())
-                    .collect()
-            })
-            .collect();
-        let compiled_title = db
-            .streams
-            .iter()
-            .map(|s| regex::Regex::new(&s.title_pattern).ok())
+            .map(|s| CompiledStream::new(s, &mut errors))
             .collect();
         Ok(Self {
             streams: db.streams,
-            compiled_url,
-            compiled_title,
+            compiled,
+            errors,
         })
     }
 
@@ -83,20 +149,26 @@ impl StreamDatabase {
         for (i, user_stream) in user_db.streams.iter().enumerate() {
             if let Some(pos) = self.streams.iter().position(|s| s.name == user_stream.name) {
                 self.streams[pos] = user_stream.clone();
-                self.compiled_url[pos] = user_db.compiled_url[i].clone();
-                self.compiled_title[pos] = user_db.compiled_title[i].clone();
+                self.compiled[pos] = user_db.compiled[i].clone();
             } else {
                 self.streams.push(user_stream.clone());
-                self.compiled_url.push(user_db.compiled_url[i].clone());
-                self.compiled_title.push(user_db.compiled_title[i].clone());
+                self.compiled.push(user_db.compiled[i].clone());
             }
+            // Diagnostics for a replaced stream no longer apply.
+            self.errors.retain(|e| e.owner != user_stream.name);
         }
+        self.errors.extend(user_db.errors.iter().cloned());
+    }
+
+    /// Patterns that failed to compile and are skipped during matching.
+    pub fn pattern_errors(&self) -> &[PatternError] {
+        &self.errors
     }
 
     /// Find the first enabled stream whose URL patterns match.
     pub fn match_url(&self, url: &str) -> Option<usize> {
         self.streams.iter().enumerate().find_map(|(i, s)| {
-            if s.enabled && self.compiled_url[i].iter().any(|re| re.is_match(url)) {
+            if s.enabled && self.compiled[i].url.iter().any(|re| re.is_match(url)) {
                 Some(i)
             } else {
                 None
@@ -108,7 +180,7 @@ impl StreamDatabase {
     pub fn match_title(&self, title: &str) -> Option<usize> {
         self.streams.iter().enumerate().find_map(|(i, s)| {
             if s.enabled {
-                if let Some(re) = &self.compiled_title[i] {
+                if let Some(re) = &self.compiled[i].title {
                     if re.is_match(title) {
                         return Some(i);
                     }
@@ -120,7 +192,7 @@ impl StreamDatabase {
 
     /// Extract the anime title from a browser title using the stream's title_pattern.
     pub fn extract_title(&self, index: usize, title: &str) -> Option<String> {
-        let re = self.compiled_title.get(index)?.as_ref()?;
+        let re = self.compiled.get(index)?.title.as_ref()?;
         let caps = re.captures(title)?;
         let extracted = caps.get(1)?.as_str().trim().to_string();
         if extracted.is_empty() {
@@ -130,6 +202,37 @@ impl StreamDatabase {
         }
     }
 
+    /// Extract the episode number using the stream's `episode_pattern`,
+    /// trying the tab title before the URL.
+    pub fn extract_episode(
+        &self,
+        index: usize,
+        title: Option<&str>,
+        url: Option<&str>,
+    ) -> Option<u32> {
+        let re = self.compiled.get(index)?.episode.as_ref()?;
+        capture_number(re, title).or_else(|| capture_number(re, url))
+    }
+
+    /// Extract the season number using the stream's `season_pattern`,
+    /// trying the tab title before the URL.
+    pub fn extract_season(
+        &self,
+        index: usize,
+        title: Option<&str>,
+        url: Option<&str>,
+    ) -> Option<u32> {
+        let re = self.compiled.get(index)?.season.as_ref()?;
+        capture_number(re, title).or_else(|| capture_number(re, url))
+    }
+
+    /// Look up the URL's episode ID in the stream's `episode_ids` table.
+    pub fn lookup_episode_id(&self, index: usize, url: &str) -> Option<&EpisodeRef> {
+        let re = self.compiled.get(index)?.episode_id.as_ref()?;
+        let id = re.captures(url)?.get(1)?.as_str();
+        self.streams.get(index)?.episode_ids.get(id)
+    }
+
     /// Get the service name for a matched stream index.
     pub fn service_name(&self, index: usize) -> Option<&str> {
         self.streams.get(index).map(|s| s.name.as_str())
@@ -146,6 +249,15 @@ impl StreamDatabase {
     }
 }
 
+/// First capture group of `re` in `text` that matched and parses as a number.
+fn capture_number(re: &Regex, text: Option<&str>) -> Option<u32> {
+    let caps = re.captures(text?)?;
+    caps.iter()
+        .skip(1)
+        .flatten()
+        .find_map(|m| m.as_str().parse().ok())
+}
+
 /// Detect if a browser player is watching a streaming service.
 ///
 /// Returns `None` if the player is not a browser, or if no streaming service matches.
@@ -156,18 +268,27 @@ pub fn detect_stream(
     if !player.is_browser {
         return None;
     }
+    let title = player.media_title.as_deref();
 
     // Strategy 1: URL matching (primary — available on Linux via MPRIS metadata URL).
     if let Some(url) = player.file_path.as_deref() {
         if url.starts_with("http") {
             if let Some(idx) = stream_db.match_url(url) {
-                let title = player.media_title.as_deref()?;
-                let extracted = stream_db.extract_title(idx, title)?;
+                let known = stream_db.lookup_episode_id(idx, url);
+                let extracted = title
+                    .and_then(|t| stream_db.extract_title(idx, t))
+                    .or_else(|| known.and_then(|k| k.title.clone()))?;
                 let service = stream_db.service_name(idx)?.to_string();
                 tracing::debug!(strategy = "url", service = %service, "Stream URL pattern matched");
                 return Some(StreamMatch {
                     service_name: service,
                     extracted_title: extracted,
+                    episode: stream_db
+                        .extract_episode(idx, title, Some(url))
+                        .or(known.map(|k| k.episode)),
+                    season: stream_db
+                        .extract_season(idx, title, Some(url))
+                        .or(known.and_then(|k| k.season)),
                 });
             }
             tracing::debug!(url = %url, "URL did not match any stream service");
@@ -175,7 +296,7 @@ pub fn detect_stream(
     }
 
     // Strategy 2: Title matching (fallback — Windows or when no URL is available).
-    if let Some(title) = player.media_title.as_deref() {
+    if let Some(title) = title {
         if let Some(idx) = stream_db.match_title(title) {
             let extracted = stream_db.extract_title(idx, title)?;
             let service = stream_db.service_name(idx)?.to_string();
@@ -183,6 +304,8 @@ pub fn detect_stream(
             return Some(StreamMatch {
                 service_name: service,
                 extracted_title: extracted,
+                episode: stream_db.extract_episode(idx, Some(title), None),
+                season: stream_db.extract_season(idx, Some(title), None),
             });
         }
     }
@@ -287,6 +410,7 @@ mod tests {
             media_title: Some("Attack on Titan - Watch on Crunchyroll".into()),
             file_path: None,
             is_browser: false,
+            ..Default::default()
         };
         assert!(detect_stream(&player, &db).is_none());
     }
@@ -299,6 +423,7 @@ mod tests {
             media_title: Some("Attack on Titan - Watch on Crunchyroll".into()),
             file_path: Some("https://www.crunchyroll.com/watch/G1XHJV2W1/episode-5".into()),
             is_browser: true,
+            ..Default::default()
         };
         let m = detect_stream(&player, &db).unwrap();
         assert_eq!(m.service_name, "Crunchyroll");
@@ -313,6 +438,7 @@ mod tests {
             media_title: Some("One Piece | Netflix".into()),
             file_path: None,
             is_browser: true,
+            ..Default::default()
         };
         let m = detect_stream(&player, &db).unwrap();
         assert_eq!(m.service_name, "Netflix");
@@ -327,6 +453,7 @@ mod tests {
             media_title: Some("GitHub - rust-lang/rust".into()),
             file_path: Some("https://github.com/rust-lang/rust".into()),
             is_browser: true,
+            ..Default::default()
         };
         assert!(detect_stream(&player, &db).is_none());
     }
@@ -364,4 +491,130 @@ mod tests {
             .unwrap();
         assert_eq!(db.service_name(idx).unwrap(), "Funimation");
     }
+
+    #[test]
+    fn test_invalid_patterns_reported() {
+        let mut db = StreamDatabase::embedded();
+        assert!(db.pattern_errors().is_empty());
+
+        let user_toml = r#"
+            [[stream]]
+            name = "Broken"
+            url_patterns = ["broken\\.com/(watch", "broken\\.com/v/"]
+            title_pattern = "^(.+?) - Broken$"
+        "#;
+        let user_db = StreamDatabase::from_toml(user_toml).unwrap();
+        assert_eq!(user_db.pattern_errors().len(), 1);
+        let err = &user_db.pattern_errors()[0];
+        assert_eq!(err.owner, "Broken");
+        assert_eq!(err.field, "url_patterns");
+
+        // The valid pattern still works.
+        db.merge_user(&user_db);
+        assert!(db.match_url("https://broken.com/v/123").is_some());
+        assert_eq!(db.pattern_errors().len(), 1);
+
+        // Replacing the stream with a fixed definition clears its diagnostics.
+        let fixed = StreamDatabase::from_toml(
+            r#"
+            [[stream]]
+            name = "Broken"
+            url_patterns = ["broken\\.com/watch"]
+            title_pattern = "^(.+?) - Broken$"
+        "#,
+        )
+        .unwrap();
+        db.merge_user(&fixed);
+        assert!(db.pattern_errors().is_empty());
+    }
+
+    #[test]
+    fn test_episode_from_title_and_url() {
+        let db = StreamDatabase::embedded();
+        let player = PlayerInfo {
+            player_name: "Firefox".into(),
+            media_title: Some("Attack on Titan Season 3 Episode 12 - Watch on Crunchyroll".into()),
+            file_path: Some("https://www.crunchyroll.com/watch/G1XHJV2W1/episode-5".into()),
+            is_browser: true,
+            ..Default::default()
+        };
+        let m = detect_stream(&player, &db).unwrap();
+        // The tab title wins over the URL.
+        assert_eq!(m.episode, Some(12));
+        assert_eq!(m.season, Some(3));
+
+        let player = PlayerInfo {
+            media_title: Some("Attack on Titan - Watch on Crunchyroll".into()),
+            ..player
+        };
+        let m = detect_stream(&player, &db).unwrap();
+        assert_eq!(m.episode, Some(5));
+        assert_eq!(m.season, None);
+    }
+
+    #[test]
+    fn test_episode_from_title_only() {
+        let db = StreamDatabase::embedded();
+        let player = PlayerInfo {
+            player_name: "Chrome".into(),
+            media_title: Some("\u{25b6} Frieren \u{2014} S2 \u{b7} E3".into()),
+            is_browser: true,
+            ..Default::default()
+        };
+        let m = detect_stream(&player, &db).unwrap();
+        assert_eq!(m.service_name, "Plex");
+        assert_eq!(m.season, Some(2));
+        assert_eq!(m.episode, Some(3));
+    }
+
+    #[test]
+    fn test_episode_id_table() {
+        let db = StreamDatabase::from_toml(
+            r#"
+            [[stream]]
+            name = "Netflix"
+            url_patterns = ["netflix\\.com/watch/"]
+            title_pattern = "^(.+?)\\s*\\|\\s*Netflix$"
+            episode_id_pattern = "netflix\\.com/watch/(\\d+)"
+
+            [stream.episode_ids]
+            "81564905" = { title = "Frieren", season = 1, episode = 5 }
+            "81564906" = { episode = 6 }
+        "#,
+        )
+        .unwrap();
+        assert!(db.pattern_errors().is_empty());
+
+        // The tab title is just "Netflix": the table supplies everything.
+        let player = PlayerInfo {
+            player_name: "Firefox".into(),
+            media_title: Some("Netflix".into()),
+            file_path: Some("https://www.netflix.com/watch/81564905?trackId=1".into()),
+            is_browser: true,
+            ..Default::default()
+        };
+        let m = detect_stream(&player, &db).unwrap();
+        assert_eq!(m.extracted_title, "Frieren");
+        assert_eq!((m.season, m.episode), (Some(1), Some(5)));
+
+        // Without a title in the table, the tab title still has to provide one.
+        let player = PlayerInfo {
+            file_path: Some("https://www.netflix.com/watch/81564906".into()),
+            ..player
+        };
+        assert!(detect_stream(&player, &db).is_none());
+        let player = PlayerInfo {
+            media_title: Some("Frieren | Netflix".into()),
+            ..player
+        };
+        let m = detect_stream(&player, &db).unwrap();
+        assert_eq!((m.season, m.episode), (None, Some(6)));
+
+        // Unknown IDs match the service but carry no episode.
+        let player = PlayerInfo {
+            file_path: Some("https://www.netflix.com/watch/1".into()),
+            ..player
+        };
+        assert_eq!(detect_stream(&player, &db).unwrap().episode, None);
+    }
 }
@@ -1,3 +1,8 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
 use iced::widget::{button, column, container, row, stack, text, tooltip};
 use iced::window;
 use iced::{Alignment, Element, Length, Subscription, Task, Theme};
@@ -5,18 +10,26 @@ use iced::{Alignment, Element, Length, Subscription, Task, Theme};
 use chrono::Utc;
 use ryuuji_core::config::AppConfig;
 use ryuuji_core::debug_log::{self, DebugEvent, SharedEventLog};
+use ryuuji_core::detect_db::DetectionDatabases;
 use ryuuji_core::models::{Anime, AnimeIds, AnimeTitle, DetectedMedia, LibraryEntry, WatchStatus};
-use ryuuji_core::orchestrator::UpdateOutcome;
+use ryuuji_core::orchestrator::{self, UpdateOutcome};
+use ryuuji_core::rename::{FilenameTemplate, RenamePlan};
 use ryuuji_core::storage::LibraryRow;
 use ryuuji_core::updater;
+use ryuuji_detect::{
+    BrowserBridge, Detector, PlayerArbiter, RecordingDetector, ReplayDetector, StreamDatabase,
+    SystemDetector,
+};
+use ryuuji_parse::KeywordSet;
 
 use crate::cover_cache::{self, CoverCache, CoverState};
 use crate::db::DbHandle;
 use crate::discord::DiscordHandle;
+use crate::format;
 use crate::keyboard::Shortcut;
 use crate::screen::{
-    history, library, now_playing, search, seasons, settings, stats, torrents, Action,
-    ContextAction, ModalKind, Page,
+    correction, debug, history, library, now_playing, search, seasons, settings, stats, torrents,
+    Action, ContextAction, ModalKind, Page,
 };
 use crate::style;
 use crate::subscription;
@@ -33,6 +46,15 @@ pub struct Ryuuji {
     config: AppConfig,
     db: Option<DbHandle>,
     event_log: SharedEventLog,
+    /// Player/stream databases including the user's override files.
+    detect_db: DetectionDatabases,
+    /// Picks which detected player to track; shared with the detection task.
+    player_arbiter: Arc<Mutex<PlayerArbiter>>,
+    /// Live detection, or a recording being written / played back.
+    detection_source: DetectionSource,
+    /// Last MPRIS query, reused between watcher events.
+    #[cfg(target_os = "linux")]
+    mpris_cache: ryuuji_detect::MprisCache,
     // Theme
     current_theme: RyuujiTheme,
     active_mode: ThemeMode,
@@ -47,6 +69,8 @@ pub struct Ryuuji {
     settings: settings::Settings,
     // Cover images
     cover_cache: CoverCache,
+    /// "This is actually…" dialog, opened from Now Playing or Debug.
+    correction: correction::CorrectionDialog,
     // App-level chrome
     modal_state: Option<ModalKind>,
     status_message: String,
@@ -54,9 +78,49 @@ pub struct Ryuuji {
     window_state: WindowState,
     // Discord Rich Presence
     discord: Option<DiscordHandle>,
+    // Browser extension endpoint, and the port it was last started with
+    browser_bridge: Option<BrowserBridge>,
+    browser_bridge_port: Option<u16>,
     // Toast notifications
     toasts: Vec<Toast>,
     next_toast_id: u64,
+    // Titles looked up online this session (`library.online_fallback`)
+    online_lookups: HashMap<String, OnlineLookup>,
+}
+
+/// Wait before retrying a failed online lookup, doubled on each failure in
+/// a row up to [`ONLINE_RETRY_MAX`].
+const ONLINE_RETRY_MIN: Duration = Duration::from_secs(30);
+const ONLINE_RETRY_MAX: Duration = Duration::from_secs(30 * 60);
+
+/// Online lookups of one title.
+#[derive(Debug, Clone, Copy, Default)]
+struct OnlineLookup {
+    /// Failed lookups in a row.
+    failures: u32,
+    /// When the title may be looked up again. `None` while a lookup is
+    /// running or after one succeeded.
+    retry_at: Option<Instant>,
+}
+
+impl OnlineLookup {
+    fn failed(&mut self) {
+        self.failures += 1;
+        let backoff = ONLINE_RETRY_MIN.saturating_mul(1 << (self.failures - 1).min(16));
+        self.retry_at = Some(Instant::now() + backoff.min(ONLINE_RETRY_MAX));
+    }
+}
+
+/// Where detection ticks get their player snapshots from.
+#[derive(Clone, Default)]
+pub enum DetectionSource {
+    /// The running system (MPRIS, IPC sockets, remote players, ...).
+    #[default]
+    Live,
+    /// Live detection, appending every snapshot to a JSON-lines file.
+    Record(Arc<std::fs::File>),
+    /// Snapshots played back from a recording, one per tick.
+    Replay(Arc<Mutex<ReplayDetector>>),
 }
 
 impl Default for Ryuuji {
@@ -83,11 +147,25 @@ impl Default for Ryuuji {
             None
         };
 
+        let player_arbiter = Arc::new(Mutex::new(PlayerArbiter::new(
+            config.general.player_priority.clone(),
+        )));
+
+        let detect_db = DetectionDatabases::load();
+        if let Some(db) = &db {
+            db.set_keywords(detect_db.keywords.clone());
+        }
+
         Self {
             page: Page::default(),
             config,
             db,
             event_log,
+            detect_db,
+            player_arbiter,
+            detection_source: DetectionSource::default(),
+            #[cfg(target_os = "linux")]
+            mpris_cache: ryuuji_detect::MprisCache::default(),
             current_theme,
             active_mode,
             now_playing: now_playing::NowPlaying::new(),
@@ -99,12 +177,16 @@ impl Default for Ryuuji {
             stats: stats::Stats::new(),
             settings: settings_screen,
             cover_cache: CoverCache::default(),
+            correction: correction::CorrectionDialog::new(),
             modal_state: None,
             status_message: "Ready".into(),
             window_state: WindowState::load(),
             discord,
+            browser_bridge: None,
+            browser_bridge_port: None,
             toasts: Vec::new(),
             next_toast_id: 0,
+            online_lookups: HashMap::new(),
         }
     }
 }
@@ -118,8 +200,19 @@ pub enum Message {
         result: Result<std::path::PathBuf, String>,
     },
     DetectionTick,
+    /// The MPRIS watcher started (`true`) or stopped.
+    #[cfg(target_os = "linux")]
+    MprisWatching(bool),
+    /// An MPRIS player appeared, quit, or changed what it's playing.
+    #[cfg(target_os = "linux")]
+    MprisChanged,
     DetectionResult(Option<DetectedMedia>),
     DetectionProcessed(Result<UpdateOutcome, String>),
+    /// Looking a title up online failed; it may be tried again.
+    OnlineRecognitionFailed {
+        query: String,
+        error: String,
+    },
     SyncPushResult(Result<(), String>),
     AppearanceChanged(ThemeMode),
     WindowEvent(window::Event),
@@ -132,12 +225,21 @@ pub enum Message {
     TorrentTick,
     Stats(stats::Message),
     Settings(settings::Message),
+    Correction(correction::Message),
     Shortcut(Shortcut),
     ShowToast(String, ToastKind),
     DismissToast(u64),
 }
 
 impl Ryuuji {
+    /// Start the app with detection coming from `source` instead of the
+    /// live system.
+    pub fn with_detection_source(source: DetectionSource) -> (Self, Task<Message>) {
+        let (mut app, task) = Self::new();
+        app.detection_source = source;
+        (app, task)
+    }
+
     pub fn new() -> (Self, Task<Message>) {
         let mut app = Self::default();
 
@@ -187,6 +289,9 @@ impl Ryuuji {
             Task::none()
         };
 
+        let pending_task = app.load_pending_updates();
+        let diagnostics_task = app.report_detect_diagnostics();
+
         // Check for app updates on startup (if enabled).
         let update_task = if app.config.update.check_on_startup {
             app.settings.update_state = updater::UpdateState::Checking;
@@ -203,7 +308,10 @@ impl Ryuuji {
             Task::none()
         };
 
-        (app, Task::batch([token_task, update_task]))
+        (
+            app,
+            Task::batch([token_task, update_task, pending_task, diagnostics_task]),
+        )
     }
 
     pub fn title(&self) -> String {
@@ -272,9 +380,71 @@ impl Ryuuji {
                 }
                 Task::none()
             }
+            #[cfg(target_os = "linux")]
+            Message::MprisWatching(watching) => {
+                self.mpris_cache.set_watching(watching);
+                Task::none()
+            }
+            #[cfg(target_os = "linux")]
+            Message::MprisChanged => {
+                self.mpris_cache.invalidate();
+                Task::done(Message::DetectionTick)
+            }
             Message::DetectionTick => {
-                let log = self.event_log.clone();
-                Task::perform(detect_and_parse(log), Message::DetectionResult)
+                let reload_task = if self.detect_db.reload_if_changed() {
+                    // Cached players were resolved against the old database.
+                    #[cfg(target_os = "linux")]
+                    self.mpris_cache.invalidate();
+                    if let Some(db) = &self.db {
+                        db.set_keywords(self.detect_db.keywords.clone());
+                    }
+                    self.report_detect_diagnostics()
+                } else {
+                    Task::none()
+                };
+                let bridge_task = self.sync_browser_bridge();
+                self.player_arbiter
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .set_priority(self.config.general.player_priority.clone());
+                let options = ryuuji_detect::DetectOptions {
+                    mpv_ipc_sockets: self.config.general.mpv_ipc_sockets.clone(),
+                    kodi: self.config.remote_players.kodi.endpoint(),
+                    vlc_http: self.config.remote_players.vlc.endpoint(),
+                    jellyfin: self.config.media_servers.jellyfin.endpoint(),
+                    plex: self.config.media_servers.plex.endpoint(),
+                    firefox_sessions: self
+                        .config
+                        .general
+                        .firefox_sessions
+                        .then(|| self.detect_db.streams.clone()),
+                    browser_bridge: self.browser_bridge.as_ref().map(BrowserBridge::reports),
+                    #[cfg(target_os = "linux")]
+                    mpris_cache: Some(self.mpris_cache.clone()),
+                };
+                let live = SystemDetector::new(self.detect_db.players.clone(), options);
+                let detector: Box<dyn Detector> = match &self.detection_source {
+                    DetectionSource::Live => Box::new(live),
+                    DetectionSource::Record(file) => {
+                        Box::new(RecordingDetector::new(live, file.clone()))
+                    }
+                    DetectionSource::Replay(replay) => Box::new(replay.clone()),
+                };
+                Task::batch([
+                    reload_task,
+                    bridge_task,
+                    Task::perform(
+                        detect_and_parse(
+                            self.event_log.clone(),
+                            detector,
+                            self.detect_db.streams.clone(),
+                            self.detect_db.keywords.clone(),
+                            self.config.library.watch_folders.clone(),
+                            self.player_arbiter.clone(),
+                        ),
+                        Message::DetectionResult,
+                    ),
+                ])
             }
             Message::DetectionResult(media) => {
                 if media.is_none() {
@@ -298,6 +468,17 @@ impl Ryuuji {
                 }
                 Task::none()
             }
+            Message::OnlineRecognitionFailed { query, error } => {
+                let lookup = self.online_lookups.entry(query).or_default();
+                lookup.failed();
+                // Only the first failure is worth showing; retries of a
+                // service that is down would keep replacing the status.
+                if lookup.failures > 1 {
+                    tracing::warn!(error = %error, failures = lookup.failures, "Online lookup failed again");
+                    return Task::none();
+                }
+                self.update(Message::DetectionProcessed(Err(error)))
+            }
             Message::DetectionProcessed(result) => {
                 let mut follow_up = Task::none();
                 match result {
@@ -317,6 +498,26 @@ impl Ryuuji {
                             } => {
                                 format!("Added {anime_title} (ep {episode}) to library")
                             }
+                            UpdateOutcome::InProgress {
+                                anime_title,
+                                episode,
+                                watched,
+                                required,
+                                ..
+                            } => {
+                                format!(
+                                    "Watching {anime_title} ep {episode} ({} / {})",
+                                    format::playback_time(*watched),
+                                    format::playback_time(*required),
+                                )
+                            }
+                            UpdateOutcome::PendingConfirmation {
+                                anime_title,
+                                episode,
+                                ..
+                            } => {
+                                format!("Confirm update: {anime_title} ep {episode}")
+                            }
                             UpdateOutcome::AlreadyCurrent { .. } => self.status_message.clone(),
                             UpdateOutcome::Unrecognized { raw_title } => {
                                 format!("Unrecognized: {raw_title}")
@@ -328,6 +529,8 @@ impl Ryuuji {
                         let anime_id = match &outcome {
                             UpdateOutcome::Updated { anime_id, .. }
                             | UpdateOutcome::AlreadyCurrent { anime_id, .. }
+                            | UpdateOutcome::InProgress { anime_id, .. }
+                            | UpdateOutcome::PendingConfirmation { anime_id, .. }
                             | UpdateOutcome::AddedToLibrary { anime_id, .. } => Some(*anime_id),
                             _ => None,
                         };
@@ -361,6 +564,26 @@ impl Ryuuji {
                         };
                         follow_up = Task::batch([follow_up, sync_task]);
 
+                        // Surface newly queued confirmations.
+                        if let UpdateOutcome::PendingConfirmation {
+                            anime_id,
+                            anime_title,
+                            episode,
+                        } = &outcome
+                        {
+                            if !self.now_playing.has_pending(*anime_id, *episode) {
+                                let toast_task = self.update(Message::ShowToast(
+                                    format!("Update {anime_title} to episode {episode}?"),
+                                    ToastKind::Info,
+                                ));
+                                follow_up = Task::batch([
+                                    follow_up,
+                                    toast_task,
+                                    self.load_pending_updates(),
+                                ]);
+                            }
+                        }
+
                         // Update Discord Rich Presence.
                         if let Some(discord) = &self.discord {
                             match &outcome {
@@ -383,7 +606,35 @@ impl Ryuuji {
                                             .and_then(|d| d.service_name.clone()),
                                     );
                                 }
-                                UpdateOutcome::AlreadyCurrent { .. } => {
+                                UpdateOutcome::InProgress {
+                                    anime_id,
+                                    anime_title,
+                                    episode,
+                                    ..
+                                } => {
+                                    // Only announce when playback moves to a new episode;
+                                    // later ticks for the same file leave presence as is.
+                                    let same_episode = matches!(
+                                        &self.now_playing.last_outcome,
+                                        Some(UpdateOutcome::InProgress {
+                                            anime_id: prev_id,
+                                            episode: prev_ep,
+                                            ..
+                                        }) if prev_id == anime_id && prev_ep == episode
+                                    );
+                                    if !same_episode {
+                                        discord.update_presence(
+                                            anime_title.clone(),
+                                            Some(*episode),
+                                            self.now_playing
+                                                .detected
+                                                .as_ref()
+                                                .and_then(|d| d.service_name.clone()),
+                                        );
+                                    }
+                                }
+                                UpdateOutcome::AlreadyCurrent { .. }
+                                | UpdateOutcome::PendingConfirmation { .. } => {
                                     // Presence is already set — no change needed.
                                 }
                                 UpdateOutcome::NothingPlaying
@@ -393,6 +644,11 @@ impl Ryuuji {
                             }
                         }
 
+                        // Not in the local database: try the primary service.
+                        if matches!(outcome, UpdateOutcome::Unrecognized { .. }) {
+                            follow_up = Task::batch([follow_up, self.spawn_online_recognition()]);
+                        }
+
                         self.now_playing.last_outcome = Some(outcome);
                     }
                     Err(e) => {
@@ -483,6 +739,35 @@ impl Ryuuji {
                     }
                     Task::none()
                 }
+                now_playing::Message::PendingLoaded(pending) => {
+                    self.now_playing.pending = pending;
+                    Task::none()
+                }
+                now_playing::Message::ApprovePending(anime_id) => {
+                    self.approve_pending_update(anime_id, false)
+                }
+                now_playing::Message::AlwaysApprovePending(anime_id) => {
+                    self.approve_pending_update(anime_id, true)
+                }
+                now_playing::Message::RejectPending(anime_id) => {
+                    self.reject_pending_update(anime_id)
+                }
+                now_playing::Message::CorrectRecognition => self.open_correction(),
+                now_playing::Message::PendingResolved(result) => {
+                    let reload = self.load_pending_updates();
+                    match result {
+                        Ok(Some(outcome)) => {
+                            let task = self.update(Message::DetectionProcessed(Ok(outcome)));
+                            Task::batch([task, reload])
+                        }
+                        Ok(None) => reload,
+                        Err(e) => {
+                            tracing::error!(error = %e, "Failed to resolve pending update");
+                            self.status_message = format!("Error: {e}");
+                            reload
+                        }
+                    }
+                }
             },
             Message::History(msg) => {
                 // Intercept ConfirmDelete to fire remote sync before local delete.
@@ -989,9 +1274,22 @@ impl Ryuuji {
                 self.toasts.retain(|t| t.id != id);
                 Task::none()
             }
+            Message::Correction(msg) => {
+                if matches!(
+                    msg,
+                    correction::Message::Pick(_) | correction::Message::Cancel
+                ) {
+                    self.modal_state = None;
+                }
+                let action = self.correction.update(msg, self.db.as_ref(), &self.config);
+                self.handle_action(action)
+            }
             Message::Settings(ref msg) => {
                 // Intercept async actions before delegating to settings.
                 match msg {
+                    settings::Message::DebugMsg(debug::Message::CorrectRecognition) => {
+                        self.open_correction()
+                    }
                     settings::Message::AniListLogin => {
                         let msg = msg.clone();
                         self.settings.update(msg, &mut self.config);
@@ -1032,6 +1330,26 @@ impl Ryuuji {
                         self.settings.update(msg, &mut self.config);
                         self.spawn_watch_folder_scan()
                     }
+                    settings::Message::PreviewRenames => {
+                        let msg = msg.clone();
+                        self.settings.update(msg, &mut self.config);
+                        if self.settings.rename_busy {
+                            self.spawn_rename_preview()
+                        } else {
+                            Task::none()
+                        }
+                    }
+                    settings::Message::ApplyRenames => {
+                        let plans = self.settings.rename_plans.clone();
+                        let msg = msg.clone();
+                        self.settings.update(msg, &mut self.config);
+                        self.spawn_rename_apply(plans)
+                    }
+                    settings::Message::UndoRename => {
+                        let msg = msg.clone();
+                        self.settings.update(msg, &mut self.config);
+                        self.spawn_rename_undo()
+                    }
                     settings::Message::CheckForUpdates => {
                         let msg = msg.clone();
                         self.settings.update(msg, &mut self.config);
@@ -1571,6 +1889,64 @@ impl Ryuuji {
         )
     }
 
+    /// Spawn a dry run of renaming library files to the configured template.
+    fn spawn_rename_preview(&self) -> Task<Message> {
+        let Some(db) = self.db.clone() else {
+            return Task::none();
+        };
+        let template = self.config.library.rename_template.clone();
+
+        Task::perform(
+            async move {
+                let template = FilenameTemplate::parse(&template).map_err(|e| e.to_string())?;
+                db.plan_renames(template).await.map_err(|e| e.to_string())
+            },
+            |result| Message::Settings(settings::Message::RenamePreviewLoaded(result)),
+        )
+    }
+
+    /// Spawn the renames from the last preview.
+    fn spawn_rename_apply(&self, plans: Vec<RenamePlan>) -> Task<Message> {
+        let Some(db) = self.db.clone() else {
+            return Task::none();
+        };
+
+        Task::perform(
+            async move {
+                let outcome = db.apply_renames(plans).await.map_err(|e| e.to_string())?;
+                Ok(format!(
+                    "Renamed {} files, skipped {}, failed {}",
+                    outcome.renamed,
+                    outcome.skipped,
+                    outcome.failed.len()
+                ))
+            },
+            |result| Message::Settings(settings::Message::RenameResult(result)),
+        )
+    }
+
+    /// Spawn an undo of the most recent rename batch.
+    fn spawn_rename_undo(&self) -> Task<Message> {
+        let Some(db) = self.db.clone() else {
+            return Task::none();
+        };
+
+        Task::perform(
+            async move {
+                match db.undo_last_rename().await.map_err(|e| e.to_string())? {
+                    Some(outcome) => Ok(format!(
+                        "Restored {} files, skipped {}, failed {}",
+                        outcome.renamed,
+                        outcome.skipped,
+                        outcome.failed.len()
+                    )),
+                    None => Ok("Nothing to undo".into()),
+                }
+            },
+            |result| Message::Settings(settings::Message::RenameResult(result)),
+        )
+    }
+
     /// Spawn a GitHub Releases check for a newer version.
     fn spawn_update_check(&self) -> Task<Message> {
         let include_pre = self.config.update.include_prerelease;
@@ -1624,84 +2000,88 @@ impl Ryuuji {
 
     /// Spawn an online search as an async task using the primary service.
     fn spawn_online_search(&self, query: String) -> Task<Message> {
-        let primary = self.config.services.primary.clone();
-        match primary.as_str() {
-            "anilist" => self.spawn_anilist_search(query),
-            "kitsu" => self.spawn_kitsu_search(query),
-            _ => self.spawn_mal_search(query),
-        }
-    }
-
-    /// Spawn an online MAL search as an async task.
-    fn spawn_mal_search(&self, query: String) -> Task<Message> {
         let Some(db) = self.db.clone() else {
             return Task::none();
         };
-        let client_id = self.settings.mal_client_id.trim().to_string();
-        if client_id.is_empty() {
+        let primary = self.config.services.primary.clone();
+        let mal_client_id = self.settings.mal_client_id.trim().to_string();
+        if !matches!(primary.as_str(), "anilist" | "kitsu") && mal_client_id.is_empty() {
             return Task::none();
         }
 
         Task::perform(
-            async move {
-                use ryuuji_api::traits::AnimeService;
-
-                let token = db
-                    .get_service_token("mal")
-                    .await
-                    .map_err(|e| e.to_string())?
-                    .ok_or_else(|| "Not logged in to MAL".to_string())?;
-
-                let client = ryuuji_api::mal::MalClient::new(client_id, token);
-                client.search_anime(&query).await.map_err(|e| e.to_string())
-            },
+            async move { search_primary_service(&db, &primary, mal_client_id, &query).await },
             |result| Message::Search(search::Message::OnlineResultsLoaded(result)),
         )
     }
 
-    /// Spawn an online AniList search as an async task.
-    fn spawn_anilist_search(&self, query: String) -> Task<Message> {
-        let Some(db) = self.db.clone() else {
-            return Task::none();
+    /// Show the "This is actually…" dialog for the current detection.
+    fn open_correction(&mut self) -> Task<Message> {
+        let Some(detected) = self.now_playing.detected.clone() else {
+            return self.update(Message::ShowToast(
+                "Nothing is playing".into(),
+                ToastKind::Info,
+            ));
         };
-
-        Task::perform(
-            async move {
-                use ryuuji_api::traits::AnimeService;
-
-                let token = db
-                    .get_service_token("anilist")
-                    .await
-                    .map_err(|e| e.to_string())?
-                    .ok_or_else(|| "Not logged in to AniList".to_string())?;
-
-                let client = ryuuji_api::anilist::AniListClient::new(token);
-                client.search_anime(&query).await.map_err(|e| e.to_string())
-            },
-            |result| Message::Search(search::Message::OnlineResultsLoaded(result)),
-        )
+        self.modal_state = Some(ModalKind::CorrectRecognition);
+        let action = self.correction.open(detected, self.db.as_ref());
+        self.handle_action(action)
     }
 
-    /// Spawn an online Kitsu search as an async task.
-    fn spawn_kitsu_search(&self, query: String) -> Task<Message> {
-        let Some(db) = self.db.clone() else {
+    /// Look the playing title up on the primary service and recognize it from
+    /// the results. A title is looked up once per session, or again after a
+    /// backoff if the lookup failed.
+    fn spawn_online_recognition(&mut self) -> Task<Message> {
+        if !self.config.library.online_fallback || !self.is_primary_service_authenticated() {
+            return Task::none();
+        }
+        let (Some(db), Some(detected)) = (self.db.clone(), self.now_playing.detected.clone())
+        else {
+            return Task::none();
+        };
+        let Some(query) = orchestrator::online_query(&detected) else {
             return Task::none();
         };
+        if detected.episode.is_none() {
+            return Task::none();
+        }
+        // Marked before the search so later ticks don't start another one;
+        // given a retry time if it fails.
+        match self.online_lookups.entry(query.clone()) {
+            Entry::Vacant(entry) => {
+                entry.insert(OnlineLookup::default());
+            }
+            Entry::Occupied(mut entry) => {
+                let lookup = entry.get_mut();
+                if lookup.retry_at.is_none_or(|at| at > Instant::now()) {
+                    return Task::none();
+                }
+                lookup.retry_at = None;
+            }
+        }
+        let primary = self.config.services.primary.clone();
+        let mal_client_id = self.settings.mal_client_id.trim().to_string();
+        let config = self.config.clone();
+        let search = query.clone();
 
         Task::perform(
             async move {
-                use ryuuji_api::traits::AnimeService;
-
-                let token = db
-                    .get_service_token("kitsu")
+                let results = search_primary_service(&db, &primary, mal_client_id, &search).await?;
+                let candidates = results
+                    .iter()
+                    .map(|r| anime_from_search_result(&primary, r))
+                    .collect();
+                db.recognize_online(detected, candidates, config)
                     .await
-                    .map_err(|e| e.to_string())?
-                    .ok_or_else(|| "Not logged in to Kitsu".to_string())?;
-
-                let client = ryuuji_api::kitsu::KitsuClient::new(token);
-                client.search_anime(&query).await.map_err(|e| e.to_string())
+                    .map_err(|e| e.to_string())
+            },
+            move |result| match result {
+                Ok(outcome) => Message::DetectionProcessed(Ok(outcome)),
+                Err(error) => Message::OnlineRecognitionFailed {
+                    query: query.clone(),
+                    error,
+                },
             },
-            |result| Message::Search(search::Message::OnlineResultsLoaded(result)),
         )
     }
 
@@ -1770,48 +2150,7 @@ impl Ryuuji {
 
         Task::perform(
             async move {
-                let ids = match primary.as_str() {
-                    "anilist" => AnimeIds {
-                        anilist: Some(result.service_id),
-                        kitsu: None,
-                        mal: None,
-                    },
-                    "kitsu" => AnimeIds {
-                        anilist: None,
-                        kitsu: Some(result.service_id),
-                        mal: None,
-                    },
-                    _ => AnimeIds {
-                        anilist: None,
-                        kitsu: None,
-                        mal: Some(result.service_id),
-                    },
-                };
-
-                let anime = Anime {
-                    id: 0,
-                    ids,
-                    title: AnimeTitle {
-                        romaji: Some(result.title.clone()),
-                        english: result.title_english.clone(),
-                        native: None,
-                    },
-                    synonyms: Vec::new(),
-                    episodes: result.episodes,
-                    cover_url: result.cover_url.clone(),
-                    season: result.season.clone(),
-                    year: result.year,
-                    synopsis: result.synopsis.clone(),
-                    genres: result.genres.clone(),
-                    media_type: result.media_type.clone(),
-                    airing_status: result.status.clone(),
-                    mean_score: result.mean_score,
-                    studios: Vec::new(),
-                    source: None,
-                    rating: None,
-                    start_date: None,
-                    end_date: None,
-                };
+                let anime = anime_from_search_result(&primary, &result);
 
                 let entry = LibraryEntry {
                     id: 0,
@@ -1857,48 +2196,7 @@ impl Ryuuji {
 
         Task::perform(
             async move {
-                let ids = match primary.as_str() {
-                    "anilist" => AnimeIds {
-                        anilist: Some(result.service_id),
-                        kitsu: None,
-                        mal: None,
-                    },
-                    "kitsu" => AnimeIds {
-                        anilist: None,
-                        kitsu: Some(result.service_id),
-                        mal: None,
-                    },
-                    _ => AnimeIds {
-                        anilist: None,
-                        kitsu: None,
-                        mal: Some(result.service_id),
-                    },
-                };
-
-                let anime = Anime {
-                    id: 0,
-                    ids,
-                    title: AnimeTitle {
-                        romaji: Some(result.title.clone()),
-                        english: result.title_english.clone(),
-                        native: None,
-                    },
-                    synonyms: Vec::new(),
-                    episodes: result.episodes,
-                    cover_url: result.cover_url.clone(),
-                    season: result.season.clone(),
-                    year: result.year,
-                    synopsis: result.synopsis.clone(),
-                    genres: result.genres.clone(),
-                    media_type: result.media_type.clone(),
-                    airing_status: result.status.clone(),
-                    mean_score: result.mean_score,
-                    studios: Vec::new(),
-                    source: None,
-                    rating: None,
-                    start_date: None,
-                    end_date: None,
-                };
+                let anime = anime_from_search_result(&primary, &result);
 
                 let entry = LibraryEntry {
                     id: 0,
@@ -1934,6 +2232,118 @@ impl Ryuuji {
         )
     }
 
+    /// Start, stop or move the browser extension endpoint to match the
+    /// config. A failed start is reported once and only retried after the
+    /// setting changes.
+    fn sync_browser_bridge(&mut self) -> Task<Message> {
+        let bridge = &self.config.browser_bridge;
+        let wanted = bridge.enabled.then_some(bridge.port);
+        if wanted == self.browser_bridge_port {
+            return Task::none();
+        }
+        self.browser_bridge_port = wanted;
+        self.browser_bridge = None;
+        let Some(port) = wanted else {
+            return Task::none();
+        };
+        match BrowserBridge::start(port) {
+            Ok(bridge) => {
+                tracing::info!(port, "Browser bridge listening");
+                self.browser_bridge = Some(bridge);
+                Task::none()
+            }
+            Err(e) => {
+                let message = format!("Browser bridge: can't listen on port {port}: {e}");
+                self.event_log
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .push(DebugEvent::Error {
+                        source: "browser_bridge".into(),
+                        message: message.clone(),
+                    });
+                self.update(Message::ShowToast(message, ToastKind::Error))
+            }
+        }
+    }
+
+    /// Log problems in the user's players.toml/streams.toml and show a toast per file.
+    fn report_detect_diagnostics(&mut self) -> Task<Message> {
+        let diagnostics = self.detect_db.diagnostics();
+        if diagnostics.is_empty() {
+            return Task::none();
+        }
+        {
+            let mut log = self.event_log.lock().unwrap_or_else(|e| e.into_inner());
+            for d in diagnostics {
+                log.push(DebugEvent::Error {
+                    source: d.file.to_string(),
+                    message: d.message.clone(),
+                });
+            }
+        }
+        let mut files: Vec<&'static str> = diagnostics.iter().map(|d| d.file).collect();
+        files.dedup();
+        let toasts: Vec<_> = files
+            .into_iter()
+            .map(|file| {
+                let count = diagnostics.iter().filter(|d| d.file == file).count();
+                let message = if count == 1 {
+                    diagnostics
+                        .iter()
+                        .find(|d| d.file == file)
+                        .map(ToString::to_string)
+                        .unwrap_or_default()
+                } else {
+                    format!("{file}: {count} problems (see Debug)")
+                };
+                Message::ShowToast(message, ToastKind::Error)
+            })
+            .collect();
+        Task::batch(toasts.into_iter().map(|m| self.update(m)))
+    }
+
+    /// Reload the list of updates awaiting confirmation.
+    fn load_pending_updates(&self) -> Task<Message> {
+        let Some(db) = self.db.clone() else {
+            return Task::none();
+        };
+        Task::perform(
+            async move { db.get_pending_updates().await.unwrap_or_default() },
+            |pending| Message::NowPlaying(now_playing::Message::PendingLoaded(pending)),
+        )
+    }
+
+    /// Apply a pending update; `always` also skips confirmation for this anime from now on.
+    fn approve_pending_update(&self, anime_id: i64, always: bool) -> Task<Message> {
+        let Some(db) = self.db.clone() else {
+            return Task::none();
+        };
+        Task::perform(
+            async move {
+                db.approve_pending_update(anime_id, always)
+                    .await
+                    .map_err(|e| e.to_string())
+            },
+            |result| Message::NowPlaying(now_playing::Message::PendingResolved(result)),
+        )
+    }
+
+    /// Dismiss a pending update without touching the library.
+    fn reject_pending_update(&self, anime_id: i64) -> Task<Message> {
+        let Some(db) = self.db.clone() else {
+            return Task::none();
+        };
+        Task::perform(
+            async move {
+                db.reject_pending_update(anime_id)
+                    .await
+                    .map(|()| None)
+                    .map_err(|e| e.to_string())
+            },
+            |result| Message::NowPlaying(now_playing::Message::PendingResolved(result)),
+        )
+    }
+
     /// Check if the primary service has an active authentication token.
     fn is_primary_service_authenticated(&self) -> bool {
         match self.config.services.primary.as_str() {
@@ -2315,6 +2725,7 @@ impl Ryuuji {
                     Page::History => Message::History(history::Message::CancelModal),
                     _ => Message::Library(library::Message::CancelModal),
                 },
+                ModalKind::CorrectRecognition => Message::Correction(correction::Message::Cancel),
             };
             crate::widgets::modal(main, modal_content, dismiss_msg)
         } else {
@@ -2348,7 +2759,7 @@ impl Ryuuji {
     }
 
     fn build_modal_content<'a>(
-        &self,
+        &'a self,
         cs: &ColorScheme,
         kind: &'a ModalKind,
     ) -> Element<'a, Message> {
@@ -2402,6 +2813,7 @@ impl Ryuuji {
                 .padding(style::SPACE_2XL)
                 .into()
             }
+            ModalKind::CorrectRecognition => self.correction.view(cs).map(Message::Correction),
         }
     }
 
@@ -2468,122 +2880,22 @@ impl Ryuuji {
 
 /// Perform media detection and filename parsing off the main thread.
 #[tracing::instrument(name = "detect_and_parse", skip_all)]
-async fn detect_and_parse(event_log: SharedEventLog) -> Option<DetectedMedia> {
-    let players = ryuuji_detect::detect_players();
-    tracing::debug!(player_count = players.len(), "Detection tick");
-
-    {
-        let mut log = event_log.lock().unwrap_or_else(|e| e.into_inner());
-        log.push(DebugEvent::DetectionTick {
-            players_found: players.len() as u32,
-        });
-    }
-
-    let player = players.into_iter().next()?;
-
-    {
-        let mut log = event_log.lock().unwrap_or_else(|e| e.into_inner());
-        log.push(DebugEvent::PlayerDetected {
-            player_name: player.player_name.clone(),
-            file_path: player.file_path.clone(),
-            is_browser: player.is_browser,
-            media_title: player.media_title.clone(),
-        });
-    }
-
-    if player.is_browser {
-        // Browser detected — try stream service matching.
-        let stream_db = ryuuji_detect::StreamDatabase::embedded();
-        let stream_match = ryuuji_detect::stream::detect_stream(&player, &stream_db);
-
-        let stream_match = match stream_match {
-            Some(m) => {
-                tracing::debug!(
-                    service = %m.service_name,
-                    title = %m.extracted_title,
-                    "Stream service matched"
-                );
-                {
-                    let mut log = event_log.lock().unwrap_or_else(|e| e.into_inner());
-                    log.push(DebugEvent::StreamMatched {
-                        service_name: m.service_name.clone(),
-                        extracted_title: m.extracted_title.clone(),
-                    });
-                }
-                m
-            }
-            None => {
-                tracing::debug!(player = %player.player_name, "Browser detected but no stream service matched");
-                {
-                    let mut log = event_log.lock().unwrap_or_else(|e| e.into_inner());
-                    log.push(DebugEvent::StreamNotMatched {
-                        player_name: player.player_name.clone(),
-                    });
-                }
-                return None;
-            }
-        };
-
-        let raw_title = stream_match.extracted_title;
-        let parsed = ryuuji_parse::parse(&raw_title);
-
-        {
-            let mut log = event_log.lock().unwrap_or_else(|e| e.into_inner());
-            log.push(DebugEvent::Parsed {
-                raw_title: raw_title.clone(),
-                title: parsed.title.clone(),
-                episode: parsed.episode_number,
-                group: parsed.release_group.clone(),
-                resolution: parsed.resolution.clone(),
-            });
-        }
-
-        return Some(DetectedMedia {
-            player_name: player.player_name,
-            anime_title: parsed.title,
-            episode: parsed.episode_number,
-            release_group: parsed.release_group,
-            resolution: parsed.resolution,
-            raw_title,
-            service_name: Some(stream_match.service_name),
-        });
-    }
-
-    // Regular media player — extract basename from file path or use media title.
-    let raw_title = player
-        .file_path
-        .as_deref()
-        .and_then(|p| {
-            std::path::Path::new(p)
-                .file_name()
-                .and_then(|f| f.to_str())
-                .map(|s| s.to_string())
-        })
-        .or_else(|| player.media_title.clone())?;
-
-    tracing::debug!(player = %player.player_name, raw_title = %raw_title, "Parsing filename");
-    let parsed = ryuuji_parse::parse(&raw_title);
-
-    {
-        let mut log = event_log.lock().unwrap_or_else(|e| e.into_inner());
-        log.push(DebugEvent::Parsed {
-            raw_title: raw_title.clone(),
-            title: parsed.title.clone(),
-            episode: parsed.episode_number,
-            group: parsed.release_group.clone(),
-            resolution: parsed.resolution.clone(),
-        });
-    }
-
-    Some(DetectedMedia {
-        player_name: player.player_name,
-        anime_title: parsed.title,
-        episode: parsed.episode_number,
-        release_group: parsed.release_group,
-        resolution: parsed.resolution,
-        raw_title,
-        service_name: None,
-    })
+async fn detect_and_parse(
+    event_log: SharedEventLog,
+    mut detector: Box<dyn Detector>,
+    streams: Arc<StreamDatabase>,
+    keywords: Arc<KeywordSet>,
+    watch_folders: Vec<String>,
+    arbiter: Arc<Mutex<PlayerArbiter>>,
+) -> Option<DetectedMedia> {
+    ryuuji_core::pipeline::detect_media(
+        &mut *detector,
+        &streams,
+        &keywords,
+        &watch_folders,
+        &arbiter,
+        &event_log,
+    )
 }
 
 /// Best-effort: add an anime to the remote service's list.
@@ -2672,3 +2984,83 @@ async fn sync_delete_from_remote(
         }
     }
 }
+
+/// Search `primary` for `query` with the stored token.
+async fn search_primary_service(
+    db: &DbHandle,
+    primary: &str,
+    mal_client_id: String,
+    query: &str,
+) -> Result<Vec<ryuuji_api::traits::AnimeSearchResult>, String> {
+    use ryuuji_api::traits::AnimeService;
+
+    let token = db
+        .get_service_token(primary)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Not logged in to {primary}"))?;
+
+    match primary {
+        "anilist" => ryuuji_api::anilist::AniListClient::new(token)
+            .search_anime(query)
+            .await
+            .map_err(|e| e.to_string()),
+        "kitsu" => ryuuji_api::kitsu::KitsuClient::new(token)
+            .search_anime(query)
+            .await
+            .map_err(|e| e.to_string()),
+        _ => ryuuji_api::mal::MalClient::new(mal_client_id, token)
+            .search_anime(query)
+            .await
+            .map_err(|e| e.to_string()),
+    }
+}
+
+/// A local anime row for a search result from `primary`.
+fn anime_from_search_result(
+    primary: &str,
+    result: &ryuuji_api::traits::AnimeSearchResult,
+) -> Anime {
+    let ids = match primary {
+        "anilist" => AnimeIds {
+            anilist: Some(result.service_id),
+            kitsu: None,
+            mal: None,
+        },
+        "kitsu" => AnimeIds {
+            anilist: None,
+            kitsu: Some(result.service_id),
+            mal: None,
+        },
+        _ => AnimeIds {
+            anilist: None,
+            kitsu: None,
+            mal: Some(result.service_id),
+        },
+    };
+
+    Anime {
+        id: 0,
+        ids,
+        title: AnimeTitle {
+            romaji: Some(result.title.clone()),
+            english: result.title_english.clone(),
+            native: None,
+        },
+        synonyms: Vec::new(),
+        episodes: result.episodes,
+        cover_url: result.cover_url.clone(),
+        season: result.season.clone(),
+        year: result.year,
+        synopsis: result.synopsis.clone(),
+        genres: result.genres.clone(),
+        media_type: result.media_type.clone(),
+        airing_status: result.status.clone(),
+        mean_score: result.mean_score,
+        studios: Vec::new(),
+        source: None,
+        rating: None,
+        start_date: None,
+        end_date: None,
+    }
+}
@@ -5,6 +5,7 @@
 //! render thread.
 
 use std::path::Path;
+use std::sync::Arc;
 
 use tokio::sync::{mpsc, oneshot};
 
@@ -15,12 +16,15 @@ use ryuuji_core::error::RyuujiError;
 use ryuuji_core::models::{
     Anime, AvailableEpisodeSummary, DetectedMedia, LibraryEntry, WatchStatus,
 };
-use ryuuji_core::orchestrator::{self, UpdateOutcome};
+use ryuuji_core::orchestrator::{self, Correction, UpdateOutcome};
 use ryuuji_core::recognition::RecognitionCache;
 use ryuuji_core::relations::RelationDatabase;
+use ryuuji_core::rename::{self, FilenameTemplate, RenameOutcome, RenamePlan};
 use ryuuji_core::scanner::{self, ScanResult};
-use ryuuji_core::storage::{HistoryRow, LibraryRow, LibraryStatistics, Storage};
+use ryuuji_core::storage::{HistoryRow, LibraryRow, LibraryStatistics, PendingUpdateRow, Storage};
 use ryuuji_core::torrent::{TorrentFeed, TorrentFilter, TorrentItem};
+use ryuuji_core::watch::{WatchPolicy, WatchTracker};
+use ryuuji_parse::KeywordSet;
 
 /// Cloneable handle to the DB actor thread.
 #[derive(Clone)]
@@ -67,6 +71,34 @@ enum DbCommand {
         config: Box<AppConfig>,
         reply: oneshot::Sender<Result<UpdateOutcome, RyuujiError>>,
     },
+    RecognizeOnline {
+        detected: DetectedMedia,
+        candidates: Vec<Anime>,
+        config: Box<AppConfig>,
+        reply: oneshot::Sender<Result<UpdateOutcome, RyuujiError>>,
+    },
+    CorrectRecognition {
+        detected: DetectedMedia,
+        correction: Correction,
+        config: Box<AppConfig>,
+        reply: oneshot::Sender<Result<UpdateOutcome, RyuujiError>>,
+    },
+    SearchAnime {
+        query: String,
+        reply: oneshot::Sender<Result<Vec<Anime>, RyuujiError>>,
+    },
+    GetPendingUpdates {
+        reply: oneshot::Sender<Result<Vec<PendingUpdateRow>, RyuujiError>>,
+    },
+    ApprovePendingUpdate {
+        anime_id: i64,
+        always: bool,
+        reply: oneshot::Sender<Result<Option<UpdateOutcome>, RyuujiError>>,
+    },
+    RejectPendingUpdate {
+        anime_id: i64,
+        reply: oneshot::Sender<Result<(), RyuujiError>>,
+    },
     SaveServiceToken {
         service: String,
         token: String,
@@ -149,6 +181,10 @@ enum DbCommand {
         reply: oneshot::Sender<Result<(), RyuujiError>>,
     },
     // ── Scanner commands ─────────────────────────────────────────
+    /// Parser keywords for scanning, renaming and torrent matching. No reply.
+    SetKeywords {
+        keywords: Arc<KeywordSet>,
+    },
     ScanWatchFolders {
         config: Box<AppConfig>,
         reply: oneshot::Sender<Result<ScanResult, RyuujiError>>,
@@ -156,6 +192,17 @@ enum DbCommand {
     GetAvailableEpisodeSummaries {
         reply: oneshot::Sender<Result<Vec<AvailableEpisodeSummary>, RyuujiError>>,
     },
+    PlanRenames {
+        template: FilenameTemplate,
+        reply: oneshot::Sender<Result<Vec<RenamePlan>, RyuujiError>>,
+    },
+    ApplyRenames {
+        plans: Vec<RenamePlan>,
+        reply: oneshot::Sender<Result<RenameOutcome, RyuujiError>>,
+    },
+    UndoLastRename {
+        reply: oneshot::Sender<Result<Option<RenameOutcome>, RyuujiError>>,
+    },
     // ── Statistics commands ──────────────────────────────────────
     GetLibraryStatistics {
         reply: oneshot::Sender<Result<LibraryStatistics, RyuujiError>>,
@@ -283,6 +330,84 @@ impl DbHandle {
             .unwrap_or_else(|_| Err(RyuujiError::Config("DB actor closed".into())))
     }
 
+    /// Recognize a detection from the primary service's search results; see
+    /// [`orchestrator::recognize_online`].
+    pub async fn recognize_online(
+        &self,
+        detected: DetectedMedia,
+        candidates: Vec<Anime>,
+        config: AppConfig,
+    ) -> Result<UpdateOutcome, RyuujiError> {
+        let (reply, rx) = oneshot::channel();
+        let _ = self.tx.send(DbCommand::RecognizeOnline {
+            detected,
+            candidates,
+            config: Box::new(config),
+            reply,
+        });
+        rx.await
+            .unwrap_or_else(|_| Err(RyuujiError::Config("DB actor closed".into())))
+    }
+
+    /// Teach recognition that a detection is a given anime and process it
+    /// again; see [`orchestrator::correct_recognition`].
+    pub async fn correct_recognition(
+        &self,
+        detected: DetectedMedia,
+        correction: Correction,
+        config: AppConfig,
+    ) -> Result<UpdateOutcome, RyuujiError> {
+        let (reply, rx) = oneshot::channel();
+        let _ = self.tx.send(DbCommand::CorrectRecognition {
+            detected,
+            correction,
+            config: Box::new(config),
+            reply,
+        });
+        rx.await
+            .unwrap_or_else(|_| Err(RyuujiError::Config("DB actor closed".into())))
+    }
+
+    /// Anime in the local database with a title containing `query`.
+    pub async fn search_anime(&self, query: String) -> Result<Vec<Anime>, RyuujiError> {
+        let (reply, rx) = oneshot::channel();
+        let _ = self.tx.send(DbCommand::SearchAnime { query, reply });
+        rx.await
+            .unwrap_or_else(|_| Err(RyuujiError::Config("DB actor closed".into())))
+    }
+
+    pub async fn get_pending_updates(&self) -> Result<Vec<PendingUpdateRow>, RyuujiError> {
+        let (reply, rx) = oneshot::channel();
+        let _ = self.tx.send(DbCommand::GetPendingUpdates { reply });
+        rx.await
+            .unwrap_or_else(|_| Err(RyuujiError::Config("DB actor closed".into())))
+    }
+
+    /// Apply a confirmed update; with `always`, stop asking for this anime.
+    pub async fn approve_pending_update(
+        &self,
+        anime_id: i64,
+        always: bool,
+    ) -> Result<Option<UpdateOutcome>, RyuujiError> {
+        let (reply, rx) = oneshot::channel();
+        let _ = self.tx.send(DbCommand::ApprovePendingUpdate {
+            anime_id,
+            always,
+            reply,
+        });
+        rx.await
+            .unwrap_or_else(|_| Err(RyuujiError::Config("DB actor closed".into())))
+    }
+
+    pub async fn reject_pending_update(&self, anime_id: i64) -> Result<(), RyuujiError> {
+        let (reply, rx) = oneshot::channel();
+        let _ = self
+            .tx
+            .send(DbCommand::RejectPendingUpdate { anime_id, reply });
+        rx.await
+            .unwrap_or_else(|_| Err(RyuujiError::Config("DB actor closed".into())))
+    }
+
     pub async fn save_service_token(
         &self,
         service: impl Into<String>,
@@ -465,6 +590,12 @@ impl DbHandle {
 
     // ── Scanner handle methods ─────────────────────────────────────
 
+    /// Parse filenames with `keywords` from now on. Call after loading the
+    /// detection databases and after every reload.
+    pub fn set_keywords(&self, keywords: Arc<KeywordSet>) {
+        let _ = self.tx.send(DbCommand::SetKeywords { keywords });
+    }
+
     pub async fn scan_watch_folders(&self, config: AppConfig) -> Result<ScanResult, RyuujiError> {
         let (reply, rx) = oneshot::channel();
         let _ = self.tx.send(DbCommand::ScanWatchFolders {
@@ -486,6 +617,33 @@ impl DbHandle {
             .unwrap_or_else(|_| Err(RyuujiError::Config("DB actor closed".into())))
     }
 
+    pub async fn plan_renames(
+        &self,
+        template: FilenameTemplate,
+    ) -> Result<Vec<RenamePlan>, RyuujiError> {
+        let (reply, rx) = oneshot::channel();
+        let _ = self.tx.send(DbCommand::PlanRenames { template, reply });
+        rx.await
+            .unwrap_or_else(|_| Err(RyuujiError::Config("DB actor closed".into())))
+    }
+
+    pub async fn apply_renames(
+        &self,
+        plans: Vec<RenamePlan>,
+    ) -> Result<RenameOutcome, RyuujiError> {
+        let (reply, rx) = oneshot::channel();
+        let _ = self.tx.send(DbCommand::ApplyRenames { plans, reply });
+        rx.await
+            .unwrap_or_else(|_| Err(RyuujiError::Config("DB actor closed".into())))
+    }
+
+    pub async fn undo_last_rename(&self) -> Result<Option<RenameOutcome>, RyuujiError> {
+        let (reply, rx) = oneshot::channel();
+        let _ = self.tx.send(DbCommand::UndoLastRename { reply });
+        rx.await
+            .unwrap_or_else(|_| Err(RyuujiError::Config("DB actor closed".into())))
+    }
+
     pub async fn get_library_statistics(&self) -> Result<LibraryStatistics, RyuujiError> {
         let (reply, rx) = oneshot::channel();
         let _ = self.tx.send(DbCommand::GetLibraryStatistics { reply });
@@ -524,7 +682,9 @@ fn actor_loop(
     event_log: SharedEventLog,
 ) {
     let mut cache = RecognitionCache::new();
+    let mut tracker = WatchTracker::new();
     let relations = RelationDatabase::embedded().unwrap_or_default();
+    let mut keywords = Arc::new(KeywordSet::default());
 
     // Block the thread waiting for commands. We use blocking_recv because
     // this thread has no tokio runtime — it's a plain OS thread.
@@ -578,80 +738,11 @@ fn actor_loop(
                     &storage,
                     &config,
                     &mut cache,
+                    &mut tracker,
                     Some(&relations),
                 );
 
-                // Push debug events based on outcome.
-                {
-                    let mut log = event_log.lock().unwrap_or_else(|e| e.into_inner());
-                    match &result {
-                        Ok(UpdateOutcome::Updated {
-                            anime_title,
-                            episode,
-                            ..
-                        }) => {
-                            log.push(DebugEvent::RecognitionResult {
-                                query: query.clone(),
-                                match_level: debug_log::MatchLevel::Exact,
-                                anime_title: Some(anime_title.clone()),
-                            });
-                            log.push(DebugEvent::LibraryUpdate {
-                                anime_title: anime_title.clone(),
-                                episode: *episode,
-                                outcome: debug_log::UpdateKind::Updated,
-                            });
-                        }
-                        Ok(UpdateOutcome::AlreadyCurrent {
-                            anime_title,
-                            episode,
-                            ..
-                        }) => {
-                            log.push(DebugEvent::RecognitionResult {
-                                query: query.clone(),
-                                match_level: debug_log::MatchLevel::Exact,
-                                anime_title: Some(anime_title.clone()),
-                            });
-                            log.push(DebugEvent::LibraryUpdate {
-                                anime_title: anime_title.clone(),
-                                episode: *episode,
-                                outcome: debug_log::UpdateKind::AlreadyCurrent,
-                            });
-                        }
-                        Ok(UpdateOutcome::AddedToLibrary {
-                            anime_title,
-                            episode,
-                            ..
-                        }) => {
-                            log.push(DebugEvent::RecognitionResult {
-                                query: query.clone(),
-                                match_level: debug_log::MatchLevel::Exact,
-                                anime_title: Some(anime_title.clone()),
-                            });
-                            log.push(DebugEvent::LibraryUpdate {
-                                anime_title: anime_title.clone(),
-                                episode: *episode,
-                                outcome: debug_log::UpdateKind::Added,
-                            });
-                        }
-                        Ok(UpdateOutcome::Unrecognized { raw_title }) => {
-                            log.push(DebugEvent::RecognitionResult {
-                                query,
-                                match_level: debug_log::MatchLevel::NoMatch,
-                                anime_title: None,
-                            });
-                            log.push(DebugEvent::Unrecognized {
-                                raw_title: raw_title.clone(),
-                            });
-                        }
-                        Err(e) => {
-                            log.push(DebugEvent::Error {
-                                source: "orchestrator".into(),
-                                message: e.to_string(),
-                            });
-                        }
-                        _ => {}
-                    }
-                }
+                log_detection_outcome(&event_log, query, cache.last_match(), &result);
 
                 // Invalidate cache when new anime is added to the library.
                 if let Ok(UpdateOutcome::AddedToLibrary { .. }) = &result {
@@ -659,6 +750,72 @@ fn actor_loop(
                 }
                 let _ = reply.send(result);
             }
+            DbCommand::RecognizeOnline {
+                detected,
+                candidates,
+                config,
+                reply,
+            } => {
+                let query = detected.anime_title.clone().unwrap_or_default();
+                // The detection was already counted by `ProcessDetection`.
+                let progress =
+                    tracker.progress(&detected, &WatchPolicy::from_config(&config.library));
+                let result = orchestrator::recognize_online(
+                    &detected,
+                    progress,
+                    &candidates,
+                    &storage,
+                    &config,
+                    &mut cache,
+                    Some(&relations),
+                );
+                log_detection_outcome(&event_log, query, cache.last_match(), &result);
+                let _ = reply.send(result);
+            }
+            DbCommand::CorrectRecognition {
+                detected,
+                correction,
+                config,
+                reply,
+            } => {
+                let query = detected.anime_title.clone().unwrap_or_default();
+                let result = orchestrator::correct_recognition(
+                    &detected,
+                    correction,
+                    &storage,
+                    &config,
+                    &mut cache,
+                    &mut tracker,
+                    Some(&relations),
+                );
+                log_detection_outcome(&event_log, query, cache.last_match(), &result);
+                let _ = reply.send(result);
+            }
+            DbCommand::SearchAnime { query, reply } => {
+                let _ = reply.send(storage.search_anime(&query));
+            }
+            DbCommand::GetPendingUpdates { reply } => {
+                let _ = reply.send(storage.get_pending_updates());
+            }
+            DbCommand::ApprovePendingUpdate {
+                anime_id,
+                always,
+                reply,
+            } => {
+                let result = orchestrator::approve_pending_update(&storage, anime_id, always);
+                if let Ok(Some(UpdateOutcome::AddedToLibrary { .. })) = &result {
+                    cache.invalidate();
+                }
+                let _ = reply.send(result);
+            }
+            DbCommand::RejectPendingUpdate { anime_id, reply } => {
+                let result = orchestrator::reject_pending_update(&storage, anime_id);
+                // A rejected online match was removed.
+                if let Ok(true) = &result {
+                    cache.invalidate();
+                }
+                let _ = reply.send(result.map(|_| ()));
+            }
             DbCommand::SaveServiceToken {
                 service,
                 token,
@@ -726,7 +883,7 @@ fn actor_loop(
             }
             DbCommand::MatchTorrentItems { mut items, reply } => {
                 ryuuji_core::torrent::matcher::match_torrent_items(
-                    &mut items, &storage, &mut cache,
+                    &mut items, &storage, &mut cache, &keywords,
                 );
                 let _ = reply.send(items);
             }
@@ -759,13 +916,26 @@ fn actor_loop(
                     reply.send(storage.update_library_rewatch(anime_id, rewatching, rewatch_count));
             }
             // ── Scanner commands ───────────────────────────────────
+            DbCommand::SetKeywords { keywords: set } => {
+                keywords = set;
+            }
             DbCommand::ScanWatchFolders { config, reply } => {
-                let result = scanner::scan_watch_folders(&storage, &mut cache, &config.library);
+                let result =
+                    scanner::scan_watch_folders(&storage, &mut cache, &config.library, &keywords);
                 let _ = reply.send(result);
             }
             DbCommand::GetAvailableEpisodeSummaries { reply } => {
                 let _ = reply.send(storage.get_available_episode_summaries());
             }
+            DbCommand::PlanRenames { template, reply } => {
+                let _ = reply.send(rename::plan_renames(&storage, &template, &keywords));
+            }
+            DbCommand::ApplyRenames { plans, reply } => {
+                let _ = reply.send(rename::apply_renames(&storage, &plans));
+            }
+            DbCommand::UndoLastRename { reply } => {
+                let _ = reply.send(rename::undo_last_rename(&storage));
+            }
             // ── Statistics commands ──────────────────────────────────
             DbCommand::GetLibraryStatistics { reply } => {
                 let _ = reply.send(storage.get_library_statistics());
@@ -827,3 +997,88 @@ fn actor_loop(
         }
     }
 }
+
+/// Push debug events describing what a detection led to.
+fn log_detection_outcome(
+    event_log: &SharedEventLog,
+    query: String,
+    match_level: debug_log::MatchLevel,
+    result: &Result<UpdateOutcome, RyuujiError>,
+) {
+    let mut log = event_log.lock().unwrap_or_else(|e| e.into_inner());
+    match result {
+        Ok(UpdateOutcome::Updated {
+            anime_title,
+            episode,
+            ..
+        }) => {
+            log.push(DebugEvent::RecognitionResult {
+                query: query.clone(),
+                match_level,
+                anime_title: Some(anime_title.clone()),
+            });
+            log.push(DebugEvent::LibraryUpdate {
+                anime_title: anime_title.clone(),
+                episode: *episode,
+                outcome: debug_log::UpdateKind::Updated,
+            });
+        }
+        Ok(UpdateOutcome::AlreadyCurrent {
+            anime_title,
+            episode,
+            ..
+        }) => {
+            log.push(DebugEvent::RecognitionResult {
+                query: query.clone(),
+                match_level,
+                anime_title: Some(anime_title.clone()),
+            });
+            log.push(DebugEvent::LibraryUpdate {
+                anime_title: anime_title.clone(),
+                episode: *episode,
+                outcome: debug_log::UpdateKind::AlreadyCurrent,
+            });
+        }
+        Ok(UpdateOutcome::InProgress { anime_title, .. })
+        | Ok(UpdateOutcome::PendingConfirmation { anime_title, .. }) => {
+            log.push(DebugEvent::RecognitionResult {
+                query: query.clone(),
+                match_level,
+                anime_title: Some(anime_title.clone()),
+            });
+        }
+        Ok(UpdateOutcome::AddedToLibrary {
+            anime_title,
+            episode,
+            ..
+        }) => {
+            log.push(DebugEvent::RecognitionResult {
+                query: query.clone(),
+                match_level,
+                anime_title: Some(anime_title.clone()),
+            });
+            log.push(DebugEvent::LibraryUpdate {
+                anime_title: anime_title.clone(),
+                episode: *episode,
+                outcome: debug_log::UpdateKind::Added,
+            });
+        }
+        Ok(UpdateOutcome::Unrecognized { raw_title }) => {
+            log.push(DebugEvent::RecognitionResult {
+                query,
+                match_level: debug_log::MatchLevel::NoMatch,
+                anime_title: None,
+            });
+            log.push(DebugEvent::Unrecognized {
+                raw_title: raw_title.clone(),
+            });
+        }
+        Err(e) => {
+            log.push(DebugEvent::Error {
+                source: "orchestrator".into(),
+                message: e.to_string(),
+            });
+        }
+        _ => {}
+    }
+}
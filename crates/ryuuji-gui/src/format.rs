@@ -68,3 +68,14 @@ pub fn rating(s: &str) -> String {
         other => other.to_string(),
     }
 }
+
+/// Format a playback position/length as `m:ss` (or `h:mm:ss` past an hour).
+pub fn playback_time(d: std::time::Duration) -> String {
+    let total = d.as_secs();
+    let (h, m, s) = (total / 3600, (total % 3600) / 60, total % 60);
+    if h > 0 {
+        format!("{h}:{m:02}:{s:02}")
+    } else {
+        format!("{m}:{s:02}")
+    }
+}
@@ -37,6 +37,42 @@ struct Cli {
     /// Set log level explicitly
     #[arg(long, value_parser = ["error", "warn", "info", "debug", "trace"])]
     log_level: Option<String>,
+
+    /// Append every detection snapshot to this JSON-lines file
+    #[arg(long, value_name = "FILE", conflicts_with = "replay_detection")]
+    record_detection: Option<std::path::PathBuf>,
+
+    /// Play back a detection recording instead of watching real players
+    #[arg(long, value_name = "FILE")]
+    replay_detection: Option<std::path::PathBuf>,
+}
+
+/// Pick the detection source from `--record-detection` / `--replay-detection`.
+fn detection_source(cli: &Cli) -> app::DetectionSource {
+    use std::sync::{Arc, Mutex};
+
+    if let Some(path) = &cli.replay_detection {
+        match ryuuji_detect::ReplayDetector::open(path) {
+            Ok(replay) => {
+                tracing::info!(path = %path.display(), snapshots = replay.remaining(), "Replaying detection");
+                return app::DetectionSource::Replay(Arc::new(Mutex::new(replay)));
+            }
+            Err(e) => {
+                tracing::error!(path = %path.display(), "Cannot read detection recording: {e}")
+            }
+        }
+    } else if let Some(path) = &cli.record_detection {
+        match ryuuji_detect::open_recording(path) {
+            Ok(file) => {
+                tracing::info!(path = %path.display(), "Recording detection");
+                return app::DetectionSource::Record(Arc::new(file));
+            }
+            Err(e) => {
+                tracing::error!(path = %path.display(), "Cannot open detection recording: {e}")
+            }
+        }
+    }
+    app::DetectionSource::Live
 }
 
 fn main() -> iced::Result {
@@ -117,6 +153,7 @@ fn main() -> iced::Result {
         "Ryuuji starting"
     );
 
+    let source = detection_source(&cli);
     let ws = window_state::WindowState::load();
     let icon = iced::window::icon::from_file_data(APP_ICON, None).ok();
 
@@ -132,14 +169,18 @@ fn main() -> iced::Result {
         win.position = iced::window::Position::Centered;
     }
 
-    iced::application(app::Ryuuji::new, app::Ryuuji::update, app::Ryuuji::view)
-        .title(app::Ryuuji::title)
-        .subscription(app::Ryuuji::subscription)
-        .theme(app::Ryuuji::theme)
-        .font(GEIST_SANS)
-        .font(GEIST_MONO)
-        .font(lucide_icons::LUCIDE_FONT_BYTES)
-        .default_font(iced::Font::with_name("Geist"))
-        .window(win)
-        .run()
+    iced::application(
+        move || app::Ryuuji::with_detection_source(source.clone()),
+        app::Ryuuji::update,
+        app::Ryuuji::view,
+    )
+    .title(app::Ryuuji::title)
+    .subscription(app::Ryuuji::subscription)
+    .theme(app::Ryuuji::theme)
+    .font(GEIST_SANS)
+    .font(GEIST_MONO)
+    .font(lucide_icons::LUCIDE_FONT_BYTES)
+    .default_font(iced::Font::with_name("Geist"))
+    .window(win)
+    .run()
 }
@@ -1,3 +1,4 @@
+pub mod correction;
 pub mod debug;
 pub mod history;
 pub mod library;
@@ -70,4 +71,6 @@ pub enum ModalKind {
         title: String,
         source: Page,
     },
+    /// "This is actually…" picker for the current detection.
+    CorrectRecognition,
 }
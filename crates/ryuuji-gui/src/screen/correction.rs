@@ -0,0 +1,220 @@
+//! "This is actually…" dialog.
+//!
+//! Lets the user pick the right anime for the current detection. The choice
+//! is saved as a title alias, optionally limited to the release group or
+//! folder, and the detection is processed again.
+
+use iced::widget::{button, column, container, row, scrollable, text, text_input, toggler};
+use iced::{Alignment, Element, Length, Task};
+
+use ryuuji_core::config::AppConfig;
+use ryuuji_core::models::{Anime, DetectedMedia};
+use ryuuji_core::orchestrator::Correction;
+
+use crate::app;
+use crate::db::DbHandle;
+use crate::screen::Action;
+use crate::style;
+use crate::theme::{self, ColorScheme};
+
+/// Most search results listed at once.
+const MAX_RESULTS: usize = 20;
+
+/// Correction dialog state.
+pub struct CorrectionDialog {
+    /// The detection being corrected.
+    detected: Option<DetectedMedia>,
+    query: String,
+    results: Vec<Anime>,
+    same_release_group: bool,
+    same_folder: bool,
+}
+
+/// Messages handled by the correction dialog.
+#[derive(Debug, Clone)]
+pub enum Message {
+    QueryChanged(String),
+    /// Local search results for a query.
+    ResultsLoaded(String, Vec<Anime>),
+    SameReleaseGroupToggled(bool),
+    SameFolderToggled(bool),
+    Pick(i64),
+    Cancel,
+}
+
+impl CorrectionDialog {
+    pub fn new() -> Self {
+        Self {
+            detected: None,
+            query: String::new(),
+            results: Vec::new(),
+            same_release_group: false,
+            same_folder: false,
+        }
+    }
+
+    /// Start correcting `detected`, searching for its parsed title. The app
+    /// shows the dialog as [`ModalKind::CorrectRecognition`](crate::screen::ModalKind).
+    pub fn open(&mut self, detected: DetectedMedia, db: Option<&DbHandle>) -> Action {
+        self.query = detected
+            .anime_title
+            .clone()
+            .unwrap_or_else(|| detected.raw_title.clone());
+        self.results.clear();
+        self.same_release_group = false;
+        self.same_folder = false;
+        self.detected = Some(detected);
+
+        self.search(db).map_or(Action::None, Action::RunTask)
+    }
+
+    /// Picking an anime or cancelling closes the dialog; the app dismisses
+    /// the modal for both.
+    pub fn update(&mut self, msg: Message, db: Option<&DbHandle>, config: &AppConfig) -> Action {
+        match msg {
+            Message::QueryChanged(query) => {
+                self.query = query;
+                self.search(db).map_or(Action::None, Action::RunTask)
+            }
+            Message::ResultsLoaded(query, results) => {
+                // Ignore results for a query the user has since changed.
+                if query == self.query {
+                    self.results = results;
+                }
+                Action::None
+            }
+            Message::SameReleaseGroupToggled(on) => {
+                self.same_release_group = on;
+                Action::None
+            }
+            Message::SameFolderToggled(on) => {
+                self.same_folder = on;
+                Action::None
+            }
+            Message::Pick(anime_id) => {
+                let (Some(db), Some(detected)) = (db.cloned(), self.detected.take()) else {
+                    return Action::None;
+                };
+                let correction = Correction {
+                    anime_id,
+                    same_release_group: self.same_release_group,
+                    same_folder: self.same_folder,
+                };
+                let config = config.clone();
+                Action::RunTask(Task::perform(
+                    async move {
+                        db.correct_recognition(detected, correction, config)
+                            .await
+                            .map_err(|e| e.to_string())
+                    },
+                    app::Message::DetectionProcessed,
+                ))
+            }
+            Message::Cancel => {
+                self.detected = None;
+                Action::None
+            }
+        }
+    }
+
+    /// Search the local database for the current query.
+    fn search(&self, db: Option<&DbHandle>) -> Option<Task<app::Message>> {
+        let query = self.query.trim().to_string();
+        if query.is_empty() {
+            return None;
+        }
+        let db = db?.clone();
+        let key = self.query.clone();
+        Some(Task::perform(
+            async move { db.search_anime(query).await.unwrap_or_default() },
+            move |results| app::Message::Correction(Message::ResultsLoaded(key.clone(), results)),
+        ))
+    }
+
+    pub fn view<'a>(&'a self, cs: &ColorScheme) -> Element<'a, Message> {
+        let Some(detected) = &self.detected else {
+            return column![].into();
+        };
+
+        let mut col = column![
+            text("This is actually\u{2026}")
+                .size(style::TEXT_LG)
+                .font(style::FONT_HEADING)
+                .line_height(style::LINE_HEIGHT_TIGHT),
+            text(detected.raw_title.as_str())
+                .size(style::TEXT_XS)
+                .color(cs.outline)
+                .line_height(style::LINE_HEIGHT_LOOSE),
+            text_input("Search your anime...", &self.query)
+                .on_input(Message::QueryChanged)
+                .size(style::INPUT_FONT_SIZE)
+                .padding(style::INPUT_PADDING)
+                .width(Length::Fill)
+                .style(theme::text_input_style(cs)),
+        ]
+        .spacing(style::SPACE_MD);
+
+        if let Some(group) = &detected.release_group {
+            col = col.push(
+                toggler(self.same_release_group)
+                    .label(format!("Only for releases by {group}"))
+                    .text_size(style::INPUT_FONT_SIZE)
+                    .on_toggle(Message::SameReleaseGroupToggled)
+                    .spacing(style::SPACE_SM)
+                    .size(style::TOGGLER_SIZE)
+                    .style(theme::toggler_style(cs)),
+            );
+        }
+        if detected.file_path.is_some() {
+            col = col.push(
+                toggler(self.same_folder)
+                    .label("Only for files in this folder")
+                    .text_size(style::INPUT_FONT_SIZE)
+                    .on_toggle(Message::SameFolderToggled)
+                    .spacing(style::SPACE_SM)
+                    .size(style::TOGGLER_SIZE)
+                    .style(theme::toggler_style(cs)),
+            );
+        }
+
+        let results: Element<'a, Message> = if self.results.is_empty() {
+            text("No matching anime in your database.")
+                .size(style::TEXT_SM)
+                .color(cs.on_surface_variant)
+                .line_height(style::LINE_HEIGHT_LOOSE)
+                .into()
+        } else {
+            let mut list = column![].spacing(style::SPACE_XXS);
+            for anime in self.results.iter().take(MAX_RESULTS) {
+                let year = anime.year.map(|y| format!(" ({y})")).unwrap_or_default();
+                list = list.push(
+                    button(
+                        text(format!("{}{year}", anime.title.preferred()))
+                            .size(style::TEXT_SM)
+                            .line_height(style::LINE_HEIGHT_LOOSE),
+                    )
+                    .width(Length::Fill)
+                    .padding([style::SPACE_XS, style::SPACE_MD])
+                    .on_press(Message::Pick(anime.id))
+                    .style(theme::list_item(false, cs)),
+                );
+            }
+            scrollable(list).height(Length::Fixed(240.0)).into()
+        };
+        col = col.push(results);
+
+        col = col.push(
+            row![button(text("Cancel").size(style::TEXT_SM))
+                .padding([style::SPACE_SM, style::SPACE_XL])
+                .on_press(Message::Cancel)
+                .style(theme::ghost_button(cs))]
+            .align_y(Alignment::Center),
+        );
+
+        container(col)
+            .style(theme::dialog_container(cs))
+            .padding(style::SPACE_2XL)
+            .width(Length::Fixed(480.0))
+            .into()
+    }
+}
@@ -4,7 +4,7 @@
 //! last parse/match results, cache statistics, and a scrollable
 //! event history log.
 
-use iced::widget::{column, container, row, scrollable, text, toggler, Space};
+use iced::widget::{button, column, container, row, scrollable, text, toggler, Space};
 use iced::{Element, Length};
 
 use ryuuji_core::debug_log::{CacheStats, DebugEvent, EventEntry, SharedEventLog};
@@ -34,6 +34,8 @@ pub enum Message {
     CacheStatsLoaded(CacheStats),
     /// Toggle verbose display.
     ToggleVerbose(bool),
+    /// Open the "This is actually…" picker for the current detection.
+    CorrectRecognition,
 }
 
 impl Debug {
@@ -59,6 +61,8 @@ impl Debug {
                 self.verbose = on;
                 Action::None
             }
+            // Handled by the app, which owns the current detection.
+            Message::CorrectRecognition => Action::None,
         }
     }
 
@@ -185,9 +189,10 @@ impl Debug {
 
         let cache_line = if let Some(ref stats) = self.cache_stats {
             format!(
-                "Indexed: {} | LRU: {} | Hits: exact {} / norm {} / fuzzy {} / lru {} | Miss: {}",
+                "Indexed: {} | LRU: {} | Hits: alias {} / exact {} / norm {} / fuzzy {} / lru {} | Miss: {}",
                 stats.entries_indexed,
                 stats.lru_size,
+                stats.hits_alias,
                 stats.hits_exact,
                 stats.hits_normalized,
                 stats.hits_fuzzy,
@@ -201,7 +206,15 @@ impl Debug {
         let card = column![
             self.label_value(cs, "Detection", &last_detection),
             self.label_value(cs, "Last parse", &last_parse),
-            self.label_value(cs, "Last match", &last_match),
+            row![
+                self.label_value(cs, "Last match", &last_match),
+                Space::new().width(Length::Fill),
+                button(text("This is actually\u{2026}").size(style::TEXT_XS))
+                    .padding([style::SPACE_XXS, style::SPACE_SM])
+                    .on_press(Message::CorrectRecognition)
+                    .style(theme::ghost_button(cs)),
+            ]
+            .align_y(iced::Alignment::Center),
             self.label_value(cs, "Cache", &cache_line),
         ]
         .spacing(style::SPACE_SM)
@@ -302,6 +315,18 @@ impl Debug {
                 let kind = if *is_browser { "browser" } else { "player" };
                 (format!("Detected {kind}: {player_name}"), cs.on_surface)
             }
+            DebugEvent::PlayerRanked {
+                player_name,
+                rank,
+                reason,
+            } => (
+                format!("#{rank} {player_name} ({reason})"),
+                if *rank == 1 {
+                    cs.on_surface
+                } else {
+                    cs.on_surface_variant
+                },
+            ),
             DebugEvent::StreamMatched {
                 service_name,
                 extracted_title,
@@ -330,6 +355,10 @@ impl Debug {
                         format!("Fuzzy: {title} ({:.0}%)", score * 100.0),
                         cs.tertiary,
                     ),
+                    ryuuji_core::debug_log::MatchLevel::Online(score) => (
+                        format!("Online: {title} ({:.0}%)", score * 100.0),
+                        cs.tertiary,
+                    ),
                     level => (format!("{level:?}: {title}"), cs.primary),
                 }
             }
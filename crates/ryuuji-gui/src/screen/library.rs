@@ -449,7 +449,7 @@ impl Library {
                 });
             }
             LibrarySort::RecentlyUpdated => {
-                entries.sort_by(|a, b| b.entry.updated_at.cmp(&a.entry.updated_at));
+                entries.sort_by_key(|e| std::cmp::Reverse(e.entry.updated_at));
             }
         }
     }
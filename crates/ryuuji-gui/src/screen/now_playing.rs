@@ -1,9 +1,10 @@
-use iced::widget::{column, container, progress_bar, row, text};
+use iced::widget::{button, column, container, progress_bar, row, text};
 use iced::{Alignment, Element, Length};
 
 use ryuuji_core::models::DetectedMedia;
 use ryuuji_core::orchestrator::UpdateOutcome;
-use ryuuji_core::storage::LibraryRow;
+use ryuuji_core::storage::{LibraryRow, PendingUpdateRow};
+use ryuuji_detect::PlaybackState;
 
 use crate::cover_cache::CoverCache;
 use crate::format;
@@ -17,6 +18,8 @@ pub struct NowPlaying {
     pub last_outcome: Option<UpdateOutcome>,
     pub matched_row: Option<LibraryRow>,
     pub episode_input: String,
+    /// Updates waiting for confirmation (`library.confirm_update`).
+    pub pending: Vec<PendingUpdateRow>,
 }
 
 /// Messages handled by the Now Playing screen.
@@ -26,6 +29,13 @@ pub enum Message {
     EpisodeChanged(i64, u32),
     EpisodeInputChanged(String),
     EpisodeInputSubmitted,
+    PendingLoaded(Vec<PendingUpdateRow>),
+    ApprovePending(i64),
+    AlwaysApprovePending(i64),
+    RejectPending(i64),
+    PendingResolved(Result<Option<UpdateOutcome>, String>),
+    /// Open the "This is actually…" picker for the current detection.
+    CorrectRecognition,
 }
 
 impl NowPlaying {
@@ -35,11 +45,19 @@ impl NowPlaying {
             last_outcome: None,
             matched_row: None,
             episode_input: String::new(),
+            pending: Vec::new(),
         }
     }
 
+    /// Whether this update is already listed as pending.
+    pub fn has_pending(&self, anime_id: i64, episode: u32) -> bool {
+        self.pending
+            .iter()
+            .any(|p| p.pending.anime_id == anime_id && p.pending.episode == episode)
+    }
+
     pub fn view<'a>(&'a self, cs: &ColorScheme, covers: &'a CoverCache) -> Element<'a, Message> {
-        match &self.detected {
+        let content = match &self.detected {
             Some(media) => playing_dashboard(
                 cs,
                 media,
@@ -48,6 +66,20 @@ impl NowPlaying {
                 &self.episode_input,
             ),
             None => np_empty_state(cs),
+        };
+
+        if self.pending.is_empty() {
+            content
+        } else {
+            column![pending_card(cs, &self.pending), content]
+                .spacing(style::SPACE_SM)
+                .padding(
+                    iced::Padding::new(0.0)
+                        .top(style::SPACE_XL)
+                        .left(style::SPACE_XL)
+                        .right(style::SPACE_XL),
+                )
+                .into()
         }
     }
 }
@@ -249,6 +281,20 @@ fn playing_dashboard<'a>(
     if let Some(res) = &media.resolution {
         meta_parts.push(res.clone());
     }
+    if let Some(pos) = media.position {
+        let mut time = match media.duration {
+            Some(len) => format!(
+                "{} / {}",
+                format::playback_time(pos),
+                format::playback_time(len)
+            ),
+            None => format::playback_time(pos),
+        };
+        if media.playback_state == Some(PlaybackState::Paused) {
+            time.push_str(" (paused)");
+        }
+        meta_parts.push(time);
+    }
     let meta_line = meta_parts.join("  \u{00B7}  ");
 
     title_block = title_block.push(
@@ -258,6 +304,20 @@ fn playing_dashboard<'a>(
             .line_height(style::LINE_HEIGHT_LOOSE),
     );
 
+    if media.anime_title.is_some() {
+        let label = if matched_row.is_some() {
+            "Not this? This is actually\u{2026}"
+        } else {
+            "This is actually\u{2026}"
+        };
+        title_block = title_block.push(
+            button(text(label).size(style::TEXT_XS))
+                .padding([style::SPACE_XXS, style::SPACE_SM])
+                .on_press(Message::CorrectRecognition)
+                .style(theme::ghost_button(cs)),
+        );
+    }
+
     let hero_row = row![cover_element, title_block.width(Length::Fill)]
         .spacing(style::SPACE_XL)
         .align_y(Alignment::Start);
@@ -415,6 +475,48 @@ fn playing_dashboard<'a>(
         .into()
 }
 
+/// Card listing updates that wait for the user's confirmation.
+fn pending_card<'a>(cs: &ColorScheme, pending: &'a [PendingUpdateRow]) -> Element<'a, Message> {
+    let mut col = column![section_heading(cs, "Confirm updates")].spacing(style::SPACE_SM);
+
+    for row_data in pending {
+        let anime_id = row_data.anime.id;
+        let label = format!(
+            "{} \u{2014} episode {}",
+            row_data.anime.title.preferred(),
+            row_data.pending.episode
+        );
+        col = col.push(
+            row![
+                text(label)
+                    .size(style::TEXT_SM)
+                    .line_height(style::LINE_HEIGHT_LOOSE)
+                    .width(Length::Fill),
+                button(text("Update").size(style::TEXT_SM))
+                    .padding([style::SPACE_XS, style::SPACE_MD])
+                    .on_press(Message::ApprovePending(anime_id))
+                    .style(theme::primary_button(cs)),
+                button(text("Always for this anime").size(style::TEXT_SM))
+                    .padding([style::SPACE_XS, style::SPACE_MD])
+                    .on_press(Message::AlwaysApprovePending(anime_id))
+                    .style(theme::ghost_button(cs)),
+                button(text("Ignore").size(style::TEXT_SM))
+                    .padding([style::SPACE_XS, style::SPACE_MD])
+                    .on_press(Message::RejectPending(anime_id))
+                    .style(theme::ghost_button(cs)),
+            ]
+            .spacing(style::SPACE_SM)
+            .align_y(Alignment::Center),
+        );
+    }
+
+    container(col)
+        .style(theme::card(cs))
+        .padding(style::SPACE_LG)
+        .width(Length::Fill)
+        .into()
+}
+
 /// Enhanced empty state when nothing is playing.
 fn np_empty_state<'a>(cs: &ColorScheme) -> Element<'a, Message> {
     let icon = lucide_icons::iced::icon_circle_play()
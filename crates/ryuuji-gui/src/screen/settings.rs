@@ -3,6 +3,7 @@ use iced::{Alignment, Element, Length, Task};
 
 use ryuuji_core::config::{AppConfig, ThemeMode};
 use ryuuji_core::models::WatchStatus;
+use ryuuji_core::rename::{FilenameTemplate, RenamePlan};
 use ryuuji_core::updater::{UpdateInfo, UpdateState};
 
 use ryuuji_core::debug_log::SharedEventLog;
@@ -15,6 +16,9 @@ use crate::style;
 use crate::theme::{self, available_themes, ColorScheme};
 use crate::toast::ToastKind;
 
+/// How many planned renames the preview lists.
+const RENAME_PREVIEW_LIMIT: usize = 10;
+
 // ── Settings Sections ─────────────────────────────────────────────
 
 /// Settings sidebar sections.
@@ -87,9 +91,16 @@ pub struct Settings {
     // General
     pub interval_input: String,
     pub close_to_tray: bool,
+    pub firefox_sessions: bool,
+    pub browser_bridge: bool,
+    pub player_priority_input: String,
+    pub mpv_sockets_input: String,
     // Library
     pub auto_update: bool,
     pub confirm_update: bool,
+    pub online_fallback: bool,
+    pub watch_percent_input: String,
+    pub watch_secs_input: String,
     // Services
     pub primary_service: String,
     pub primary_service_options: Vec<String>,
@@ -127,6 +138,11 @@ pub struct Settings {
     pub scan_on_startup: bool,
     pub scan_busy: bool,
     pub scan_status: String,
+    // Renaming
+    pub rename_template_input: String,
+    pub rename_plans: Vec<RenamePlan>,
+    pub rename_busy: bool,
+    pub rename_status: String,
     // Data
     pub library_stats: Option<LibraryStats>,
     pub export_status: String,
@@ -153,9 +169,20 @@ pub enum Message {
     IntervalChanged(String),
     IntervalSubmitted,
     CloseToTrayToggled(bool),
+    FirefoxSessionsToggled(bool),
+    BrowserBridgeToggled(bool),
+    PlayerPriorityChanged(String),
+    PlayerPrioritySubmitted,
+    MpvSocketsChanged(String),
+    MpvSocketsSubmitted,
     // Library
     AutoUpdateToggled(bool),
     ConfirmUpdateToggled(bool),
+    OnlineFallbackToggled(bool),
+    WatchPercentChanged(String),
+    WatchPercentSubmitted,
+    WatchSecsChanged(String),
+    WatchSecsSubmitted,
     // Services
     PrimaryServiceChanged(String),
     // AniList
@@ -203,6 +230,13 @@ pub enum Message {
     ScanOnStartupToggled(bool),
     ScanNow,
     ScanResult(Result<String, String>),
+    RenameTemplateChanged(String),
+    RenameTemplateSubmitted,
+    PreviewRenames,
+    RenamePreviewLoaded(Result<Vec<RenamePlan>, String>),
+    ApplyRenames,
+    UndoRename,
+    RenameResult(Result<String, String>),
     // Integrations
     DiscordEnabledToggled(bool),
     // Data
@@ -239,8 +273,15 @@ impl Settings {
             available_theme_names: theme_names,
             interval_input: config.general.detection_interval.to_string(),
             close_to_tray: config.general.close_to_tray,
+            firefox_sessions: config.general.firefox_sessions,
+            browser_bridge: config.browser_bridge.enabled,
+            player_priority_input: config.general.player_priority.join(", "),
+            mpv_sockets_input: config.general.mpv_ipc_sockets.join(", "),
             auto_update: config.library.auto_update,
             confirm_update: config.library.confirm_update,
+            online_fallback: config.library.online_fallback,
+            watch_percent_input: config.library.watch_threshold_percent.to_string(),
+            watch_secs_input: config.library.watch_threshold_secs.to_string(),
             primary_service: config.services.primary.clone(),
             primary_service_options: vec!["anilist".into(), "kitsu".into(), "mal".into()],
             // AniList
@@ -284,6 +325,10 @@ impl Settings {
             scan_on_startup: config.library.scan_on_startup,
             scan_busy: false,
             scan_status: String::new(),
+            rename_template_input: config.library.rename_template.clone(),
+            rename_plans: Vec::new(),
+            rename_busy: false,
+            rename_status: String::new(),
             library_stats: None,
             export_status: String::new(),
             export_busy: false,
@@ -339,6 +384,52 @@ impl Settings {
                 let _ = config.save();
                 Action::None
             }
+            Message::FirefoxSessionsToggled(val) => {
+                self.firefox_sessions = val;
+                config.general.firefox_sessions = val;
+                let _ = config.save();
+                Action::None
+            }
+            Message::BrowserBridgeToggled(val) => {
+                self.browser_bridge = val;
+                config.browser_bridge.enabled = val;
+                let _ = config.save();
+                Action::None
+            }
+            Message::PlayerPriorityChanged(val) => {
+                self.player_priority_input = val;
+                Action::None
+            }
+            Message::PlayerPrioritySubmitted => {
+                let priority: Vec<String> = self
+                    .player_priority_input
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .map(String::from)
+                    .collect();
+                self.player_priority_input = priority.join(", ");
+                config.general.player_priority = priority;
+                let _ = config.save();
+                Action::None
+            }
+            Message::MpvSocketsChanged(val) => {
+                self.mpv_sockets_input = val;
+                Action::None
+            }
+            Message::MpvSocketsSubmitted => {
+                let sockets: Vec<String> = self
+                    .mpv_sockets_input
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .map(String::from)
+                    .collect();
+                self.mpv_sockets_input = sockets.join(", ");
+                config.general.mpv_ipc_sockets = sockets;
+                let _ = config.save();
+                Action::None
+            }
 
             // ── Library ─────────────────────────────────────────
             Message::AutoUpdateToggled(val) => {
@@ -353,6 +444,41 @@ impl Settings {
                 let _ = config.save();
                 Action::None
             }
+            Message::OnlineFallbackToggled(val) => {
+                self.online_fallback = val;
+                config.library.online_fallback = val;
+                let _ = config.save();
+                Action::None
+            }
+            Message::WatchPercentChanged(val) => {
+                self.watch_percent_input = val;
+                Action::None
+            }
+            Message::WatchPercentSubmitted => {
+                let percent = self
+                    .watch_percent_input
+                    .parse::<u8>()
+                    .unwrap_or(config.library.watch_threshold_percent)
+                    .min(100);
+                self.watch_percent_input = percent.to_string();
+                config.library.watch_threshold_percent = percent;
+                let _ = config.save();
+                Action::None
+            }
+            Message::WatchSecsChanged(val) => {
+                self.watch_secs_input = val;
+                Action::None
+            }
+            Message::WatchSecsSubmitted => {
+                let secs = self
+                    .watch_secs_input
+                    .parse::<u64>()
+                    .unwrap_or(config.library.watch_threshold_secs);
+                self.watch_secs_input = secs.to_string();
+                config.library.watch_threshold_secs = secs;
+                let _ = config.save();
+                Action::None
+            }
 
             // ── Services ────────────────────────────────────────
             Message::PrimaryServiceChanged(svc) => {
@@ -665,6 +791,71 @@ impl Settings {
                 }
                 Action::None
             }
+            Message::RenameTemplateChanged(val) => {
+                self.rename_template_input = val;
+                Action::None
+            }
+            Message::RenameTemplateSubmitted => {
+                self.rename_plans.clear();
+                match FilenameTemplate::parse(&self.rename_template_input) {
+                    Ok(_) => {
+                        config.library.rename_template = self.rename_template_input.clone();
+                        let _ = config.save();
+                        self.rename_status.clear();
+                    }
+                    Err(e) => self.rename_status = format!("Rename failed: {e}"),
+                }
+                Action::None
+            }
+            Message::PreviewRenames => {
+                self.rename_plans.clear();
+                match FilenameTemplate::parse(&self.rename_template_input) {
+                    Ok(_) => {
+                        config.library.rename_template = self.rename_template_input.clone();
+                        let _ = config.save();
+                        self.rename_busy = true;
+                        self.rename_status = "Working out new names...".into();
+                    }
+                    Err(e) => self.rename_status = format!("Rename failed: {e}"),
+                }
+                Action::None // app.rs handles the async task
+            }
+            Message::RenamePreviewLoaded(result) => {
+                self.rename_busy = false;
+                match result {
+                    Ok(plans) => {
+                        let conflicts = plans.iter().filter(|p| p.conflict.is_some()).count();
+                        self.rename_status = match (plans.len(), conflicts) {
+                            (0, _) => "All files already match the template".into(),
+                            (n, 0) => format!("{n} files would be renamed"),
+                            (n, c) => format!("{} files would be renamed, {c} skipped", n - c),
+                        };
+                        self.rename_plans = plans;
+                    }
+                    Err(e) => {
+                        tracing::warn!(error = %e, "Rename preview failed");
+                        self.rename_status = format!("Rename failed: {e}");
+                    }
+                }
+                Action::None
+            }
+            Message::ApplyRenames | Message::UndoRename => {
+                self.rename_busy = true;
+                self.rename_status = "Renaming files...".into();
+                Action::None // app.rs handles the async task
+            }
+            Message::RenameResult(result) => {
+                self.rename_busy = false;
+                self.rename_plans.clear();
+                match result {
+                    Ok(summary) => self.rename_status = summary,
+                    Err(e) => {
+                        tracing::warn!(error = %e, "Renaming files failed");
+                        self.rename_status = format!("Rename failed: {e}");
+                    }
+                }
+                Action::None
+            }
 
             // ── Integrations ────────────────────────────────────
             Message::DiscordEnabledToggled(val) => {
@@ -977,6 +1168,50 @@ impl Settings {
                 ]
                 .align_y(Alignment::Center)
                 .spacing(style::SPACE_MD),
+                row![
+                    text("Preferred players")
+                        .size(style::INPUT_FONT_SIZE)
+                        .line_height(style::LINE_HEIGHT_NORMAL)
+                        .width(Length::Fill),
+                    text_input("mpv, VLC", &self.player_priority_input)
+                        .on_input(Message::PlayerPriorityChanged)
+                        .on_submit(Message::PlayerPrioritySubmitted)
+                        .size(style::INPUT_FONT_SIZE)
+                        .padding(style::INPUT_PADDING)
+                        .width(Length::Fixed(200.0))
+                        .style(theme::text_input_style(cs)),
+                ]
+                .align_y(Alignment::Center)
+                .spacing(style::SPACE_MD),
+                row![
+                    text("mpv IPC sockets")
+                        .size(style::INPUT_FONT_SIZE)
+                        .line_height(style::LINE_HEIGHT_NORMAL)
+                        .width(Length::Fill),
+                    text_input("/tmp/mpvsocket*", &self.mpv_sockets_input)
+                        .on_input(Message::MpvSocketsChanged)
+                        .on_submit(Message::MpvSocketsSubmitted)
+                        .size(style::INPUT_FONT_SIZE)
+                        .padding(style::INPUT_PADDING)
+                        .width(Length::Fixed(200.0))
+                        .style(theme::text_input_style(cs)),
+                ]
+                .align_y(Alignment::Center)
+                .spacing(style::SPACE_MD),
+                toggler(self.firefox_sessions)
+                    .label("Read Firefox tabs from session file")
+                    .text_size(style::INPUT_FONT_SIZE)
+                    .on_toggle(Message::FirefoxSessionsToggled)
+                    .spacing(style::SPACE_SM)
+                    .size(style::TOGGLER_SIZE)
+                    .style(theme::toggler_style(cs)),
+                toggler(self.browser_bridge)
+                    .label("Accept reports from the browser extension")
+                    .text_size(style::INPUT_FONT_SIZE)
+                    .on_toggle(Message::BrowserBridgeToggled)
+                    .spacing(style::SPACE_SM)
+                    .size(style::TOGGLER_SIZE)
+                    .style(theme::toggler_style(cs)),
                 toggler(self.close_to_tray)
                     .label("Close to system tray")
                     .text_size(style::INPUT_FONT_SIZE)
@@ -1015,6 +1250,43 @@ impl Settings {
                     .spacing(style::SPACE_SM)
                     .size(style::TOGGLER_SIZE)
                     .style(theme::toggler_style(cs)),
+                toggler(self.online_fallback)
+                    .label("Look up unknown titles on the primary service")
+                    .text_size(style::INPUT_FONT_SIZE)
+                    .on_toggle(Message::OnlineFallbackToggled)
+                    .spacing(style::SPACE_SM)
+                    .size(style::TOGGLER_SIZE)
+                    .style(theme::toggler_style(cs)),
+                row![
+                    text("Mark watched after (% played)")
+                        .size(style::INPUT_FONT_SIZE)
+                        .line_height(style::LINE_HEIGHT_NORMAL)
+                        .width(Length::Fill),
+                    text_input("80", &self.watch_percent_input)
+                        .on_input(Message::WatchPercentChanged)
+                        .on_submit(Message::WatchPercentSubmitted)
+                        .size(style::INPUT_FONT_SIZE)
+                        .padding(style::INPUT_PADDING)
+                        .width(Length::Fixed(80.0))
+                        .style(theme::text_input_style(cs)),
+                ]
+                .align_y(Alignment::Center)
+                .spacing(style::SPACE_MD),
+                row![
+                    text("...or after (seconds played, 0 = off)")
+                        .size(style::INPUT_FONT_SIZE)
+                        .line_height(style::LINE_HEIGHT_NORMAL)
+                        .width(Length::Fill),
+                    text_input("0", &self.watch_secs_input)
+                        .on_input(Message::WatchSecsChanged)
+                        .on_submit(Message::WatchSecsSubmitted)
+                        .size(style::INPUT_FONT_SIZE)
+                        .padding(style::INPUT_PADDING)
+                        .width(Length::Fixed(80.0))
+                        .style(theme::text_input_style(cs)),
+                ]
+                .align_y(Alignment::Center)
+                .spacing(style::SPACE_MD),
             ]
             .spacing(style::SPACE_SM),
         )
@@ -1600,6 +1872,8 @@ impl Settings {
             );
         }
 
+        content = content.push(self.rename_section(cs));
+
         container(content)
             .style(theme::card(cs))
             .padding(style::SPACE_LG)
@@ -1607,6 +1881,92 @@ impl Settings {
             .into()
     }
 
+    /// Template input, preview and rename/undo buttons for indexed files.
+    fn rename_section<'a>(&'a self, cs: &ColorScheme) -> Element<'a, Message> {
+        let action_btn = |label: &'a str, msg: Option<Message>| {
+            let mut btn = button(text(label).size(style::TEXT_SM))
+                .padding([style::SPACE_SM, style::SPACE_XL])
+                .style(theme::primary_button(cs));
+            if !self.rename_busy {
+                btn = btn.on_press_maybe(msg);
+            }
+            btn
+        };
+        let can_apply = self.rename_plans.iter().any(|p| p.conflict.is_none());
+
+        let mut content = column![
+            rule::horizontal(1),
+            text("Rename files")
+                .size(style::INPUT_FONT_SIZE)
+                .line_height(style::LINE_HEIGHT_NORMAL),
+            text_input(
+                "{title} - S{season:02}E{episode:02}.{ext}",
+                &self.rename_template_input
+            )
+            .on_input(Message::RenameTemplateChanged)
+            .on_submit(Message::RenameTemplateSubmitted)
+            .size(style::INPUT_FONT_SIZE)
+            .padding(style::INPUT_PADDING)
+            .width(Length::Fill)
+            .style(theme::text_input_style(cs)),
+            row![
+                action_btn("Preview", Some(Message::PreviewRenames)),
+                action_btn("Rename", can_apply.then_some(Message::ApplyRenames)),
+                action_btn("Undo Last Rename", Some(Message::UndoRename)),
+            ]
+            .spacing(style::SPACE_SM),
+        ]
+        .spacing(style::SPACE_SM);
+
+        if !self.rename_status.is_empty() {
+            let color = if self.rename_status.contains("failed") {
+                cs.error
+            } else {
+                cs.on_surface_variant
+            };
+            content = content.push(
+                text(&self.rename_status)
+                    .size(style::TEXT_SM)
+                    .color(color)
+                    .line_height(style::LINE_HEIGHT_LOOSE),
+            );
+        }
+
+        for plan in self.rename_plans.iter().take(RENAME_PREVIEW_LIMIT) {
+            let file_name = |path: &str| {
+                std::path::Path::new(path)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default()
+            };
+            let (line, color) = match &plan.conflict {
+                Some(reason) => (format!("{} ({reason})", file_name(&plan.from)), cs.error),
+                None => (
+                    format!("{} \u{2192} {}", file_name(&plan.from), file_name(&plan.to)),
+                    cs.on_surface,
+                ),
+            };
+            content = content.push(
+                text(line)
+                    .size(style::TEXT_XS)
+                    .color(color)
+                    .line_height(style::LINE_HEIGHT_NORMAL),
+            );
+        }
+        if self.rename_plans.len() > RENAME_PREVIEW_LIMIT {
+            content = content.push(
+                text(format!(
+                    "...and {} more",
+                    self.rename_plans.len() - RENAME_PREVIEW_LIMIT
+                ))
+                .size(style::TEXT_XS)
+                .color(cs.on_surface_variant),
+            );
+        }
+
+        content.into()
+    }
+
     fn about_card(&self, cs: &ColorScheme) -> Element<'_, Message> {
         let version = env!("CARGO_PKG_VERSION");
         let config_path = AppConfig::config_path().display().to_string();
@@ -833,7 +833,7 @@ impl Torrents {
                 }
             };
 
-            let ep_str = item.episode.map(|e| e.to_string()).unwrap_or_default();
+            let ep_str = item.episode_label().unwrap_or_default();
             let group_str = item.release_group.as_deref().unwrap_or("-");
             let size_str = item.size.as_deref().unwrap_or("-");
             let sl_str = match (item.seeders, item.leechers) {
@@ -1277,6 +1277,8 @@ impl Torrents {
                             FilterElement::ReleaseGroup,
                             FilterElement::Resolution,
                             FilterElement::Size,
+                            FilterElement::Batch,
+                            FilterElement::Airing,
                         ][..],
                         Some(cond.element),
                         move |e| Message::ConditionElementChanged(i, e),
@@ -1547,8 +1549,11 @@ fn torrent_detail_panel<'a>(
         ]
     };
 
-    if let Some(ep) = item.episode {
-        info_rows = info_rows.push(info_row("Episode", ep.to_string(), cs));
+    if let Some(ep) = item.episode_label() {
+        info_rows = info_rows.push(info_row("Episode", ep, cs));
+    }
+    if item.is_batch {
+        info_rows = info_rows.push(info_row("Batch", "Yes".into(), cs));
     }
     if let Some(ref group) = item.release_group {
         info_rows = info_rows.push(info_row("Group", group.clone(), cs));
@@ -11,6 +11,7 @@ use crate::keyboard;
 /// Compose all application subscriptions.
 ///
 /// - Detection tick (always active)
+/// - MPRIS change events (Linux), which keep the tick off the session bus
 /// - Keyboard shortcuts (always active)
 /// - OS appearance check (only when ThemeMode::System)
 /// - Window events (resize/move for state persistence)
@@ -27,6 +28,9 @@ pub fn subscriptions(
         keyboard::keyboard_subscription(),
     ];
 
+    #[cfg(target_os = "linux")]
+    subs.push(mpris_events());
+
     if theme_mode == ThemeMode::System {
         subs.push(appearance_check());
     }
@@ -43,6 +47,68 @@ fn detection_tick(interval_secs: u64) -> Subscription<Message> {
     iced::time::every(Duration::from_secs(interval_secs)).map(|_| Message::DetectionTick)
 }
 
+/// Triggers detection as soon as an MPRIS player appears, quits, changes
+/// track/playback state, or seeks. While the watcher runs, ticks reuse the
+/// last MPRIS query (see [`ryuuji_detect::MprisCache`]) and only poll the
+/// backends that have no change notifications (IPC sockets, remote players,
+/// /proc).
+#[cfg(target_os = "linux")]
+fn mpris_events() -> Subscription<Message> {
+    Subscription::run(|| {
+        iced::stream::channel(4, async |output| {
+            std::thread::spawn(move || watch_mpris(output));
+        })
+    })
+}
+
+/// Players usually emit a burst of signals for one change (Metadata, then
+/// PlaybackStatus); wait this long for the burst to settle.
+#[cfg(target_os = "linux")]
+const MPRIS_SETTLE: Duration = Duration::from_millis(250);
+
+/// Longest wait after the first signal of a burst. Players that signal
+/// position ticks never go quiet for [`MPRIS_SETTLE`].
+#[cfg(target_os = "linux")]
+const MPRIS_SETTLE_MAX: Duration = Duration::from_secs(1);
+
+#[cfg(target_os = "linux")]
+fn watch_mpris(mut output: iced::futures::channel::mpsc::Sender<Message>) {
+    use std::sync::mpsc::RecvTimeoutError;
+    use std::time::Instant;
+
+    use iced::futures::SinkExt;
+
+    let watcher = match ryuuji_detect::MprisWatcher::spawn() {
+        Ok(w) => w,
+        Err(e) => {
+            tracing::warn!("MPRIS watcher unavailable, relying on polling: {e}");
+            return;
+        }
+    };
+    if iced::futures::executor::block_on(output.send(Message::MprisWatching(true))).is_err() {
+        return;
+    }
+    while !output.is_closed() {
+        match watcher.recv_timeout(Duration::from_secs(1)) {
+            Ok(_) => {
+                let deadline = Instant::now() + MPRIS_SETTLE_MAX;
+                loop {
+                    let wait = MPRIS_SETTLE.min(deadline.saturating_duration_since(Instant::now()));
+                    if wait.is_zero() || watcher.recv_timeout(wait).is_err() {
+                        break;
+                    }
+                }
+                // A full buffer means a refresh is already queued.
+                let _ = output.try_send(Message::MprisChanged);
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+    // Back to querying the bus on every tick.
+    let _ = iced::futures::executor::block_on(output.send(Message::MprisWatching(false)));
+}
+
 /// Forwards window resize and move events for state persistence.
 fn window_events() -> Subscription<Message> {
     iced::window::events().map(|(_id, event)| Message::WindowEvent(event))
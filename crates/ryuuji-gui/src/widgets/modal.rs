@@ -249,11 +249,11 @@ impl<'a, Message: Clone + 'a> overlay::Overlay<Message, Theme, iced::Renderer>
 
         match event {
             // Click outside -> dismiss.
-            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
-                if cursor.position_over(layout.bounds()).is_none() {
-                    shell.publish(self.on_blur.clone());
-                    shell.capture_event();
-                }
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left))
+                if cursor.position_over(layout.bounds()).is_none() =>
+            {
+                shell.publish(self.on_blur.clone());
+                shell.capture_event();
             }
             // Escape -> dismiss.
             Event::Keyboard(iced::keyboard::Event::KeyPressed {
@@ -0,0 +1,77 @@
+//! Parser throughput on a mix of release names, as seen in torrent feeds
+//! and watch folders.
+//!
+//! Run with `cargo bench -p ryuuji-parse`.
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use std::hint::black_box;
+
+use ryuuji_parse::{tokenizer, ParseContext};
+
+const NAMES: &[&str] = &[
+    "[SubsPlease] Sousou no Frieren - 05 (1080p) [ABCD1234].mkv",
+    "[Erai-raws] Dandadan - 12 [1080p][Multiple Subtitle][ENG][POR-BR].mkv",
+    "[HorribleSubs]_Naruto_Shippuuden_-_500_[720p].mkv",
+    "[Judas] Kusuriya no Hitorigoto - S01E05 [1080p][HEVC x265 10bit][Multi-Subs].mkv",
+    "Mushoku Tensei S2 - 05 [1080p][HEVC].mkv",
+    "[Group] Title (01-12) [BD 1080p] [Batch]",
+    "[Group] Title 2nd Season - 05v2 [1080p].mkv",
+    "Steins;Gate - 01 [1080p][HEVC].mkv",
+    "[LoliHouse] 葬送的芙莉莲 / Sousou no Frieren - 05 [WebRip 1080p HEVC-10bit AAC][简繁内封字幕].mkv",
+    "【喵萌奶茶屋】★10月新番★[葬送的芙莉莲 / Sousou no Frieren][05][1080p][简日双语].mp4",
+    "[桜都字幕组] 葬送的芙莉莲 ／ Sousou no Frieren [０５][１０８０Ｐ][简繁内封].mp4",
+    "[Lilith-Raws] Sousou no Frieren - 05 [Baha][WEB-DL][1080p][AVC AAC][CHT][MP4].mp4",
+];
+
+fn bench_parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    group.throughput(Throughput::Elements(NAMES.len() as u64));
+
+    group.bench_function("tokenize", |b| {
+        let mut tokens = Vec::new();
+        b.iter(|| {
+            for name in NAMES {
+                black_box(tokenizer::tokenize_into(name, &mut tokens));
+            }
+        })
+    });
+    group.bench_function("owned", |b| {
+        b.iter(|| {
+            for name in NAMES {
+                black_box(ryuuji_parse::parse(name));
+            }
+        })
+    });
+    group.bench_function("borrowed", |b| {
+        let mut cx = ParseContext::new();
+        b.iter(|| {
+            for name in NAMES {
+                black_box(cx.parse(name));
+            }
+        })
+    });
+    group.bench_function("borrowed_cold", |b| {
+        // A fresh context per batch, to show what the reused buffers save.
+        b.iter_batched(
+            ParseContext::new,
+            |mut cx| {
+                for name in NAMES {
+                    black_box(cx.parse(name));
+                }
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("detailed", |b| {
+        b.iter(|| {
+            for name in NAMES {
+                black_box(ryuuji_parse::parse_detailed(name));
+            }
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_parse);
+criterion_main!(benches);
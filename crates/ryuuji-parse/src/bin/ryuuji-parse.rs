@@ -0,0 +1,539 @@
+//! Command-line front end to the parser.
+//!
+//! Reads release names from arguments, stdin or a directory walk and prints
+//! what the parser found in each, as JSON Lines, TSV or an aligned table.
+//! `--diff` re-parses the names in a JSON Lines file written by an earlier
+//! run and reports every name whose elements changed, which is how a parser
+//! upgrade is checked against a large archive before rolling it out:
+//!
+//! ```text
+//! ryuuji-parse --dir ~/Anime > baseline.jsonl
+//! # ...upgrade...
+//! ryuuji-parse --diff baseline.jsonl
+//! ```
+
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::io::{self, BufRead, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Parser, ValueEnum};
+use serde_json::{Map, Value};
+
+use ryuuji_parse::{parse_path_under, parse_with_keywords, KeywordSet};
+
+/// Extensions `--dir` picks up unless `--all-files` is given.
+const VIDEO_EXTENSIONS: &[&str] = &["mkv", "mp4", "avi", "ogm", "wmv", "webm", "flv", "m4v"];
+
+/// Names `--fields` and `--missing` accept, in `Elements` order.
+const FIELDS: &[&str] = &[
+    "title",
+    "episode",
+    "episode_number",
+    "episode_number_end",
+    "release_group",
+    "resolution",
+    "video_codec",
+    "audio_codec",
+    "season",
+    "season_number",
+    "checksum",
+    "source",
+    "year",
+    "episode_title",
+    "part",
+    "part_number",
+    "volume",
+    "volume_number",
+    "volume_number_end",
+    "is_batch",
+    "release_version",
+    "release_info",
+    "anime_type",
+    "language",
+    "subtitles",
+    "video_term",
+    "audio_term",
+    "file_extension",
+    "streaming_source",
+];
+
+#[derive(Parser)]
+#[command(name = "ryuuji-parse", version, about = "Parse anime release names")]
+struct Cli {
+    /// Names to parse. Read one per line from stdin when neither names nor
+    /// --dir are given.
+    names: Vec<String>,
+
+    /// Walk this directory and parse every video file in it.
+    #[arg(long, value_name = "DIR")]
+    dir: Vec<PathBuf>,
+
+    /// With --dir, parse every file rather than only videos.
+    #[arg(long, requires = "dir")]
+    all_files: bool,
+
+    /// Parse names as paths, filling title and season in from folder names.
+    #[arg(long)]
+    path: bool,
+
+    /// Extra keywords and release groups, in the app's keywords.toml format.
+    #[arg(long, value_name = "FILE")]
+    keywords: Option<PathBuf>,
+
+    /// Output format.
+    #[arg(long, short, value_enum, default_value_t = Format::Jsonl, conflicts_with = "diff")]
+    format: Format,
+
+    /// Columns for tsv and table output.
+    #[arg(
+        long,
+        value_delimiter = ',',
+        default_value = "title,season,episode,release_group,resolution"
+    )]
+    fields: Vec<String>,
+
+    /// Only show names missing at least one of these fields.
+    #[arg(
+        long,
+        value_delimiter = ',',
+        value_name = "FIELDS",
+        conflicts_with = "diff"
+    )]
+    missing: Vec<String>,
+
+    /// Only show batch releases.
+    #[arg(long, conflicts_with = "diff")]
+    batch: bool,
+
+    /// Compare against JSON Lines output from an earlier run and print what
+    /// changed. Re-parses the baseline's names unless others are given.
+    #[arg(long, value_name = "BASELINE")]
+    diff: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    /// One JSON object per line, with the name under "input".
+    Jsonl,
+    /// Tab-separated columns with a header row.
+    Tsv,
+    /// Columns padded to line up, for reading.
+    Table,
+}
+
+/// One parsed name: its `Elements` as a JSON object, without "input".
+struct Record {
+    input: String,
+    elements: Map<String, Value>,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(&cli) {
+        Ok(code) => code,
+        // `ryuuji-parse ... | head` closes the pipe early; that's not an error.
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("ryuuji-parse: {e}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn run(cli: &Cli) -> io::Result<ExitCode> {
+    for field in cli.fields.iter().chain(&cli.missing) {
+        if !FIELDS.contains(&field.as_str()) {
+            return Err(invalid(format!(
+                "unknown field \"{field}\" (expected one of: {})",
+                FIELDS.join(", ")
+            )));
+        }
+    }
+
+    let keywords = match &cli.keywords {
+        Some(path) => KeywordSet::from_toml(&fs::read_to_string(path)?)
+            .map_err(|e| invalid(format!("{}: {e}", path.display())))?,
+        None => KeywordSet::default(),
+    };
+
+    let baseline = match &cli.diff {
+        Some(path) => Some(read_baseline(io::BufReader::new(fs::File::open(path)?))?),
+        None => None,
+    };
+
+    let names = if !cli.names.is_empty() || !cli.dir.is_empty() {
+        let mut names = cli.names.clone();
+        for dir in &cli.dir {
+            walk(dir, cli.all_files, &mut names)?;
+        }
+        names
+    } else if let Some(baseline) = &baseline {
+        baseline.iter().map(|r| r.input.clone()).collect()
+    } else {
+        io::stdin()
+            .lock()
+            .lines()
+            .filter(|line| !matches!(line, Ok(l) if l.trim().is_empty()))
+            .collect::<io::Result<_>>()?
+    };
+
+    let records = names
+        .into_iter()
+        .map(|input| parse_record(input, cli.path, &keywords))
+        .filter(|r| keep(r, &cli.missing, cli.batch));
+
+    let mut out = BufWriter::new(io::stdout().lock());
+    if let Some(baseline) = baseline {
+        let records: Vec<_> = records.collect();
+        let summary = write_diff(&mut out, &baseline, &records)?;
+        out.flush()?;
+        eprintln!(
+            "{} names: {} changed, {} added, {} removed",
+            records.len(),
+            summary.changed,
+            summary.added,
+            summary.removed
+        );
+        return Ok(if summary.is_empty() {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
+        });
+    }
+
+    match cli.format {
+        Format::Jsonl => {
+            for record in records {
+                writeln!(out, "{}", to_json_line(&record))?;
+            }
+        }
+        Format::Tsv => {
+            writeln!(out, "input\t{}", cli.fields.join("\t"))?;
+            for record in records {
+                write!(out, "{}", tsv_cell(&record.input))?;
+                for field in &cli.fields {
+                    write!(out, "\t{}", tsv_cell(&field_text(&record, field)))?;
+                }
+                writeln!(out)?;
+            }
+        }
+        Format::Table => {
+            let records: Vec<_> = records.collect();
+            write_table(&mut out, &cli.fields, &records)?;
+        }
+    }
+    out.flush()?;
+    Ok(ExitCode::SUCCESS)
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+/// Append the files under `dir` to `names`, sorted so runs line up.
+fn walk(dir: &Path, all_files: bool, names: &mut Vec<String>) -> io::Result<()> {
+    for entry in walkdir::WalkDir::new(dir).sort_by_file_name() {
+        let entry = entry.map_err(io::Error::other)?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let path = entry.path();
+        let is_video = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| VIDEO_EXTENSIONS.contains(&e.to_lowercase().as_str()));
+        if all_files || is_video {
+            names.push(path.to_string_lossy().into_owned());
+        }
+    }
+    Ok(())
+}
+
+fn parse_record(input: String, as_path: bool, keywords: &KeywordSet) -> Record {
+    let elements = if as_path {
+        parse_path_under(&input, "", keywords).elements
+    } else {
+        // Directory walks give paths; without --path only the filename counts.
+        let filename = input.rsplit(['/', '\\']).next().unwrap_or(&input);
+        parse_with_keywords(filename, keywords)
+    };
+    let elements = match serde_json::to_value(elements) {
+        Ok(Value::Object(map)) => map,
+        _ => Map::new(),
+    };
+    Record { input, elements }
+}
+
+/// Whether a record passes the `--missing` and `--batch` filters.
+fn keep(record: &Record, missing: &[String], batch_only: bool) -> bool {
+    if batch_only && record.elements.get("is_batch") != Some(&Value::Bool(true)) {
+        return false;
+    }
+    missing.is_empty() || missing.iter().any(|f| !record.elements.contains_key(f))
+}
+
+/// The record as one JSON object, "input" first so lines read well.
+fn to_json_line(record: &Record) -> String {
+    let mut line = format!("{{\"input\":{}", Value::from(record.input.as_str()));
+    for (key, value) in &record.elements {
+        line.push_str(&format!(",{}:{value}", Value::from(key.as_str())));
+    }
+    line.push('}');
+    line
+}
+
+/// A field as plain text; lists are comma-separated, missing fields empty.
+fn field_text(record: &Record, field: &str) -> String {
+    match record.elements.get(field) {
+        None => String::new(),
+        Some(value) => value_text(value),
+    }
+}
+
+fn value_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Array(items) => items.iter().map(value_text).collect::<Vec<_>>().join(","),
+        other => other.to_string(),
+    }
+}
+
+/// Tabs and newlines would break the row apart.
+fn tsv_cell(text: &str) -> String {
+    text.replace(['\t', '\n', '\r'], " ")
+}
+
+fn write_table(out: &mut impl Write, fields: &[String], records: &[Record]) -> io::Result<()> {
+    let header: Vec<&str> = std::iter::once("input")
+        .chain(fields.iter().map(String::as_str))
+        .collect();
+    let rows: Vec<Vec<String>> = records
+        .iter()
+        .map(|r| {
+            std::iter::once(r.input.clone())
+                .chain(fields.iter().map(|f| field_text(r, f)))
+                .collect()
+        })
+        .collect();
+
+    let mut widths: Vec<usize> = header.iter().map(|h| h.chars().count()).collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let header: Vec<String> = header.iter().map(|h| h.to_string()).collect();
+    for row in std::iter::once(&header).chain(&rows) {
+        let mut line = String::new();
+        for (i, (cell, width)) in row.iter().zip(&widths).enumerate() {
+            if i > 0 {
+                line.push_str("  ");
+            }
+            line.push_str(cell);
+            line.extend(std::iter::repeat_n(' ', width - cell.chars().count()));
+        }
+        writeln!(out, "{}", line.trim_end())?;
+    }
+    Ok(())
+}
+
+fn read_baseline(reader: impl BufRead) -> io::Result<Vec<Record>> {
+    let mut records = Vec::new();
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut elements = match serde_json::from_str(&line) {
+            Ok(Value::Object(map)) => map,
+            _ => {
+                return Err(invalid(format!(
+                    "baseline line {}: not a JSON object",
+                    i + 1
+                )))
+            }
+        };
+        let Some(Value::String(input)) = elements.remove("input") else {
+            return Err(invalid(format!("baseline line {}: no \"input\"", i + 1)));
+        };
+        records.push(Record { input, elements });
+    }
+    Ok(records)
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct DiffSummary {
+    changed: usize,
+    added: usize,
+    removed: usize,
+}
+
+impl DiffSummary {
+    fn is_empty(&self) -> bool {
+        self.changed == 0 && self.added == 0 && self.removed == 0
+    }
+}
+
+/// Write one block per name whose elements differ from `baseline`:
+///
+/// ```text
+/// ~ [Group] Title - 05.mkv
+///     episode: "05" -> (none)
+/// ```
+///
+/// `+` marks names the baseline doesn't have and `-` baseline names that
+/// weren't parsed this time.
+fn write_diff(
+    out: &mut impl Write,
+    baseline: &[Record],
+    records: &[Record],
+) -> io::Result<DiffSummary> {
+    let before: HashMap<&str, &Map<String, Value>> = baseline
+        .iter()
+        .map(|r| (r.input.as_str(), &r.elements))
+        .collect();
+    let mut summary = DiffSummary::default();
+    let mut seen = BTreeSet::new();
+
+    for record in records {
+        seen.insert(record.input.as_str());
+        let Some(old) = before.get(record.input.as_str()) else {
+            summary.added += 1;
+            writeln!(out, "+ {}", record.input)?;
+            continue;
+        };
+        let keys: BTreeSet<&String> = old.keys().chain(record.elements.keys()).collect();
+        let changes: Vec<_> = keys
+            .into_iter()
+            .filter(|k| old.get(*k) != record.elements.get(*k))
+            .collect();
+        if changes.is_empty() {
+            continue;
+        }
+        summary.changed += 1;
+        writeln!(out, "~ {}", record.input)?;
+        for key in changes {
+            writeln!(
+                out,
+                "    {key}: {} -> {}",
+                diff_value(old.get(key)),
+                diff_value(record.elements.get(key))
+            )?;
+        }
+    }
+
+    for record in baseline {
+        if !seen.contains(record.input.as_str()) {
+            summary.removed += 1;
+            writeln!(out, "- {}", record.input)?;
+        }
+    }
+    Ok(summary)
+}
+
+fn diff_value(value: Option<&Value>) -> String {
+    value.map_or_else(|| "(none)".to_string(), Value::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(input: &str) -> Record {
+        parse_record(input.to_string(), false, &KeywordSet::default())
+    }
+
+    #[test]
+    fn test_jsonl_round_trips_through_baseline() {
+        let records = vec![
+            record("[SubsPlease] Sousou no Frieren - 05 (1080p) [ABCD1234].mkv"),
+            record("[SweetSub][Sousou no Frieren][01-28][BDRip][1080P]"),
+        ];
+        let jsonl: String = records.iter().map(|r| to_json_line(r) + "\n").collect();
+        let baseline = read_baseline(jsonl.as_bytes()).unwrap();
+        assert_eq!(baseline.len(), 2);
+        assert_eq!(baseline[0].input, records[0].input);
+        assert_eq!(baseline[0].elements, records[0].elements);
+
+        let mut out = Vec::new();
+        let summary = write_diff(&mut out, &baseline, &records).unwrap();
+        assert!(summary.is_empty());
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn test_diff_reports_changed_added_and_removed() {
+        let baseline = read_baseline(
+            concat!(
+                r#"{"input":"[Group] Show - 05.mkv","title":"Show","episode":"06"}"#,
+                "\n",
+                r#"{"input":"Gone.mkv","title":"Gone"}"#,
+                "\n",
+            )
+            .as_bytes(),
+        )
+        .unwrap();
+        let records = vec![record("[Group] Show - 05.mkv"), record("New - 01.mkv")];
+
+        let mut out = Vec::new();
+        let summary = write_diff(&mut out, &baseline, &records).unwrap();
+        assert_eq!(
+            summary,
+            DiffSummary {
+                changed: 1,
+                added: 1,
+                removed: 1
+            }
+        );
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("~ [Group] Show - 05.mkv\n"));
+        assert!(out.contains("    episode: \"06\" -> \"05\"\n"));
+        assert!(out.contains("    release_group: (none) -> \"Group\"\n"));
+        assert!(out.contains("+ New - 01.mkv\n"));
+        assert!(out.contains("- Gone.mkv\n"));
+    }
+
+    #[test]
+    fn test_filters() {
+        let full = record("[SubsPlease] Sousou no Frieren - 05 (1080p).mkv");
+        let no_episode = record("[SubsPlease] Sousou no Frieren (1080p).mkv");
+        let batch = record("[SweetSub][Sousou no Frieren][01-28][BDRip][1080P]");
+        let missing = ["title".to_string(), "episode".to_string()];
+
+        assert!(keep(&full, &[], false));
+        assert!(!keep(&full, &missing, false));
+        assert!(keep(&no_episode, &missing, false));
+        assert!(!keep(&full, &[], true));
+        assert!(keep(&batch, &[], true));
+    }
+
+    #[test]
+    fn test_walked_paths_parse_filename_only() {
+        let r = record("/anime/Sousou no Frieren/[SubsPlease] Sousou no Frieren - 05.mkv");
+        assert_eq!(field_text(&r, "title"), "Sousou no Frieren");
+        assert_eq!(field_text(&r, "episode_number"), "5");
+    }
+
+    #[test]
+    fn test_table_aligns_columns() {
+        let records = vec![
+            record("[SubsPlease] Sousou no Frieren - 05 (1080p).mkv"),
+            record("Show - 12.mkv"),
+        ];
+        let fields = ["title".to_string(), "episode".to_string()];
+        let mut out = Vec::new();
+        write_table(&mut out, &fields, &records).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 3);
+        let column = lines[0].find("title").unwrap();
+        assert_eq!(
+            lines[1][column..].split("  ").next(),
+            Some("Sousou no Frieren")
+        );
+        assert_eq!(lines[2][column..].split("  ").next(), Some("Show"));
+    }
+}
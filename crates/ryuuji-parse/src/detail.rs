@@ -0,0 +1,142 @@
+//! Provenance for parsed elements.
+//!
+//! [`parse_detailed`](crate::parse_detailed) records, for every element it
+//! finds, the byte span it came from, the parser pass or strategy that
+//! produced it, and a confidence. Spans let a UI highlight the input;
+//! confidence tells a solid episode match from a guess.
+
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use crate::elements::Elements;
+use crate::parser::episode::EpisodeStrategy;
+use crate::parser::season::SeasonStrategy;
+
+/// Output of [`parse_detailed`](crate::parse_detailed): the usual elements
+/// plus where each one came from.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DetailedElements {
+    pub elements: Elements,
+    /// One entry per element found, in input order.
+    pub details: Vec<ElementDetail>,
+}
+
+impl DetailedElements {
+    /// The first detail recorded for `kind`.
+    pub fn detail(&self, kind: ElementKind) -> Option<&ElementDetail> {
+        self.details.iter().find(|d| d.kind == kind)
+    }
+}
+
+/// How one element was found.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ElementDetail {
+    pub kind: ElementKind,
+    /// The element's value, as stored in [`Elements`].
+    pub value: String,
+    /// Byte range in the input the element was read from.
+    pub span: Range<usize>,
+    /// The parser pass that identified it.
+    pub pass: Pass,
+    /// The pattern that matched, for episodes and seasons.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strategy: Option<Strategy>,
+    /// 0.0–1.0; how sure the parser is that this is what it says.
+    pub confidence: f32,
+}
+
+/// Which [`Elements`] field a detail describes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ElementKind {
+    Title,
+    Episode,
+    Season,
+    Volume,
+    Year,
+    ReleaseGroup,
+    Resolution,
+    Checksum,
+    EpisodeTitle,
+    VideoCodec,
+    AudioCodec,
+    Source,
+    VideoTerm,
+    AudioTerm,
+    Language,
+    Subtitles,
+    ReleaseInfo,
+    ReleaseVersion,
+    AnimeType,
+    StreamingSource,
+}
+
+/// The passes of [`parse`](crate::parse), in order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Pass {
+    BracketKeyword,
+    ReleaseGroup,
+    Checksum,
+    FreeTextKeyword,
+    Resolution,
+    Year,
+    Season,
+    Volume,
+    Episode,
+    Title,
+    EpisodeTitle,
+}
+
+/// The episode or season pattern behind a match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Strategy {
+    Episode(EpisodeStrategy),
+    Season(SeasonStrategy),
+}
+
+/// Collects details while parsing; does nothing for plain [`parse`](crate::parse).
+pub(crate) struct Recorder {
+    details: Option<Vec<ElementDetail>>,
+}
+
+impl Recorder {
+    pub(crate) fn off() -> Self {
+        Self { details: None }
+    }
+
+    pub(crate) fn on() -> Self {
+        Self {
+            details: Some(Vec::new()),
+        }
+    }
+
+    pub(crate) fn record(
+        &mut self,
+        kind: ElementKind,
+        value: &str,
+        span: Range<usize>,
+        pass: Pass,
+        strategy: Option<Strategy>,
+        confidence: f32,
+    ) {
+        if let Some(details) = &mut self.details {
+            details.push(ElementDetail {
+                kind,
+                value: value.to_string(),
+                span,
+                pass,
+                strategy,
+                confidence,
+            });
+        }
+    }
+
+    pub(crate) fn finish(self) -> Vec<ElementDetail> {
+        let mut details = self.details.unwrap_or_default();
+        details.sort_by_key(|d| d.span.start);
+        details
+    }
+}
@@ -1,7 +1,11 @@
+use std::borrow::Cow;
+
 use serde::{Deserialize, Serialize};
 
 /// Parsed elements extracted from an anime filename.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+#[serde(default)]
 pub struct Elements {
     /// The anime title.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -12,6 +16,9 @@ pub struct Elements {
     /// Episode number parsed as u32 when possible.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub episode_number: Option<u32>,
+    /// Last episode of a multi-episode file ("01-02", "01~03", "E01E02").
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub episode_number_end: Option<u32>,
     /// Release group name (e.g., "SubsPlease").
     #[serde(skip_serializing_if = "Option::is_none")]
     pub release_group: Option<String>,
@@ -54,6 +61,15 @@ pub struct Elements {
     /// Volume number parsed as u32.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub volume_number: Option<u32>,
+    /// Last volume of a volume range ("Vol.1-3" → 3).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub volume_number_end: Option<u32>,
+    /// Whether this names a batch of episodes rather than a single file:
+    /// marked "Batch" or "Complete", or covering an episode or volume range.
+    /// The range, when known, is in `episode_number..=episode_number_end`
+    /// or `volume_number..=volume_number_end`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_batch: bool,
     /// Release version (e.g., "v2").
     #[serde(skip_serializing_if = "Option::is_none")]
     pub release_version: Option<String>,
@@ -82,3 +98,115 @@ pub struct Elements {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub streaming_source: Option<String>,
 }
+
+/// [`Elements`] borrowing from the parsed filename, as returned by
+/// [`ParseContext::parse`](crate::ParseContext::parse). Values are slices of
+/// the input where possible and owned only when the parser had to build
+/// them, e.g. "1080p" from "1920x1080" or a folded full-width "０５".
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ElementsRef<'a> {
+    /// The anime title.
+    pub title: Option<Cow<'a, str>>,
+    /// Episode number (as string to handle "01", "12.5", "S2", etc.).
+    pub episode: Option<Cow<'a, str>>,
+    /// Episode number parsed as u32 when possible.
+    pub episode_number: Option<u32>,
+    /// Last episode of a multi-episode file ("01-02", "01~03", "E01E02").
+    pub episode_number_end: Option<u32>,
+    /// Release group name (e.g., "SubsPlease").
+    pub release_group: Option<Cow<'a, str>>,
+    /// Video resolution (e.g., "1080p", "720p").
+    pub resolution: Option<Cow<'a, str>>,
+    /// Video codec (e.g., "x264", "HEVC").
+    pub video_codec: Option<Cow<'a, str>>,
+    /// Audio codec (e.g., "FLAC", "AAC").
+    pub audio_codec: Option<Cow<'a, str>>,
+    /// Season number as string (e.g., "2", "01").
+    pub season: Option<Cow<'a, str>>,
+    /// Season number parsed as u32 when possible.
+    pub season_number: Option<u32>,
+    /// File checksum (e.g., "ABCD1234").
+    pub checksum: Option<Cow<'a, str>>,
+    /// Source (e.g., "BD", "WEB", "TV").
+    pub source: Option<Cow<'a, str>>,
+    /// Year of release.
+    pub year: Option<u32>,
+    /// Episode title (text after episode number).
+    pub episode_title: Option<Cow<'a, str>>,
+    /// Part identifier (e.g., "Part 2").
+    pub part: Option<Cow<'a, str>>,
+    /// Part number parsed as u32.
+    pub part_number: Option<u32>,
+    /// Volume identifier (e.g., "Vol.3").
+    pub volume: Option<Cow<'a, str>>,
+    /// Volume number parsed as u32.
+    pub volume_number: Option<u32>,
+    /// Last volume of a volume range ("Vol.1-3" → 3).
+    pub volume_number_end: Option<u32>,
+    /// Whether this names a batch of episodes rather than a single file:
+    /// marked "Batch" or "Complete", or covering an episode or volume range.
+    /// The range, when known, is in `episode_number..=episode_number_end`
+    /// or `volume_number..=volume_number_end`.
+    pub is_batch: bool,
+    /// Release version (e.g., "v2").
+    pub release_version: Option<Cow<'a, str>>,
+    /// Release info terms (e.g., "Remastered", "Uncensored").
+    pub release_info: Vec<Cow<'a, str>>,
+    /// Anime type (e.g., "OVA", "Special", "Movie").
+    pub anime_type: Option<Cow<'a, str>>,
+    /// Language tags found.
+    pub language: Vec<Cow<'a, str>>,
+    /// Subtitle-related tags.
+    pub subtitles: Vec<Cow<'a, str>>,
+    /// Video-related terms (HDR, 10bit, etc.).
+    pub video_term: Vec<Cow<'a, str>>,
+    /// Audio-related terms (Dual Audio, channels, etc.).
+    pub audio_term: Vec<Cow<'a, str>>,
+    /// File extension (e.g., "mkv").
+    pub file_extension: Option<Cow<'a, str>>,
+    /// Streaming source (e.g., "CR", "AMZN").
+    pub streaming_source: Option<Cow<'a, str>>,
+}
+
+impl ElementsRef<'_> {
+    /// Copy the borrowed values out into owned [`Elements`].
+    pub fn into_owned(self) -> Elements {
+        Elements {
+            title: self.title.map(Cow::into_owned),
+            episode: self.episode.map(Cow::into_owned),
+            episode_number: self.episode_number,
+            episode_number_end: self.episode_number_end,
+            release_group: self.release_group.map(Cow::into_owned),
+            resolution: self.resolution.map(Cow::into_owned),
+            video_codec: self.video_codec.map(Cow::into_owned),
+            audio_codec: self.audio_codec.map(Cow::into_owned),
+            season: self.season.map(Cow::into_owned),
+            season_number: self.season_number,
+            checksum: self.checksum.map(Cow::into_owned),
+            source: self.source.map(Cow::into_owned),
+            year: self.year,
+            episode_title: self.episode_title.map(Cow::into_owned),
+            part: self.part.map(Cow::into_owned),
+            part_number: self.part_number,
+            volume: self.volume.map(Cow::into_owned),
+            volume_number: self.volume_number,
+            volume_number_end: self.volume_number_end,
+            is_batch: self.is_batch,
+            release_version: self.release_version.map(Cow::into_owned),
+            release_info: self.release_info.into_iter().map(Cow::into_owned).collect(),
+            anime_type: self.anime_type.map(Cow::into_owned),
+            language: self.language.into_iter().map(Cow::into_owned).collect(),
+            subtitles: self.subtitles.into_iter().map(Cow::into_owned).collect(),
+            video_term: self.video_term.into_iter().map(Cow::into_owned).collect(),
+            audio_term: self.audio_term.into_iter().map(Cow::into_owned).collect(),
+            file_extension: self.file_extension.map(Cow::into_owned),
+            streaming_source: self.streaming_source.map(Cow::into_owned),
+        }
+    }
+}
+
+impl From<ElementsRef<'_>> for Elements {
+    fn from(elements: ElementsRef<'_>) -> Self {
+        elements.into_owned()
+    }
+}
@@ -1,5 +1,10 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::LazyLock;
+
 use bitflags::bitflags;
 use phf::phf_map;
+use regex::Regex;
+use serde::Deserialize;
 
 bitflags! {
     /// Flags controlling when and how a keyword matches.
@@ -14,7 +19,9 @@ bitflags! {
 }
 
 /// The category a keyword belongs to, determining which element it populates.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+#[serde(rename_all = "snake_case")]
 pub enum KeywordKind {
     VideoCodec,
     AudioCodec,
@@ -101,6 +108,7 @@ pub static KEYWORDS: phf::Map<&'static str, KeywordEntry> = phf_map! {
     "10BITS" => KeywordEntry::new(KeywordKind::VideoColorDepth),
     "HI10" => KeywordEntry::new(KeywordKind::VideoColorDepth),
     "HI10P" => KeywordEntry::new(KeywordKind::VideoColorDepth),
+    "MA10P" => KeywordEntry::new(KeywordKind::VideoColorDepth),
     "HI444" => KeywordEntry::new(KeywordKind::VideoColorDepth),
     "HI444P" => KeywordEntry::new(KeywordKind::VideoColorDepth),
     "HI444PP" => KeywordEntry::new(KeywordKind::VideoColorDepth),
@@ -214,7 +222,6 @@ pub static KEYWORDS: phf::Map<&'static str, KeywordEntry> = phf_map! {
     "WEB-RIP" => KeywordEntry::new(KeywordKind::Source),
     "HDCAM" => KeywordEntry::new(KeywordKind::Source),
     "TS" => KeywordEntry::ambiguous(KeywordKind::Source),
-    "BATCH" => KeywordEntry::new(KeywordKind::Source),
     "VHS" => KeywordEntry::new(KeywordKind::Source),
     "VHSRIP" => KeywordEntry::new(KeywordKind::Source),
     "LASERDISC" => KeywordEntry::new(KeywordKind::Source),
@@ -247,6 +254,12 @@ pub static KEYWORDS: phf::Map<&'static str, KeywordEntry> = phf_map! {
     "SPECIAL" => KeywordEntry::new(KeywordKind::EpisodeType),
     "SPECIALS" => KeywordEntry::new(KeywordKind::EpisodeType),
     "OVA" => KeywordEntry::new(KeywordKind::EpisodeType),
+    "剧场版" => KeywordEntry::new(KeywordKind::EpisodeType),
+    "劇場版" => KeywordEntry::new(KeywordKind::EpisodeType),
+    "特别篇" => KeywordEntry::new(KeywordKind::EpisodeType),
+    "特別篇" => KeywordEntry::new(KeywordKind::EpisodeType),
+    "总集篇" => KeywordEntry::new(KeywordKind::EpisodeType),
+    "総集編" => KeywordEntry::new(KeywordKind::EpisodeType),
     "ONA" => KeywordEntry::new(KeywordKind::EpisodeType),
     "OAD" => KeywordEntry::new(KeywordKind::EpisodeType),
     "OAV" => KeywordEntry::new(KeywordKind::EpisodeType),
@@ -304,6 +317,7 @@ pub static KEYWORDS: phf::Map<&'static str, KeywordEntry> = phf_map! {
     "REPACK" => KeywordEntry::new(KeywordKind::ReleaseInfo),
     "REVISED" => KeywordEntry::new(KeywordKind::ReleaseInfo),
     "COMPLETE" => KeywordEntry::new(KeywordKind::ReleaseInfo),
+    "BATCH" => KeywordEntry::new(KeywordKind::ReleaseInfo),
     "FINAL" => KeywordEntry::ambiguous(KeywordKind::ReleaseInfo),
     "PATCHED" => KeywordEntry::new(KeywordKind::ReleaseInfo),
     "WIDESCREEN" => KeywordEntry::new(KeywordKind::ReleaseInfo),
@@ -366,6 +380,36 @@ pub static KEYWORDS: phf::Map<&'static str, KeywordEntry> = phf_map! {
     "TELUGU" => KeywordEntry::new(KeywordKind::Language),
     "MULTI" => KeywordEntry::ambiguous(KeywordKind::Language),
 
+    // ── Chinese fansub languages ─────────────────────────────────
+    // Subtitle languages; combined tags like "简繁内封" are matched by
+    // `cjk_pattern`.
+    "CHS" => KeywordEntry::new(KeywordKind::Language),
+    "CHT" => KeywordEntry::new(KeywordKind::Language),
+    "JPSC" => KeywordEntry::new(KeywordKind::Language),
+    "JPTC" => KeywordEntry::new(KeywordKind::Language),
+    "GB" => KeywordEntry::new(KeywordKind::Language),
+    "BIG5" => KeywordEntry::new(KeywordKind::Language),
+    "简体" => KeywordEntry::new(KeywordKind::Language),
+    "簡體" => KeywordEntry::new(KeywordKind::Language),
+    "繁体" => KeywordEntry::new(KeywordKind::Language),
+    "繁體" => KeywordEntry::new(KeywordKind::Language),
+    "简中" => KeywordEntry::new(KeywordKind::Language),
+    "繁中" => KeywordEntry::new(KeywordKind::Language),
+    "简繁" => KeywordEntry::new(KeywordKind::Language),
+    "繁简" => KeywordEntry::new(KeywordKind::Language),
+    "簡繁" => KeywordEntry::new(KeywordKind::Language),
+    "简日" => KeywordEntry::new(KeywordKind::Language),
+    "繁日" => KeywordEntry::new(KeywordKind::Language),
+    "简繁日" => KeywordEntry::new(KeywordKind::Language),
+    "中日" => KeywordEntry::new(KeywordKind::Language),
+    "中文" => KeywordEntry::new(KeywordKind::Language),
+    "国语" => KeywordEntry::new(KeywordKind::Language),
+    "國語" => KeywordEntry::new(KeywordKind::Language),
+    "粤语" => KeywordEntry::new(KeywordKind::Language),
+    "粵語" => KeywordEntry::new(KeywordKind::Language),
+    "日语" => KeywordEntry::new(KeywordKind::Language),
+    "日語" => KeywordEntry::new(KeywordKind::Language),
+
     // ── Device compatibility ─────────────────────────────────────
     "ANDROID" => KeywordEntry::ambiguous(KeywordKind::DeviceCompat),
     "IPAD3" => KeywordEntry::new(KeywordKind::DeviceCompat),
@@ -391,10 +435,79 @@ pub static KEYWORDS: phf::Map<&'static str, KeywordEntry> = phf_map! {
     "M2TS" => KeywordEntry::new(KeywordKind::FileExtension),
 };
 
+/// Chinese subtitle tags, built from a language ("简繁", "简日", "中文"), a
+/// "bilingual" marker and how the subtitles are carried: "简日双语",
+/// "简繁内封字幕", "繁日内嵌", "外挂字幕".
+static RE_CJK_SUBTITLES: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^(?:[简繁簡日中英]{1,3}(?:体|體|文)?)?(?:双语|雙語)?(?:内封|內封|内嵌|內嵌|外挂|外掛)?(?:字幕|中字)?$",
+    )
+    .unwrap()
+});
+
+/// Airing-season tags: "新番", "10月新番"; and "国漫" (Chinese animation).
+static RE_CJK_RELEASE_INFO: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(?:(?:\d{1,2}月)?新番|国漫|國漫)$").unwrap());
+
+static CJK_SUBTITLES: KeywordEntry = KeywordEntry::new(KeywordKind::Subtitles);
+static CJK_RELEASE_INFO: KeywordEntry = KeywordEntry::new(KeywordKind::ReleaseInfo);
+
+/// Built-in keywords: the [`KEYWORDS`] table, then Chinese fansub tags that
+/// come in too many combinations to list.
+fn builtin(key: &str) -> Option<&'static KeywordEntry> {
+    KEYWORDS.get(key).or_else(|| cjk_pattern(key))
+}
+
+fn cjk_pattern(text: &str) -> Option<&'static KeywordEntry> {
+    // Both patterns are all-CJK; skip the regexes for everything else.
+    if text.is_ascii() {
+        return None;
+    }
+    // Every part of the subtitle pattern is optional, so require a
+    // subtitle-specific part; bare languages are in the table.
+    let has_marker = ["双语", "雙語", "内", "內", "外", "字幕", "中字"]
+        .iter()
+        .any(|m| text.contains(m));
+    if has_marker && RE_CJK_SUBTITLES.is_match(text) {
+        return Some(&CJK_SUBTITLES);
+    }
+    if RE_CJK_RELEASE_INFO.is_match(text) {
+        return Some(&CJK_RELEASE_INFO);
+    }
+    None
+}
+
+/// Call `f` with `s` case-folded by `ascii` (or `unicode` for non-ASCII
+/// text), without allocating unless `s` is long or needs Unicode folding.
+fn with_case<T>(
+    s: &str,
+    ascii: fn(&mut [u8]),
+    unicode: fn(&str) -> String,
+    f: impl FnOnce(&str) -> T,
+) -> T {
+    let mut buf = [0u8; 64];
+    if s.is_ascii() && s.len() <= buf.len() {
+        let buf = &mut buf[..s.len()];
+        buf.copy_from_slice(s.as_bytes());
+        ascii(buf);
+        return f(std::str::from_utf8(buf).expect("ASCII stays ASCII"));
+    }
+    let folded = unicode(s);
+    f(&folded)
+}
+
+fn with_upper<T>(s: &str, f: impl FnOnce(&str) -> T) -> T {
+    with_case(s, <[u8]>::make_ascii_uppercase, str::to_uppercase, f)
+}
+
+fn with_lower<T>(s: &str, f: impl FnOnce(&str) -> T) -> T {
+    with_case(s, <[u8]>::make_ascii_lowercase, str::to_lowercase, f)
+}
+
 /// Look up a keyword (case-insensitive), ignoring flags.
 /// Preserves backward compatibility with the old API.
 pub fn lookup(s: &str) -> Option<KeywordKind> {
-    KEYWORDS.get(s.to_uppercase().as_str()).map(|e| e.kind)
+    with_upper(s, builtin).map(|e| e.kind)
 }
 
 /// Look up a keyword with contextual matching.
@@ -403,9 +516,181 @@ pub fn lookup(s: &str) -> Option<KeywordKind> {
 /// This prevents short/common words like "BD", "SD", "SP" from being matched
 /// when they appear in free text (where they're more likely part of a title).
 pub fn lookup_contextual(s: &str, is_enclosed: bool) -> Option<&'static KeywordEntry> {
-    let entry = KEYWORDS.get(s.to_uppercase().as_str())?;
+    let entry = with_upper(s, builtin)?;
     if !is_enclosed && entry.flags.contains(KeywordFlags::AMBIGUOUS) {
         return None;
     }
     Some(entry)
 }
+
+/// Keywords and release groups supplied at runtime, layered over [`KEYWORDS`].
+///
+/// Loaded from TOML (or JSON, via serde) in this shape:
+///
+/// ```toml
+/// release_groups = ["SubsPlease", "Erai-raws"]
+///
+/// [keywords]
+/// ADN = { kind = "streaming_source" }
+/// SP = { kind = "episode_type", ambiguous = true }
+/// ```
+///
+/// Keyword entries replace built-in entries with the same text. Ambiguous
+/// keywords only match inside brackets.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(from = "KeywordSetFile")]
+pub struct KeywordSet {
+    /// Keyed by uppercase text, like [`KEYWORDS`].
+    keywords: HashMap<String, KeywordEntry>,
+    /// Lowercase release group names.
+    release_groups: HashSet<String>,
+}
+
+/// The serialized form of a [`KeywordSet`], which is also its TypeScript type.
+#[derive(Deserialize)]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+#[serde(rename = "KeywordSet", deny_unknown_fields)]
+struct KeywordSetFile {
+    #[serde(default)]
+    #[cfg_attr(feature = "wasm", tsify(type = "Record<string, KeywordDef>"))]
+    keywords: HashMap<String, KeywordDef>,
+    #[serde(default)]
+    release_groups: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+#[serde(deny_unknown_fields)]
+struct KeywordDef {
+    kind: KeywordKind,
+    #[serde(default)]
+    ambiguous: bool,
+}
+
+impl From<KeywordSetFile> for KeywordSet {
+    fn from(file: KeywordSetFile) -> Self {
+        let keywords = file
+            .keywords
+            .into_iter()
+            .map(|(text, def)| {
+                let entry = if def.ambiguous {
+                    KeywordEntry::ambiguous(def.kind)
+                } else {
+                    KeywordEntry::new(def.kind)
+                };
+                (text.to_uppercase(), entry)
+            })
+            .collect();
+        let release_groups = file
+            .release_groups
+            .iter()
+            .map(|g| g.trim().to_lowercase())
+            .filter(|g| !g.is_empty())
+            .collect();
+        Self {
+            keywords,
+            release_groups,
+        }
+    }
+}
+
+impl KeywordSet {
+    /// Parse a keyword set from TOML.
+    pub fn from_toml(toml_str: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(toml_str)
+    }
+
+    /// [`lookup_contextual`], checking this set before the built-in table.
+    pub fn lookup_contextual(&self, s: &str, is_enclosed: bool) -> Option<KeywordEntry> {
+        let entry = with_upper(s, |key| match self.keywords.get(key) {
+            Some(entry) => Some(*entry),
+            None => builtin(key).copied(),
+        })?;
+        if !is_enclosed && entry.flags.contains(KeywordFlags::AMBIGUOUS) {
+            return None;
+        }
+        Some(entry)
+    }
+
+    /// Whether `s` is a known release group (case-insensitive).
+    pub fn is_release_group(&self, s: &str) -> bool {
+        !self.release_groups.is_empty() && with_lower(s, |name| self.release_groups.contains(name))
+    }
+
+    /// Number of keywords in this set (not counting built-ins).
+    pub fn keyword_count(&self) -> usize {
+        self.keywords.len()
+    }
+
+    /// Number of known release groups.
+    pub fn release_group_count(&self) -> usize {
+        self.release_groups.len()
+    }
+}
+
+/// An empty set, for parsing with built-in keywords only.
+pub(crate) static NO_KEYWORDS: LazyLock<KeywordSet> = LazyLock::new(KeywordSet::default);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USER_SET: &str = r#"
+        release_groups = ["SubsPlease", " Erai-raws "]
+
+        [keywords]
+        ADN = { kind = "streaming_source" }
+        hevc = { kind = "video_term" }
+        NF = { kind = "streaming_source", ambiguous = true }
+    "#;
+
+    #[test]
+    fn test_keyword_set_overrides() {
+        let set = KeywordSet::from_toml(USER_SET).unwrap();
+        assert_eq!(set.keyword_count(), 3);
+        assert_eq!(
+            set.lookup_contextual("adn", false).map(|e| e.kind),
+            Some(KeywordKind::StreamingSource)
+        );
+        // Overrides the built-in codec entry.
+        assert_eq!(
+            set.lookup_contextual("HEVC", false).map(|e| e.kind),
+            Some(KeywordKind::VideoTerm)
+        );
+        // Built-ins still apply.
+        assert_eq!(
+            set.lookup_contextual("FLAC", false).map(|e| e.kind),
+            Some(KeywordKind::AudioCodec)
+        );
+        assert!(set.lookup_contextual("NF", false).is_none());
+        assert!(set.lookup_contextual("NF", true).is_some());
+
+        assert!(set.is_release_group("erai-raws"));
+        assert!(!set.is_release_group("Erai"));
+    }
+
+    #[test]
+    fn test_cjk_tags() {
+        let kind = |s| lookup_contextual(s, true).map(|e| e.kind);
+        assert_eq!(kind("简繁内封"), Some(KeywordKind::Subtitles));
+        assert_eq!(kind("简日双语"), Some(KeywordKind::Subtitles));
+        assert_eq!(kind("繁體內嵌"), Some(KeywordKind::Subtitles));
+        assert_eq!(kind("简繁日内封字幕"), Some(KeywordKind::Subtitles));
+        assert_eq!(kind("外挂字幕"), Some(KeywordKind::Subtitles));
+        assert_eq!(kind("简体"), Some(KeywordKind::Language));
+        assert_eq!(kind("CHT"), Some(KeywordKind::Language));
+        assert_eq!(kind("10月新番"), Some(KeywordKind::ReleaseInfo));
+        assert_eq!(kind("劇場版"), Some(KeywordKind::EpisodeType));
+
+        // Titles and bare characters aren't tags.
+        assert_eq!(kind("葬送的芙莉莲"), None);
+        assert_eq!(kind("日"), None);
+        assert_eq!(kind("内"), None);
+    }
+
+    #[test]
+    fn test_keyword_set_rejects_unknown_kind() {
+        let err = KeywordSet::from_toml("[keywords]\nADN = { kind = \"website\" }").unwrap_err();
+        assert!(err.to_string().contains("unknown variant"));
+    }
+}
@@ -1,7 +1,14 @@
+pub mod detail;
 pub mod elements;
 pub mod keyword;
+pub mod matcher;
+pub mod normalize;
 pub mod parser;
+pub mod path;
 pub mod tokenizer;
 
-pub use elements::Elements;
-pub use parser::parse;
+pub use detail::DetailedElements;
+pub use elements::{Elements, ElementsRef};
+pub use keyword::KeywordSet;
+pub use parser::{parse, parse_detailed, parse_with_keywords, ParseContext};
+pub use path::{parse_path, parse_path_under, ElementSource, ElementSources, PathElements};
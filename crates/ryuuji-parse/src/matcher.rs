@@ -0,0 +1,153 @@
+//! Matching a parsed title against a list of candidates, each known by one
+//! or more titles.
+
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use serde::{Deserialize, Serialize};
+
+use crate::normalize::normalize;
+
+/// Minimum fuzzy score (0.0–1.0) to consider a match valid.
+pub const FUZZY_THRESHOLD: f64 = 0.6;
+
+/// The candidate [`match_title`] picked.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+pub struct TitleMatch {
+    /// Position of the candidate in the list passed in.
+    pub index: usize,
+    pub kind: MatchKind,
+    /// 1.0 for exact and normalized matches, the fuzzy score otherwise.
+    pub confidence: f64,
+}
+
+/// Which pass of [`match_title`] found the candidate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+#[serde(rename_all = "snake_case")]
+pub enum MatchKind {
+    /// One of the candidate's titles is the query, character for character.
+    Exact,
+    /// Equal after [`normalize`].
+    Normalized,
+    /// Closest fuzzy (Skim) score, at or above [`FUZZY_THRESHOLD`].
+    Fuzzy,
+}
+
+/// Find the candidate whose titles best match `query`; `titles` lists a
+/// candidate's title variants.
+///
+/// Strategy: exact → normalized → fuzzy (Skim) → `None`. The first
+/// candidate wins ties.
+///
+/// # Example
+/// ```
+/// use ryuuji_parse::matcher::{match_title, MatchKind};
+///
+/// let candidates = [vec!["Shingeki no Kyojin", "Attack on Titan"], vec!["Frieren"]];
+/// let m = match_title("attack on titan", &candidates, |c| c.clone()).unwrap();
+/// assert_eq!((m.index, m.kind), (0, MatchKind::Normalized));
+/// ```
+pub fn match_title<C>(
+    query: &str,
+    candidates: &[C],
+    titles: impl Fn(&C) -> Vec<&str>,
+) -> Option<TitleMatch> {
+    if query.is_empty() || candidates.is_empty() {
+        return None;
+    }
+
+    let exact = |index| TitleMatch {
+        index,
+        kind: MatchKind::Exact,
+        confidence: 1.0,
+    };
+
+    // Pass 1: Exact match against any title variant or synonym.
+    if let Some(index) = candidates.iter().position(|c| titles(c).contains(&query)) {
+        return Some(exact(index));
+    }
+
+    // Pass 2: Normalized match (lowercase, no punctuation).
+    let normalized_query = normalize(query);
+    if let Some(index) = candidates
+        .iter()
+        .position(|c| titles(c).iter().any(|t| normalize(t) == normalized_query))
+    {
+        return Some(TitleMatch {
+            kind: MatchKind::Normalized,
+            ..exact(index)
+        });
+    }
+
+    // Pass 3: Fuzzy match using Skim algorithm.
+    let matcher = SkimMatcherV2::default();
+    let max_possible = matcher
+        .fuzzy_match(&normalized_query, &normalized_query)
+        .unwrap_or(1)
+        .max(1);
+
+    let mut best: Option<(usize, i64)> = None;
+    for (index, candidate) in candidates.iter().enumerate() {
+        let score = titles(candidate)
+            .iter()
+            .filter_map(|t| matcher.fuzzy_match(&normalize(t), &normalized_query))
+            .max()
+            .unwrap_or(0);
+        if score > best.map_or(0, |(_, s)| s) {
+            best = Some((index, score));
+        }
+    }
+
+    let (index, score) = best?;
+    let confidence = score as f64 / max_possible as f64;
+    (confidence >= FUZZY_THRESHOLD).then_some(TitleMatch {
+        index,
+        kind: MatchKind::Fuzzy,
+        confidence,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidates() -> Vec<Vec<&'static str>> {
+        vec![
+            vec![
+                "Sousou no Frieren",
+                "Frieren: Beyond Journey's End",
+                "葬送のフリーレン",
+                "Frieren",
+            ],
+            vec!["Shingeki no Kyojin", "Attack on Titan"],
+        ]
+    }
+
+    fn best(query: &str) -> Option<TitleMatch> {
+        match_title(query, &candidates(), |c| c.clone())
+    }
+
+    #[test]
+    fn test_passes_in_order() {
+        let m = best("Attack on Titan").unwrap();
+        assert_eq!((m.index, m.kind, m.confidence), (1, MatchKind::Exact, 1.0));
+
+        let m = best("frieren beyond journeys end").unwrap();
+        assert_eq!((m.index, m.kind), (0, MatchKind::Normalized));
+
+        let m = best("Frieren Beyond Journey").unwrap();
+        assert_eq!((m.index, m.kind), (0, MatchKind::Fuzzy));
+        assert!(m.confidence >= FUZZY_THRESHOLD);
+    }
+
+    #[test]
+    fn test_no_match() {
+        assert_eq!(best("Completely Different Anime"), None);
+        assert_eq!(best(""), None);
+        assert_eq!(
+            match_title("Frieren", &[] as &[Vec<&str>], |c| c.clone()),
+            None
+        );
+    }
+}
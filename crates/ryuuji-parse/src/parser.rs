@@ -1,26 +1,42 @@
 pub mod episode;
 pub mod season;
 pub mod title;
+pub mod volume;
 
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::sync::Arc;
+
+use regex::{Captures, Regex};
 use tracing::trace;
 
-use crate::elements::Elements;
-use crate::keyword::{self, KeywordKind};
+use crate::detail::{DetailedElements, ElementKind, Pass, Recorder, Strategy};
+use crate::elements::{Elements, ElementsRef};
+use crate::keyword::{self, KeywordKind, KeywordSet};
 use crate::tokenizer::{self, Token, TokenKind};
 
+thread_local! {
+    /// Buffers reused by [`parse`] and friends on each thread.
+    static CONTEXT: RefCell<ParseContext> = RefCell::new(ParseContext::new());
+}
+
 /// Parse an anime filename into its component elements.
 ///
-/// Uses a 10-pass strategy inspired by anitomy:
+/// Uses an 11-pass strategy inspired by anitomy:
 /// 1. Keywords in bracketed tokens (contextual matching)
-/// 2. Release group (first unidentified bracket before free text)
+/// 2. Release group (a known group anywhere, else the first unidentified
+///    bracket before free text)
 /// 3. Checksum (8-char hex in brackets)
 /// 4. Keywords in free text (contextual matching)
 /// 5. Resolution (NNNNxNNNN or NNNNp)
 /// 6. Year (4-digit 1950–2050)
 /// 7. Season (S2, "2nd Season", 第2期, etc.)
-/// 8. Episode number (13 strategies)
-/// 9. Title (remaining unidentified free text before episode)
-/// 10. Episode title (unidentified text after episode number)
+/// 8. Volume (Vol.3, Vol.1-3)
+/// 9. Episode number (13 strategies)
+/// 10. Title (remaining unidentified free text before episode)
+/// 11. Episode title (unidentified text after episode number)
+///
+/// Finally, [`Elements::is_batch`] is set for batch releases.
 ///
 /// # Example
 /// ```
@@ -31,43 +47,248 @@ use crate::tokenizer::{self, Token, TokenKind};
 /// assert_eq!(result.resolution.as_deref(), Some("1080p"));
 /// assert_eq!(result.checksum.as_deref(), Some("ABCD1234"));
 /// ```
+///
+/// Only the built-in keywords are used; see [`parse_with_keywords`] to add a
+/// [`KeywordSet`].
+///
+/// This is [`ParseContext::parse`] on a per-thread context, with the result
+/// copied out. Use a [`ParseContext`] directly to parse in bulk without the
+/// copies.
 pub fn parse(filename: &str) -> Elements {
-    let (tokens, extension) = tokenizer::tokenize(filename);
-    let mut elements = Elements::default();
-    let mut identified = vec![false; tokens.len()];
+    with_context(|cx| cx.parse(filename).into_owned())
+}
+
+/// Like [`parse`], also using the keywords and release groups in `keywords`.
+pub fn parse_with_keywords(filename: &str, keywords: &KeywordSet) -> Elements {
+    with_context(|cx| cx.parse_with_keywords(filename, keywords).into_owned())
+}
+
+/// Like [`parse`], but also reports each element's byte span in `filename`,
+/// the pass and strategy that found it, and a confidence value.
+///
+/// # Example
+/// ```
+/// use ryuuji_parse::detail::{ElementKind, Strategy};
+/// use ryuuji_parse::parser::episode::EpisodeStrategy;
+///
+/// let input = "[SubsPlease] Sousou no Frieren - 05 (1080p).mkv";
+/// let result = ryuuji_parse::parse_detailed(input);
+/// let episode = result.detail(ElementKind::Episode).unwrap();
+/// assert_eq!(&input[episode.span.clone()], "05");
+/// assert_eq!(episode.strategy, Some(Strategy::Episode(EpisodeStrategy::DashSeparated)));
+/// ```
+pub fn parse_detailed(filename: &str) -> DetailedElements {
+    let mut recorder = Recorder::on();
+    let elements = with_context(|cx| {
+        cx.parse_with(filename, &keyword::NO_KEYWORDS, &mut recorder)
+            .into_owned()
+    });
+    DetailedElements {
+        elements,
+        details: recorder.finish(),
+    }
+}
+
+fn with_context<T>(f: impl FnOnce(&mut ParseContext) -> T) -> T {
+    CONTEXT.with(|cx| match cx.try_borrow_mut() {
+        Ok(mut cx) => f(&mut cx),
+        Err(_) => f(&mut ParseContext::new()),
+    })
+}
 
-    elements.file_extension = extension;
+/// Reusable state for parsing many filenames, such as a torrent feed or a
+/// folder scan.
+///
+/// Each parse allocates its token buffer once, at the size the previous
+/// parse needed; the other buffers are kept between calls. Results are
+/// [`ElementsRef`]s that borrow from the input, so a typical parse allocates
+/// nothing per token or per element.
+///
+/// # Example
+/// ```
+/// use std::borrow::Cow;
+///
+/// let mut cx = ryuuji_parse::ParseContext::new();
+/// for name in [
+///     "[SubsPlease] Sousou no Frieren - 05 (1080p).mkv",
+///     "[Erai-raws] Dandadan - 12 [1080p][Multiple Subtitle].mkv",
+/// ] {
+///     let elements = cx.parse(name);
+///     assert!(matches!(elements.title, Some(Cow::Borrowed(_))));
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct ParseContext {
+    /// Token count of the previous parse. Tokens borrow from their input, so
+    /// only the size of their buffer carries over.
+    token_capacity: usize,
+    identified: Vec<bool>,
+    /// Used by [`ParseContext::parse`] on top of the built-in keywords.
+    keywords: Option<Arc<KeywordSet>>,
+}
+
+impl ParseContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A context whose [`parse`](ParseContext::parse) also uses `keywords`.
+    pub fn with_keywords(keywords: Arc<KeywordSet>) -> Self {
+        Self {
+            keywords: Some(keywords),
+            ..Self::default()
+        }
+    }
+
+    /// Parse `filename` like [`parse`], borrowing from it where possible.
+    pub fn parse<'a>(&mut self, filename: &'a str) -> ElementsRef<'a> {
+        let keywords = self.keywords.clone();
+        let keywords = keywords.as_deref().unwrap_or(&keyword::NO_KEYWORDS);
+        self.parse_with(filename, keywords, &mut Recorder::off())
+    }
+
+    /// Like [`ParseContext::parse`], with `keywords` instead of the
+    /// context's own set.
+    pub fn parse_with_keywords<'a>(
+        &mut self,
+        filename: &'a str,
+        keywords: &KeywordSet,
+    ) -> ElementsRef<'a> {
+        self.parse_with(filename, keywords, &mut Recorder::off())
+    }
+
+    fn parse_with<'a>(
+        &mut self,
+        filename: &'a str,
+        keywords: &KeywordSet,
+        rec: &mut Recorder,
+    ) -> ElementsRef<'a> {
+        let mut tokens = Vec::with_capacity(self.token_capacity);
+        let extension = tokenizer::tokenize_into(filename, &mut tokens);
+        self.token_capacity = tokens.len();
+        self.identified.clear();
+        self.identified.resize(tokens.len(), false);
+
+        let elements = parse_tokens(
+            filename,
+            &tokens,
+            extension,
+            &mut self.identified,
+            keywords,
+            rec,
+        );
+        elements
+    }
+}
+
+/// [`Regex::captures`] allocates even when there is no match, and most
+/// tokens don't match most patterns, so check first.
+fn captures<'t>(re: &Regex, text: &'t str) -> Option<Captures<'t>> {
+    if !re.is_match(text) {
+        return None;
+    }
+    re.captures(text)
+}
+
+/// `part`, a slice of `text`, borrowing from the input when `text` does.
+fn reborrow<'a>(text: &Cow<'a, str>, part: &str) -> Cow<'a, str> {
+    if let Cow::Borrowed(text) = text {
+        let start = (part.as_ptr() as usize).wrapping_sub(text.as_ptr() as usize);
+        let within = start
+            .checked_add(part.len())
+            .and_then(|end| text.get(start..end));
+        if let Some(within) = within.filter(|w| w.as_ptr() == part.as_ptr()) {
+            return Cow::Borrowed(within);
+        }
+    }
+    Cow::Owned(part.to_string())
+}
+
+/// A value computed from `text`, borrowing from the input if it is a slice.
+fn rebase<'a>(text: &Cow<'a, str>, value: Cow<'_, str>) -> Cow<'a, str> {
+    match value {
+        Cow::Borrowed(part) => reborrow(text, part),
+        Cow::Owned(value) => Cow::Owned(value),
+    }
+}
+
+fn parse_tokens<'a>(
+    filename: &'a str,
+    tokens: &[Token<'a>],
+    extension: Option<&'static str>,
+    identified: &mut [bool],
+    keywords: &KeywordSet,
+    rec: &mut Recorder,
+) -> ElementsRef<'a> {
+    let mut elements = ElementsRef {
+        file_extension: extension.map(Cow::Borrowed),
+        ..Default::default()
+    };
     trace!(filename, tokens = tokens.len(), "Tokenized");
 
     // Pass 1: Identify keywords in bracketed tokens (contextual).
-    identify_keywords_contextual(&tokens, &mut elements, &mut identified, true);
+    identify_keywords_contextual(tokens, &mut elements, identified, true, keywords, rec);
 
     // Pass 2: Extract release group (first unidentified bracket before free text).
-    extract_release_group(&tokens, &mut elements, &mut identified);
+    extract_release_group(tokens, &mut elements, identified, keywords, rec);
 
     // Pass 3: Extract checksum (8-char hex in brackets).
-    extract_checksum(&tokens, &mut elements, &mut identified);
+    extract_checksum(tokens, &mut elements, identified, rec);
 
     // Pass 4: Identify keywords in free text (contextual — ambiguous keywords skipped).
-    identify_keywords_contextual(&tokens, &mut elements, &mut identified, false);
+    identify_keywords_contextual(tokens, &mut elements, identified, false, keywords, rec);
 
     // Pass 5: Extract resolution from remaining tokens.
-    extract_resolution(&tokens, &mut elements, &mut identified);
+    extract_resolution(tokens, &mut elements, identified, rec);
 
     // Pass 6: Extract year.
-    extract_year(&tokens, &mut elements, &mut identified);
+    extract_year(tokens, &mut elements, identified, rec);
 
     // Pass 7: Extract season.
-    extract_season(&tokens, &mut elements, &mut identified);
-
-    // Pass 8: Extract episode number.
-    let episode_idx = extract_episode(&tokens, &mut elements, &mut identified);
+    extract_season(tokens, &mut elements, identified, rec);
+
+    // Pass 8: Extract volume.
+    extract_volume(tokens, &mut elements, identified, rec);
+
+    // Pass 9: Extract episode number.
+    let episode_idx = extract_episode(tokens, &mut elements, identified, rec);
+
+    // Pass 10: Extract title, from a bracket if there's no free text for it
+    // ("[Group][Title][05][1080p]").
+    let title = match title::extract_title_spanned(filename, tokens, identified) {
+        // A title with no episode after it may have swallowed one.
+        Some(found) => Some((found, if episode_idx.is_some() { 0.8 } else { 0.6 })),
+        None => title::extract_bracketed_title_spanned(tokens, identified, episode_idx)
+            .map(|found| (found, 0.6)),
+    };
+    if let Some(((text, span), confidence)) = title {
+        rec.record(
+            ElementKind::Title,
+            &text,
+            span,
+            Pass::Title,
+            None,
+            confidence,
+        );
+        elements.title = Some(text);
+    }
 
-    // Pass 9: Extract title.
-    elements.title = title::extract_title(&tokens, &identified);
+    // Pass 11: Extract episode title.
+    if let Some((text, span)) =
+        title::extract_episode_title_spanned(filename, tokens, identified, episode_idx)
+    {
+        rec.record(
+            ElementKind::EpisodeTitle,
+            &text,
+            span,
+            Pass::EpisodeTitle,
+            None,
+            0.6,
+        );
+        elements.episode_title = Some(text);
+    }
 
-    // Pass 10: Extract episode title.
-    elements.episode_title = title::extract_episode_title(&tokens, &identified, episode_idx);
+    elements.is_batch = is_batch(&elements);
 
     trace!(
         title = elements.title.as_deref().unwrap_or(""),
@@ -82,11 +303,13 @@ pub fn parse(filename: &str) -> Elements {
 /// Pass 1 & 4: Identify keywords using contextual matching.
 /// When `enclosed_only` is true, only processes Bracketed tokens.
 /// When false, processes FreeText tokens (ambiguous keywords are skipped).
-fn identify_keywords_contextual(
-    tokens: &[Token],
-    elements: &mut Elements,
+fn identify_keywords_contextual<'a>(
+    tokens: &[Token<'a>],
+    elements: &mut ElementsRef<'a>,
     identified: &mut [bool],
     enclosed_only: bool,
+    keywords: &KeywordSet,
+    rec: &mut Recorder,
 ) {
     for (i, token) in tokens.iter().enumerate() {
         if identified[i] {
@@ -104,33 +327,138 @@ fn identify_keywords_contextual(
 
         let is_enclosed = token.kind == TokenKind::Bracketed || token.is_enclosed;
 
-        if let Some(entry) = keyword::lookup_contextual(&token.text, is_enclosed) {
+        // Known release groups are left for pass 2.
+        if keywords.is_release_group(&token.text) {
+            continue;
+        }
+        if enclosed_only {
+            if let Some(tags) = compound_tag(&token.text, keywords) {
+                for (text, kind) in tags {
+                    if let Some(kind) = apply_keyword(kind, text.clone(), elements) {
+                        rec.record(
+                            kind,
+                            &text,
+                            token.span.clone(),
+                            Pass::BracketKeyword,
+                            None,
+                            0.85,
+                        );
+                    }
+                }
+                identified[i] = true;
+                continue;
+            }
+        }
+        if let Some(entry) = keywords.lookup_contextual(&token.text, is_enclosed) {
             // Skip PREFIX_NUMBER keywords — they're handled by dedicated passes
             // (season, episode, volume) which need to see them in context.
             if entry.flags.contains(keyword::KeywordFlags::PREFIX_NUMBER) {
                 continue;
             }
-            apply_keyword(entry.kind, &token.text, elements);
+            if let Some(kind) = apply_keyword(entry.kind, token.text.clone(), elements) {
+                let (pass, confidence) = if enclosed_only {
+                    (Pass::BracketKeyword, 0.9)
+                } else {
+                    (Pass::FreeTextKeyword, 0.75)
+                };
+                rec.record(
+                    kind,
+                    &token.text,
+                    token.span.clone(),
+                    pass,
+                    None,
+                    confidence,
+                );
+            }
             identified[i] = true;
         }
     }
 }
 
-/// Pass 2: First bracketed token (before free text) is likely the release group.
-fn extract_release_group(tokens: &[Token], elements: &mut Elements, identified: &mut [bool]) {
+/// A bracket packing several tags, common in Chinese releases: "[AVC AAC]",
+/// "[GB_MP4]", "[WebRip 1080p HEVC-10bit AAC]". Returns each word's text and
+/// kind, or `None` unless every word is a keyword or a resolution.
+fn compound_tag<'a>(
+    text: &Cow<'a, str>,
+    keywords: &KeywordSet,
+) -> Option<Vec<(Cow<'a, str>, KeywordKind)>> {
+    let words = || text.split([' ', '_']).filter(|w| !w.is_empty());
+    words().nth(1)?;
+    let tag = |word: &str| -> Option<(Cow<'a, str>, KeywordKind)> {
+        if let Some(res) = parse_resolution(word) {
+            return Some((rebase(text, res), KeywordKind::Resolution));
+        }
+        let entry = keywords.lookup_contextual(word, true)?;
+        (!entry.flags.contains(keyword::KeywordFlags::PREFIX_NUMBER))
+            .then(|| (reborrow(text, word), entry.kind))
+    };
+    let mut tags = Vec::new();
+    for word in words() {
+        match tag(word) {
+            Some(t) => tags.push(t),
+            // "HEVC-10bit"
+            None if word.contains('-') => {
+                for part in word.split('-') {
+                    tags.push(tag(part)?);
+                }
+            }
+            None => return None,
+        }
+    }
+    Some(tags)
+}
+
+/// Pass 2: A known release group name, wherever it is; otherwise the first
+/// bracketed token (before free text) is likely the release group.
+fn extract_release_group<'a>(
+    tokens: &[Token<'a>],
+    elements: &mut ElementsRef<'a>,
+    identified: &mut [bool],
+    keywords: &KeywordSet,
+    rec: &mut Recorder,
+) {
     if elements.release_group.is_some() {
         return;
     }
+    // Prefer bracketed mentions of a known group, then free text.
+    let known = [TokenKind::Bracketed, TokenKind::FreeText]
+        .iter()
+        .find_map(|kind| {
+            tokens.iter().enumerate().position(|(i, t)| {
+                !identified[i] && t.kind == *kind && keywords.is_release_group(&t.text)
+            })
+        });
+    if let Some(i) = known {
+        elements.release_group = Some(tokens[i].text.clone());
+        identified[i] = true;
+        rec.record(
+            ElementKind::ReleaseGroup,
+            &tokens[i].text,
+            tokens[i].span.clone(),
+            Pass::ReleaseGroup,
+            None,
+            0.95,
+        );
+        return;
+    }
     for (i, token) in tokens.iter().enumerate() {
         if identified[i] {
             continue;
         }
         if token.kind == TokenKind::Bracketed
-            && keyword::lookup_contextual(&token.text, true).is_none()
+            && keywords.lookup_contextual(&token.text, true).is_none()
             && !is_checksum(&token.text)
         {
             elements.release_group = Some(token.text.clone());
             identified[i] = true;
+            rec.record(
+                ElementKind::ReleaseGroup,
+                &token.text,
+                token.span.clone(),
+                Pass::ReleaseGroup,
+                None,
+                0.8,
+            );
             return;
         }
         if token.kind == TokenKind::FreeText && token.text != "-" {
@@ -140,11 +468,24 @@ fn extract_release_group(tokens: &[Token], elements: &mut Elements, identified:
 }
 
 /// Pass 3: 8-character hex string in brackets is a CRC32 checksum.
-fn extract_checksum(tokens: &[Token], elements: &mut Elements, identified: &mut [bool]) {
+fn extract_checksum<'a>(
+    tokens: &[Token<'a>],
+    elements: &mut ElementsRef<'a>,
+    identified: &mut [bool],
+    rec: &mut Recorder,
+) {
     for (i, token) in tokens.iter().enumerate() {
         if token.kind == TokenKind::Bracketed && !identified[i] && is_checksum(&token.text) {
             elements.checksum = Some(token.text.clone());
             identified[i] = true;
+            rec.record(
+                ElementKind::Checksum,
+                &token.text,
+                token.span.clone(),
+                Pass::Checksum,
+                None,
+                0.95,
+            );
             return;
         }
     }
@@ -155,7 +496,12 @@ fn is_checksum(s: &str) -> bool {
 }
 
 /// Pass 5: Extract resolution patterns from remaining tokens.
-fn extract_resolution(tokens: &[Token], elements: &mut Elements, identified: &mut [bool]) {
+fn extract_resolution<'a>(
+    tokens: &[Token<'a>],
+    elements: &mut ElementsRef<'a>,
+    identified: &mut [bool],
+    rec: &mut Recorder,
+) {
     if elements.resolution.is_some() {
         return;
     }
@@ -165,6 +511,15 @@ fn extract_resolution(tokens: &[Token], elements: &mut Elements, identified: &mu
         }
         if matches!(token.kind, TokenKind::FreeText | TokenKind::Bracketed) {
             if let Some(res) = parse_resolution(&token.text) {
+                let res = rebase(&token.text, res);
+                rec.record(
+                    ElementKind::Resolution,
+                    &res,
+                    token.span.clone(),
+                    Pass::Resolution,
+                    None,
+                    0.95,
+                );
                 elements.resolution = Some(res);
                 identified[i] = true;
                 return;
@@ -174,7 +529,12 @@ fn extract_resolution(tokens: &[Token], elements: &mut Elements, identified: &mu
 }
 
 /// Pass 6: Extract year (4-digit 1950-2050).
-fn extract_year(tokens: &[Token], elements: &mut Elements, identified: &mut [bool]) {
+fn extract_year<'a>(
+    tokens: &[Token<'a>],
+    elements: &mut ElementsRef<'a>,
+    identified: &mut [bool],
+    rec: &mut Recorder,
+) {
     if elements.year.is_some() {
         return;
     }
@@ -187,6 +547,14 @@ fn extract_year(tokens: &[Token], elements: &mut Elements, identified: &mut [boo
             if let Some(year) = parse_year(&token.text) {
                 elements.year = Some(year);
                 identified[i] = true;
+                rec.record(
+                    ElementKind::Year,
+                    &token.text,
+                    token.span.clone(),
+                    Pass::Year,
+                    None,
+                    0.9,
+                );
                 return;
             }
         }
@@ -202,6 +570,15 @@ fn extract_year(tokens: &[Token], elements: &mut Elements, identified: &mut [boo
                 if let Some(year) = parse_year(&token.text) {
                     elements.year = Some(year);
                     identified[i] = true;
+                    // Could be a title like "2001 Nights".
+                    rec.record(
+                        ElementKind::Year,
+                        &token.text,
+                        token.span.clone(),
+                        Pass::Year,
+                        None,
+                        0.6,
+                    );
                     return;
                 }
             }
@@ -211,7 +588,12 @@ fn extract_year(tokens: &[Token], elements: &mut Elements, identified: &mut [boo
 }
 
 /// Pass 7: Extract season number.
-fn extract_season(tokens: &[Token], elements: &mut Elements, identified: &mut [bool]) {
+fn extract_season<'a>(
+    tokens: &[Token<'a>],
+    elements: &mut ElementsRef<'a>,
+    identified: &mut [bool],
+    rec: &mut Recorder,
+) {
     if elements.season_number.is_some() {
         return;
     }
@@ -224,7 +606,8 @@ fn extract_season(tokens: &[Token], elements: &mut Elements, identified: &mut [b
         }
         if matches!(token.kind, TokenKind::FreeText | TokenKind::Bracketed) {
             if let Some(m) = season::try_extract(&token.text) {
-                elements.season = Some(m.raw);
+                record_season(rec, &m, token.span.clone());
+                elements.season = Some(reborrow(&token.text, m.raw));
                 elements.season_number = Some(m.number);
                 identified[i] = true;
                 return;
@@ -234,31 +617,132 @@ fn extract_season(tokens: &[Token], elements: &mut Elements, identified: &mut [b
 
     // Try multi-word patterns: "Season 2", "2nd Season", "Saison 3".
     // Look at pairs of consecutive free text tokens.
-    let free_indices: Vec<usize> = tokens
-        .iter()
-        .enumerate()
-        .filter(|(i, t)| !identified[*i] && t.kind == TokenKind::FreeText && t.text != "-")
-        .map(|(i, _)| i)
-        .collect();
-
-    for pair in free_indices.windows(2) {
-        let combined = format!("{} {}", tokens[pair[0]].text, tokens[pair[1]].text);
-        if let Some(m) = season::try_extract(&combined) {
-            elements.season = Some(m.raw);
-            elements.season_number = Some(m.number);
-            identified[pair[0]] = true;
-            identified[pair[1]] = true;
-            return;
+    let is_season_word =
+        |t: &Token| t.text.eq_ignore_ascii_case("season") || t.text.eq_ignore_ascii_case("saison");
+    let found = free_text_pairs(tokens, identified).find_map(|(a, b)| {
+        if !is_season_word(&tokens[a]) && !is_season_word(&tokens[b]) {
+            return None;
         }
+        let combined = format!("{} {}", tokens[a].text, tokens[b].text);
+        let m = season::try_extract(&combined)?;
+        record_season(rec, &m, tokens[a].span.start..tokens[b].span.end);
+        Some((a, b, m.raw.to_string(), m.number))
+    });
+    if let Some((a, b, raw, number)) = found {
+        elements.season = Some(Cow::Owned(raw));
+        elements.season_number = Some(number);
+        identified[a] = true;
+        identified[b] = true;
     }
 }
 
-/// Pass 8: Extract episode number using 13 strategies.
+fn record_season(rec: &mut Recorder, m: &season::SeasonMatch, span: std::ops::Range<usize>) {
+    rec.record(
+        ElementKind::Season,
+        m.raw,
+        span,
+        Pass::Season,
+        Some(Strategy::Season(m.strategy)),
+        m.strategy.confidence(),
+    );
+}
+
+/// Pass 8: Extract volume number, and a range if one follows.
+fn extract_volume<'a>(
+    tokens: &[Token<'a>],
+    elements: &mut ElementsRef<'a>,
+    identified: &mut [bool],
+    rec: &mut Recorder,
+) {
+    // "Vol3" in one token, or "Vol.3" / "Volume 3" split across two.
+    let is_free =
+        |i: usize| !identified[i] && tokens[i].kind == TokenKind::FreeText && tokens[i].text != "-";
+    let found = (0..tokens.len())
+        .filter(|&i| is_free(i))
+        .find_map(|i| {
+            let m = volume::try_extract(&tokens[i].text)?;
+            Some((i, i, reborrow(&tokens[i].text, m.raw), m.number))
+        })
+        .or_else(|| {
+            free_text_pairs(tokens, identified).find_map(|(a, b)| {
+                let word = tokens[a].text.get(..3)?;
+                if !word.eq_ignore_ascii_case("vol") {
+                    return None;
+                }
+                let combined = format!("{} {}", tokens[a].text, tokens[b].text);
+                let m = volume::try_extract(&combined)?;
+                Some((a, b, Cow::Owned(m.raw.to_string()), m.number))
+            })
+        });
+    let Some((first, mut last, mut raw, number)) = found else {
+        return;
+    };
+
+    elements.volume_number = Some(number);
+    identified[first..=last].fill(true);
+    // "Vol.1-3": same adjacency rule as episode ranges.
+    if let (Some(dash), Some(end_token)) = (tokens.get(last + 1), tokens.get(last + 2)) {
+        if dash.kind == TokenKind::FreeText
+            && dash.text == "-"
+            && end_token.kind == TokenKind::FreeText
+        {
+            if let Some(end) = episode::try_range_end(number, &end_token.text) {
+                elements.volume_number_end = Some(end);
+                raw.to_mut().push('-');
+                raw.to_mut().push_str(&end_token.text);
+                identified[last + 1] = true;
+                identified[last + 2] = true;
+                last += 2;
+            }
+        }
+    }
+
+    rec.record(
+        ElementKind::Volume,
+        &raw,
+        tokens[first].span.start..tokens[last].span.end,
+        Pass::Volume,
+        None,
+        0.9,
+    );
+    elements.volume = Some(raw);
+}
+
+/// Consecutive pairs of unidentified free text tokens, for patterns split
+/// across two words.
+fn free_text_pairs<'t>(
+    tokens: &'t [Token],
+    identified: &'t [bool],
+) -> impl Iterator<Item = (usize, usize)> + 't {
+    let free = move || {
+        (0..tokens.len()).filter(move |&i| {
+            !identified[i] && tokens[i].kind == TokenKind::FreeText && tokens[i].text != "-"
+        })
+    };
+    free().zip(free().skip(1))
+}
+
+/// Whether the parsed release is a batch. A two-episode range is usually a
+/// double-episode file, so it takes at least three to count.
+fn is_batch(elements: &ElementsRef) -> bool {
+    let marked = elements
+        .release_info
+        .iter()
+        .any(|info| info.eq_ignore_ascii_case("batch") || info.eq_ignore_ascii_case("complete"));
+    let episodes = match (elements.episode_number, elements.episode_number_end) {
+        (Some(start), Some(end)) => end.saturating_sub(start) + 1,
+        _ => 1,
+    };
+    marked || episodes > 2 || elements.volume_number_end.is_some()
+}
+
+/// Pass 9: Extract episode number using 13 strategies.
 /// Returns the token index where the episode was found (for episode title extraction).
-fn extract_episode(
-    tokens: &[Token],
-    elements: &mut Elements,
+fn extract_episode<'a>(
+    tokens: &[Token<'a>],
+    elements: &mut ElementsRef<'a>,
     identified: &mut [bool],
+    rec: &mut Recorder,
 ) -> Option<usize> {
     // Strategy 3: Dash-separated "- 08", "- 08v2".
     // This is the most common anime filename pattern.
@@ -267,12 +751,22 @@ fn extract_episode(
             continue;
         }
         if tokens[i].text == "-" {
+            // The dash inside "S01E01-E02" is part of the range.
+            if i > 0 && continues_range(&tokens[i - 1], tokens.get(i + 1)) {
+                continue;
+            }
             identified[i] = true;
             if let Some(next) = next_free_text(tokens, identified, i) {
                 if let Some(m) = episode::try_extract(&tokens[next].text) {
-                    apply_episode(&m, elements);
-                    identified[next] = true;
-                    return Some(next);
+                    let strategy = match m.strategy {
+                        episode::EpisodeStrategy::PlainNumber => {
+                            episode::EpisodeStrategy::DashSeparated
+                        }
+                        other => other,
+                    };
+                    return Some(take_episode(
+                        &m, strategy, tokens, next, elements, identified, rec,
+                    ));
                 }
             }
         }
@@ -286,10 +780,9 @@ fn extract_episode(
         }
         if matches!(token.kind, TokenKind::FreeText | TokenKind::Bracketed) {
             if let Some(m) = episode::try_extract(&token.text) {
-                // For combined S01E05, also set the season.
-                apply_episode(&m, elements);
-                identified[i] = true;
-                return Some(i);
+                return Some(take_episode(
+                    &m, m.strategy, tokens, i, elements, identified, rec,
+                ));
             }
         }
     }
@@ -300,9 +793,15 @@ fn extract_episode(
             continue;
         }
         if let Some(m) = episode::try_plain_number(&token.text) {
-            apply_episode(&m, elements);
-            identified[i] = true;
-            return Some(i);
+            return Some(take_episode(
+                &m,
+                episode::EpisodeStrategy::IsolatedBracket,
+                tokens,
+                i,
+                elements,
+                identified,
+                rec,
+            ));
         }
     }
 
@@ -315,9 +814,9 @@ fn extract_episode(
         if token.kind == TokenKind::FreeText && token.text != "-" {
             if saw_text {
                 if let Some(m) = episode::try_plain_number(&token.text) {
-                    apply_episode(&m, elements);
-                    identified[i] = true;
-                    return Some(i);
+                    return Some(take_episode(
+                        &m, m.strategy, tokens, i, elements, identified, rec,
+                    ));
                 }
             } else {
                 // Check if this is text (not a number) to mark as "seen title text".
@@ -331,21 +830,118 @@ fn extract_episode(
     None
 }
 
-/// Apply an episode match to the elements.
-fn apply_episode(m: &episode::EpisodeMatch, elements: &mut Elements) {
-    elements.episode = Some(m.raw.clone());
+/// Take the episode match for token `idx`, extend it to a range if one
+/// follows, and record it. Returns the index of the last episode token.
+fn take_episode<'a>(
+    m: &episode::EpisodeMatch,
+    strategy: episode::EpisodeStrategy,
+    tokens: &[Token<'a>],
+    idx: usize,
+    elements: &mut ElementsRef<'a>,
+    identified: &mut [bool],
+    rec: &mut Recorder,
+) -> usize {
+    let had_season = elements.season_number.is_some();
+    // For combined S01E05, also set the season.
+    apply_episode(m, reborrow(&tokens[idx].text, m.raw), elements);
+    identified[idx] = true;
+    let last = extend_episode_range(tokens, elements, identified, idx);
+
+    let span = tokens[idx].span.start..tokens[last].span.end;
+    let confidence = strategy.confidence();
+    let strategy = Some(Strategy::Episode(strategy));
+    if let Some(raw) = &elements.episode {
+        rec.record(
+            ElementKind::Episode,
+            raw,
+            span.clone(),
+            Pass::Episode,
+            strategy,
+            confidence,
+        );
+    }
+    if !had_season {
+        if let Some(season) = &elements.season {
+            rec.record(
+                ElementKind::Season,
+                season,
+                span,
+                Pass::Episode,
+                strategy,
+                0.95,
+            );
+        }
+    }
+    last
+}
+
+/// Apply an episode match to the elements, with `raw` as the episode text.
+fn apply_episode<'a>(m: &episode::EpisodeMatch, raw: Cow<'a, str>, elements: &mut ElementsRef<'a>) {
+    elements.episode = Some(raw);
     elements.episode_number = Some(m.number);
+    elements.episode_number_end = m.end;
     if let Some(season) = m.season {
         if elements.season_number.is_none() {
             elements.season_number = Some(season);
-            elements.season = Some(season.to_string());
+            elements.season = Some(Cow::Owned(season.to_string()));
         }
     }
     if let Some(ref version) = m.version {
-        elements.release_version = Some(version.clone());
+        elements.release_version = Some(Cow::Owned(version.clone()));
     }
 }
 
+/// The tokenizer splits "01-02" and "S01E01-E02" at the dash. If the episode
+/// token at `idx` is directly followed by a dash and a later episode, take
+/// them as the end of a range. Returns the index of the last episode token.
+fn extend_episode_range<'a>(
+    tokens: &[Token<'a>],
+    elements: &mut ElementsRef<'a>,
+    identified: &mut [bool],
+    idx: usize,
+) -> usize {
+    if elements.episode_number_end.is_some() {
+        return idx;
+    }
+    let (Some(start), Some(dash), Some(end_token)) = (
+        elements.episode_number,
+        tokens.get(idx + 1),
+        tokens.get(idx + 2),
+    ) else {
+        return idx;
+    };
+    // "05 - 06" with spaces is an episode followed by something else.
+    if dash.kind != TokenKind::FreeText || dash.text != "-" || end_token.kind != TokenKind::FreeText
+    {
+        return idx;
+    }
+    match episode::try_range_end(start, &end_token.text) {
+        Some(end) => {
+            elements.episode_number_end = Some(end);
+            if let Some(raw) = &mut elements.episode {
+                raw.to_mut().push('-');
+                raw.to_mut().push_str(&end_token.text);
+            }
+            identified[idx + 1] = true;
+            identified[idx + 2] = true;
+            idx + 2
+        }
+        None => idx,
+    }
+}
+
+/// Whether `prev`, a dash and `next` read as one episode range.
+fn continues_range(prev: &Token, next: Option<&Token>) -> bool {
+    let Some(next) = next else {
+        return false;
+    };
+    prev.kind == TokenKind::FreeText
+        && next.kind == TokenKind::FreeText
+        && episode::try_extract(&prev.text)
+            .and_then(|m| episode::try_range_end(m.number, &next.text))
+            .is_some()
+}
+
 /// Find the next unidentified free text token after index `start`.
 fn next_free_text(tokens: &[Token], identified: &[bool], start: usize) -> Option<usize> {
     for i in (start + 1)..tokens.len() {
@@ -363,23 +959,22 @@ fn next_free_text(tokens: &[Token], identified: &[bool], start: usize) -> Option
 }
 
 /// Try to parse a resolution string.
-fn parse_resolution(s: &str) -> Option<String> {
-    let lower = s.to_lowercase();
-
+fn parse_resolution(s: &str) -> Option<Cow<'_, str>> {
     // "1920x1080" → "1080p"
-    if let Some(pos) = lower.find('x') {
-        let height = &lower[pos + 1..];
-        if height.parse::<u32>().is_ok() {
-            return Some(format!("{height}p"));
+    if let Some((width, height)) = s.split_once(['x', 'X']) {
+        if width.parse::<u32>().is_ok() && height.parse::<u32>().is_ok() {
+            return Some(Cow::Owned(format!("{height}p")));
         }
     }
 
     // Already a resolution like "1080p" or "1080i"
-    if lower.ends_with('p') || lower.ends_with('i') {
-        let num_part = &lower[..lower.len() - 1];
-        if num_part.parse::<u32>().is_ok() {
-            return Some(lower);
-        }
+    let unit = s.chars().next_back()?;
+    if matches!(unit, 'p' | 'i' | 'P' | 'I') && s[..s.len() - 1].parse::<u32>().is_ok() {
+        return Some(if unit.is_ascii_lowercase() {
+            Cow::Borrowed(s)
+        } else {
+            Cow::Owned(s.to_ascii_lowercase())
+        });
     }
 
     None
@@ -398,61 +993,62 @@ fn parse_year(s: &str) -> Option<u32> {
 }
 
 /// Apply a keyword match to the appropriate element field.
-fn apply_keyword(kind: KeywordKind, text: &str, elements: &mut Elements) {
-    match kind {
-        KeywordKind::VideoCodec => {
-            if elements.video_codec.is_none() {
-                elements.video_codec = Some(text.to_string());
-            }
-        }
-        KeywordKind::AudioCodec => {
-            if elements.audio_codec.is_none() {
-                elements.audio_codec = Some(text.to_string());
-            }
-        }
-        KeywordKind::Resolution => {
-            if elements.resolution.is_none() {
-                elements.resolution = Some(text.to_string());
-            }
-        }
-        KeywordKind::Source => {
-            if elements.source.is_none() {
-                elements.source = Some(text.to_string());
-            }
+/// Returns the field it was stored in, if any.
+fn apply_keyword<'a>(
+    kind: KeywordKind,
+    text: Cow<'a, str>,
+    elements: &mut ElementsRef<'a>,
+) -> Option<ElementKind> {
+    fn set<'a>(
+        field: &mut Option<Cow<'a, str>>,
+        text: Cow<'a, str>,
+        kind: ElementKind,
+    ) -> Option<ElementKind> {
+        if field.is_some() {
+            return None;
         }
+        *field = Some(text);
+        Some(kind)
+    }
+    fn push<'a>(
+        field: &mut Vec<Cow<'a, str>>,
+        text: Cow<'a, str>,
+        kind: ElementKind,
+    ) -> Option<ElementKind> {
+        field.push(text);
+        Some(kind)
+    }
+
+    match kind {
+        KeywordKind::VideoCodec => set(&mut elements.video_codec, text, ElementKind::VideoCodec),
+        KeywordKind::AudioCodec => set(&mut elements.audio_codec, text, ElementKind::AudioCodec),
+        KeywordKind::Resolution => set(&mut elements.resolution, text, ElementKind::Resolution),
+        KeywordKind::Source => set(&mut elements.source, text, ElementKind::Source),
         KeywordKind::VideoColorDepth
         | KeywordKind::VideoDynamicRange
         | KeywordKind::VideoTerm
         | KeywordKind::VideoFrameRate => {
-            elements.video_term.push(text.to_string());
+            push(&mut elements.video_term, text, ElementKind::VideoTerm)
         }
         KeywordKind::AudioChannels | KeywordKind::AudioTerm => {
-            elements.audio_term.push(text.to_string());
-        }
-        KeywordKind::Language => {
-            elements.language.push(text.to_string());
-        }
-        KeywordKind::Subtitles => {
-            elements.subtitles.push(text.to_string());
+            push(&mut elements.audio_term, text, ElementKind::AudioTerm)
         }
+        KeywordKind::Language => push(&mut elements.language, text, ElementKind::Language),
+        KeywordKind::Subtitles => push(&mut elements.subtitles, text, ElementKind::Subtitles),
         KeywordKind::ReleaseInfo => {
-            elements.release_info.push(text.to_string());
-        }
-        KeywordKind::ReleaseVersion => {
-            if elements.release_version.is_none() {
-                elements.release_version = Some(text.to_string());
-            }
-        }
-        KeywordKind::EpisodeType => {
-            if elements.anime_type.is_none() {
-                elements.anime_type = Some(text.to_string());
-            }
-        }
-        KeywordKind::StreamingSource => {
-            if elements.streaming_source.is_none() {
-                elements.streaming_source = Some(text.to_string());
-            }
+            push(&mut elements.release_info, text, ElementKind::ReleaseInfo)
         }
+        KeywordKind::ReleaseVersion => set(
+            &mut elements.release_version,
+            text,
+            ElementKind::ReleaseVersion,
+        ),
+        KeywordKind::EpisodeType => set(&mut elements.anime_type, text, ElementKind::AnimeType),
+        KeywordKind::StreamingSource => set(
+            &mut elements.streaming_source,
+            text,
+            ElementKind::StreamingSource,
+        ),
         KeywordKind::Season
         | KeywordKind::Episode
         | KeywordKind::Part
@@ -460,6 +1056,7 @@ fn apply_keyword(kind: KeywordKind, text: &str, elements: &mut Elements) {
         | KeywordKind::DeviceCompat
         | KeywordKind::FileExtension => {
             // Handled by dedicated passes or not stored separately.
+            None
         }
     }
 }
@@ -512,16 +1109,16 @@ mod tests {
     #[test]
     fn test_episode_number_parsing() {
         assert_eq!(
-            episode::try_extract("05").map(|m| (m.raw.clone(), m.number)),
-            Some(("05".into(), 5))
+            episode::try_extract("05").map(|m| (m.raw, m.number)),
+            Some(("05", 5))
         );
         assert_eq!(
-            episode::try_extract("12v2").map(|m| (m.raw.clone(), m.number)),
-            Some(("12v2".into(), 12))
+            episode::try_extract("12v2").map(|m| (m.raw, m.number)),
+            Some(("12v2", 12))
         );
         assert_eq!(
-            episode::try_extract("12.5").map(|m| (m.raw.clone(), m.number)),
-            Some(("12.5".into(), 12))
+            episode::try_extract("12.5").map(|m| (m.raw, m.number)),
+            Some(("12.5", 12))
         );
         assert!(episode::try_extract("2024").is_none());
         assert!(episode::try_extract("abc").is_none());
@@ -650,6 +1247,289 @@ mod tests {
         assert_eq!(r.episode_number, None);
     }
 
+    #[test]
+    fn test_episode_ranges() {
+        let r = parse("[Group] Title - 01-02 [1080p].mkv");
+        assert_eq!(r.title.as_deref(), Some("Title"));
+        assert_eq!(r.episode_number, Some(1));
+        assert_eq!(r.episode_number_end, Some(2));
+        assert_eq!(r.episode.as_deref(), Some("01-02"));
+
+        let r = parse("[Group] Title - 01~03 [1080p].mkv");
+        assert_eq!((r.episode_number, r.episode_number_end), (Some(1), Some(3)));
+
+        let r = parse("Title E01E02 [1080p].mkv");
+        assert_eq!((r.episode_number, r.episode_number_end), (Some(1), Some(2)));
+
+        let r = parse("Title S01E01-E02 [1080p].mkv");
+        assert_eq!((r.episode_number, r.episode_number_end), (Some(1), Some(2)));
+        assert_eq!(r.season_number, Some(1));
+    }
+
+    #[test]
+    fn test_single_episode_has_no_range() {
+        let r = parse("[Group] Title - 05 - The Journey Begins [1080p].mkv");
+        assert_eq!(r.episode_number, Some(5));
+        assert_eq!(r.episode_number_end, None);
+
+        // Spaced dashes separate the episode from what follows.
+        let r = parse("[Group] Title - 05 - 06 [1080p].mkv");
+        assert_eq!(r.episode_number, Some(5));
+        assert_eq!(r.episode_number_end, None);
+    }
+
+    #[test]
+    fn test_batches() {
+        let r = parse("[Group] Title (01-12) [BD 1080p] [Batch]");
+        assert!(r.is_batch);
+        assert_eq!(r.title.as_deref(), Some("Title"));
+        assert_eq!(
+            (r.episode_number, r.episode_number_end),
+            (Some(1), Some(12))
+        );
+
+        let r = parse("Title S01 Complete");
+        assert!(r.is_batch);
+        assert_eq!(r.season_number, Some(1));
+        assert_eq!(r.episode_number, None);
+
+        let r = parse("[Group] Title Vol.1-3 [BD]");
+        assert!(r.is_batch);
+        assert_eq!(r.title.as_deref(), Some("Title"));
+        assert_eq!((r.volume_number, r.volume_number_end), (Some(1), Some(3)));
+        assert_eq!(r.episode_number, None);
+    }
+
+    #[test]
+    fn test_single_releases_are_not_batches() {
+        let r = parse("[Group] Title - 05 [1080p].mkv");
+        assert!(!r.is_batch);
+
+        // A double episode is one file, not a batch.
+        let r = parse("[Group] Title - 01-02 [1080p].mkv");
+        assert!(!r.is_batch);
+
+        let r = parse("[Group] Title Vol.2 - 05 [1080p].mkv");
+        assert!(!r.is_batch);
+        assert_eq!(r.volume_number, Some(2));
+        assert_eq!(r.episode_number, Some(5));
+        assert_eq!(r.title.as_deref(), Some("Title"));
+    }
+
+    #[test]
+    fn test_parse_detailed() {
+        use crate::detail::{ElementKind, Pass, Strategy};
+        use episode::EpisodeStrategy;
+
+        let input = "[SubsPlease] Sousou no Frieren S2 - 05v2 (1080p) [ABCD1234].mkv";
+        let r = parse_detailed(input);
+        assert_eq!(r.elements.title.as_deref(), Some("Sousou no Frieren"));
+        let text = |kind| {
+            let d = r.detail(kind).unwrap();
+            (&input[d.span.clone()], d.pass, d.strategy)
+        };
+        assert_eq!(
+            text(ElementKind::Title),
+            ("Sousou no Frieren", Pass::Title, None)
+        );
+        assert_eq!(
+            text(ElementKind::ReleaseGroup),
+            ("SubsPlease", Pass::ReleaseGroup, None)
+        );
+        assert_eq!(
+            text(ElementKind::Episode),
+            (
+                "05v2",
+                Pass::Episode,
+                Some(Strategy::Episode(EpisodeStrategy::VersionSuffix))
+            )
+        );
+        assert_eq!(text(ElementKind::Season).0, "S2");
+        assert_eq!(text(ElementKind::Resolution).0, "1080p");
+        assert_eq!(text(ElementKind::Checksum).0, "ABCD1234");
+
+        // Details come in input order.
+        assert!(r
+            .details
+            .windows(2)
+            .all(|w| w[0].span.start <= w[1].span.start));
+    }
+
+    #[test]
+    fn test_parse_context_borrows() {
+        let mut cx = ParseContext::new();
+        let r = cx.parse("[SubsPlease] Sousou no Frieren - 05 (1920x1080) [ABCD1234].mkv");
+        assert!(matches!(r.title, Some(Cow::Borrowed("Sousou no Frieren"))));
+        assert!(matches!(r.release_group, Some(Cow::Borrowed("SubsPlease"))));
+        assert!(matches!(r.episode, Some(Cow::Borrowed("05"))));
+        assert!(matches!(r.checksum, Some(Cow::Borrowed("ABCD1234"))));
+        // Values the parser builds are owned.
+        assert!(matches!(r.resolution, Some(Cow::Owned(ref res)) if res == "1080p"));
+
+        let r = cx.parse("[Group] Ｔｉｔｌｅ - ０５ [720p].mkv");
+        assert!(matches!(r.title, Some(Cow::Owned(ref title)) if title == "Title"));
+        assert!(matches!(r.resolution, Some(Cow::Borrowed("720p"))));
+
+        // The flags are kept for the next parse, and its tokens are sized
+        // from this one.
+        let flags = cx.identified.as_ptr();
+        let input = "[Group] Title - 06 [720p].mkv";
+        cx.parse(input);
+        assert_eq!(cx.identified.as_ptr(), flags);
+        assert_eq!(cx.token_capacity, tokenizer::tokenize(input).0.len());
+    }
+
+    #[test]
+    fn test_parse_context_matches_parse() {
+        let mut cx = ParseContext::new();
+        for input in [
+            "[HorribleSubs]_Naruto_Shippuuden_-_500_[720p].mkv",
+            "[Group] Title Season 2 - 01-02 [BD][1080p].mkv",
+            "[Group] Title Vol.1-3 [BD]",
+            "[桜都字幕组] 葬送的芙莉莲 ／ Sousou no Frieren [０５][１０８０Ｐ][简繁内封].mp4",
+            "[LoliHouse] Title - 05 - The Journey Begins [WebRip 1080p HEVC-10bit AAC].mkv",
+        ] {
+            let borrowed = serde_json::to_value(cx.parse(input).into_owned()).unwrap();
+            let owned = serde_json::to_value(parse(input)).unwrap();
+            assert_eq!(borrowed, owned, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_detailed_confidence() {
+        use crate::detail::ElementKind;
+
+        let dashed = parse_detailed("[Group] Title - 05 [1080p].mkv");
+        let guessed = parse_detailed("Title 05 [1080p].mkv");
+        let confidence = |r: &crate::detail::DetailedElements| {
+            r.detail(ElementKind::Episode).unwrap().confidence
+        };
+        assert!(confidence(&dashed) > confidence(&guessed));
+
+        let r = parse_detailed("[Group] Title S01E01-E02 [1080p].mkv");
+        let episode = r.detail(ElementKind::Episode).unwrap();
+        assert_eq!(episode.value, "S01E01-E02");
+        let season = r.detail(ElementKind::Season).unwrap();
+        assert_eq!(season.span, episode.span);
+
+        // Plain parsing records nothing but gives the same elements.
+        assert_eq!(
+            parse("[Group] Title - 05 [1080p].mkv").episode_number,
+            dashed.elements.episode_number
+        );
+    }
+
+    #[test]
+    fn test_user_keywords() {
+        let set = KeywordSet::from_toml(
+            r#"
+            release_groups = ["Erai-raws", "ASW"]
+            [keywords]
+            ADN = { kind = "streaming_source" }
+            "#,
+        )
+        .unwrap();
+
+        let r = parse_with_keywords("[Erai-raws] Title - 05 [1080p][ADN].mkv", &set);
+        assert_eq!(r.release_group.as_deref(), Some("Erai-raws"));
+        assert_eq!(r.streaming_source.as_deref(), Some("ADN"));
+        assert_eq!(r.title.as_deref(), Some("Title"));
+
+        // A known group is found even when it isn't the first bracket.
+        let r = parse_with_keywords("Title - 05 [1080p] [ASW].mkv", &set);
+        assert_eq!(r.release_group.as_deref(), Some("ASW"));
+        assert_eq!(r.episode_number, Some(5));
+
+        // Without the set, ADN is just another bracket.
+        let r = parse("[Erai-raws] Title - 05 [1080p][ADN].mkv");
+        assert_eq!(r.streaming_source, None);
+
+        let mut cx = ParseContext::with_keywords(Arc::new(set));
+        let r = cx.parse("[Erai-raws] Title - 05 [1080p][ADN].mkv");
+        assert_eq!(r.streaming_source.as_deref(), Some("ADN"));
+    }
+
+    /// One `[[release]]` of `tests/fixtures/cjk.toml`.
+    #[derive(serde::Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Fixture {
+        input: String,
+        title: Option<String>,
+        episode: Option<u32>,
+        episode_end: Option<u32>,
+        season: Option<u32>,
+        year: Option<u32>,
+        group: Option<String>,
+        resolution: Option<String>,
+        source: Option<String>,
+        video_codec: Option<String>,
+        audio_codec: Option<String>,
+        language: Option<Vec<String>>,
+        subtitles: Option<Vec<String>>,
+        release_info: Option<Vec<String>>,
+        is_batch: Option<bool>,
+    }
+
+    #[test]
+    fn test_cjk_fixtures() {
+        #[derive(serde::Deserialize)]
+        struct Fixtures {
+            release: Vec<Fixture>,
+        }
+        let fixtures: Fixtures =
+            toml::from_str(include_str!("../tests/fixtures/cjk.toml")).unwrap();
+        assert!(fixtures.release.len() >= 30);
+
+        let mut failures = Vec::new();
+        for f in &fixtures.release {
+            let r = parse(&f.input);
+            let mut check = |field: &str, ok: bool| {
+                if !ok {
+                    failures.push(format!("{field} of {:?}: got {r:?}", f.input));
+                }
+            };
+            fn matches<T: PartialEq>(want: &Option<T>, got: &T) -> bool {
+                want.as_ref().is_none_or(|w| w == got)
+            }
+            check(
+                "title",
+                matches(&f.title.as_ref().map(Some), &r.title.as_ref()),
+            );
+            check("episode", matches(&f.episode.map(Some), &r.episode_number));
+            check(
+                "episode_end",
+                matches(&f.episode_end.map(Some), &r.episode_number_end),
+            );
+            check("season", matches(&f.season.map(Some), &r.season_number));
+            check("year", matches(&f.year.map(Some), &r.year));
+            check(
+                "group",
+                matches(&f.group.as_ref().map(Some), &r.release_group.as_ref()),
+            );
+            check(
+                "resolution",
+                matches(&f.resolution.as_ref().map(Some), &r.resolution.as_ref()),
+            );
+            check(
+                "source",
+                matches(&f.source.as_ref().map(Some), &r.source.as_ref()),
+            );
+            check(
+                "video_codec",
+                matches(&f.video_codec.as_ref().map(Some), &r.video_codec.as_ref()),
+            );
+            check(
+                "audio_codec",
+                matches(&f.audio_codec.as_ref().map(Some), &r.audio_codec.as_ref()),
+            );
+            check("language", matches(&f.language, &r.language));
+            check("subtitles", matches(&f.subtitles, &r.subtitles));
+            check("release_info", matches(&f.release_info, &r.release_info));
+            check("is_batch", matches(&f.is_batch, &r.is_batch));
+        }
+        assert!(failures.is_empty(), "{}", failures.join("\n"));
+    }
+
     #[test]
     fn test_multiple_keywords() {
         let r = parse("[Group] Title - 05 [1080p][HEVC][FLAC].mkv");
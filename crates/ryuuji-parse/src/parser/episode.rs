@@ -1,22 +1,73 @@
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 use std::sync::LazyLock;
 
 /// Result of a successful episode extraction.
-pub struct EpisodeMatch {
+pub struct EpisodeMatch<'t> {
     /// Raw episode string (e.g., "05", "12.5", "01-13").
-    pub raw: String,
+    pub raw: &'t str,
     /// Parsed episode number.
     pub number: u32,
+    /// Last episode of a multi-episode range (e.g., "01-02" → 2).
+    pub end: Option<u32>,
     /// Season number if extracted from combined pattern (e.g., S01E05 → season 1).
     pub season: Option<u32>,
     /// Release version if extracted (e.g., "05v2" → version "v2").
     pub version: Option<String>,
+    /// Which strategy matched.
+    pub strategy: EpisodeStrategy,
+}
+
+/// The episode strategies, as reported by [`parse_detailed`](crate::parse_detailed).
+/// Numbers refer to the strategy list in [`parse`](crate::parse).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EpisodeStrategy {
+    /// 1: S01E05, 01x05.
+    Combined,
+    /// 2: EP05, E05, #05, Episode 05, E01E02.
+    KeywordPrefix,
+    /// 3: The number after " - ".
+    DashSeparated,
+    /// 4: 05v2.
+    VersionSuffix,
+    /// 5: 07.5.
+    Fractional,
+    /// 6: 01-13, 01~03.
+    Range,
+    /// 7: 第05話, 第05话, 第01-12集, 全12話.
+    JapaneseCounter,
+    /// 8: 12END, 12完: a season's last episode.
+    FinalMarker,
+    /// 9: 4a, 111C.
+    Partial,
+    /// 10: [12].
+    IsolatedBracket,
+    /// 11/12: A bare number after the title.
+    PlainNumber,
+    /// 13: Vol.3 EP05.
+    VolumeEpisode,
+}
+
+impl EpisodeStrategy {
+    /// How likely a match is to really be the episode.
+    pub fn confidence(self) -> f32 {
+        match self {
+            Self::Combined | Self::JapaneseCounter => 0.95,
+            Self::KeywordPrefix | Self::DashSeparated | Self::VersionSuffix | Self::FinalMarker => {
+                0.9
+            }
+            Self::Fractional | Self::Range | Self::VolumeEpisode => 0.8,
+            Self::Partial | Self::IsolatedBracket => 0.6,
+            Self::PlainNumber => 0.5,
+        }
+    }
 }
 
 // ── Regex patterns (compiled once) ──────────────────────────────
 
 static RE_COMBINED: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r"(?i)^S(\d{1,2})E(\d{1,4})$").unwrap());
+    LazyLock::new(|| Regex::new(r"(?i)^S(\d{1,2})E(\d{1,4})(?:-?E(\d{1,4}))?$").unwrap());
 
 static RE_COMBINED_X: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"^(\d{1,2})[xX](\d{1,4})$").unwrap());
@@ -24,6 +75,12 @@ static RE_COMBINED_X: LazyLock<Regex> =
 static RE_KEYWORD_PREFIX: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"(?i)^(?:EP\.?|E|EPS|EPISODE|#)\s*(\d{1,4})(?:v(\d))?$").unwrap());
 
+static RE_MULTI_EPISODE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)^EP?(\d{1,4})-?EP?(\d{1,4})$").unwrap());
+
+static RE_RANGE_END: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)^(?:EP?)?(\d{1,4})$").unwrap());
+
 static RE_VERSION_SUFFIX: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"^(\d{1,4})[vV](\d)$").unwrap());
 
@@ -32,8 +89,17 @@ static RE_FRACTIONAL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(\d{1,4})
 static RE_RANGE: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"^(\d{1,4})\s*[-~]\s*(\d{1,4})$").unwrap());
 
+/// 第05話 (Japanese), 第05话 / 第05集 (Chinese), 第05回, with an optional
+/// range: 第01-12集.
 static RE_JAPANESE_COUNTER: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r"^第(\d{1,4})[話集]$").unwrap());
+    LazyLock::new(|| Regex::new(r"^第\s*(\d{1,4})(?:\s*[-~]\s*(\d{1,4}))?\s*[話话集回]$").unwrap());
+
+/// 全12話 / 全12集: "all 12 episodes".
+static RE_EPISODE_TOTAL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^全\s*(\d{1,4})\s*[話话集回]$").unwrap());
+
+static RE_FINAL_MARKER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)^(\d{1,4})(?:v(\d))?\s*(?:END|FIN|完|終|终)$").unwrap());
 
 static RE_PARTIAL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(\d{1,4})[a-cA-C]$").unwrap());
 
@@ -43,7 +109,7 @@ static RE_VOL_EPISODE: LazyLock<Regex> = LazyLock::new(|| {
 
 /// Try all 13 episode strategies in order of specificity.
 /// Returns the first successful match.
-pub fn try_extract(text: &str) -> Option<EpisodeMatch> {
+pub fn try_extract(text: &str) -> Option<EpisodeMatch<'_>> {
     let text = text.trim();
     if text.is_empty() {
         return None;
@@ -58,7 +124,10 @@ pub fn try_extract(text: &str) -> Option<EpisodeMatch> {
     if let Some(m) = try_combined(text) {
         return Some(m);
     }
-    // Strategy 2: Keyword-prefixed EP05, E05, #05, Episode 05.
+    // Strategy 2: Keyword-prefixed EP05, E05, #05, Episode 05, and E01E02.
+    if let Some(m) = try_multi_episode(text) {
+        return Some(m);
+    }
     if let Some(m) = try_keyword_prefix(text) {
         return Some(m);
     }
@@ -78,6 +147,10 @@ pub fn try_extract(text: &str) -> Option<EpisodeMatch> {
     if let Some(m) = try_japanese_counter(text) {
         return Some(m);
     }
+    // Strategy 8: Final episode 12END, 12完.
+    if let Some(m) = try_final_marker(text) {
+        return Some(m);
+    }
     // Strategy 9: Partial 4a, 111C.
     if let Some(m) = try_partial(text) {
         return Some(m);
@@ -95,157 +168,232 @@ pub fn try_extract(text: &str) -> Option<EpisodeMatch> {
 }
 
 /// Strategy 1: Combined format S01E05 or 01x05.
-fn try_combined(text: &str) -> Option<EpisodeMatch> {
-    if let Some(caps) = RE_COMBINED.captures(text) {
+fn try_combined(text: &str) -> Option<EpisodeMatch<'_>> {
+    if let Some(caps) = super::captures(&RE_COMBINED, text) {
         let season: u32 = caps[1].parse().ok()?;
         let episode: u32 = caps[2].parse().ok()?;
+        let end = match caps.get(3) {
+            Some(end) => Some(range_end(episode, end.as_str().parse().ok()?)?),
+            None => None,
+        };
         return Some(EpisodeMatch {
-            raw: text.to_string(),
+            raw: text,
             number: episode,
+            end,
             season: Some(season),
             version: None,
+            strategy: EpisodeStrategy::Combined,
         });
     }
-    if let Some(caps) = RE_COMBINED_X.captures(text) {
+    if let Some(caps) = super::captures(&RE_COMBINED_X, text) {
         let season: u32 = caps[1].parse().ok()?;
         let episode: u32 = caps[2].parse().ok()?;
         return Some(EpisodeMatch {
-            raw: text.to_string(),
+            raw: text,
             number: episode,
+            end: None,
             season: Some(season),
             version: None,
+            strategy: EpisodeStrategy::Combined,
         });
     }
     None
 }
 
 /// Strategy 2: Keyword-prefixed episode numbers.
-fn try_keyword_prefix(text: &str) -> Option<EpisodeMatch> {
-    let caps = RE_KEYWORD_PREFIX.captures(text)?;
+fn try_keyword_prefix(text: &str) -> Option<EpisodeMatch<'_>> {
+    let caps = super::captures(&RE_KEYWORD_PREFIX, text)?;
     let number: u32 = caps[1].parse().ok()?;
     if number > 1999 {
         return None;
     }
     let version = caps.get(2).map(|m| format!("v{}", m.as_str()));
     Some(EpisodeMatch {
-        raw: text.to_string(),
+        raw: text,
         number,
+        end: None,
         season: None,
         version,
+        strategy: EpisodeStrategy::KeywordPrefix,
+    })
+}
+
+/// Strategy 2: Consecutive episodes in one token (E01E02, EP01-EP02).
+fn try_multi_episode(text: &str) -> Option<EpisodeMatch<'_>> {
+    let caps = super::captures(&RE_MULTI_EPISODE, text)?;
+    let start: u32 = caps[1].parse().ok()?;
+    let end = range_end(start, caps[2].parse().ok()?)?;
+    Some(EpisodeMatch {
+        raw: text,
+        number: start,
+        end: Some(end),
+        season: None,
+        version: None,
+        strategy: EpisodeStrategy::KeywordPrefix,
     })
 }
 
 /// Strategy 4: Version suffix (05v2 → episode 5, version "v2").
-fn try_version_suffix(text: &str) -> Option<EpisodeMatch> {
-    let caps = RE_VERSION_SUFFIX.captures(text)?;
+fn try_version_suffix(text: &str) -> Option<EpisodeMatch<'_>> {
+    let caps = super::captures(&RE_VERSION_SUFFIX, text)?;
     let number: u32 = caps[1].parse().ok()?;
     if number > 1999 {
         return None;
     }
     Some(EpisodeMatch {
-        raw: text.to_string(),
+        raw: text,
         number,
+        end: None,
         season: None,
         version: Some(format!("v{}", &caps[2])),
+        strategy: EpisodeStrategy::VersionSuffix,
     })
 }
 
 /// Strategy 5: Fractional episode (07.5).
-fn try_fractional(text: &str) -> Option<EpisodeMatch> {
-    let caps = RE_FRACTIONAL.captures(text)?;
+fn try_fractional(text: &str) -> Option<EpisodeMatch<'_>> {
+    let caps = super::captures(&RE_FRACTIONAL, text)?;
     let number: u32 = caps[1].parse().ok()?;
     if number > 1999 {
         return None;
     }
     Some(EpisodeMatch {
-        raw: text.to_string(),
+        raw: text,
         number,
+        end: None,
         season: None,
         version: None,
+        strategy: EpisodeStrategy::Fractional,
     })
 }
 
 /// Strategy 6: Episode range (01-13).
-fn try_range(text: &str) -> Option<EpisodeMatch> {
-    let caps = RE_RANGE.captures(text)?;
+fn try_range(text: &str) -> Option<EpisodeMatch<'_>> {
+    let caps = super::captures(&RE_RANGE, text)?;
     let start: u32 = caps[1].parse().ok()?;
-    let end: u32 = caps[2].parse().ok()?;
-    if start > 1999 || end > 1999 || start >= end {
+    let end = range_end(start, caps[2].parse().ok()?)?;
+    Some(EpisodeMatch {
+        raw: text,
+        number: start,
+        end: Some(end),
+        season: None,
+        version: None,
+        strategy: EpisodeStrategy::Range,
+    })
+}
+
+/// Strategy 7: Japanese counter (第05話, 第05集, 第01-12集, 全12話).
+fn try_japanese_counter(text: &str) -> Option<EpisodeMatch<'_>> {
+    let (number, end) = if let Some(caps) = super::captures(&RE_JAPANESE_COUNTER, text) {
+        let number: u32 = caps[1].parse().ok()?;
+        let end = match caps.get(2) {
+            Some(end) => Some(range_end(number, end.as_str().parse().ok()?)?),
+            None => None,
+        };
+        (number, end)
+    } else {
+        let caps = super::captures(&RE_EPISODE_TOTAL, text)?;
+        (1, Some(range_end(1, caps[1].parse().ok()?)?))
+    };
+    if number > 1999 {
         return None;
     }
     Some(EpisodeMatch {
-        raw: text.to_string(),
-        number: start,
+        raw: text,
+        number,
+        end,
         season: None,
         version: None,
+        strategy: EpisodeStrategy::JapaneseCounter,
     })
 }
 
-/// Strategy 7: Japanese counter (第05話, 第05集).
-fn try_japanese_counter(text: &str) -> Option<EpisodeMatch> {
-    let caps = RE_JAPANESE_COUNTER.captures(text)?;
+/// Strategy 8: Episode marked as the last (12END, 12 END, 12完).
+fn try_final_marker(text: &str) -> Option<EpisodeMatch<'_>> {
+    let caps = super::captures(&RE_FINAL_MARKER, text)?;
     let number: u32 = caps[1].parse().ok()?;
     if number > 1999 {
         return None;
     }
+    let version = caps.get(2).map(|m| format!("v{}", m.as_str()));
     Some(EpisodeMatch {
-        raw: text.to_string(),
+        raw: text,
         number,
+        end: None,
         season: None,
-        version: None,
+        version,
+        strategy: EpisodeStrategy::FinalMarker,
     })
 }
 
 /// Strategy 9: Partial episode (4a, 111C).
-fn try_partial(text: &str) -> Option<EpisodeMatch> {
-    let caps = RE_PARTIAL.captures(text)?;
+fn try_partial(text: &str) -> Option<EpisodeMatch<'_>> {
+    let caps = super::captures(&RE_PARTIAL, text)?;
     let number: u32 = caps[1].parse().ok()?;
     if number > 1999 {
         return None;
     }
     Some(EpisodeMatch {
-        raw: text.to_string(),
+        raw: text,
         number,
+        end: None,
         season: None,
         version: None,
+        strategy: EpisodeStrategy::Partial,
     })
 }
 
 /// Strategy 13: Volume + episode (Vol.3 EP05).
-fn try_vol_episode(text: &str) -> Option<EpisodeMatch> {
+fn try_vol_episode(text: &str) -> Option<EpisodeMatch<'_>> {
     // Only trigger if text contains "vol" (case-insensitive).
     if !text.to_lowercase().contains("vol") {
         return None;
     }
-    let caps = RE_VOL_EPISODE.captures(text)?;
+    let caps = super::captures(&RE_VOL_EPISODE, text)?;
     let number: u32 = caps[1].parse().ok()?;
     if number > 1999 {
         return None;
     }
     let version = caps.get(2).map(|m| format!("v{}", m.as_str()));
     Some(EpisodeMatch {
-        raw: text.to_string(),
+        raw: text,
         number,
+        end: None,
         season: None,
         version,
+        strategy: EpisodeStrategy::VolumeEpisode,
     })
 }
 
 /// Parse a plain number as an episode number (strategies 11/12).
-pub fn try_plain_number(text: &str) -> Option<EpisodeMatch> {
+pub fn try_plain_number(text: &str) -> Option<EpisodeMatch<'_>> {
     let text = text.trim();
     let number: u32 = text.parse().ok()?;
     if number > 1999 || is_year_like(text) {
         return None;
     }
     Some(EpisodeMatch {
-        raw: text.to_string(),
+        raw: text,
         number,
+        end: None,
         season: None,
         version: None,
+        strategy: EpisodeStrategy::PlainNumber,
     })
 }
 
+/// End of a range split across tokens (the "02" or "E02" in "01-02" /
+/// "S01E01-E02"), if it continues a range starting at `start`.
+pub fn try_range_end(start: u32, text: &str) -> Option<u32> {
+    let caps = super::captures(&RE_RANGE_END, text.trim())?;
+    range_end(start, caps[1].parse().ok()?)
+}
+
+/// Validate a range: the end must come after the start.
+fn range_end(start: u32, end: u32) -> Option<u32> {
+    (start < end && start <= 1999 && end <= 1999).then_some(end)
+}
+
 /// Check if a 4-digit number looks like a year (1950-2050).
 fn is_year_like(s: &str) -> bool {
     if s.len() == 4 {
@@ -307,6 +455,33 @@ mod tests {
     fn test_range() {
         let m = try_extract("01-13").unwrap();
         assert_eq!(m.number, 1);
+        assert_eq!(m.end, Some(13));
+
+        let m = try_extract("01~03").unwrap();
+        assert_eq!((m.number, m.end), (1, Some(3)));
+
+        // Backwards ranges aren't ranges.
+        assert!(try_extract("05-03").is_none());
+    }
+
+    #[test]
+    fn test_multi_episode() {
+        let m = try_extract("E01E02").unwrap();
+        assert_eq!((m.number, m.end), (1, Some(2)));
+
+        let m = try_extract("S01E03E04").unwrap();
+        assert_eq!((m.number, m.end, m.season), (3, Some(4), Some(1)));
+
+        let m = try_extract("S01E05").unwrap();
+        assert_eq!(m.end, None);
+    }
+
+    #[test]
+    fn test_range_end() {
+        assert_eq!(try_range_end(1, "02"), Some(2));
+        assert_eq!(try_range_end(1, "E02"), Some(2));
+        assert_eq!(try_range_end(5, "03"), None);
+        assert_eq!(try_range_end(1, "Title"), None);
     }
 
     #[test]
@@ -316,6 +491,28 @@ mod tests {
 
         let m = try_extract("第12集").unwrap();
         assert_eq!(m.number, 12);
+
+        let m = try_extract("第05话").unwrap();
+        assert_eq!((m.number, m.end), (5, None));
+
+        let m = try_extract("第01-12集").unwrap();
+        assert_eq!((m.number, m.end), (1, Some(12)));
+
+        let m = try_extract("全24話").unwrap();
+        assert_eq!((m.number, m.end), (1, Some(24)));
+    }
+
+    #[test]
+    fn test_final_marker() {
+        let m = try_extract("12END").unwrap();
+        assert_eq!(m.number, 12);
+        assert_eq!(m.strategy, EpisodeStrategy::FinalMarker);
+
+        let m = try_extract("24 完").unwrap();
+        assert_eq!(m.number, 24);
+
+        let m = try_extract("13v2 END").unwrap();
+        assert_eq!((m.number, m.version.as_deref()), (13, Some("v2")));
     }
 
     #[test]
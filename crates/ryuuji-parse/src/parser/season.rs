@@ -1,12 +1,40 @@
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 use std::sync::LazyLock;
 
 /// Result of a successful season extraction.
-pub struct SeasonMatch {
+pub struct SeasonMatch<'t> {
     /// Raw season string.
-    pub raw: String,
+    pub raw: &'t str,
     /// Parsed season number.
     pub number: u32,
+    /// Which pattern matched.
+    pub strategy: SeasonStrategy,
+}
+
+/// The season patterns, as reported by [`parse_detailed`](crate::parse_detailed).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SeasonStrategy {
+    /// "S2", "S01".
+    SPrefix,
+    /// "Season 2", "Season II", "Saison 3".
+    SeasonWord,
+    /// "2nd Season".
+    NthSeason,
+    /// "第2期", "2期", "第二季".
+    JapaneseCounter,
+}
+
+impl SeasonStrategy {
+    /// How likely a match is to really be the season.
+    pub fn confidence(self) -> f32 {
+        match self {
+            Self::SeasonWord | Self::NthSeason | Self::JapaneseCounter => 0.95,
+            // "S2" could be part of a title.
+            Self::SPrefix => 0.85,
+        }
+    }
 }
 
 // ── Regex patterns ──────────────────────────────────────────────
@@ -22,12 +50,12 @@ static RE_SEASON_WORD: LazyLock<Regex> =
 static RE_NTH_SEASON: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"(?i)^(\d{1,2})(?:st|nd|rd|th)\s+Season$").unwrap());
 
-/// Japanese: "第2期", "2期".
+/// Japanese "第2期", "2期"; Chinese "第2季", "第二季".
 static RE_JAPANESE_SEASON: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r"^(?:第)?(\d{1,2})期$").unwrap());
+    LazyLock::new(|| Regex::new(r"^(?:第)?(\d{1,2}|[一二三四五六七八九十]{1,3})[期季]$").unwrap());
 
 /// Try all season extraction strategies.
-pub fn try_extract(text: &str) -> Option<SeasonMatch> {
+pub fn try_extract(text: &str) -> Option<SeasonMatch<'_>> {
     let text = text.trim();
     if text.is_empty() {
         return None;
@@ -50,43 +78,50 @@ pub fn try_extract(text: &str) -> Option<SeasonMatch> {
 }
 
 /// "S2", "S01".
-fn try_s_prefix(text: &str) -> Option<SeasonMatch> {
-    let caps = RE_S_PREFIX.captures(text)?;
+fn try_s_prefix(text: &str) -> Option<SeasonMatch<'_>> {
+    let caps = super::captures(&RE_S_PREFIX, text)?;
     let number: u32 = caps[1].parse().ok()?;
     Some(SeasonMatch {
-        raw: text.to_string(),
+        raw: text,
         number,
+        strategy: SeasonStrategy::SPrefix,
     })
 }
 
 /// "Season 2", "Season II", "Saison 3".
-fn try_season_word(text: &str) -> Option<SeasonMatch> {
-    let caps = RE_SEASON_WORD.captures(text)?;
+fn try_season_word(text: &str) -> Option<SeasonMatch<'_>> {
+    let caps = super::captures(&RE_SEASON_WORD, text)?;
     let value = &caps[1];
     let number = parse_number_or_roman(value)?;
     Some(SeasonMatch {
-        raw: text.to_string(),
+        raw: text,
         number,
+        strategy: SeasonStrategy::SeasonWord,
     })
 }
 
 /// "2nd Season", "3rd Season".
-fn try_nth_season(text: &str) -> Option<SeasonMatch> {
-    let caps = RE_NTH_SEASON.captures(text)?;
+fn try_nth_season(text: &str) -> Option<SeasonMatch<'_>> {
+    let caps = super::captures(&RE_NTH_SEASON, text)?;
     let number: u32 = caps[1].parse().ok()?;
     Some(SeasonMatch {
-        raw: text.to_string(),
+        raw: text,
         number,
+        strategy: SeasonStrategy::NthSeason,
     })
 }
 
-/// "第2期", "2期".
-fn try_japanese_season(text: &str) -> Option<SeasonMatch> {
-    let caps = RE_JAPANESE_SEASON.captures(text)?;
-    let number: u32 = caps[1].parse().ok()?;
+/// "第2期", "2期", "第二季".
+fn try_japanese_season(text: &str) -> Option<SeasonMatch<'_>> {
+    let caps = super::captures(&RE_JAPANESE_SEASON, text)?;
+    let number = match caps[1].parse::<u32>() {
+        Ok(n) => n,
+        Err(_) => cjk_numeral_to_u32(&caps[1])?,
+    };
     Some(SeasonMatch {
-        raw: text.to_string(),
+        raw: text,
         number,
+        strategy: SeasonStrategy::JapaneseCounter,
     })
 }
 
@@ -127,6 +162,25 @@ fn roman_to_u32(s: &str) -> Option<u32> {
     }
 }
 
+/// Chinese numerals from 一 to 九十九 (1–99).
+fn cjk_numeral_to_u32(s: &str) -> Option<u32> {
+    let digit = |c: char| {
+        "一二三四五六七八九"
+            .chars()
+            .position(|d| d == c)
+            .map(|i| i as u32 + 1)
+    };
+    let chars: Vec<char> = s.chars().collect();
+    match chars.as_slice() {
+        ['十'] => Some(10),
+        ['十', ones] => Some(10 + digit(*ones)?),
+        [tens, '十'] => Some(digit(*tens)? * 10),
+        [tens, '十', ones] => Some(digit(*tens)? * 10 + digit(*ones)?),
+        [ones] => digit(*ones),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -171,6 +225,14 @@ mod tests {
         assert_eq!(m.number, 2);
     }
 
+    #[test]
+    fn test_chinese_season() {
+        assert_eq!(try_extract("第2季").map(|m| m.number), Some(2));
+        assert_eq!(try_extract("第二季").map(|m| m.number), Some(2));
+        assert_eq!(try_extract("第十二季").map(|m| m.number), Some(12));
+        assert!(try_extract("第季").is_none());
+    }
+
     #[test]
     fn test_roman_numerals() {
         assert_eq!(roman_to_u32("I"), Some(1));
@@ -1,66 +1,86 @@
+use std::borrow::Cow;
+use std::ops::Range;
+
 use crate::tokenizer::{Token, TokenKind};
 
 /// Extract the anime title from remaining unidentified free text tokens.
 /// The title is the longest consecutive run of unidentified free text
 /// that appears before the episode number.
-pub fn extract_title(tokens: &[Token], identified: &[bool]) -> Option<String> {
-    let mut title_parts: Vec<&str> = Vec::new();
-    let mut started = false;
-
-    for (i, token) in tokens.iter().enumerate() {
-        if identified[i] {
-            if started {
-                break;
-            }
-            continue;
-        }
-
-        match token.kind {
-            TokenKind::FreeText => {
-                if token.text == "-" {
-                    if started {
-                        break;
-                    }
-                    continue;
-                }
-                started = true;
-                title_parts.push(&token.text);
-            }
-            TokenKind::Delimiter if started => {
-                title_parts.push(" ");
-            }
-            _ => {
-                if started {
-                    break;
-                }
-            }
-        }
-    }
+pub fn extract_title<'a>(
+    input: &'a str,
+    tokens: &[Token<'a>],
+    identified: &[bool],
+) -> Option<Cow<'a, str>> {
+    extract_title_spanned(input, tokens, identified).map(|(title, _)| title)
+}
 
-    if title_parts.is_empty() {
-        return None;
-    }
+/// [`extract_title`], also returning the title's byte span in the input.
+pub fn extract_title_spanned<'a>(
+    input: &'a str,
+    tokens: &[Token<'a>],
+    identified: &[bool],
+) -> Option<(Cow<'a, str>, Range<usize>)> {
+    let run = free_text_run(tokens, identified, 0)?;
+    run_text(input, tokens, run)
+}
 
-    let title = title_parts.join("").trim().to_string();
-    if title.is_empty() {
-        None
+/// For names with no free text title ("[Group][Title][05][1080p]"): the
+/// first unidentified bracket before the episode, preferring one with Latin
+/// letters ("[爱恋字幕社][药屋少女的呢喃][Kusuriya no Hitorigoto][05]").
+pub fn extract_bracketed_title_spanned<'a>(
+    tokens: &[Token<'a>],
+    identified: &[bool],
+    episode_index: Option<usize>,
+) -> Option<(Cow<'a, str>, Range<usize>)> {
+    let end = episode_index.unwrap_or(tokens.len());
+    let mut candidates = tokens[..end]
+        .iter()
+        .enumerate()
+        .filter(|(i, t)| !identified[*i] && t.kind == TokenKind::Bracketed)
+        .map(|(_, t)| t);
+    let first = candidates.clone().next()?;
+    let token = candidates
+        .find(|t| t.text.chars().any(|c| c.is_ascii_alphabetic()))
+        .unwrap_or(first);
+    let title = if token.text.contains('_') {
+        Cow::Owned(token.text.replace('_', " "))
     } else {
-        Some(title)
-    }
+        token.text.clone()
+    };
+    let title = trimmed(title);
+    (!title.is_empty()).then(|| (title, token.span.clone()))
 }
 
 /// Extract the episode title from unidentified free text that appears
 /// *after* the episode number position.
-pub fn extract_episode_title(
-    tokens: &[Token],
+pub fn extract_episode_title<'a>(
+    input: &'a str,
+    tokens: &[Token<'a>],
+    identified: &[bool],
+    episode_index: Option<usize>,
+) -> Option<Cow<'a, str>> {
+    extract_episode_title_spanned(input, tokens, identified, episode_index).map(|(title, _)| title)
+}
+
+/// [`extract_episode_title`], also returning the byte span in the input.
+pub fn extract_episode_title_spanned<'a>(
+    input: &'a str,
+    tokens: &[Token<'a>],
     identified: &[bool],
     episode_index: Option<usize>,
-) -> Option<String> {
-    let start_after = episode_index? + 1;
-    let mut parts: Vec<&str> = Vec::new();
-    let mut started = false;
+) -> Option<(Cow<'a, str>, Range<usize>)> {
+    let run = free_text_run(tokens, identified, episode_index? + 1)?;
+    run_text(input, tokens, run)
+}
+
+/// The first and last token of the first run of unidentified free text at
+/// or after `start`. Delimiters may separate the words; a dash, bracket or
+/// identified token ends the run. Leading dashes are skipped.
+fn free_text_run(tokens: &[Token], identified: &[bool], start: usize) -> Option<(usize, usize)> {
+    let mut run: Option<(usize, usize)> = None;
 
-    for i in start_after..tokens.len() {
+    for (i, token) in tokens.iter().enumerate().skip(start) {
+        let started = run.is_some();
         if identified[i] {
             if started {
                 break;
@@ -68,37 +88,61 @@ pub fn extract_episode_title(
             continue;
         }
 
-        match tokens[i].kind {
-            TokenKind::FreeText => {
-                if tokens[i].text == "-" {
-                    if started {
-                        break;
-                    }
-                    // Skip leading dash after episode.
-                    continue;
+        match token.kind {
+            TokenKind::FreeText if token.text == "-" => {
+                if started {
+                    break;
                 }
-                started = true;
-                parts.push(&tokens[i].text);
             }
-            TokenKind::Delimiter if started => {
-                parts.push(" ");
+            TokenKind::FreeText => {
+                run = Some((run.map_or(i, |(first, _)| first), i));
             }
-            _ => {
+            TokenKind::Delimiter => {}
+            TokenKind::Bracketed => {
                 if started {
                     break;
                 }
             }
         }
     }
+    run
+}
 
-    if parts.is_empty() {
-        return None;
-    }
-
-    let title = parts.join("").trim().to_string();
-    if title.is_empty() {
-        None
+/// The words of a run joined by single spaces, and their span. Borrowed
+/// from `input` when it already reads that way, as it usually does.
+fn run_text<'a>(
+    input: &'a str,
+    tokens: &[Token<'a>],
+    (first, last): (usize, usize),
+) -> Option<(Cow<'a, str>, Range<usize>)> {
+    let span = tokens[first].span.start..tokens[last].span.end;
+    let run = &tokens[first..=last];
+    let text = if first == last {
+        run[0].text.clone()
     } else {
-        Some(title)
+        let raw = &input[span.clone()];
+        let mut rest = raw;
+        let matches_input = run.iter().all(|t| match rest.strip_prefix(&*t.text) {
+            Some(r) => {
+                rest = r;
+                true
+            }
+            None => false,
+        });
+        if matches_input && rest.is_empty() {
+            Cow::Borrowed(raw)
+        } else {
+            Cow::Owned(run.iter().map(|t| &*t.text).collect())
+        }
+    };
+    let text = trimmed(text);
+    (!text.is_empty()).then_some((text, span))
+}
+
+fn trimmed(text: Cow<'_, str>) -> Cow<'_, str> {
+    match text {
+        Cow::Borrowed(s) => Cow::Borrowed(s.trim()),
+        Cow::Owned(s) if s.trim().len() == s.len() => Cow::Owned(s),
+        Cow::Owned(s) => Cow::Owned(s.trim().to_string()),
     }
 }
@@ -0,0 +1,39 @@
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// Result of a successful volume extraction.
+pub struct VolumeMatch<'t> {
+    /// Raw volume string.
+    pub raw: &'t str,
+    /// Parsed volume number.
+    pub number: u32,
+}
+
+/// "Vol3", "Vol.3", "Vol 3", "Volume 03".
+static RE_VOLUME: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)^Vol(?:ume)?\.?\s*(\d{1,3})$").unwrap());
+
+/// Try to read a volume from `text`.
+pub fn try_extract(text: &str) -> Option<VolumeMatch<'_>> {
+    let text = text.trim();
+    let caps = super::captures(&RE_VOLUME, text)?;
+    Some(VolumeMatch {
+        raw: text,
+        number: caps[1].parse().ok()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_volume_patterns() {
+        assert_eq!(try_extract("Vol3").map(|m| m.number), Some(3));
+        assert_eq!(try_extract("Vol.3").map(|m| m.number), Some(3));
+        assert_eq!(try_extract("vol 12").map(|m| m.number), Some(12));
+        assert_eq!(try_extract("Volume 03").map(|m| m.number), Some(3));
+        assert!(try_extract("Vol").is_none());
+        assert!(try_extract("Volley 3").is_none());
+    }
+}
@@ -0,0 +1,299 @@
+//! Parse a full file path, using directory names to fill in what the
+//! filename leaves out.
+//!
+//! Libraries are often organised as `Title/Season 2/Episode 05.mkv`, where
+//! the filename alone has no title. [`parse_path`] parses the filename, then
+//! walks up the directories until it finds a title, picking up a season and
+//! year on the way. It looks at most [`MAX_DIR_DEPTH`] levels up, and
+//! [`parse_path_under`] also stops at a library root.
+
+use serde::{Deserialize, Serialize};
+
+use crate::elements::Elements;
+use crate::keyword::{KeywordSet, NO_KEYWORDS};
+use crate::parser::{parse_with_keywords, season};
+
+/// Folder names that say nothing about the anime inside them.
+const GENERIC_DIRS: &[&str] = &[
+    "anime",
+    "animes",
+    "complete",
+    "completed",
+    "data",
+    "download",
+    "downloads",
+    "extras",
+    "home",
+    "incoming",
+    "library",
+    "media",
+    "mnt",
+    "movies",
+    "new folder",
+    "public",
+    "seeding",
+    "series",
+    "shows",
+    "specials",
+    "storage",
+    "torrents",
+    "tv",
+    "tv shows",
+    "users",
+    "video",
+    "videos",
+];
+
+/// Folders whose children are named after users (`/home/alice`,
+/// `C:\Users\alice`), not anime.
+const USER_DIRS: &[&str] = &["home", "users"];
+
+/// How many directories above the file are looked at. Deeper ones are
+/// where the library lives, not what's in it.
+pub const MAX_DIR_DEPTH: usize = 3;
+
+/// Words the parser takes for a title in names like `Episode 05.mkv`.
+const EPISODE_WORDS: &[&str] = &["e", "ep", "eps", "episode", "episodio", "folge"];
+
+/// Where an element of a [`PathElements`] was found.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+#[serde(rename_all = "snake_case")]
+pub enum ElementSource {
+    /// The filename itself.
+    Filename,
+    /// A directory `depth` levels up (1 = the file's parent).
+    Directory { depth: usize, name: String },
+}
+
+/// Which path component each path-derived element came from.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+#[serde(default)]
+pub struct ElementSources {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<ElementSource>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub season: Option<ElementSource>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub year: Option<ElementSource>,
+}
+
+/// Result of [`parse_path`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+pub struct PathElements {
+    /// The filename's elements, with title, season and year filled in from
+    /// directories when the filename lacks them.
+    pub elements: Elements,
+    pub sources: ElementSources,
+}
+
+/// Parse a file path. Both `/` and `\` separate components, so Windows paths
+/// work on any platform.
+///
+/// # Example
+/// ```
+/// let r = ryuuji_parse::parse_path("/anime/Mushoku Tensei/Season 2/Episode 05.mkv");
+/// assert_eq!(r.elements.title.as_deref(), Some("Mushoku Tensei"));
+/// assert_eq!(r.elements.season_number, Some(2));
+/// assert_eq!(r.elements.episode_number, Some(5));
+/// ```
+pub fn parse_path(path: &str) -> PathElements {
+    parse_path_under(path, "", &NO_KEYWORDS)
+}
+
+/// Like [`parse_path`], but never takes anything from `root` or the
+/// directories above it, and also uses `keywords`. Pass the library folder a
+/// file was found in.
+///
+/// A `path` outside `root` is parsed as if no root had been given.
+///
+/// # Example
+/// ```
+/// use ryuuji_parse::KeywordSet;
+///
+/// let r = ryuuji_parse::parse_path_under(
+///     "/srv/Anime Club/05.mkv",
+///     "/srv/Anime Club",
+///     &KeywordSet::default(),
+/// );
+/// assert_eq!(r.elements.title, None);
+/// ```
+pub fn parse_path_under(path: &str, root: &str, keywords: &KeywordSet) -> PathElements {
+    let parts = components(path);
+    let root = components(root);
+    let Some((filename, dirs)) = parts.split_last() else {
+        return PathElements::default();
+    };
+    // Directories, nearest first, with the one above each.
+    let below_root = dirs.strip_prefix(root.as_slice()).unwrap_or(dirs);
+    let above = dirs.len() - below_root.len();
+    let dirs = below_root
+        .iter()
+        .enumerate()
+        .rev()
+        .map(|(i, dir)| (*dir, (i + above).checked_sub(1).map(|p| parts[p])))
+        .take(MAX_DIR_DEPTH);
+
+    let mut elements = parse_with_keywords(filename, keywords);
+    // "E05 - The Beginning.mkv": the only text is the episode title.
+    if elements.title.is_some() && elements.title == elements.episode_title {
+        elements.title = None;
+    }
+    if elements
+        .title
+        .as_deref()
+        .is_some_and(|t| EPISODE_WORDS.contains(&t.to_lowercase().as_str()))
+    {
+        elements.title = None;
+    }
+
+    let mut sources = ElementSources {
+        title: elements.title.as_ref().map(|_| ElementSource::Filename),
+        season: elements.season_number.map(|_| ElementSource::Filename),
+        year: elements.year.map(|_| ElementSource::Filename),
+    };
+
+    for (i, (dir, parent)) in dirs.enumerate() {
+        if elements.title.is_some() {
+            break;
+        }
+        if is_generic_dir(dir)
+            || parent.is_some_and(|p| USER_DIRS.contains(&p.to_lowercase().as_str()))
+        {
+            continue;
+        }
+        let source = || ElementSource::Directory {
+            depth: i + 1,
+            name: dir.to_string(),
+        };
+
+        // "Season 2", "S2": a season folder, not a title.
+        if let Some(m) = season::try_extract(dir) {
+            if elements.season_number.is_none() {
+                elements.season = Some(m.raw.to_string());
+                elements.season_number = Some(m.number);
+                sources.season = Some(source());
+            }
+            continue;
+        }
+
+        let parsed = parse_with_keywords(dir, keywords);
+        if elements.season_number.is_none() && parsed.season_number.is_some() {
+            elements.season = parsed.season;
+            elements.season_number = parsed.season_number;
+            sources.season = Some(source());
+        }
+        if elements.year.is_none() && parsed.year.is_some() {
+            elements.year = parsed.year;
+            sources.year = Some(source());
+        }
+        if parsed.title.is_some() {
+            elements.title = parsed.title;
+            sources.title = Some(source());
+        }
+    }
+
+    PathElements { elements, sources }
+}
+
+/// Both `/` and `\` separate components.
+fn components(path: &str) -> Vec<&str> {
+    path.split(['/', '\\']).filter(|c| !c.is_empty()).collect()
+}
+
+fn is_generic_dir(name: &str) -> bool {
+    let name = name.trim().to_lowercase();
+    // Drive letters ("C:") and hidden folders.
+    name.ends_with(':') || name.starts_with('.') || GENERIC_DIRS.contains(&name.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dir(depth: usize, name: &str) -> Option<ElementSource> {
+        Some(ElementSource::Directory {
+            depth,
+            name: name.into(),
+        })
+    }
+
+    #[test]
+    fn test_title_from_series_folder() {
+        let r = parse_path("/home/user/Anime/Mushoku Tensei/Season 2/Episode 05.mkv");
+        assert_eq!(r.elements.title.as_deref(), Some("Mushoku Tensei"));
+        assert_eq!(r.elements.season_number, Some(2));
+        assert_eq!(r.elements.episode_number, Some(5));
+        assert_eq!(r.sources.title, dir(2, "Mushoku Tensei"));
+        assert_eq!(r.sources.season, dir(1, "Season 2"));
+    }
+
+    #[test]
+    fn test_year_and_episode_title() {
+        let r = parse_path(r"D:\Downloads\Show (2023)\E05 - The Beginning.mkv");
+        assert_eq!(r.elements.title.as_deref(), Some("Show"));
+        assert_eq!(r.elements.year, Some(2023));
+        assert_eq!(r.elements.episode_number, Some(5));
+        assert_eq!(r.elements.episode_title.as_deref(), Some("The Beginning"));
+        assert_eq!(r.sources.year, dir(1, "Show (2023)"));
+    }
+
+    #[test]
+    fn test_filename_wins() {
+        let r = parse_path("/anime/Frieren/[SubsPlease] Sousou no Frieren S2 - 05 (1080p).mkv");
+        assert_eq!(r.elements.title.as_deref(), Some("Sousou no Frieren"));
+        assert_eq!(r.sources.title, Some(ElementSource::Filename));
+        assert_eq!(r.sources.season, Some(ElementSource::Filename));
+    }
+
+    #[test]
+    fn test_generic_folders_skipped() {
+        let r = parse_path("/mnt/Downloads/05.mkv");
+        assert_eq!(r.elements.title, None);
+        assert_eq!(r.elements.episode_number, Some(5));
+        assert_eq!(r.sources, ElementSources::default());
+    }
+
+    #[test]
+    fn test_deep_absolute_paths() {
+        // A user's home folder is not a title.
+        let r = parse_path("/home/alice/Videos/05.mkv");
+        assert_eq!(r.elements.title, None);
+        let r = parse_path(r"C:\Users\alice\05.mkv");
+        assert_eq!(r.elements.title, None);
+
+        // Nothing more than MAX_DIR_DEPTH levels up is looked at.
+        let r = parse_path("/Frieren/Season 1/Downloads/Videos/Media/05.mkv");
+        assert_eq!(r.elements.title, None);
+        assert_eq!(r.elements.season_number, None);
+        assert_eq!(r.elements.episode_number, Some(5));
+        let r = parse_path("/srv/share/Frieren/Season 1/Extras/05.mkv");
+        assert_eq!(r.elements.title.as_deref(), Some("Frieren"));
+        assert_eq!(r.sources.title, dir(3, "Frieren"));
+    }
+
+    #[test]
+    fn test_stops_at_root() {
+        let under = |path, root| parse_path_under(path, root, &NO_KEYWORDS);
+        let r = under("/srv/Anime Club/Frieren/05.mkv", "/srv/Anime Club");
+        assert_eq!(r.elements.title.as_deref(), Some("Frieren"));
+        let r = under("/srv/Anime Club/05.mkv", "/srv/Anime Club/");
+        assert_eq!(r.elements.title, None);
+        // Windows separators on either side.
+        let r = under(r"D:\Shows\Season 2\05.mkv", "D:/Shows");
+        assert_eq!(r.elements.title, None);
+        assert_eq!(r.elements.season_number, Some(2));
+        // Outside the root: parsed as usual.
+        let r = under("/mnt/Frieren/05.mkv", "/srv/Anime Club");
+        assert_eq!(r.elements.title.as_deref(), Some("Frieren"));
+    }
+
+    #[test]
+    fn test_bare_filename() {
+        let r = parse_path("[Group] Title - 05 [1080p].mkv");
+        assert_eq!(r.elements.title.as_deref(), Some("Title"));
+        assert!(parse_path("").elements.title.is_none());
+    }
+}
@@ -1,3 +1,6 @@
+use std::borrow::Cow;
+use std::ops::Range;
+
 /// Token types produced by the tokenizer.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum TokenKind {
@@ -11,12 +14,18 @@ pub enum TokenKind {
 
 /// A single token from a filename.
 #[derive(Debug, Clone)]
-pub struct Token {
+pub struct Token<'a> {
     pub kind: TokenKind,
-    pub text: String,
+    /// The token's text, borrowed from the input unless full-width
+    /// characters had to be folded.
+    pub text: Cow<'a, str>,
     /// Whether this token was inside a bracket pair.
     /// Always true for `Bracketed` tokens.
     pub is_enclosed: bool,
+    /// Byte range of the token in the input. For `Bracketed` tokens this
+    /// covers the content, not the brackets. `text` may differ from the
+    /// input here when full-width characters were folded.
+    pub span: Range<usize>,
 }
 
 /// Opening/closing bracket pairs, including CJK brackets.
@@ -27,18 +36,43 @@ const BRACKETS: &[(char, char)] = &[
     ('\u{300C}', '\u{300D}'), // 「」
     ('\u{300E}', '\u{300F}'), // 『』
     ('\u{3010}', '\u{3011}'), // 【】
+    ('\u{3014}', '\u{3015}'), // 〔〕
+    ('\u{3016}', '\u{3017}'), // 〖〗
+    ('\u{FF08}', '\u{FF09}'), // （）
+    ('\u{FF3B}', '\u{FF3D}'), // ［］
 ];
 
 /// Characters that separate tokens (excluding dash, which gets special treatment).
 fn is_soft_delimiter(c: char) -> bool {
-    matches!(c, ' ' | '_' | '.' | '\u{3000}')
-    // space, underscore, dot, ideographic space
+    matches!(c, ' ' | '_' | '.' | '\u{3000}' | '\u{2605}' | '\u{2606}')
+    // space, underscore, dot, ideographic space, ★, ☆
 }
 
 /// Dash-family characters that act as token separators but are preserved as FreeText.
 fn is_dash(c: char) -> bool {
-    matches!(c, '-' | '\u{2013}' | '\u{2014}')
-    // hyphen-minus, en-dash, em-dash
+    matches!(c, '-' | '\u{2013}' | '\u{2014}' | '\u{FF0D}')
+    // hyphen-minus, en-dash, em-dash, full-width hyphen
+}
+
+/// Fold full-width digits and Latin letters ("０５", "１０８０Ｐ") to ASCII,
+/// and wave dashes ("01～12") to `~`. Other characters are kept as they are.
+fn normalize_width(c: char) -> char {
+    match c {
+        '\u{FF10}'..='\u{FF19}' | '\u{FF21}'..='\u{FF3A}' | '\u{FF41}'..='\u{FF5A}' => {
+            char::from_u32(c as u32 - 0xFEE0).unwrap_or(c)
+        }
+        '\u{FF5E}' | '\u{301C}' => '~',
+        _ => c,
+    }
+}
+
+/// The text of `raw`, borrowed unless it has characters to fold.
+fn token_text(raw: &str) -> Cow<'_, str> {
+    if raw.chars().all(|c| normalize_width(c) == c) {
+        Cow::Borrowed(raw)
+    } else {
+        Cow::Owned(raw.chars().map(normalize_width).collect())
+    }
 }
 
 fn opening_bracket(c: char) -> Option<char> {
@@ -55,35 +89,40 @@ fn opening_bracket(c: char) -> Option<char> {
 /// Handles:
 /// - Bracket-enclosed groups `[...]`, `(...)`, `{...}`, and CJK brackets
 /// - Delimiter-separated free text (space, underscore, dot)
-/// - Dashes (`-`, `–`, `—`) emitted as `FreeText("-")` tokens
+/// - Dashes (`-`, `–`, `—`, `－`) emitted as `FreeText("-")` tokens
+/// - Full-width digits and letters, folded to ASCII in token text
 /// - File extension extraction
-pub fn tokenize(input: &str) -> (Vec<Token>, Option<String>) {
-    let (input, extension) = strip_extension(input);
-    let chars: Vec<char> = input.chars().collect();
+pub fn tokenize(input: &str) -> (Vec<Token<'_>>, Option<String>) {
     let mut tokens = Vec::new();
-    let mut i = 0;
+    let extension = tokenize_into(input, &mut tokens);
+    (tokens, extension.map(str::to_string))
+}
 
-    while i < chars.len() {
-        let c = chars[i];
+/// [`tokenize`] into a caller-owned buffer, which is cleared first, so a
+/// buffer can be reused across many inputs.
+pub fn tokenize_into<'a>(input: &'a str, tokens: &mut Vec<Token<'a>>) -> Option<&'static str> {
+    tokens.clear();
+    let (input, extension) = strip_extension(input);
+    let at = |i: usize| input[i..].chars().next();
+    let mut i = 0;
 
+    while let Some(c) = at(i) {
         // Bracket-enclosed content.
         if let Some(close) = opening_bracket(c) {
-            i += 1;
-            let start = i;
-            while i < chars.len() && chars[i] != close {
-                i += 1;
-            }
-            let text: String = chars[start..i].iter().collect();
-            if !text.is_empty() {
+            let start = i + c.len_utf8();
+            let end = input[start..]
+                .find(close)
+                .map_or(input.len(), |pos| start + pos);
+            if start < end {
                 tokens.push(Token {
                     kind: TokenKind::Bracketed,
-                    text,
+                    text: token_text(&input[start..end]),
                     is_enclosed: true,
+                    span: start..end,
                 });
             }
-            if i < chars.len() {
-                i += 1; // skip closing bracket
-            }
+            // Skip the closing bracket, if there is one.
+            i = at(end).map_or(end, |close| end + close.len_utf8());
             continue;
         }
 
@@ -91,26 +130,29 @@ pub fn tokenize(input: &str) -> (Vec<Token>, Option<String>) {
         if is_dash(c) {
             tokens.push(Token {
                 kind: TokenKind::FreeText,
-                text: "-".into(),
+                text: Cow::Borrowed("-"),
                 is_enclosed: false,
+                span: i..i + c.len_utf8(),
             });
-            i += 1;
+            i += c.len_utf8();
             // Skip trailing soft delimiters after dash.
-            while i < chars.len() && is_soft_delimiter(chars[i]) {
-                i += 1;
+            while let Some(c) = at(i).filter(|&c| is_soft_delimiter(c)) {
+                i += c.len_utf8();
             }
             continue;
         }
 
         // Soft delimiters (space, underscore, dot, ideographic space).
         if is_soft_delimiter(c) {
-            while i < chars.len() && is_soft_delimiter(chars[i]) {
-                i += 1;
+            let start = i;
+            while let Some(c) = at(i).filter(|&c| is_soft_delimiter(c)) {
+                i += c.len_utf8();
             }
             tokens.push(Token {
                 kind: TokenKind::Delimiter,
-                text: String::from(" "),
+                text: Cow::Borrowed(" "),
                 is_enclosed: false,
+                span: start..i,
             });
             continue;
         }
@@ -118,42 +160,45 @@ pub fn tokenize(input: &str) -> (Vec<Token>, Option<String>) {
         // Free text: everything else until a delimiter or bracket.
         // A dot between digits (e.g., "07.5", "H.264") is kept as part of the token.
         let start = i;
-        while i < chars.len() && !is_dash(chars[i]) && opening_bracket(chars[i]).is_none() {
-            if is_soft_delimiter(chars[i]) {
+        let is_digit = |c: Option<char>| c.is_some_and(|c| normalize_width(c).is_ascii_digit());
+        while let Some(c) = at(i) {
+            if is_dash(c) || opening_bracket(c).is_some() {
+                break;
+            }
+            if is_soft_delimiter(c) {
                 // Keep dots between digits as part of the token.
-                if chars[i] == '.'
+                if c == '.'
                     && i > start
-                    && i + 1 < chars.len()
-                    && chars[i - 1].is_ascii_digit()
-                    && chars[i + 1].is_ascii_digit()
+                    && is_digit(input[..i].chars().next_back())
+                    && is_digit(at(i + 1))
                 {
                     i += 1;
                     continue;
                 }
                 break;
             }
-            i += 1;
+            i += c.len_utf8();
         }
-        let text: String = chars[start..i].iter().collect();
-        if !text.is_empty() {
+        if start < i {
             tokens.push(Token {
                 kind: TokenKind::FreeText,
-                text,
+                text: token_text(&input[start..i]),
                 is_enclosed: false,
+                span: start..i,
             });
         }
     }
 
-    (tokens, extension.map(|s| s.to_string()))
+    extension
 }
 
 /// Legacy tokenize function that discards the file extension and returns only tokens.
-pub fn tokenize_compat(input: &str) -> Vec<Token> {
+pub fn tokenize_compat(input: &str) -> Vec<Token<'_>> {
     tokenize(input).0
 }
 
 /// Strip common video file extensions, returning the base name and extracted extension.
-fn strip_extension(input: &str) -> (&str, Option<&str>) {
+fn strip_extension(input: &str) -> (&str, Option<&'static str>) {
     for ext in &[
         ".mkv", ".mp4", ".avi", ".ogm", ".wmv", ".mpg", ".flv", ".webm", ".m4v", ".ts", ".mov",
         ".3gp", ".rm", ".rmvb", ".m2ts",
@@ -198,7 +243,7 @@ mod tests {
         let free: Vec<_> = tokens
             .iter()
             .filter(|t| t.kind == TokenKind::FreeText && t.text != "-")
-            .map(|t| t.text.as_str())
+            .map(|t| t.text.as_ref())
             .collect();
         assert!(free.contains(&"Anime"));
         assert!(free.contains(&"Title"));
@@ -241,17 +286,65 @@ mod tests {
         let texts: Vec<_> = tokens
             .iter()
             .filter(|t| t.kind == TokenKind::FreeText)
-            .map(|t| t.text.as_str())
+            .map(|t| t.text.as_ref())
             .collect();
         assert!(texts.contains(&"Title"));
         assert!(texts.contains(&"-"));
         assert!(texts.contains(&"05"));
     }
 
+    #[test]
+    fn test_spans() {
+        let input = "【Group】 Title – 05.mkv";
+        let (tokens, _) = tokenize(input);
+        for token in tokens.iter().filter(|t| t.kind != TokenKind::Delimiter) {
+            if token.text != "-" {
+                assert_eq!(&input[token.span.clone()], token.text);
+            }
+        }
+        let dash = tokens.iter().find(|t| t.text == "-").unwrap();
+        assert_eq!(&input[dash.span.clone()], "–");
+    }
+
+    #[test]
+    fn test_full_width() {
+        let input = "［桜都字幕组］葬送的芙莉莲（第０５話）［１０８０Ｐ］【01～12】";
+        let (tokens, _) = tokenize(input);
+        let texts: Vec<_> = tokens.iter().map(|t| t.text.as_ref()).collect();
+        assert_eq!(
+            texts,
+            ["桜都字幕组", "葬送的芙莉莲", "第05話", "1080P", "01~12"]
+        );
+        // Spans still point at the original characters.
+        assert_eq!(&input[tokens[3].span.clone()], "１０８０Ｐ");
+    }
+
+    #[test]
+    fn test_star_delimiters() {
+        let (tokens, _) = tokenize("【Group】★10月新番★[Title]");
+        let free: Vec<_> = tokens
+            .iter()
+            .filter(|t| t.kind == TokenKind::FreeText)
+            .map(|t| t.text.as_ref())
+            .collect();
+        assert_eq!(free, ["10月新番"]);
+    }
+
     #[test]
     fn test_en_dash() {
         let (tokens, _) = tokenize("Title \u{2013} 05");
         let has_dash = tokens.iter().any(|t| t.text == "-");
         assert!(has_dash, "En-dash should be normalized to '-'");
     }
+
+    #[test]
+    fn test_tokens_borrow_input() {
+        let (tokens, _) = tokenize("[Group] Title - ０５.mkv");
+        let borrowed: Vec<_> = tokens
+            .iter()
+            .map(|t| matches!(t.text, Cow::Borrowed(_)))
+            .collect();
+        // Only the folded full-width episode is copied.
+        assert_eq!(borrowed, [true, true, true, true, true, false]);
+    }
 }
@@ -1,7 +1,200 @@
+//! WebAssembly bindings for the filename parser, title normalization and
+//! title matching, for the website playground and browser tooling.
+//!
+//! Results are plain JS objects. Their TypeScript types are generated from
+//! the Rust types with tsify, so they can't drift from what is serialized.
+//! `parse_filename` and `parse_filename_with_keywords` are the older
+//! JSON-string forms of `parse` and `parse_with_keywords`.
+
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
 use wasm_bindgen::prelude::*;
 
+use ryuuji_parse::tokenizer::{self, TokenKind};
+use ryuuji_parse::KeywordSet;
+
+/// Serialize to a plain JS object (no `Map`s, `undefined` for `None`).
+fn to_js<T: Serialize>(value: &T) -> Result<JsValue, JsError> {
+    let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+    Ok(value.serialize(&serializer)?)
+}
+
+/// Parse a filename.
+#[wasm_bindgen(unchecked_return_type = "Elements")]
+pub fn parse(input: &str) -> Result<JsValue, JsError> {
+    to_js(&ryuuji_parse::parse(input))
+}
+
+/// Parse with extra keywords and release groups.
+#[wasm_bindgen(unchecked_return_type = "Elements")]
+pub fn parse_with_keywords(
+    input: &str,
+    #[wasm_bindgen(unchecked_param_type = "KeywordSet")] keywords: JsValue,
+) -> Result<JsValue, JsError> {
+    let keywords: KeywordSet = serde_wasm_bindgen::from_value(keywords)?;
+    to_js(&ryuuji_parse::parse_with_keywords(input, &keywords))
+}
+
+/// Parse a file path, filling title, season and year in from directory
+/// names when the filename lacks them.
+#[wasm_bindgen(unchecked_return_type = "PathElements")]
+pub fn parse_path(path: &str) -> Result<JsValue, JsError> {
+    to_js(&ryuuji_parse::parse_path(path))
+}
+
+#[derive(Serialize, Tsify)]
+struct Token {
+    #[tsify(type = "\"bracketed\" | \"free_text\" | \"delimiter\"")]
+    kind: &'static str,
+    text: String,
+    is_enclosed: bool,
+    /// Byte offsets into the UTF-8 input.
+    start: usize,
+    end: usize,
+}
+
+#[derive(Serialize, Tsify)]
+struct Tokens {
+    tokens: Vec<Token>,
+    /// The video extension stripped before tokenizing, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[tsify(optional)]
+    extension: Option<String>,
+}
+
+/// Split a filename into the tokens the parser works on, for debugging.
+#[wasm_bindgen(unchecked_return_type = "Tokens")]
+pub fn tokenize(input: &str) -> Result<JsValue, JsError> {
+    to_js(&tokens(input))
+}
+
+fn tokens(input: &str) -> Tokens {
+    let (tokens, extension) = tokenizer::tokenize(input);
+    let tokens = tokens
+        .into_iter()
+        .map(|t| Token {
+            kind: match t.kind {
+                TokenKind::Bracketed => "bracketed",
+                TokenKind::FreeText => "free_text",
+                TokenKind::Delimiter => "delimiter",
+            },
+            text: t.text.into_owned(),
+            is_enclosed: t.is_enclosed,
+            start: t.span.start,
+            end: t.span.end,
+        })
+        .collect();
+    Tokens { tokens, extension }
+}
+
+/// Normalize a title the way the app does before comparing titles.
+#[wasm_bindgen]
+pub fn normalize(title: &str) -> String {
+    ryuuji_parse::normalize::normalize(title)
+}
+
+/// A candidate for `match_title`: one title, or all of its variants.
+#[derive(Deserialize, Tsify)]
+#[serde(untagged)]
+enum Candidate {
+    Title(String),
+    Titles(Vec<String>),
+}
+
+impl Candidate {
+    fn titles(&self) -> Vec<&str> {
+        match self {
+            Candidate::Title(t) => vec![t.as_str()],
+            Candidate::Titles(ts) => ts.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
+/// Match a title against `candidates`: exact, then normalized, then fuzzy.
+/// Returns `undefined` when nothing is close enough.
+#[wasm_bindgen(unchecked_return_type = "TitleMatch | undefined")]
+pub fn match_title(
+    query: &str,
+    #[wasm_bindgen(unchecked_param_type = "Candidate[]")] candidates: JsValue,
+) -> Result<JsValue, JsError> {
+    let candidates: Vec<Candidate> = serde_wasm_bindgen::from_value(candidates)?;
+    match ryuuji_parse::matcher::match_title(query, &candidates, Candidate::titles) {
+        Some(m) => to_js(&m),
+        None => Ok(JsValue::UNDEFINED),
+    }
+}
+
+/// [`parse`], as a JSON string.
 #[wasm_bindgen]
 pub fn parse_filename(input: &str) -> String {
     let elements = ryuuji_parse::parse(input);
     serde_json::to_string(&elements).unwrap_or_else(|_| "{}".to_string())
 }
+
+/// Parse with extra keywords and release groups, given as JSON in the shape
+/// of `keywords.toml`: `{"release_groups": [...], "keywords": {"ADN": {"kind": "streaming_source"}}}`.
+#[wasm_bindgen]
+pub fn parse_filename_with_keywords(input: &str, keywords: &str) -> Result<String, JsError> {
+    let keywords: ryuuji_parse::KeywordSet = serde_json::from_str(keywords)?;
+    let elements = ryuuji_parse::parse_with_keywords(input, &keywords);
+    Ok(serde_json::to_string(&elements)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ryuuji_parse::matcher::MatchKind;
+    use ryuuji_parse::{Elements, PathElements};
+
+    #[test]
+    fn test_declarations() {
+        // Absent fields are skipped, not null.
+        assert!(Elements::DECL.contains("    title?: string;"));
+        assert!(Elements::DECL.contains("    release_info?: string[];"));
+        assert!(PathElements::DECL.contains("    sources: ElementSources;"));
+        assert!(Tokens::DECL.contains("    extension?: string;"));
+        assert!(Token::DECL.contains(r#"kind: "bracketed" | "free_text" | "delimiter";"#));
+        assert_eq!(
+            Candidate::DECL,
+            "export type Candidate = string | string[];"
+        );
+    }
+
+    #[test]
+    fn test_tokens() {
+        let input = "[Group] Title - 05.mkv";
+        let Tokens { tokens, extension } = tokens(input);
+        assert_eq!(extension.as_deref(), Some("mkv"));
+        assert_eq!(
+            (tokens[0].kind, tokens[0].text.as_str()),
+            ("bracketed", "Group")
+        );
+        for token in &tokens {
+            assert!(input[token.start..token.end].contains(&token.text));
+        }
+    }
+
+    #[test]
+    fn test_candidates() {
+        let candidates: Vec<Candidate> =
+            serde_json::from_str(r#"["Frieren", ["Shingeki no Kyojin", "Attack on Titan"]]"#)
+                .unwrap();
+        let m =
+            ryuuji_parse::matcher::match_title("attack on titan", &candidates, Candidate::titles)
+                .unwrap();
+        assert_eq!((m.index, m.kind), (1, MatchKind::Normalized));
+    }
+
+    #[test]
+    fn test_json_forms() {
+        let json = parse_filename("[SubsPlease] Sousou no Frieren - 05 (1080p).mkv");
+        let elements: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(elements["episode_number"], 5);
+        assert!(elements.get("checksum").is_none());
+
+        let keywords = r#"{"keywords": {"ADN": {"kind": "streaming_source"}}}"#;
+        let json = parse_filename_with_keywords("Title - 05 [ADN].mkv", keywords).unwrap();
+        let elements: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(elements["streaming_source"], "ADN");
+    }
+}
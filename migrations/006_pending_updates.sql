@@ -0,0 +1,14 @@
+-- Progress updates waiting for the user to confirm them (library.confirm_update).
+CREATE TABLE IF NOT EXISTS pending_update (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    anime_id INTEGER NOT NULL REFERENCES anime(id) ON DELETE CASCADE,
+    episode INTEGER NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    UNIQUE(anime_id, episode)
+);
+
+-- Anime whose updates are applied without asking ("always for this anime").
+CREATE TABLE IF NOT EXISTS auto_confirm_anime (
+    anime_id INTEGER PRIMARY KEY REFERENCES anime(id) ON DELETE CASCADE
+);
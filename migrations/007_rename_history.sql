@@ -0,0 +1,10 @@
+-- Files renamed by the library renamer, so a batch can be undone.
+CREATE TABLE IF NOT EXISTS rename_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    batch_id INTEGER NOT NULL,
+    old_path TEXT NOT NULL,
+    new_path TEXT NOT NULL,
+    renamed_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_rename_history_batch ON rename_history(batch_id);
@@ -0,0 +1,14 @@
+-- User corrections to title recognition: a detected title that means a
+-- specific anime, optionally only for one release group or folder.
+-- season is 0 for the first season or none. An empty release_group or
+-- path_prefix applies everywhere.
+CREATE TABLE IF NOT EXISTS title_alias (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    title TEXT NOT NULL,
+    season INTEGER NOT NULL DEFAULT 0,
+    anime_id INTEGER NOT NULL REFERENCES anime(id) ON DELETE CASCADE,
+    release_group TEXT NOT NULL DEFAULT '',
+    path_prefix TEXT NOT NULL DEFAULT '',
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    UNIQUE(title, season, release_group, path_prefix)
+);
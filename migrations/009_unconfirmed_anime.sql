@@ -0,0 +1,5 @@
+-- Anime added from a weak online match (library.online_fallback_threshold).
+-- Their updates wait for confirmation until the user approves one.
+CREATE TABLE IF NOT EXISTS unconfirmed_anime (
+    anime_id INTEGER PRIMARY KEY REFERENCES anime(id) ON DELETE CASCADE
+);